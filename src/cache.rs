@@ -0,0 +1,904 @@
+use crate::expressions::{Expression, TronType};
+#[cfg(feature = "bigint")]
+use crate::library::math::bigint::BigInt;
+#[cfg(feature = "bigint")]
+use crate::library::math::decimal::Decimal;
+use crate::scanner::{keyword_aliases, LiteralValue, Statement, Token, TokenType, TokenType::*};
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const MAGIC: &[u8] = b"TRONC";
+const SNAPSHOT_MAGIC: &[u8] = b"TRONS";
+const FORMAT_VERSION: u8 = 5;
+/// Every `TokenType`, tokens are stored as their position in this list so the cache doesn't depend on the enum's layout.
+const TOKEN_TYPES: [TokenType; 62] = [
+    Colon,
+    LeftBrace,
+    RightBrace,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Dot,
+    Minus,
+    Plus,
+    Semicolon,
+    Slash,
+    Star,
+    Power,
+    Percent,
+    Bang,
+    BangEqual,
+    Equal,
+    EqualEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Increment,
+    Decrement,
+    Identifier,
+    StringLit,
+    Number,
+    And,
+    Else,
+    False,
+    Function,
+    For,
+    If,
+    Elif,
+    Null,
+    Or,
+    Nor,
+    Xor,
+    Return,
+    True,
+    Variable,
+    While,
+    Eof,
+    Use,
+    Break,
+    Switch,
+    Case,
+    Default,
+    Question,
+    Line,
+    Backtick,
+    DollarBrace,
+    Repeat,
+    Loop,
+    Do,
+    Continue,
+    QuestionQuestion,
+    QuestionDot,
+    DocComment,
+    GreaterGreater,
+];
+
+/// A program as it is after resolving, which is everything the interpreter needs to run it.
+///
+/// # Fields
+///
+/// - `statements`: The parsed statements.
+/// - `locals`: The scope depths found by the resolver, by expression id.
+/// - `strict`: Whether the source turned strict mode on with `#strict`, which also changes how the program runs.
+///
+/// ### Last Updated: (v3.2.0)
+pub struct CompiledProgram {
+    pub statements: Vec<Statement>,
+    pub locals: HashMap<usize, usize>,
+    pub strict: bool,
+}
+
+/// Returns the path of the cache file of a source file, which is stored next to it with the `.tronc` extension.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn cache_path(source_path: &Path) -> PathBuf {
+    source_path.with_extension("tronc")
+}
+
+/// Loads a program from its cache file.
+///
+/// The cache is only used when it was written by the same version of Tron for exactly the same source, with the same
+/// keyword aliases and with `--strict` (`strict`) on or off alike. A missing, stale or unreadable cache returns `None`,
+/// and the source has to be scanned, parsed and resolved again.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn load(path: &Path, source: &str, strict: bool) -> Option<CompiledProgram> {
+    let bytes = fs::read(path).ok()?;
+    let mut decoder = Decoder {
+        bytes: &bytes,
+        position: 0,
+    };
+    if decoder.take(MAGIC.len())? != MAGIC
+        || decoder.byte()? != FORMAT_VERSION
+        || decoder.string()? != env!("CARGO_PKG_VERSION")
+        || decoder.number()? != source_hash(source, strict)
+    {
+        return None;
+    }
+    let statements = decoder.list(Decoder::statement)?;
+    let locals = decoder
+        .list(|decoder| Some((decoder.number()? as usize, decoder.number()? as usize)))?
+        .into_iter()
+        .collect();
+    let strict = decoder.byte()? == 1;
+    Some(CompiledProgram {
+        statements,
+        locals,
+        strict,
+    })
+}
+
+/// Writes a resolved program to its cache file.
+///
+/// Caching is best effort: a program that can't be stored (e.g. the file isn't writable) still runs normally.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn store(path: &Path, source: &str, strict: bool, program: &CompiledProgram) {
+    let mut encoder = Encoder {
+        bytes: MAGIC.to_vec(),
+        failed: false,
+    };
+    encoder.bytes.push(FORMAT_VERSION);
+    encoder.string(env!("CARGO_PKG_VERSION"));
+    encoder.number(source_hash(source, strict));
+    encoder.list(&program.statements, Encoder::statement);
+    let mut locals: Vec<(&usize, &usize)> = program.locals.iter().collect();
+    locals.sort();
+    encoder.list(&locals, |encoder, (id, depth)| {
+        encoder.number(**id as u64);
+        encoder.number(**depth as u64);
+    });
+    encoder.bytes.push(program.strict as u8);
+    if !encoder.failed {
+        let _ = fs::write(path, encoder.bytes);
+    }
+}
+
+/// Writes the variables of a REPL session to a snapshot file, for `:save`.
+///
+/// Snapshots use the same encoding as the cache. Only data is stored (numbers, strings, bools, null, and arrays and
+/// objects of them), variables holding functions are skipped.
+///
+/// # Return Value
+///
+/// The number of variables written, or an error message when the file can't be written.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn save_snapshot(path: &Path, variables: &[(Rc<str>, TronType)]) -> Result<usize, String> {
+    let mut encoder = Encoder {
+        bytes: SNAPSHOT_MAGIC.to_vec(),
+        failed: false,
+    };
+    encoder.bytes.push(FORMAT_VERSION);
+    let variables: Vec<&(Rc<str>, TronType)> = variables
+        .iter()
+        .filter(|(_, value)| is_data(value))
+        .collect();
+    encoder.list(&variables, |encoder, (name, value)| {
+        encoder.string(name);
+        encoder.value(value);
+    });
+    fs::write(path, encoder.bytes).map_err(|e| e.to_string())?;
+    Ok(variables.len())
+}
+
+/// Reads the variables of a snapshot file written by `save_snapshot()`, for `:load`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn load_snapshot(path: &Path) -> Result<Vec<(Rc<str>, TronType)>, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let mut decoder = Decoder {
+        bytes: &bytes,
+        position: 0,
+    };
+    if decoder.take(SNAPSHOT_MAGIC.len()) != Some(SNAPSHOT_MAGIC)
+        || decoder.byte() != Some(FORMAT_VERSION)
+    {
+        return Err("not a snapshot of this version of Tron".to_string());
+    }
+    decoder
+        .list(|decoder| Some((decoder.text()?, decoder.value()?)))
+        .ok_or_else(|| "the snapshot is corrupted".to_string())
+}
+
+fn is_data(value: &TronType) -> bool {
+    match value {
+        TronType::Callable(_) => false,
+        TronType::ArrayValue(items) => items.iter().all(is_data),
+        TronType::Object(fields) => fields.values().all(is_data),
+        _ => true,
+    }
+}
+
+// the keyword aliases change how the source scans and `--strict` what compiles, a program compiled with other aliases
+// or without `--strict` is stale
+fn source_hash(source: &str, strict: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    strict.hash(&mut hasher);
+    let installed = keyword_aliases();
+    let mut aliases: Vec<(&String, String)> = installed
+        .iter()
+        .map(|(alias, token_type)| (alias, token_type.to_string()))
+        .collect();
+    aliases.sort();
+    aliases.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+    // set when the program contains something the format can't store
+    failed: bool,
+}
+
+impl Encoder {
+    // numbers are stored as LEB128 varints, ids, lines and lengths mostly fit in one or two bytes
+    fn number(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+    fn string(&mut self, value: &str) {
+        self.number(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+    fn list<T>(&mut self, items: &[T], mut encode: impl FnMut(&mut Self, &T)) {
+        self.number(items.len() as u64);
+        for item in items {
+            encode(self, item);
+        }
+    }
+    fn option<T>(&mut self, item: &Option<T>, encode: impl FnOnce(&mut Self, &T)) {
+        match item {
+            Some(item) => {
+                self.bytes.push(1);
+                encode(self, item);
+            }
+            None => self.bytes.push(0),
+        }
+    }
+    fn token(&mut self, token: &Token) {
+        match TOKEN_TYPES.iter().position(|t| *t == token.token_type) {
+            Some(index) => self.bytes.push(index as u8),
+            None => self.failed = true,
+        }
+        self.string(&token.lexeme);
+        match &token.literal {
+            None => self.bytes.push(0),
+            Some(LiteralValue::NumericValue(n)) => {
+                self.bytes.push(1);
+                self.bytes.extend_from_slice(&n.to_le_bytes());
+            }
+            Some(LiteralValue::StringValue(s)) => {
+                self.bytes.push(2);
+                self.string(s);
+            }
+        }
+        self.number(token.line_number as u64);
+        self.number(token.column as u64);
+    }
+    fn params(&mut self, params: &[(Token, Token)]) {
+        self.list(params, |encoder, (name, value_type)| {
+            encoder.token(name);
+            encoder.token(value_type);
+        });
+    }
+    fn body(&mut self, body: &[Rc<Statement>]) {
+        self.list(body, |encoder, stmt| encoder.statement(stmt));
+    }
+    fn value(&mut self, value: &TronType) {
+        match value {
+            TronType::Number(n) => {
+                self.bytes.push(0);
+                self.bytes.extend_from_slice(&n.to_le_bytes());
+            }
+            TronType::StringValue(s) => {
+                self.bytes.push(1);
+                self.string(s);
+            }
+            TronType::True => self.bytes.push(2),
+            TronType::False => self.bytes.push(3),
+            TronType::Null => self.bytes.push(4),
+            TronType::ArrayValue(items) => {
+                self.bytes.push(5);
+                self.list(items, Encoder::value);
+            }
+            TronType::Object(fields) => {
+                self.bytes.push(6);
+                // sorted so the same object is always stored the same way
+                let mut fields: Vec<(&String, &TronType)> = fields.iter().collect();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                self.list(&fields, |encoder, (key, value)| {
+                    encoder.string(key);
+                    encoder.value(value);
+                });
+            }
+            #[cfg(feature = "bigint")]
+            TronType::BigInt(x) => {
+                self.bytes.push(7);
+                self.string(&x.to_string());
+            }
+            #[cfg(feature = "bigint")]
+            TronType::Decimal(x) => {
+                self.bytes.push(8);
+                self.string(&x.to_string());
+            }
+            // sets, deques and buffers are only made by natives, so they're never part of a compiled program
+            TronType::Set(_) | TronType::Deque(_) | TronType::Buffer(_) | TronType::Callable(_) => {
+                self.failed = true
+            }
+        }
+    }
+    fn statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Expression { expression, line } => {
+                self.bytes.push(0);
+                self.expression(expression);
+                self.number(*line as u64);
+            }
+            Statement::Use { expression, line } => {
+                self.bytes.push(1);
+                self.expression(expression);
+                self.number(*line as u64);
+            }
+            Statement::Variable {
+                name,
+                value_type,
+                value,
+                line,
+            } => {
+                self.bytes.push(2);
+                self.token(name);
+                self.token(value_type);
+                self.expression(value);
+                self.number(*line as u64);
+            }
+            Statement::Block { statements, line } => {
+                self.bytes.push(3);
+                self.body(statements);
+                self.number(*line as u64);
+            }
+            Statement::While {
+                conditions,
+                body,
+                increment,
+                label,
+                line,
+            } => {
+                self.bytes.push(4);
+                self.list(conditions, Self::expression);
+                self.statement(body);
+                self.option(increment, Self::expression);
+                self.option(label, Self::token);
+                self.number(*line as u64);
+            }
+            Statement::DoWhile {
+                body,
+                conditions,
+                label,
+                line,
+            } => {
+                self.bytes.push(5);
+                self.statement(body);
+                self.list(conditions, Self::expression);
+                self.option(label, Self::token);
+                self.number(*line as u64);
+            }
+            Statement::If {
+                conditions,
+                then_branch,
+                elif_branches,
+                else_branch,
+                line,
+            } => {
+                self.bytes.push(6);
+                self.list(conditions, Self::expression);
+                self.statement(then_branch);
+                self.list(elif_branches, |encoder, (conditions, branch)| {
+                    encoder.list(conditions, Self::expression);
+                    encoder.statement(branch);
+                });
+                self.option(else_branch, |encoder, branch| encoder.statement(branch));
+                self.number(*line as u64);
+            }
+            Statement::Function {
+                name,
+                params,
+                body,
+                output_type,
+                doc,
+                line,
+            } => {
+                self.bytes.push(7);
+                self.token(name);
+                self.params(params);
+                self.body(body);
+                self.token(output_type);
+                self.option(doc, |encoder, doc| encoder.string(doc));
+                self.number(*line as u64);
+            }
+            Statement::Return {
+                keyword,
+                value,
+                line,
+            } => {
+                self.bytes.push(8);
+                self.token(keyword);
+                self.option(value, Self::expression);
+                self.number(*line as u64);
+            }
+            Statement::Break {
+                keyword,
+                label,
+                line,
+            } => {
+                self.bytes.push(9);
+                self.token(keyword);
+                self.option(label, Self::token);
+                self.number(*line as u64);
+            }
+            Statement::Continue {
+                keyword,
+                label,
+                line,
+            } => {
+                self.bytes.push(10);
+                self.token(keyword);
+                self.option(label, Self::token);
+                self.number(*line as u64);
+            }
+            Statement::Switch {
+                condition,
+                case_branches,
+                default_branch,
+                line,
+            } => {
+                self.bytes.push(11);
+                self.expression(condition);
+                self.list(case_branches, |encoder, (value, body)| {
+                    encoder.expression(value);
+                    encoder.list(body, Self::statement);
+                });
+                self.option(default_branch, |encoder, body| {
+                    encoder.list(body, Self::statement)
+                });
+                self.number(*line as u64);
+            }
+        }
+    }
+    fn expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Object { id, properties } => {
+                self.bytes.push(0);
+                self.number(*id as u64);
+                self.list(properties, |encoder, (key, value)| {
+                    encoder.token(key);
+                    encoder.expression(value);
+                });
+            }
+            Expression::ObjectCall { id, key, name } => {
+                self.bytes.push(1);
+                self.number(*id as u64);
+                self.token(key);
+                self.token(name);
+            }
+            Expression::Array { id, elements } => {
+                self.bytes.push(2);
+                self.number(*id as u64);
+                self.list(elements, |encoder, element| encoder.expression(element));
+            }
+            Expression::Index { id, object, index } => {
+                self.bytes.push(3);
+                self.number(*id as u64);
+                self.expression(object);
+                self.expression(index);
+            }
+            Expression::Assign { id, name, value } => {
+                self.bytes.push(4);
+                self.number(*id as u64);
+                self.token(name);
+                self.expression(value);
+            }
+            Expression::Binary {
+                id,
+                left,
+                operator,
+                right,
+            } => {
+                self.bytes.push(5);
+                self.number(*id as u64);
+                self.expression(left);
+                self.token(operator);
+                self.expression(right);
+            }
+            Expression::Call {
+                id,
+                callee,
+                paren,
+                arguments,
+            } => {
+                self.bytes.push(6);
+                self.number(*id as u64);
+                self.expression(callee);
+                self.token(paren);
+                self.list(arguments, Self::expression);
+            }
+            Expression::Grouping { id, expression } => {
+                self.bytes.push(7);
+                self.number(*id as u64);
+                self.expression(expression);
+            }
+            Expression::Literal { id, value } => {
+                self.bytes.push(8);
+                self.number(*id as u64);
+                self.value(value);
+            }
+            Expression::Logical {
+                id,
+                left,
+                operator,
+                right,
+            } => {
+                self.bytes.push(9);
+                self.number(*id as u64);
+                self.expression(left);
+                self.token(operator);
+                self.expression(right);
+            }
+            Expression::Unary {
+                id,
+                operator,
+                right,
+            } => {
+                self.bytes.push(10);
+                self.number(*id as u64);
+                self.token(operator);
+                self.expression(right);
+            }
+            Expression::Variable { id, name } => {
+                self.bytes.push(11);
+                self.number(*id as u64);
+                self.token(name);
+            }
+            Expression::Function {
+                id,
+                name,
+                params,
+                body,
+                output_type,
+            } => {
+                self.bytes.push(12);
+                self.number(*id as u64);
+                self.token(name);
+                self.params(params);
+                self.body(body);
+                self.token(output_type);
+            }
+            Expression::Template { id, parts } => {
+                self.bytes.push(13);
+                self.number(*id as u64);
+                self.list(parts, Self::expression);
+            }
+            Expression::Comparison {
+                id,
+                operands,
+                operators,
+            } => {
+                self.bytes.push(14);
+                self.number(*id as u64);
+                self.list(operands, Self::expression);
+                self.list(operators, Self::token);
+            }
+            Expression::OptionalGet { id, object, key } => {
+                self.bytes.push(15);
+                self.number(*id as u64);
+                self.expression(object);
+                self.token(key);
+            }
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let end = self.position.checked_add(length)?;
+        let slice = self.bytes.get(self.position..end)?;
+        self.position = end;
+        Some(slice)
+    }
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+    fn number(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+    fn float(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+    fn usize(&mut self) -> Option<usize> {
+        Some(self.number()? as usize)
+    }
+    fn string(&mut self) -> Option<String> {
+        let length = self.usize()?;
+        String::from_utf8(self.take(length)?.to_vec()).ok()
+    }
+    fn text(&mut self) -> Option<Rc<str>> {
+        Some(self.string()?.into())
+    }
+    fn list<T>(&mut self, mut decode: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        let length = self.usize()?;
+        // every item takes at least one byte, so a corrupt length can't allocate more than the file
+        if length > self.bytes.len() - self.position {
+            return None;
+        }
+        let mut items = Vec::with_capacity(length);
+        for _ in 0..length {
+            items.push(decode(self)?);
+        }
+        Some(items)
+    }
+    fn option<T>(&mut self, decode: impl FnOnce(&mut Self) -> Option<T>) -> Option<Option<T>> {
+        match self.byte()? {
+            0 => Some(None),
+            1 => Some(Some(decode(self)?)),
+            _ => None,
+        }
+    }
+    fn token(&mut self) -> Option<Token> {
+        let token_type = *TOKEN_TYPES.get(self.byte()? as usize)?;
+        let lexeme = self.text()?;
+        let literal = match self.byte()? {
+            0 => None,
+            1 => Some(LiteralValue::NumericValue(self.float()?)),
+            2 => Some(LiteralValue::StringValue(self.string()?)),
+            _ => return None,
+        };
+        Some(Token {
+            token_type,
+            lexeme,
+            literal,
+            line_number: self.usize()?,
+            column: self.usize()?,
+        })
+    }
+    fn params(&mut self) -> Option<Rc<[(Token, Token)]>> {
+        Some(
+            self.list(|decoder| Some((decoder.token()?, decoder.token()?)))?
+                .into(),
+        )
+    }
+    fn body(&mut self) -> Option<Vec<Rc<Statement>>> {
+        self.list(|decoder| Some(Rc::new(decoder.statement()?)))
+    }
+    fn value(&mut self) -> Option<TronType> {
+        match self.byte()? {
+            0 => Some(TronType::Number(self.float()?)),
+            1 => Some(TronType::StringValue(self.string()?)),
+            2 => Some(TronType::True),
+            3 => Some(TronType::False),
+            4 => Some(TronType::Null),
+            5 => Some(TronType::ArrayValue(self.list(Decoder::value)?)),
+            6 => Some(TronType::Object(
+                self.list(|decoder| Some((decoder.string()?, decoder.value()?)))?
+                    .into_iter()
+                    .collect(),
+            )),
+            #[cfg(feature = "bigint")]
+            7 => Some(TronType::BigInt(BigInt::parse(&self.string()?)?)),
+            #[cfg(feature = "bigint")]
+            8 => Some(TronType::Decimal(Decimal::parse(&self.string()?)?)),
+            _ => None,
+        }
+    }
+    fn statement(&mut self) -> Option<Statement> {
+        let stmt = match self.byte()? {
+            0 => Statement::Expression {
+                expression: self.expression()?,
+                line: self.usize()?,
+            },
+            1 => Statement::Use {
+                expression: self.expression()?,
+                line: self.usize()?,
+            },
+            2 => Statement::Variable {
+                name: self.token()?,
+                value_type: self.token()?,
+                value: self.expression()?,
+                line: self.usize()?,
+            },
+            3 => Statement::Block {
+                statements: self.body()?,
+                line: self.usize()?,
+            },
+            4 => Statement::While {
+                conditions: self.list(Self::expression)?,
+                body: Rc::new(self.statement()?),
+                increment: self.option(Self::expression)?,
+                label: self.option(Self::token)?,
+                line: self.usize()?,
+            },
+            5 => Statement::DoWhile {
+                body: Rc::new(self.statement()?),
+                conditions: self.list(Self::expression)?,
+                label: self.option(Self::token)?,
+                line: self.usize()?,
+            },
+            6 => Statement::If {
+                conditions: self.list(Self::expression)?,
+                then_branch: Rc::new(self.statement()?),
+                elif_branches: self.list(|decoder| {
+                    Some((
+                        decoder.list(Self::expression)?,
+                        Rc::new(decoder.statement()?),
+                    ))
+                })?,
+                else_branch: self.option(|decoder| Some(Rc::new(decoder.statement()?)))?,
+                line: self.usize()?,
+            },
+            7 => Statement::Function {
+                name: self.token()?,
+                params: self.params()?,
+                body: self.body()?.into(),
+                output_type: self.token()?,
+                doc: self.option(Self::text)?,
+                line: self.usize()?,
+            },
+            8 => Statement::Return {
+                keyword: self.token()?,
+                value: self.option(Self::expression)?,
+                line: self.usize()?,
+            },
+            9 => Statement::Break {
+                keyword: self.token()?,
+                label: self.option(Self::token)?,
+                line: self.usize()?,
+            },
+            10 => Statement::Continue {
+                keyword: self.token()?,
+                label: self.option(Self::token)?,
+                line: self.usize()?,
+            },
+            11 => Statement::Switch {
+                condition: self.expression()?,
+                case_branches: self.list(|decoder| {
+                    Some((decoder.expression()?, decoder.list(Self::statement)?))
+                })?,
+                default_branch: self.option(|decoder| decoder.list(Self::statement))?,
+                line: self.usize()?,
+            },
+            _ => return None,
+        };
+        Some(stmt)
+    }
+    fn expression(&mut self) -> Option<Expression> {
+        let tag = self.byte()?;
+        let id = self.usize()?;
+        let expr = match tag {
+            0 => Expression::Object {
+                id,
+                properties: self.list(|decoder| Some((decoder.token()?, decoder.expression()?)))?,
+            },
+            1 => Expression::ObjectCall {
+                id,
+                key: self.token()?,
+                name: self.token()?,
+            },
+            2 => Expression::Array {
+                id,
+                elements: self.list(|decoder| Some(Rc::new(decoder.expression()?)))?,
+            },
+            3 => Expression::Index {
+                id,
+                object: Rc::new(self.expression()?),
+                index: Rc::new(self.expression()?),
+            },
+            4 => Expression::Assign {
+                id,
+                name: self.token()?,
+                value: Rc::new(self.expression()?),
+            },
+            5 => Expression::Binary {
+                id,
+                left: Rc::new(self.expression()?),
+                operator: self.token()?,
+                right: Rc::new(self.expression()?),
+            },
+            6 => Expression::Call {
+                id,
+                callee: Rc::new(self.expression()?),
+                paren: self.token()?,
+                arguments: self.list(Self::expression)?,
+            },
+            7 => Expression::Grouping {
+                id,
+                expression: Rc::new(self.expression()?),
+            },
+            8 => Expression::Literal {
+                id,
+                value: self.value()?,
+            },
+            9 => Expression::Logical {
+                id,
+                left: Rc::new(self.expression()?),
+                operator: self.token()?,
+                right: Rc::new(self.expression()?),
+            },
+            10 => Expression::Unary {
+                id,
+                operator: self.token()?,
+                right: Rc::new(self.expression()?),
+            },
+            11 => Expression::Variable {
+                id,
+                name: self.token()?,
+            },
+            12 => Expression::Function {
+                id,
+                name: self.token()?,
+                params: self.params()?,
+                body: self.body()?.into(),
+                output_type: self.token()?,
+            },
+            13 => Expression::Template {
+                id,
+                parts: self.list(Self::expression)?,
+            },
+            14 => Expression::Comparison {
+                id,
+                operands: self.list(Self::expression)?,
+                operators: self.list(Self::token)?,
+            },
+            15 => Expression::OptionalGet {
+                id,
+                object: Rc::new(self.expression()?),
+                key: self.token()?,
+            },
+            _ => return None,
+        };
+        Some(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::set_keyword_aliases;
+
+    #[test]
+    fn the_aliases_are_part_of_the_key() {
+        let source = "si true { @print(1); }";
+        let without = source_hash(source, false);
+        set_keyword_aliases(&[("si".to_string(), "if".to_string())].into());
+        let with = source_hash(source, false);
+        assert_ne!(without, with);
+        assert_eq!(with, source_hash(source, false));
+    }
+
+    #[test]
+    fn strict_mode_is_part_of_the_key() {
+        let source = "@print(x);";
+        assert_ne!(source_hash(source, false), source_hash(source, true));
+    }
+}
@@ -1 +1,226 @@
-// run benchmarks
+use crate::commands::build::load_aliases;
+use crate::interpreter::Interpreter;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::scanner::{Scanner, Token};
+use crate::utils::TronError;
+use std::time::{Duration, Instant};
+use std::{fs, path::PathBuf, process::exit};
+
+// code repeated to build the synthetic source when no file is given, `{n}` keeps the names unique
+const SAMPLE: &str = r#"fn step_{n}(a: number, b: number): number {
+    let total: number = a + b * 2 - (a / 4);
+    let items: array = [a, b, total, "item {n}"];
+    let point: object = { x: a, y: b, label: "point" };
+    if total > 10 & a < 5 {
+        return total - 1;
+    } else if total == 0 || b != 3 {
+        return @length(items);
+    } else {
+        let i: number = 0;
+        while i < 3 {
+            i++;
+        }
+    }
+    for let j: number = 0; j < 10; j++ {
+        total = total + point.x;
+    }
+    return total;
+}
+"#;
+
+// loop-heavy program run by `--run` when no file is given
+const LOOP_SAMPLE: &str = r#"fn fib(n: number): number {
+    if n < 2 {
+        return n;
+    }
+    return fib(n - 1) + fib(n - 2);
+}
+let total: number = 0;
+let items: array = [1, 2, 3, 4, 5];
+for let i: number = 0; i < 50000; i++ {
+    total = total + items[2] * 2 - i / 4;
+}
+let i: number = 0;
+while i < 20000 {
+    i++;
+}
+let result: number = fib(16);
+"#;
+
+// the same string built by `--strings` with `+` and with a buffer, `{n}` is the number of pieces
+const CONCAT_SAMPLE: &str = r#"let s = "";
+for let i = 0; i < {n}; i++ {
+    s = s + "piece " + i + ";";
+}
+"#;
+const BUFFER_SAMPLE: &str = r#"let b = @buffer();
+for let i = 0; i < {n}; i++ {
+    @buffer_push(b, "piece " + i + ";");
+}
+let s = @buffer_to_string(b);
+"#;
+
+const MEGABYTE: usize = 1 << 20;
+
+fn synthetic_source(copies: usize) -> String {
+    (0..copies)
+        .map(|n| SAMPLE.replace("{n}", &n.to_string()))
+        .collect()
+}
+
+fn flag_value(flags: &[String], name: &str, default: usize) -> usize {
+    match flags.iter().position(|flag| flag == name) {
+        Some(index) => match flags.get(index + 1).and_then(|v| v.parse().ok()) {
+            Some(value) if value > 0 => value,
+            _ => {
+                TronError::throw("E0002", 0, vec![]);
+                exit(64);
+            }
+        },
+        None => default,
+    }
+}
+
+fn report(phase: &str, total: Duration, iterations: usize, units: usize, unit: &str) {
+    let average = total / iterations as u32;
+    let per_second = units as f64 / average.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "\x1B[36m{:<6}\x1B[0m {:>10.3} ms  ({:.0} {}/s)",
+        phase,
+        average.as_secs_f64() * 1000.0,
+        per_second,
+        unit
+    );
+}
+
+// runs a program once and returns how long it took, from resolving to the end of the interpretation
+fn run_once(source: &str) -> Duration {
+    let stmts = match Scanner::new(source)
+        .scan_tokens()
+        .and_then(|tokens| Parser::new(tokens).parse())
+    {
+        Ok(stmts) => stmts,
+        Err(msg) => {
+            println!("{}", msg);
+            exit(1);
+        }
+    };
+    let mut interpreter = Interpreter::new();
+    let start = Instant::now();
+    let result = Resolver::new()
+        .resolve(&stmts.iter().collect(), &mut interpreter.environment)
+        .and_then(|locals| {
+            interpreter.resolve(locals);
+            interpreter.interpret(stmts.iter().collect())
+        });
+    let elapsed = start.elapsed();
+    if let Err(msg) = result {
+        println!("{}", msg);
+        exit(1);
+    }
+    elapsed
+}
+
+// `--strings`: builds a string of `--pieces` pieces with `s = s + piece` and with `@buffer_push`, the first copies the
+// string built so far at every step so the gap grows with the number of pieces
+fn bench_strings(flags: &[String], iterations: usize) {
+    let pieces = flag_value(flags, "--pieces", 20000);
+    println!("{} pieces, {} iterations", pieces, iterations);
+    for (phase, sample) in [("concat", CONCAT_SAMPLE), ("buffer", BUFFER_SAMPLE)] {
+        let source = sample.replace("{n}", &pieces.to_string());
+        let total = (0..iterations).map(|_| run_once(&source)).sum();
+        report(phase, total, iterations, pieces, "pieces");
+    }
+}
+
+// measures scanning and parsing of a file, or of a large generated source when no file is given,
+// with `--run` the program is also resolved and interpreted
+pub fn cli_bench(args: &[String], path: PathBuf) {
+    let (file, flags) = match args.first() {
+        Some(file) if !file.starts_with("--") => (Some(file), &args[1..]),
+        _ => (None, args),
+    };
+    let iterations = flag_value(flags, "--iterations", 10);
+    let run = flags.iter().any(|flag| flag == "--run");
+    if file.is_none() && flags.iter().any(|flag| flag == "--strings") {
+        return bench_strings(flags, iterations);
+    }
+    let source = match file {
+        Some(file) => match fs::read_to_string(path.join(file)) {
+            Ok(source) => {
+                load_aliases(&path);
+                source
+            }
+            Err(_) => {
+                TronError::throw("E0001", 0, vec![]);
+                exit(1);
+            }
+        },
+        None if run => LOOP_SAMPLE.to_string(),
+        None if flags.iter().any(|flag| flag == "--megabytes") => {
+            synthetic_source(flag_value(flags, "--megabytes", 1) * MEGABYTE / SAMPLE.len())
+        }
+        None => synthetic_source(flag_value(flags, "--copies", 2000)),
+    };
+    let lines = source.lines().count();
+    let mut tokens: Vec<Token> = vec![];
+    let mut scan_time = Duration::ZERO;
+    let mut parse_time = Duration::ZERO;
+    let mut run_time = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        tokens = match Scanner::new(&source).scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(msg) => {
+                println!("{}", msg);
+                exit(1);
+            }
+        };
+        scan_time += start.elapsed();
+        let input = tokens.clone();
+        let start = Instant::now();
+        let parsed = Parser::new(input).parse();
+        parse_time += start.elapsed();
+        let stmts = match parsed {
+            Ok(stmts) => stmts,
+            Err(msg) => {
+                println!("{}", msg);
+                exit(1);
+            }
+        };
+        if run {
+            let mut interpreter = Interpreter::new();
+            let start = Instant::now();
+            let result = Resolver::new()
+                .resolve(&stmts.iter().collect(), &mut interpreter.environment)
+                .and_then(|locals| {
+                    interpreter.resolve(locals);
+                    interpreter.interpret(stmts.iter().collect())
+                });
+            run_time += start.elapsed();
+            if let Err(msg) = result {
+                println!("{}", msg);
+                exit(1);
+            }
+        }
+    }
+    println!(
+        "{} lines, {:.1} MB, {} tokens, {} iterations",
+        lines,
+        source.len() as f64 / MEGABYTE as f64,
+        tokens.len(),
+        iterations
+    );
+    report("scan", scan_time, iterations, lines, "lines");
+    let scan_average = scan_time / iterations as u32;
+    println!(
+        "{:22}({:.1} MB/s)",
+        "",
+        source.len() as f64 / MEGABYTE as f64 / scan_average.as_secs_f64().max(f64::EPSILON)
+    );
+    report("parse", parse_time, iterations, tokens.len(), "tokens");
+    if run {
+        report("run", run_time, iterations, 1, "runs");
+    }
+}
@@ -0,0 +1,46 @@
+use crate::project::{check_imports, Manifest};
+use crate::scanner::set_keyword_aliases;
+use crate::utils::TronError;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+// reads tron.toml from the current directory and installs its keyword aliases, exits when there's no valid manifest
+pub fn load_manifest(path: &Path) -> Manifest {
+    match Manifest::load(path) {
+        Ok(manifest) => {
+            set_keyword_aliases(&manifest.aliases);
+            manifest
+        }
+        Err(msg) => {
+            TronError::throw("E0007", 0, vec![msg]);
+            exit(1);
+        }
+    }
+}
+
+// a single file, `tron -e` and the REPL use the keyword aliases of the project in the current directory, when there's one
+pub fn load_aliases(path: &Path) {
+    if path.join("tron.toml").exists() {
+        load_manifest(path);
+    }
+}
+
+// checks the import graph of the project without running it
+pub fn cli_build(path: PathBuf) {
+    let manifest = load_manifest(&path);
+    match check_imports(&path, &path.join(&manifest.entry)) {
+        Ok(files) => println!(
+            "\x1B[32m{}\x1B[0m v{}: checked {} files, no problems found",
+            manifest.name,
+            manifest.version,
+            files.len()
+        ),
+        Err(problems) => {
+            println!(
+                "\x1B[31m{}\x1B[0m v{}: {} problems found",
+                manifest.name, manifest.version, problems
+            );
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::commands::build::load_manifest;
+use crate::interpreter::Interpreter;
+use crate::lints::{ComplexityLinter, ConstantLinter};
+use crate::parser::Parser;
+use crate::project::{check_imports, display};
+use crate::resolver::{FunctionUses, Resolver};
+use crate::scanner::{is_strict, Scanner};
+use crate::typecheck::TypeChecker;
+use crate::utils::TronError;
+use std::{collections::HashSet, fs, path::PathBuf, process::exit};
+
+// checks the type annotations of a file and everything it imports without running them,
+// without a file, of the entry of the project in tron.toml, `--run-lints` also evaluates their constant expressions,
+// dead code (unreachable statements, branches that never run, unused functions) and functions too complex or too long
+// are reported as warnings
+pub fn cli_check(args: &[String], path: PathBuf) {
+    let (file, flags) = match args.first() {
+        Some(file) if !file.starts_with("--") => (Some(file), &args[1..]),
+        _ => (None, args),
+    };
+    let run_lints = flags.iter().any(|flag| flag == "--run-lints");
+    // a single file is checked with the limits of the project around it, when there's one
+    let manifest = match file {
+        Some(_) if !path.join("tron.toml").exists() => None,
+        _ => Some(load_manifest(&path)),
+    };
+    let entry = match file {
+        Some(file) => path.join(file),
+        None => path.join(manifest.as_ref().map_or("", |manifest| &manifest.entry)),
+    };
+    let max_complexity = limit(flags, "--max-complexity")
+        .or(manifest
+            .as_ref()
+            .and_then(|manifest| manifest.max_complexity))
+        .unwrap_or(ComplexityLinter::MAX_COMPLEXITY);
+    let max_lines = limit(flags, "--max-function-lines")
+        .or(manifest
+            .as_ref()
+            .and_then(|manifest| manifest.max_function_lines))
+        .unwrap_or(ComplexityLinter::MAX_LINES);
+    let files = match check_imports(&path, &entry) {
+        Ok(files) => files,
+        Err(_) => exit(1),
+    };
+    let root = fs::canonicalize(&path).unwrap_or(path);
+    let interpreter = Interpreter::new();
+    let natives = interpreter.environment.natives.borrow();
+    let mut problems = 0;
+    let warned = TronError::warned();
+    // the functions of every file, a function of a module is used by the files importing it
+    let mut functions = vec![];
+    let mut used = HashSet::new();
+    for file in &files {
+        // the lines of the problems are relative to the file printed before them
+        eprintln!("\x1B[36m{}\x1B[0m", display(&root, file));
+        let statements = fs::read_to_string(file)
+            .map_err(|e| format!("{}: {}", file.display(), e))
+            .and_then(|source| Scanner::new(&source).scan_tokens_with_directives())
+            .and_then(|(tokens, directives)| {
+                Parser::new(tokens)
+                    .with_strict(is_strict(&directives))
+                    .parse()
+                    .map(|statements| (statements, directives))
+            });
+        match statements {
+            Ok((statements, directives)) => {
+                let reported = TronError::reported() - TronError::warned();
+                ComplexityLinter::new(max_complexity, max_lines, directives.clone())
+                    .check(&statements);
+                let resolved = Resolver::new().with_directives(directives).find_dead_code(
+                    &statements.iter().collect(),
+                    &mut interpreter.environment.clone(),
+                );
+                // the errors of strict mode are reported as they're found, they're counted below
+                if let Ok(FunctionUses {
+                    declared,
+                    used: names,
+                }) = resolved
+                {
+                    functions.push((file, declared));
+                    used.extend(names);
+                }
+                problems += TronError::reported() - TronError::warned() - reported;
+                problems += TypeChecker::new(&natives).check(&statements);
+                if run_lints {
+                    let linter = ConstantLinter::new(interpreter.environment.clone());
+                    problems += linter.check(&statements);
+                }
+            }
+            Err(msg) => {
+                TronError::throw("E2001", 0, vec![msg]);
+                problems += 1;
+            }
+        }
+    }
+    for (file, declared) in functions {
+        let unused: Vec<_> = declared
+            .into_iter()
+            .filter(|(name, _)| !used.contains(name))
+            .collect();
+        if !unused.is_empty() {
+            eprintln!("\x1B[36m{}\x1B[0m", display(&root, file));
+        }
+        for (name, line) in unused {
+            TronError::warn("W3009", line, vec![name.to_string()]);
+        }
+    }
+    let warnings = match TronError::warned() - warned {
+        0 => String::new(),
+        1 => ", 1 warning".to_string(),
+        warnings => format!(", {} warnings", warnings),
+    };
+    if problems > 0 {
+        println!("\x1B[31m{} problems found{}\x1B[0m", problems, warnings);
+        exit(1);
+    }
+    println!(
+        "\x1B[32mchecked {} files, no problems found{}\x1B[0m",
+        files.len(),
+        warnings
+    );
+}
+
+// the value of a flag like `--max-complexity 15`
+fn limit(flags: &[String], name: &str) -> Option<usize> {
+    let position = flags.iter().position(|flag| flag == name)?;
+    match flags.get(position + 1).and_then(|value| value.parse().ok()) {
+        Some(value) => Some(value),
+        None => {
+            TronError::throw("E0004", 0, vec![name.to_string()]);
+            exit(1);
+        }
+    }
+}
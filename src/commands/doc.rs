@@ -0,0 +1,93 @@
+use crate::commands::build::{load_aliases, load_manifest};
+use crate::interpreter::Interpreter;
+use crate::library::docs::describe_native;
+use crate::project::check_imports;
+use crate::project::docs::{module_doc, render, Format, ModuleDoc};
+use crate::utils::TronError;
+use std::{fs, path::Path, path::PathBuf, process::exit};
+
+fn read_module(root: &Path, file: &Path) -> ModuleDoc {
+    match module_doc(root, file) {
+        Ok(module) => module,
+        Err(msg) => {
+            TronError::throw("E2001", 0, vec![msg]);
+            exit(1);
+        }
+    }
+}
+
+// without a format, lists the natives and the documented functions of a file,
+// with `--markdown` or `--html`, generates the API documentation of a file and everything it imports
+pub fn cli_doc(args: &[String], path: PathBuf) {
+    // the value of `--out` isn't the file to document
+    let file = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| !arg.starts_with("--") && (*index == 0 || args[index - 1] != "--out"))
+        .map(|(_, arg)| arg);
+    let format = if args.iter().any(|arg| arg == "--html") {
+        Some(Format::Html)
+    } else if args.iter().any(|arg| arg == "--markdown") {
+        Some(Format::Markdown)
+    } else {
+        None
+    };
+    if file.is_some() {
+        load_aliases(&path);
+    }
+    let Some(format) = format else {
+        list(file, &path);
+        return;
+    };
+    let (title, entry) = match file {
+        Some(file) => (file.clone(), path.join(file)),
+        None => {
+            let manifest = load_manifest(&path);
+            (manifest.name, path.join(manifest.entry))
+        }
+    };
+    let files = match check_imports(&path, &entry) {
+        Ok(files) => files,
+        Err(_) => exit(1),
+    };
+    let root = fs::canonicalize(&path).unwrap_or(path);
+    let modules: Vec<ModuleDoc> = files.iter().map(|f| read_module(&root, f)).collect();
+    let output = render(&title, &modules, format);
+    match args.iter().position(|arg| arg == "--out") {
+        Some(index) => {
+            let Some(out) = args.get(index + 1) else {
+                TronError::throw("E0002", 0, vec![]);
+                exit(64);
+            };
+            if let Err(error) = fs::write(root.join(out), output) {
+                TronError::throw("E0010", 0, vec![out.clone(), error.to_string()]);
+                exit(1);
+            }
+            println!("documented {} files in {}", modules.len(), out);
+        }
+        None => print!("{}", output),
+    }
+}
+
+fn list(file: Option<&String>, path: &Path) {
+    let interpreter = Interpreter::new();
+    let natives = interpreter.environment.natives.borrow();
+    println!("\x1B[36mnatives\x1B[0m");
+    for (name, _) in natives.natives() {
+        if let Some(help) = describe_native(&natives, &name) {
+            println!("  {}", help.replace('\n', "\n  "));
+        }
+    }
+    let Some(file) = file else {
+        return;
+    };
+    let module = read_module(path, &path.join(file));
+    println!("\n\x1B[36m{}\x1B[0m", file);
+    for function in module.functions {
+        println!("  {}", function.signature);
+        match function.doc {
+            Some(doc) => println!("      {}", doc.replace('\n', "\n      ")),
+            None => println!("      no documentation"),
+        }
+    }
+}
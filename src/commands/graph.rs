@@ -0,0 +1,53 @@
+use crate::commands::build::{load_aliases, load_manifest};
+use crate::project::check_imports;
+use crate::project::graph::{call_graph, render, Format};
+use crate::utils::TronError;
+use std::{fs, path::PathBuf, process::exit};
+
+// prints the call graph of a file and everything it imports, or of the project in tron.toml, as Graphviz DOT
+// (`--dot`, the default) or JSON (`--json`), `--out <file>` writes it to a file instead
+pub fn cli_graph(args: &[String], path: PathBuf) {
+    // the value of `--out` isn't the file to graph
+    let file = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| !arg.starts_with("--") && (*index == 0 || args[index - 1] != "--out"))
+        .map(|(_, arg)| arg);
+    let format = match args.iter().any(|arg| arg == "--json") {
+        true => Format::Json,
+        false => Format::Dot,
+    };
+    let entry = match file {
+        Some(file) => {
+            load_aliases(&path);
+            path.join(file)
+        }
+        None => path.join(load_manifest(&path).entry),
+    };
+    let files = match check_imports(&path, &entry) {
+        Ok(files) => files,
+        Err(_) => exit(1),
+    };
+    let root = fs::canonicalize(&path).unwrap_or(path);
+    let graph = match call_graph(&root, &files) {
+        Ok(graph) => graph,
+        Err(msg) => {
+            TronError::throw("E2001", 0, vec![msg]);
+            exit(1);
+        }
+    };
+    let output = render(&graph, format);
+    match args.iter().position(|arg| arg == "--out") {
+        Some(index) => {
+            let Some(out) = args.get(index + 1) else {
+                TronError::throw("E0002", 0, vec![]);
+                exit(64);
+            };
+            if let Err(error) = fs::write(root.join(out), output) {
+                TronError::throw("E0010", 0, vec![out.clone(), error.to_string()]);
+                exit(1);
+            }
+        }
+        None => print!("{}", output),
+    }
+}
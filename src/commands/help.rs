@@ -1,8 +1,51 @@
 pub fn cli_help() {
     println!(
         "
-        \x1B[36mtron\x1B[0m \x1B[32mrun\x1B[0m \x1B[31m<filename>\x1B[0m - run tron files
-        \x1B[36mtron\x1B[0m \x1B[32mversion\x1B[0m - installed version
+        \x1B[36mtron\x1B[0m \x1B[32mrun\x1B[0m \x1B[31m<filename>\x1B[0m \x1B[33m[flags]\x1B[0m - run tron files
+            \x1B[33m--strict-types\x1B[0m - disable implicit type conversions
+            \x1B[33m--strict-bool\x1B[0m - require booleans in conditions and logical operators
+            \x1B[33m--strict\x1B[0m - strict mode (also `#strict` in the file): declare names before using them, no implicit conversions
+            \x1B[33m--warn-coercions\x1B[0m - report implicit conversions to bool and string while running, to prepare for strict mode
+            \x1B[33m--allow-infinity\x1B[0m - let a division by zero give infinity or NaN instead of an error
+            \x1B[33m--plugin <path>\x1B[0m - load the native functions of a shared library (repeatable)
+            \x1B[33m--cache\x1B[0m - reuse the parsed program from a .tronc file while the source is unchanged
+            \x1B[33m--lang <code>\x1B[0m - print diagnostics in another language, from locales/<code>.toml (LANG by default)
+            \x1B[33m--deterministic\x1B[0m - same output on every run: seeded random numbers, frozen clock, sorted objects, no input
+            \x1B[33m--seed <n>\x1B[0m, \x1B[33m--epoch <seconds>\x1B[0m, \x1B[33m--input <file>\x1B[0m - the seed, clock and input of deterministic mode
+            \x1B[33m--record <file>\x1B[0m - record the results of commands, downloads and file reads into a cassette
+            \x1B[33m--replay <file>\x1B[0m - replay them from the cassette instead, to test offline
+            \x1B[33m--ask\x1B[0m - ask before running commands, writing files or accessing the network
+            \x1B[33m--dry-run\x1B[0m - show the commands of @cmd and @spawn_detached instead of running them
+            \x1B[33m--audit-cmds <file>\x1B[0m - append every shell command, its duration and exit status to the file
+        \x1B[36mtron\x1B[0m \x1B[32mrun\x1B[0m \x1B[33m[flags]\x1B[0m - run the entry file of the project in tron.toml
+        \x1B[36mtron\x1B[0m \x1B[32m-e\x1B[0m \x1B[31m<source>\x1B[0m \x1B[33m[flags]\x1B[0m - run a one-liner, e.g. `tron -e '@print(1 + 2);'`
+        \x1B[36mtron\x1B[0m \x1B[32mrepl\x1B[0m \x1B[33m[flags]\x1B[0m - interactive session, expressions print their value, unfinished input continues on the next line, `:help` lists the session commands
+        \x1B[36mtron\x1B[0m \x1B[32minit\x1B[0m \x1B[31m[name]\x1B[0m \x1B[33m[flags]\x1B[0m - create a project with a tron.toml, main.tron, tests/ and .gitignore, in a new directory when a name is given
+            \x1B[33m--template <app|lib|dir>\x1B[0m - start from a program (app), a package (lib) or a directory holding a template, where {{name}} is the name of the project
+        \x1B[36mtron\x1B[0m \x1B[32mbuild\x1B[0m - check the imports of the project in tron.toml without running it
+        \x1B[36mtron\x1B[0m \x1B[32mcheck\x1B[0m \x1B[31m[filename]\x1B[0m - check the type annotations of a file and its imports, or of the project in tron.toml, and warn about dead code
+            \x1B[33m--run-lints\x1B[0m - also evaluate the expressions made only of literals and report every error they raise
+            \x1B[33m--max-complexity <n>\x1B[0m - warn about the functions with a cyclomatic complexity over n (10 by default)
+            \x1B[33m--max-function-lines <n>\x1B[0m - warn about the functions longer than n lines (50 by default)
+        \x1B[36mtron\x1B[0m \x1B[32madd\x1B[0m \x1B[31m<name>\x1B[0m \x1B[33m[git url]\x1B[0m - add a dependency to tron.toml and install it
+        \x1B[36mtron\x1B[0m \x1B[32minstall\x1B[0m - install the dependencies of tron.toml into tron_modules/
+        \x1B[36mtron\x1B[0m \x1B[32mbench\x1B[0m \x1B[31m[filename]\x1B[0m \x1B[33m[flags]\x1B[0m - measure scanning and parsing, of a large generated file by default
+            \x1B[33m--iterations <n>\x1B[0m - number of runs to average (10)
+            \x1B[33m--copies <n>\x1B[0m - size of the generated file in sample functions (2000)
+            \x1B[33m--megabytes <n>\x1B[0m - size of the generated file in megabytes instead, to measure scanning throughput
+            \x1B[33m--run\x1B[0m - also resolve and interpret the program, a loop-heavy one by default
+            \x1B[33m--strings\x1B[0m - compare building a string with `+` and with `@buffer_push` instead
+            \x1B[33m--pieces <n>\x1B[0m - number of pieces the strings of `--strings` are built from (20000)
+        \x1B[36mtron\x1B[0m \x1B[32mdoc\x1B[0m \x1B[31m[filename]\x1B[0m - list the natives, and the functions of a file, with their documentation
+            \x1B[33m--markdown\x1B[0m - generate Markdown docs of the file and its imports, or of the project in tron.toml
+            \x1B[33m--html\x1B[0m - generate HTML docs instead
+            \x1B[33m--out <path>\x1B[0m - write the docs to a file instead of printing them
+        \x1B[36mtron\x1B[0m \x1B[32mgraph\x1B[0m \x1B[31m[filename]\x1B[0m - print the call graph of a file and its imports, or of the project in tron.toml, as Graphviz DOT
+            \x1B[33m--json\x1B[0m - print it as JSON instead
+            \x1B[33m--out <path>\x1B[0m - write the graph to a file instead of printing it
+        \x1B[36mtron\x1B[0m \x1B[32mversion\x1B[0m \x1B[33m[flags]\x1B[0m - installed version
+            \x1B[33m--check\x1B[0m - also tell whether a newer release is available
+        \x1B[36mtron\x1B[0m \x1B[32mupgrade\x1B[0m - replace tron with the latest release for the platform, after verifying its checksum
         \x1B[36mtron\x1B[0m \x1B[32mupdate\x1B[0m - install the latest version
 
              \x1B[36mTron Programming Language (3.1.0)\x1B[0m
@@ -0,0 +1,157 @@
+use crate::utils::TronError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+// the files of the `app` template, a program with a module and a test for it
+const APP: [(&str, &str); 5] = [
+    (
+        "tron.toml",
+        "[project]\nname = \"{{name}}\"\nversion = \"0.1.0\"\nentry = \"main.tron\"\n\n[dependencies]\n",
+    ),
+    (
+        "main.tron",
+        "use \"src/greet.tron\";\n\n@print(greet(\"world\"));\n",
+    ),
+    (
+        "src/greet.tron",
+        "/// Returns the greeting for `name`.\nfn greet(name: string): string {\n    return \"Hello, \" + name + \"!\";\n}\n",
+    ),
+    (
+        "tests/greet_test.tron",
+        "use \"src/greet.tron\";\n\nif greet(\"Tron\") != \"Hello, Tron!\" {\n    @panic(\"greet failed\");\n}\n@print(\"greet_test passed\");\n",
+    ),
+    (".gitignore", GITIGNORE),
+];
+
+// the files of the `lib` template, a package other projects install with `tron add`
+const LIB: [(&str, &str); 4] = [
+    (
+        "tron.toml",
+        "[project]\nname = \"{{name}}\"\nversion = \"0.1.0\"\nentry = \"lib.tron\"\n\n[dependencies]\n",
+    ),
+    (
+        "lib.tron",
+        "/// Returns `value`, moved into the range from `low` to `high`.\nfn clamp(value: number, low: number, high: number): number {\n    if value < low {\n        return low;\n    }\n    if value > high {\n        return high;\n    }\n    return value;\n}\n",
+    ),
+    (
+        "tests/lib_test.tron",
+        "use \"lib.tron\";\n\nif clamp(12, 0, 10) != 10 || clamp(-1, 0, 10) != 0 || clamp(5, 0, 10) != 5 {\n    @panic(\"clamp failed\");\n}\n@print(\"lib_test passed\");\n",
+    ),
+    (".gitignore", GITIGNORE),
+];
+
+const GITIGNORE: &str = "tron_modules/\n*.tronc\ntron-crash-*.log\n";
+
+// creates a project from a template, `tron init [name] [--template app|lib|<dir>]`
+pub fn cli_init(args: &[String], path: PathBuf) {
+    let mut name = None;
+    let mut template = "app".to_string();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--template" => match args.next() {
+                Some(value) => template = value.clone(),
+                None => {
+                    TronError::throw("E0004", 0, vec![arg.clone()]);
+                    exit(64);
+                }
+            },
+            flag if flag.starts_with("--") => {
+                TronError::throw("E0004", 0, vec![flag.to_string()]);
+                exit(64);
+            }
+            value => name = Some(value.to_string()),
+        }
+    }
+    // `tron init hello` creates `hello/`, `tron init` uses the current directory and its name
+    let root = match &name {
+        Some(name) => path.join(name),
+        None => path,
+    };
+    let name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "app".to_string());
+    if root.join("tron.toml").exists() {
+        TronError::throw(
+            "E0007",
+            0,
+            vec![format!("{} already has a tron.toml", root.display())],
+        );
+        exit(1);
+    }
+    let files = template_files(&template).unwrap_or_else(|msg| {
+        TronError::throw("E0007", 0, vec![msg]);
+        exit(1);
+    });
+    for (file, contents) in files {
+        let target = root.join(&file);
+        // files the directory has already are kept, like a README of a repository just cloned
+        if target.exists() {
+            println!("\x1B[33mKept\x1B[0m {}", file);
+            continue;
+        }
+        let written = target
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&target, contents.replace("{{name}}", &name)));
+        if let Err(e) = written {
+            TronError::throw(
+                "E0010",
+                0,
+                vec![target.display().to_string(), e.to_string()],
+            );
+            exit(1);
+        }
+        println!("\x1B[32mCreated\x1B[0m {}", file);
+    }
+    println!(
+        "Created the project {} from the {} template",
+        name, template
+    );
+}
+
+// the files of a built-in template, or of a directory holding a custom one
+fn template_files(template: &str) -> Result<Vec<(String, String)>, String> {
+    let builtin: &[(&str, &str)] = match template {
+        "app" => &APP,
+        "lib" => &LIB,
+        dir => {
+            let dir = Path::new(dir);
+            if !dir.join("tron.toml").is_file() {
+                return Err(format!(
+                    "{} isn't a template, use app, lib or a directory with a tron.toml",
+                    dir.display()
+                ));
+            }
+            let mut files = vec![];
+            collect(dir, dir, &mut files)?;
+            return Ok(files);
+        }
+    };
+    Ok(builtin
+        .iter()
+        .map(|(file, contents)| (file.to_string(), contents.to_string()))
+        .collect())
+}
+
+// the text files under `dir`, with their paths relative to the template
+fn collect(root: &Path, dir: &Path, files: &mut Vec<(String, String)>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.file_name().is_some_and(|name| name == ".git") {
+            continue;
+        }
+        if path.is_dir() {
+            collect(root, &path, files)?;
+            continue;
+        }
+        let contents =
+            fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        files.push((relative.to_string_lossy().into_owned(), contents));
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use super::build::load_manifest;
+use crate::project::packages::{
+    add_dependency, install_package, read_lockfile, registry_source, write_lockfile, LockedPackage,
+};
+use crate::utils::TronError;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::{fs, process::exit};
+
+// installs the dependencies of tron.toml into tron_modules/ and records their commits in tron.lock
+pub fn cli_install(path: PathBuf) {
+    let manifest = load_manifest(&path);
+    let locked = read_lockfile(&path).unwrap_or_else(|msg| {
+        TronError::throw("E0007", 0, vec![msg]);
+        exit(1);
+    });
+    let mut lock = BTreeMap::new();
+    let mut failed = 0;
+    for (name, source) in &manifest.dependencies {
+        let previous = locked.get(name).filter(|package| package.source == *source);
+        println!("\x1B[36mInstalling\x1B[0m {} ({})", name, source);
+        match install_package(&path, name, source, previous.map(|p| p.commit.as_str())) {
+            Ok(commit) => {
+                lock.insert(
+                    name.clone(),
+                    LockedPackage {
+                        source: source.clone(),
+                        commit,
+                    },
+                );
+            }
+            Err(msg) => {
+                TronError::throw("E0008", 0, vec![name.clone(), msg]);
+                if let Some(package) = previous {
+                    lock.insert(name.clone(), package.clone());
+                }
+                failed += 1;
+            }
+        }
+    }
+    if let Err(msg) = write_lockfile(&path, &lock) {
+        TronError::throw("E0008", 0, vec!["tron.lock".to_string(), msg]);
+        exit(1);
+    }
+    if failed > 0 {
+        exit(1);
+    }
+    println!("Installed {} packages", lock.len());
+}
+
+// adds a dependency to tron.toml and installs it, `tron add <name> [git url]`
+pub fn cli_add(args: &[String], path: PathBuf) {
+    let manifest = load_manifest(&path);
+    let name = match args.first() {
+        Some(name) => name,
+        None => {
+            TronError::throw("E0002", 0, vec![]);
+            exit(64);
+        }
+    };
+    let source = match args.get(1) {
+        Some(source) => source.clone(),
+        None => registry_source(&manifest, name).unwrap_or_else(|msg| {
+            TronError::throw("E0008", 0, vec![name.clone(), msg]);
+            exit(1);
+        }),
+    };
+    // fetched before tron.toml is changed, so a wrong URL doesn't end up in the manifest
+    if let Err(msg) = install_package(&path, name, &source, None) {
+        TronError::throw("E0008", 0, vec![name.clone(), msg]);
+        exit(1);
+    }
+    let manifest_path = path.join("tron.toml");
+    let updated = fs::read_to_string(&manifest_path)
+        .map(|contents| add_dependency(&contents, name, &source))
+        .and_then(|contents| fs::write(&manifest_path, contents));
+    if let Err(e) = updated {
+        TronError::throw("E0007", 0, vec![e.to_string()]);
+        exit(1);
+    }
+    cli_install(path);
+}
@@ -1,11 +1,13 @@
+pub mod bench;
+pub mod build;
+pub mod check;
+pub mod doc;
+pub mod graph;
+pub mod help;
+pub mod init;
+pub mod install;
+pub mod repl;
 pub mod run;
-pub mod version;
 pub mod update;
-pub mod help;
-
-
-
-
-
-
-
+pub mod upgrade;
+pub mod version;
@@ -1 +0,0 @@
-// initialize new project
\ No newline at end of file
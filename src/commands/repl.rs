@@ -0,0 +1,233 @@
+use crate::cache::{load_snapshot, save_snapshot};
+use crate::commands::build::load_aliases;
+use crate::expressions::{CallableImpl, TronType};
+use crate::interpreter::Interpreter;
+use crate::library::docs::signature;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::scanner::{Directives, Scanner, Statement, INCOMPLETE_INPUT};
+use crate::settings::Settings;
+use crate::utils::TronError;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+const HELP: &str = "\
+:help           show this list
+:vars           list the variables and functions of the session
+:type <expr>    show the type of an expression without printing it
+:time <input>   run an input and show how long it took
+:clear          forget every variable and function
+:save <path>    write the variables, except functions, to a snapshot file
+:load <path>    define the variables of a snapshot file";
+
+// what became of an input: it ran, or it isn't finished and the next line continues it
+enum Input {
+    Done,
+    Incomplete,
+}
+
+// the state of a REPL session
+struct Session {
+    interpreter: Interpreter,
+    settings: Settings,
+}
+
+// an interactive session, every line runs in the same environment and bare expressions print their value
+pub fn cli_repl(flags: &[String], path: PathBuf) {
+    load_aliases(&path);
+    let mut session = Session::new(Settings::from_args(flags));
+    println!(
+        "Tron v{} REPL, :help for the commands, press Ctrl-D to exit",
+        env!("CARGO_PKG_VERSION")
+    );
+    // the lines of an input that isn't finished yet, an empty line runs it anyway to show what's wrong
+    let mut pending = String::new();
+    loop {
+        print!("{}", if pending.is_empty() { "> " } else { "... " });
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if pending.is_empty() && line.trim().is_empty() => continue,
+            Ok(_) if pending.is_empty() && line.trim_start().starts_with(':') => {
+                session.command(line.trim())
+            }
+            Ok(_) if line.trim().is_empty() => {
+                session.eval(&std::mem::take(&mut pending));
+            }
+            Ok(_) => {
+                pending.push_str(&line);
+                if let Input::Done = session.eval_incremental(&pending) {
+                    pending.clear();
+                }
+            }
+        }
+    }
+    println!();
+}
+
+impl Session {
+    fn new(settings: Settings) -> Self {
+        let mut interpreter = Interpreter::new();
+        interpreter.environment.set_settings(settings.clone());
+        interpreter.interactive = true;
+        Self {
+            interpreter,
+            settings,
+        }
+    }
+
+    // scans, parses and resolves an input, errors are already reported so the session just goes on,
+    // the expression ids continue the ones of the previous inputs
+    fn compile(&mut self, source: &str) -> Option<Vec<Statement>> {
+        let (tokens, directives) = Scanner::new(source).scan_tokens_with_directives().ok()?;
+        let mut parser = Parser::new(tokens).with_first_id(self.interpreter.environment.next_id());
+        self.resolve(parser.parse().ok()?, directives)
+    }
+
+    fn resolve(
+        &mut self,
+        statements: Vec<Statement>,
+        directives: Directives,
+    ) -> Option<Vec<Statement>> {
+        let resolver = Resolver::new().with_directives(directives);
+        let locals = resolver
+            .resolve(
+                &statements.iter().collect(),
+                &mut self.interpreter.environment,
+            )
+            .ok()?;
+        self.interpreter.resolve(locals);
+        Some(statements)
+    }
+
+    fn eval(&mut self, source: &str) {
+        if let Some(statements) = self.compile(source) {
+            self.run(statements);
+        }
+    }
+
+    fn run(&mut self, statements: Vec<Statement>) {
+        let _ = self.interpreter.interpret(statements.iter().collect());
+    }
+
+    // runs an input once it's complete
+    fn eval_incremental(&mut self, source: &str) -> Input {
+        match parse_incremental(source, self.interpreter.environment.next_id()) {
+            Ok((statements, directives)) => {
+                if let Some(statements) = self.resolve(statements, directives) {
+                    self.run(statements);
+                }
+                Input::Done
+            }
+            Err(true) => Input::Incomplete,
+            Err(false) => Input::Done,
+        }
+    }
+
+    // runs a `:command` of the session
+    fn command(&mut self, line: &str) {
+        let (name, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+        match (name, argument) {
+            (":help", _) => println!("{}", HELP),
+            (":vars", _) => self.vars(),
+            (":clear", _) => {
+                *self = Session::new(self.settings.clone());
+                println!("cleared the session");
+            }
+            (":type", source) if !source.is_empty() => self.type_of(source),
+            (":time", source) if !source.is_empty() => {
+                let start = Instant::now();
+                self.eval(source);
+                println!("took {:?}", start.elapsed());
+            }
+            (":save", path) if !path.is_empty() => self.save(path),
+            (":load", path) if !path.is_empty() => self.load(path),
+            (":type" | ":time", _) => println!("usage: {} <expression>", name),
+            (":save" | ":load", _) => println!("usage: {} <path>", name),
+            _ => println!("unknown command {}, :help lists the commands", name),
+        }
+    }
+
+    // the variables and functions defined in the session, natives and constants like `Infinity` aren't listed
+    fn variables(&self) -> Vec<(Rc<str>, TronType)> {
+        let standard = Interpreter::new().environment;
+        let mut variables: Vec<(Rc<str>, TronType)> = self
+            .interpreter
+            .environment
+            .values
+            .borrow()
+            .iter()
+            .filter(|(name, value)| {
+                !name.contains(' ')
+                    && !matches!(value, TronType::Callable(CallableImpl::StdFunction(_)))
+                    && !standard.values.borrow().contains_key(*name)
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+        variables
+    }
+
+    fn vars(&self) {
+        let variables = self.variables();
+        if variables.is_empty() {
+            println!("no variables");
+        }
+        for (name, value) in variables {
+            match value {
+                TronType::Callable(CallableImpl::Function(function)) => println!(
+                    "{}",
+                    signature(&function.name, &function.params, &function.output_type)
+                ),
+                value => println!("{}: {} = {:?}", name, value.to_type(), value),
+            }
+        }
+    }
+
+    // evaluates a single expression without echoing it and prints the name of its type
+    fn type_of(&mut self, source: &str) {
+        if let Ok(value) = self.interpreter.eval_expr(source) {
+            println!("{}", value.to_type());
+        }
+    }
+
+    // writes the variables of the session, except functions, to a snapshot file
+    fn save(&self, path: &str) {
+        match save_snapshot(Path::new(path), &self.variables()) {
+            Ok(count) => println!("saved {} variables to {}", count, path),
+            Err(msg) => TronError::throw("E0010", 0, vec![path.to_string(), msg]),
+        }
+    }
+
+    // defines the variables of a snapshot file in the session, replacing the ones with the same name
+    fn load(&mut self, path: &str) {
+        match load_snapshot(Path::new(path)) {
+            Ok(variables) => {
+                let count = variables.len();
+                for (name, value) in variables {
+                    self.interpreter.set_global(&name, value);
+                }
+                println!("loaded {} variables from {}", count, path);
+            }
+            Err(msg) => TronError::throw("E0011", 0, vec![path.to_string(), msg]),
+        }
+    }
+}
+
+// scans and parses an input that may continue on the next line, the error tells whether it's only incomplete,
+// an input that already had errors reported isn't worth continuing
+fn parse_incremental(source: &str, first_id: usize) -> Result<(Vec<Statement>, Directives), bool> {
+    let reported = TronError::reported();
+    let incomplete = |msg: String| msg == INCOMPLETE_INPUT && TronError::reported() == reported;
+    let (tokens, directives) = Scanner::new(source)
+        .incremental()
+        .scan_tokens_with_directives()
+        .map_err(incomplete)?;
+    let mut parser = Parser::new(tokens).incremental().with_first_id(first_id);
+    let statements = parser.parse().map_err(incomplete)?;
+    Ok((statements, directives))
+}
@@ -1,11 +1,21 @@
-use crate::run_file;
+use super::build::{load_aliases, load_manifest};
+use crate::project::check_imports;
+use crate::settings::Settings;
+use crate::utils::TronError;
+use crate::{run, run_file};
 use std::{path::PathBuf, process::exit};
 
-pub fn cli_run(command: &str, path: PathBuf) {
+pub fn cli_run(command: &str, flags: &[String], path: PathBuf) {
+    load_aliases(&path);
+    run_entry(command, flags, path);
+}
+
+// runs a file once the aliases of the project are installed
+fn run_entry(command: &str, flags: &[String], path: PathBuf) {
     let path_buf = path.join(command);
     let input = path_buf.to_str();
     match input {
-        Some(input) => match run_file(input) {
+        Some(input) => match run_file(input, Settings::from_args(flags)) {
             Ok(_) => exit(0),
             Err(_msg) => {
                 exit(1);
@@ -17,3 +27,25 @@ pub fn cli_run(command: &str, path: PathBuf) {
         }
     }
 }
+
+// runs the entry file of the project in the current directory after checking its imports
+pub fn cli_run_project(flags: &[String], path: PathBuf) {
+    let manifest = load_manifest(&path);
+    if check_imports(&path, &path.join(&manifest.entry)).is_err() {
+        exit(1);
+    }
+    run_entry(&manifest.entry, flags, path);
+}
+
+// runs the source given to `tron -e`, the flags are the ones of `tron run`
+pub fn cli_eval(args: &[String], path: PathBuf) {
+    load_aliases(&path);
+    let Some(source) = args.first() else {
+        TronError::throw("E0002", 0, vec![]);
+        exit(64);
+    };
+    match run(source, Settings::from_args(&args[1..]), None) {
+        Ok(_) => exit(0),
+        Err(_) => exit(1),
+    }
+}
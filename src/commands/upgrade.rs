@@ -0,0 +1,183 @@
+use crate::library::download::{download, fetch_text};
+use crate::utils::TronError;
+use std::{env, fs, process::exit};
+
+/// The version of the running interpreter.
+///
+/// ### Last Updated: (v3.2.0)
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// where the releases are published, `TRON_RELEASES` points at a mirror instead
+const RELEASES: &str = "https://tronlang.org/releases";
+
+// the latest release, `<releases>/latest` holds its version number, like `3.2.0` or `3.3.0-rc1`
+fn latest_version() -> Result<String, String> {
+    let latest = fetch_text(&format!("{}/latest", releases()))?;
+    let latest = latest.trim().trim_start_matches('v').to_string();
+    match parse_version(&latest) {
+        Some(_) => Ok(latest),
+        None => Err(format!(
+            "the latest release has the invalid version {}",
+            latest
+        )),
+    }
+}
+
+fn releases() -> String {
+    env::var("TRON_RELEASES").unwrap_or_else(|_| RELEASES.to_string())
+}
+
+// a version, compared by its numbers and then by whether it's a release, so a pre-release comes before its release
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    numbers: Vec<u32>,
+    release: bool,
+    pre_release: String,
+}
+
+// `3.10.0` with the numbers [3, 10, 0], and `3.2.0-rc1` with the same numbers as `3.2.0` and the pre-release `rc1`
+fn parse_version(version: &str) -> Option<Version> {
+    let (numbers, pre_release) = match version.split_once('-') {
+        Some((_, "")) => return None,
+        Some((numbers, pre_release)) => (numbers, pre_release),
+        None => (version, ""),
+    };
+    Some(Version {
+        numbers: numbers
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?,
+        release: pre_release.is_empty(),
+        pre_release: pre_release.to_string(),
+    })
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    parse_version(latest) > parse_version(current)
+}
+
+// reports whether a newer release is out, `tron version --check`
+pub fn cli_check_version() {
+    match latest_version() {
+        Ok(latest) if is_newer(&latest, VERSION) => {
+            println!(
+                "v{} is installed, \x1B[32mv{}\x1B[0m is available, run `tron upgrade` to install it",
+                VERSION, latest
+            );
+        }
+        Ok(_) => println!("v{} is the latest version", VERSION),
+        Err(msg) => {
+            TronError::throw("E0013", 0, vec![msg]);
+            exit(1);
+        }
+    }
+}
+
+// replaces the running executable with the latest release for the platform, after checking its checksum
+pub fn cli_upgrade() {
+    let latest = latest_version().unwrap_or_else(|msg| {
+        TronError::throw("E0013", 0, vec![msg]);
+        exit(1);
+    });
+    if !is_newer(&latest, VERSION) {
+        println!("v{} is the latest version", VERSION);
+        return;
+    }
+    println!("\x1B[36mUpgrading\x1B[0m v{} to v{}", VERSION, latest);
+    if let Err(msg) = upgrade(&latest) {
+        TronError::throw("E0012", 0, vec![format!("v{}", latest), msg]);
+        exit(1);
+    }
+    println!("Upgraded to v{}", latest);
+}
+
+fn upgrade(version: &str) -> Result<(), String> {
+    // `tron-linux-x86_64`, `tron-macos-aarch64` or `tron-windows-x86_64.exe`
+    let binary = format!(
+        "tron-{}-{}{}",
+        env::consts::OS,
+        env::consts::ARCH,
+        env::consts::EXE_SUFFIX
+    );
+    let url = format!("{}/v{}/{}", releases(), version, binary);
+    // `<sha256>  <file>`, as `sha256sum` prints it
+    let checksum = fetch_text(&format!("{}.sha256", url))
+        .map_err(|msg| format!("no release for {}: {}", binary, msg))?;
+    let checksum = checksum
+        .split_whitespace()
+        .next()
+        .filter(|hash| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or("the checksum of the release is invalid")?
+        .to_lowercase();
+    let current = env::current_exe().map_err(|e| e.to_string())?;
+    let new = current.with_extension("new");
+    download(&url, &new.to_string_lossy(), Some(&checksum))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&new, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+    // Windows doesn't replace a running executable, but lets it be renamed out of the way
+    #[cfg(windows)]
+    {
+        let old = current.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(&current, &old).map_err(|e| e.to_string())?;
+    }
+    let replaced = fs::rename(&new, &current);
+    // the old executable is put back, so a failed upgrade doesn't leave the interpreter missing
+    #[cfg(windows)]
+    {
+        if replaced.is_err() {
+            let _ = fs::rename(current.with_extension("old"), &current);
+        }
+    }
+    replaced.map_err(|e| {
+        format!(
+            "failed to replace {}, try again with the permissions to write it: {}",
+            current.display(),
+            e
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        let version = parse_version("3.10.0").unwrap();
+        assert_eq!(version.numbers, [3, 10, 0]);
+        assert!(version.release);
+        let version = parse_version("3.2.0-rc1").unwrap();
+        assert_eq!(version.numbers, [3, 2, 0]);
+        assert!(!version.release);
+        assert_eq!(version.pre_release, "rc1");
+        for invalid in ["", "3.x.0", "3..0", "3.2.0-", "<html>"] {
+            assert_eq!(parse_version(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn compares_versions_by_their_numbers() {
+        assert!(is_newer("3.2.0", "3.1.0"));
+        assert!(is_newer("3.10.0", "3.9.0"));
+        assert!(is_newer("4.0.0", "3.99.99"));
+        assert!(!is_newer("3.1.0", "3.1.0"));
+        assert!(!is_newer("3.0.9", "3.1.0"));
+    }
+
+    #[test]
+    fn a_pre_release_comes_before_its_release() {
+        assert!(is_newer("3.2.0-rc1", "3.1.0"));
+        assert!(is_newer("3.2.0", "3.2.0-rc1"));
+        assert!(is_newer("3.2.0-rc2", "3.2.0-rc1"));
+        assert!(!is_newer("3.2.0-rc1", "3.2.0"));
+    }
+
+    #[test]
+    fn an_invalid_version_is_never_newer() {
+        assert!(!is_newer("latest", "3.1.0"));
+    }
+}
@@ -1,3 +1,9 @@
-pub fn cli_version() {
-    println!("v3.1.0");
-}
\ No newline at end of file
+use super::upgrade::{cli_check_version, VERSION};
+
+// prints the installed version, `--check` also looks for a newer release
+pub fn cli_version(args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("--check") => cli_check_version(),
+        _ => println!("v{}", VERSION),
+    }
+}
@@ -0,0 +1,305 @@
+use super::Environment;
+use crate::expressions::{CallableImpl, TronType};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+type Values = RefCell<HashMap<Rc<str>, TronType>>;
+
+// collections don't run before this many scopes are tracked, and then when the live ones doubled
+const MIN_THRESHOLD: usize = 1000;
+
+// the scopes and enclosing links functions were declared in, as weak references so tracking them doesn't keep them
+// alive, a scope can be listed more than once until the next collection
+#[derive(Default)]
+struct Heap {
+    scopes: Vec<Weak<Values>>,
+    links: Vec<Weak<Environment>>,
+    threshold: usize,
+    collections: usize,
+    freed: usize,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap {
+        threshold: MIN_THRESHOLD,
+        ..Heap::default()
+    });
+}
+
+// what `@gc_stats()` reports: the scopes alive, and the collections run and scopes they emptied so far
+pub struct Stats {
+    pub scopes: usize,
+    pub collections: usize,
+    pub freed: usize,
+}
+
+// records the scopes of the environment a function is declared in, the only ones a cycle can go through since only
+// functions hold environments, and collects the cycles once enough scopes were recorded since the last collection
+pub fn track(environment: &Environment) {
+    let collect_now = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        let mut current = environment;
+        // the outermost scope lives as long as the program
+        while let Some(link) = &current.enclosing {
+            heap.scopes.push(Rc::downgrade(&current.values));
+            heap.links.push(Rc::downgrade(link));
+            current = link;
+        }
+        heap.scopes.len() >= heap.threshold
+    });
+    if collect_now {
+        collect();
+    }
+}
+
+pub fn stats() -> Stats {
+    HEAP.with(|heap| {
+        let heap = heap.borrow();
+        Stats {
+            scopes: heap.scopes.iter().filter(|s| s.strong_count() > 0).count(),
+            collections: heap.collections,
+            freed: heap.freed,
+        }
+    })
+}
+
+// A scope that declares a function is referenced by that function (its parent environment), so the two keep each other
+// alive with `Rc` alone. Cycles are found by trial deletion: the references between tracked nodes are subtracted from
+// their reference counts, whatever is left comes from outside (the interpreter, the Rust stack, natives) and makes a
+// node a root. Scopes that can't be reached from a root are garbage and are emptied, which breaks their cycles.
+// References this can't see, like the ones captured by natives, only ever keep more alive, never less.
+pub fn collect() -> usize {
+    let graph = HEAP.with(|heap| {
+        let heap = heap.borrow();
+        Graph::new(
+            heap.scopes.iter().filter_map(Weak::upgrade),
+            heap.links.iter().filter_map(Weak::upgrade),
+        )
+    });
+    // the upgrades above hold one reference each
+    let mut outside: Vec<usize> = graph.nodes.iter().map(|node| node.count() - 1).collect();
+    let mut edges: Vec<Vec<usize>> = Vec::with_capacity(graph.nodes.len());
+    for index in 0..graph.nodes.len() {
+        match graph.children(index) {
+            Some(children) => {
+                for &child in &children {
+                    outside[child] = outside[child].saturating_sub(1);
+                }
+                edges.push(children);
+            }
+            // a scope in use while the collection runs is always kept
+            None => {
+                outside[index] += 1;
+                edges.push(vec![]);
+            }
+        }
+    }
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<usize> = (0..graph.nodes.len())
+        .filter(|&index| outside[index] > 0)
+        .collect();
+    while let Some(index) = pending.pop() {
+        if reachable.insert(index) {
+            pending.extend(&edges[index]);
+        }
+    }
+    // emptied after the walk, and the values dropped once no scope is borrowed anymore
+    let mut garbage = vec![];
+    for (index, node) in graph.nodes.iter().enumerate() {
+        if let Node::Scope(values) = node {
+            if !reachable.contains(&index) {
+                garbage.push(std::mem::take(&mut *values.borrow_mut()));
+            }
+        }
+    }
+    let freed = garbage.len();
+    drop(garbage);
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.collections += 1;
+        heap.freed += freed;
+        // every scope once, without the ones that were freed
+        heap.scopes = graph.nodes.iter().filter_map(Node::scope).collect();
+        heap.links = graph.nodes.iter().filter_map(Node::link).collect();
+        heap.threshold = MIN_THRESHOLD.max(heap.scopes.len() * 2);
+    });
+    drop(graph);
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.scopes.retain(|scope| scope.strong_count() > 0);
+        heap.links.retain(|link| link.strong_count() > 0);
+    });
+    freed
+}
+
+enum Node {
+    Scope(Rc<Values>),
+    Link(Rc<Environment>),
+}
+
+impl Node {
+    fn scope(&self) -> Option<Weak<Values>> {
+        match self {
+            Node::Scope(values) => Some(Rc::downgrade(values)),
+            Node::Link(_) => None,
+        }
+    }
+    fn link(&self) -> Option<Weak<Environment>> {
+        match self {
+            Node::Link(link) => Some(Rc::downgrade(link)),
+            Node::Scope(_) => None,
+        }
+    }
+    fn count(&self) -> usize {
+        match self {
+            Node::Scope(values) => Rc::strong_count(values),
+            Node::Link(link) => Rc::strong_count(link),
+        }
+    }
+}
+
+struct Graph {
+    nodes: Vec<Node>,
+    scopes: HashMap<*const Values, usize>,
+    links: HashMap<*const Environment, usize>,
+}
+
+impl Graph {
+    // a scope listed more than once becomes a single node
+    fn new(
+        scopes: impl Iterator<Item = Rc<Values>>,
+        links: impl Iterator<Item = Rc<Environment>>,
+    ) -> Self {
+        let mut graph = Graph {
+            nodes: vec![],
+            scopes: HashMap::new(),
+            links: HashMap::new(),
+        };
+        for scope in scopes {
+            if !graph.scopes.contains_key(&Rc::as_ptr(&scope)) {
+                graph.scopes.insert(Rc::as_ptr(&scope), graph.nodes.len());
+                graph.nodes.push(Node::Scope(scope));
+            }
+        }
+        for link in links {
+            if !graph.links.contains_key(&Rc::as_ptr(&link)) {
+                graph.links.insert(Rc::as_ptr(&link), graph.nodes.len());
+                graph.nodes.push(Node::Link(link));
+            }
+        }
+        graph
+    }
+
+    // the tracked nodes a node references directly, once per reference, or `None` for a scope that is borrowed
+    fn children(&self, index: usize) -> Option<Vec<usize>> {
+        let mut children = vec![];
+        match &self.nodes[index] {
+            // a mutable borrow, so a scope borrowed in any way is found
+            Node::Scope(values) => {
+                for value in values.try_borrow_mut().ok()?.values() {
+                    self.value(value, &mut children);
+                }
+            }
+            Node::Link(link) => self.environment(link, &mut children),
+        }
+        Some(children)
+    }
+
+    fn environment(&self, environment: &Environment, children: &mut Vec<usize>) {
+        children.extend(self.scopes.get(&Rc::as_ptr(&environment.values)));
+        children.extend(self.scopes.get(&Rc::as_ptr(&environment.globals)));
+        if let Some(link) = &environment.enclosing {
+            children.extend(self.links.get(&Rc::as_ptr(link)));
+        }
+    }
+
+    fn value(&self, value: &TronType, children: &mut Vec<usize>) {
+        match value {
+            TronType::Callable(CallableImpl::Function(function)) => {
+                self.environment(&function.parent_env, children)
+            }
+            TronType::ArrayValue(values) => {
+                for value in values {
+                    self.value(value, children);
+                }
+            }
+            TronType::Object(fields) => {
+                for value in fields.values() {
+                    self.value(value, children);
+                }
+            }
+            // a deque a native is changing can't be looked into, its references then count as outside ones
+            TronType::Deque(deque) => {
+                if let Ok(elements) = deque.try_borrow() {
+                    for value in elements.iter() {
+                        self.value(value, children);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    // runs a script, then collects the cycles it left behind and returns how many scopes were emptied
+    fn collect_after(source: &str) -> (Interpreter, usize) {
+        let mut interpreter = Interpreter::new();
+        interpreter.execute(source).unwrap();
+        let freed = collect();
+        (interpreter, freed)
+    }
+
+    #[test]
+    fn a_function_declared_in_its_own_scope_is_reclaimed() {
+        let (_, freed) = collect_after("fn make() { fn inner() { return 1; } } make();");
+        assert_eq!(freed, 1);
+        assert_eq!(stats().scopes, 0);
+    }
+
+    #[test]
+    fn a_cycle_through_two_scopes_and_an_object_is_reclaimed() {
+        let (_, freed) = collect_after(
+            "fn make() {
+                let o = {};
+                { let b = 1; o = { f: fn() { return b; } }; }
+            }
+            make();",
+        );
+        assert_eq!(freed, 2);
+        assert_eq!(stats().scopes, 0);
+    }
+
+    #[test]
+    fn a_cycle_reachable_from_a_live_binding_is_kept() {
+        let (mut interpreter, freed) = collect_after(
+            "fn make() { let n = 7; fn inner() { return n; } return inner; }
+            let kept = make();",
+        );
+        assert_eq!(freed, 0);
+        assert_eq!(stats().scopes, 1);
+        // the scope wasn't emptied, the function still finds its variable
+        interpreter.execute("let result = kept();").unwrap();
+        assert_eq!(
+            interpreter.get_global("result"),
+            Some(TronType::Number(7.0))
+        );
+    }
+
+    #[test]
+    fn a_cycle_through_a_deque_is_reclaimed() {
+        let (_, freed) = collect_after(
+            "fn make() { let d = @deque([]); @push_back(d, fn() { return d; }); }
+            make();",
+        );
+        assert_eq!(freed, 1);
+        assert_eq!(stats().scopes, 0);
+    }
+}
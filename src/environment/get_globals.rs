@@ -1,17 +1,18 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::expressions::{CallableImpl, StdFunctionImpl, TronType};
+use crate::expressions::{Arity, CallableImpl, StdFunctionImpl, TronType};
+use crate::library::context::NativeContext;
 
 use super::clock_impl;
 
-pub fn get_globals() -> Rc<RefCell<HashMap<String, TronType>>> {
+pub fn get_globals() -> Rc<RefCell<HashMap<Rc<str>, TronType>>> {
     let mut env = HashMap::new();
     let fun_impl: StdFunctionImpl = StdFunctionImpl {
         name: "clock".to_string(),
-        arity: 0,
-        function: Rc::new(clock_impl),
+        arity: Arity::exactly(0),
+        function: Rc::new(|_: &NativeContext, args: &Vec<TronType>| clock_impl(args)),
     };
     let callable_impl = CallableImpl::StdFunction(fun_impl);
-    env.insert("clock".to_string(), TronType::Callable(callable_impl));
+    env.insert("clock".into(), TronType::Callable(callable_impl));
     Rc::new(RefCell::new(env))
 }
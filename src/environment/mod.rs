@@ -1,105 +1,207 @@
-use crate::{expressions::TronType, utils::TronError};
+use crate::{
+    expressions::{Arity, TronType},
+    library::registry::NativeRegistry,
+    settings::Settings,
+    utils::TronError,
+};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 mod clock_impl;
+pub mod gc;
 use clock_impl::clock_impl;
 mod get_globals;
 use get_globals::get_globals;
 
 #[derive(Clone, Debug)]
 pub struct Environment {
-    pub values: Rc<RefCell<HashMap<String, TronType>>>,
-    pub value_types: Rc<RefCell<HashMap<String, String>>>,
+    pub values: Rc<RefCell<HashMap<Rc<str>, TronType>>>,
     locals: Rc<RefCell<HashMap<usize, usize>>>,
-    pub enclosing: Option<Box<Environment>>,
+    // values of the outermost environment, shared by every environment so globals are found without walking the chain
+    globals: Rc<RefCell<HashMap<Rc<str>, TronType>>>,
+    pub settings: Rc<RefCell<Settings>>,
+    pub natives: Rc<RefCell<NativeRegistry>>,
+    pub enclosing: Option<Rc<Environment>>,
 }
 
 impl Environment {
     pub fn new(locals: HashMap<usize, usize>) -> Self {
+        let values = get_globals();
+        let mut natives = NativeRegistry::default();
+        natives.register("clock", Arity::exactly(0));
         Self {
-            values: get_globals(),
-            value_types: Rc::new(RefCell::new(HashMap::new())),
+            globals: values.clone(),
+            values,
             locals: Rc::new(RefCell::new(locals)),
+            settings: Rc::new(RefCell::new(Settings::default())),
+            natives: Rc::new(RefCell::new(natives)),
             enclosing: None,
         }
     }
-    pub fn get_value_type(&self, name: &str) -> Option<String> {
-        self.value_types.borrow().get(name).cloned()
-    }
-    pub fn set_value_type(&self, name: String, type_annotation: String) {
-        self.value_types.borrow_mut().insert(name, type_annotation);
+    pub fn set_settings(&self, settings: Settings) {
+        *self.settings.borrow_mut() = settings;
     }
     pub fn resolve(&self, locals: HashMap<usize, usize>) {
         for (key, val) in locals.iter() {
             self.locals.borrow_mut().insert(*key, *val);
         }
     }
+    // an expression id above every resolved one, code parsed from it into this environment can't collide with earlier code
+    pub fn next_id(&self) -> usize {
+        self.locals.borrow().keys().max().map_or(0, |id| id + 1)
+    }
     pub fn enclose(&self) -> Environment {
         Self {
             values: Rc::new(RefCell::new(HashMap::new())),
-            value_types: self.value_types.clone(),
             locals: self.locals.clone(),
-            enclosing: Some(Box::new(self.clone())),
+            globals: self.globals.clone(),
+            settings: self.settings.clone(),
+            natives: self.natives.clone(),
+            enclosing: Some(Rc::new(self.clone())),
         }
     }
-    pub fn define(&self, name: String, value: TronType) {
-        self.values.borrow_mut().insert(name, value);
+    pub fn define(&self, name: impl Into<Rc<str>>, value: TronType) {
+        self.values.borrow_mut().insert(name.into(), value);
     }
+    // variables are looked up where the resolver found them: `distance` environments up for locals, or in the globals
     pub fn get(&self, name: &str, expr_id: usize) -> Option<TronType> {
         let distance = self.locals.borrow().get(&expr_id).cloned();
-        self.get_internal(name, distance)
+        match distance {
+            Some(distance) => self.get_at(distance, name),
+            None => self.globals.borrow().get(name).cloned(),
+        }
     }
-    fn get_internal(&self, name: &str, distance: Option<usize>) -> Option<TronType> {
-        if distance.is_none() {
-            match &self.enclosing {
-                None => self.values.borrow().get(name).cloned(),
-                Some(env) => env.get_internal(name, distance),
-            }
-        } else {
-            let distance = distance.unwrap();
-            if distance == 0 {
-                self.values.borrow().get(name).cloned()
-            } else {
-                match &self.enclosing {
-                    None => {
-                        TronError::throw("E3004", 0, vec![]);
-                        Some(TronType::Null)
-                    }
-                    Some(env) => {
-                        assert!(distance > 0);
-                        env.get_internal(name, Some(distance - 1))
-                    }
-                }
+    // looks a name up in the outermost environment, ignoring the scopes found by the resolver
+    pub fn get_global(&self, name: &str) -> Option<TronType> {
+        self.globals.borrow().get(name).cloned()
+    }
+    // defines a name in the outermost environment, wherever this environment is nested
+    pub fn define_global(&self, name: impl Into<Rc<str>>, value: TronType) {
+        self.globals.borrow_mut().insert(name.into(), value);
+    }
+    // every name a lookup from this environment could find, for suggestions when a name isn't defined
+    pub fn visible_names(&self) -> Vec<Rc<str>> {
+        let mut names: Vec<Rc<str>> = self.globals.borrow().keys().cloned().collect();
+        names.extend(
+            self.natives
+                .borrow()
+                .natives()
+                .into_iter()
+                .map(|(name, _)| name),
+        );
+        let mut env = Some(self);
+        while let Some(current) = env {
+            names.extend(current.values.borrow().keys().cloned());
+            env = current.enclosing.as_deref();
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+    pub fn get_at(&self, distance: usize, name: &str) -> Option<TronType> {
+        match self.ancestor(distance) {
+            Some(env) => env.values.borrow().get(name).cloned(),
+            None => {
+                TronError::throw("E3004", 0, vec![]);
+                Some(TronType::Null)
             }
         }
     }
     pub fn assign(&self, name: &str, value: TronType, expr_id: usize) -> bool {
         let distance = self.locals.borrow().get(&expr_id).cloned();
-        self.assign_internal(name, value, distance)
-    }
-    fn assign_internal(&self, name: &str, value: TronType, distance: Option<usize>) -> bool {
-        if distance.is_none() {
-            match &self.enclosing {
-                Some(env) => env.assign_internal(name, value, distance),
-                None => match self.values.borrow_mut().insert(name.to_string(), value) {
-                    Some(_) => true,
-                    None => false,
-                },
-            }
-        } else {
-            let distance = distance.unwrap();
-            if distance == 0 {
-                self.values.borrow_mut().insert(name.to_string(), value);
+        match distance {
+            Some(distance) => self.assign_at(distance, name, value),
+            None => Self::store(&mut self.globals.borrow_mut(), name, value),
+        }
+    }
+    pub fn assign_at(&self, distance: usize, name: &str, value: TronType) -> bool {
+        match self.ancestor(distance) {
+            Some(env) => {
+                Self::store(&mut env.values.borrow_mut(), name, value);
                 true
-            } else {
-                match &self.enclosing {
-                    None => {
-                        TronError::throw("E3005", 0, vec![]);
-                        false
-                    }
-                    Some(env) => env.assign_internal(name, value, Some(distance - 1)),
-                };
+            }
+            None => {
+                TronError::throw("E3005", 0, vec![]);
+                false
+            }
+        }
+    }
+    // overwrites the value in place when the variable exists, so assignments don't allocate a new key
+    fn store(values: &mut HashMap<Rc<str>, TronType>, name: &str, value: TronType) -> bool {
+        match values.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
                 true
             }
+            None => {
+                values.insert(name.into(), value);
+                false
+            }
+        }
+    }
+    fn ancestor(&self, distance: usize) -> Option<&Environment> {
+        let mut env = self;
+        for _ in 0..distance {
+            env = env.enclosing.as_deref()?;
+        }
+        Some(env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    #[test]
+    fn variables_are_found_at_their_resolved_distance() {
+        // expression 1 was resolved one scope up, expression 2 wasn't resolved and is a global
+        let global = Environment::new(HashMap::from([(1, 1)]));
+        global.define("x", TronType::Number(1.0));
+        let outer = global.enclose();
+        outer.define("x", TronType::Number(2.0));
+        let inner = outer.enclose();
+        inner.define("x", TronType::Number(3.0));
+        assert_eq!(inner.get("x", 1), Some(TronType::Number(2.0)));
+        assert_eq!(inner.get("x", 2), Some(TronType::Number(1.0)));
+        assert_eq!(inner.get_at(0, "x"), Some(TronType::Number(3.0)));
+    }
+
+    #[test]
+    fn assignments_change_the_scope_at_their_resolved_distance() {
+        let global = Environment::new(HashMap::from([(1, 1)]));
+        let outer = global.enclose();
+        outer.define("x", TronType::Number(2.0));
+        let inner = outer.enclose();
+        inner.define("x", TronType::Number(3.0));
+        assert!(inner.assign("x", TronType::Number(20.0), 1));
+        assert_eq!(outer.get_at(0, "x"), Some(TronType::Number(20.0)));
+        assert_eq!(inner.get_at(0, "x"), Some(TronType::Number(3.0)));
+        // an unresolved assignment goes to the globals, and tells whether the name existed
+        assert!(!inner.assign("y", TronType::Number(4.0), 2));
+        assert_eq!(global.get_global("y"), Some(TronType::Number(4.0)));
+        assert!(inner.assign("y", TronType::Number(5.0), 2));
+    }
+
+    #[test]
+    fn a_closure_keeps_the_variable_it_was_resolved_to() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .execute(
+                "let a = \"global\";
+                let first = \"\";
+                let second = \"\";
+                {
+                    fn show() { return a; }
+                    first = show();
+                    let a = \"block\"; // tron:allow shadowing
+                    second = show();
+                }",
+            )
+            .unwrap();
+        for name in ["first", "second"] {
+            assert_eq!(
+                interpreter.get_global(name),
+                Some(TronType::StringValue("global".to_string()))
+            );
         }
     }
 }
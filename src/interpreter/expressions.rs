@@ -3,33 +3,108 @@ pub enum CallableImpl {
     Function(FunctionImpl),
     StdFunction(StdFunctionImpl),
 }
+impl CallableImpl {
+    /// Returns the name of the function or native, the way it's shown by `@print`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn name(&self) -> &str {
+        match self {
+            CallableImpl::Function(function) => &function.name,
+            CallableImpl::StdFunction(native) => &native.name,
+        }
+    }
+    /// Returns the number of arguments the function or native accepts.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn arity(&self) -> Arity {
+        match self {
+            CallableImpl::Function(function) => Arity::exactly(function.arity),
+            CallableImpl::StdFunction(native) => native.arity,
+        }
+    }
+}
 #[derive(Clone, Debug)]
 pub struct FunctionImpl {
     pub name: String,
     pub arity: usize,
     pub parent_env: Environment,
-    pub params: Vec<(Token, Token)>,
-    pub body: Vec<Box<Statement>>,
+    pub params: Rc<[(Token, Token)]>,
+    pub body: Rc<[Rc<Statement>]>,
     pub output_type: Token,
+    pub doc: Option<Rc<str>>,
+    // the source file the function is declared in, its errors point there wherever it's called from
+    pub file: usize,
 }
+pub type NativeFunction = Rc<dyn Fn(&NativeContext, &Vec<TronType>) -> TronType>;
 #[derive(Clone)]
 pub struct StdFunctionImpl {
     pub name: String,
-    pub arity: usize,
-    pub function: Rc<dyn Fn(&Vec<TronType>) -> TronType>,
+    pub arity: Arity,
+    pub function: NativeFunction,
 }
-#[derive(Clone)]
-
-pub struct ObjectImpl {
-    pub name: String,
-    pub params: Vec<(Token, Expression)>,
+/// The number of arguments a native accepts, checked before the native is called.
+///
+/// A native with a fixed number of arguments has the same `min` and `max`, one with optional arguments accepts
+/// anything from `min` to `max`, and a variadic one, like `@print`, has no `max`. A plain `usize` converts to a fixed
+/// arity, so `declare_function("@pow".to_string(), 2, ...)` still reads the way it always did.
+///
+/// # Fields
+///
+/// - `min`: The number of arguments the native needs.
+/// - `max`: The number of arguments the native accepts at most, `None` for any number.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
 }
-
-#[derive(Clone)]
-pub struct CallbackImpl {
-    pub id: usize,
-    pub args: Vec<(Token, Option<Token>)>,
-    pub body: Vec<Box<Statement>>,
+impl Arity {
+    /// An arity of exactly `count` arguments.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn exactly(count: usize) -> Self {
+        Self {
+            min: count,
+            max: Some(count),
+        }
+    }
+    /// A variadic arity of `min` arguments or more.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn at_least(min: usize) -> Self {
+        Self { min, max: None }
+    }
+    /// An arity of `min` to `max` arguments, for natives with optional arguments.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn between(min: usize, max: usize) -> Self {
+        Self {
+            min,
+            max: Some(max),
+        }
+    }
+    /// Returns whether a call with `count` arguments is valid.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+impl From<usize> for Arity {
+    fn from(count: usize) -> Self {
+        Arity::exactly(count)
+    }
+}
+impl std::fmt::Display for Arity {
+    // `2`, `1..3` or `1..`, the way `@name/arity` shows a native
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{}..{}", self.min, max),
+            None => write!(f, "{}..", self.min),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -43,6 +118,13 @@ pub enum TronType {
     ArrayValue(Vec<TronType>),
     Callable(CallableImpl),
     Object(HashMap<String, TronType>),
+    #[cfg(feature = "bigint")]
+    BigInt(crate::library::math::bigint::BigInt),
+    #[cfg(feature = "bigint")]
+    Decimal(crate::library::math::decimal::Decimal),
+    Set(crate::library::set::Set),
+    Deque(crate::library::queue::Deque),
+    Buffer(crate::library::string::buffer::Buffer),
 }
 
 use std::{
@@ -54,7 +136,7 @@ use std::{
 use TronType::*;
 impl std::fmt::Debug for TronType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 impl PartialEq for TronType {
@@ -81,6 +163,23 @@ impl PartialEq for TronType {
             (True, True) => true,
             (False, False) => true,
             (Null, Null) => true,
+            #[cfg(feature = "bigint")]
+            (BigInt(x), BigInt(y)) => x == y,
+            (Set(x), Set(y)) => x == y,
+            (Deque(x), Deque(y)) => crate::library::queue::deques_equal(x, y),
+            (Buffer(x), Buffer(y)) => x == y,
+            // an integer is equal to the number with the same value, so `@bigint(5) == 5`
+            #[cfg(feature = "bigint")]
+            (BigInt(x), Number(y)) | (Number(y), BigInt(x)) => {
+                crate::library::math::bigint::BigInt::from_f32(*y).is_some_and(|y| *x == y)
+            }
+            // and so is a decimal, `@decimal("0.50") == 0.5`
+            #[cfg(feature = "bigint")]
+            (Decimal(_), Decimal(_) | BigInt(_) | Number(_))
+            | (BigInt(_) | Number(_), Decimal(_)) => match (to_decimal(self), to_decimal(other)) {
+                (Some(x), Some(y)) => x == y,
+                _ => false,
+            },
             _ => false,
         }
     }
@@ -103,18 +202,37 @@ fn unwrap_as_string(literal: Option<LiteralValue>) -> String {
         }
     }
 }
-impl TronType {
-    pub fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for TronType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let string = match self {
             TronType::Object(fields) => {
+                let mut fields: Vec<_> = fields.iter().collect();
+                // hash maps iterate in a different order on every run
+                if deterministic::is_enabled() {
+                    fields.sort_by(|a, b| a.0.cmp(b.0));
+                }
                 let fields_str = fields
-                    .iter()
-                    .map(|(key, value)| format!("{}: {}", key, value.to_string()))
+                    .into_iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
                     .collect::<Vec<_>>()
                     .join(", ");
                 format!("{{{}}}", fields_str)
             }
             TronType::Number(x) => x.to_string(),
+            #[cfg(feature = "bigint")]
+            TronType::BigInt(x) => x.to_string(),
+            #[cfg(feature = "bigint")]
+            TronType::Decimal(x) => x.to_string(),
+            TronType::Set(x) => format!(
+                "set({})",
+                x.members()
+                    .iter()
+                    .map(|member| member.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TronType::Deque(x) => crate::library::queue::display_deque(x),
+            TronType::Buffer(x) => format!("buffer(\"{}\")", x.borrow()),
             TronType::ArrayValue(x) => format!("\"{:?}\"", x),
             TronType::StringValue(x) => format!("\"{}\"", x),
             TronType::True => "true".to_string(),
@@ -126,12 +244,29 @@ impl TronType {
             TronType::Callable(CallableImpl::StdFunction(StdFunctionImpl {
                 name, arity, ..
             })) => format!("{name}/{arity}"),
+        };
+        f.write_str(&string)
+    }
+}
+impl TronType {
+    pub fn to_plain_string(&self) -> String {
+        match self {
+            TronType::StringValue(x) => x.clone(),
+            TronType::Buffer(x) => x.borrow().clone(),
+            other => other.to_string(),
         }
     }
     pub fn to_type(&self) -> &str {
         match self {
             TronType::Object(_) => "object",
             TronType::Number(_) => "number",
+            #[cfg(feature = "bigint")]
+            TronType::BigInt(_) => "bigint",
+            #[cfg(feature = "bigint")]
+            TronType::Decimal(_) => "decimal",
+            TronType::Set(_) => "set",
+            TronType::Deque(_) => "deque",
+            TronType::Buffer(_) => "buffer",
             TronType::StringValue(_) => "string",
             TronType::ArrayValue(_) => "array",
             TronType::True => "boolean",
@@ -164,71 +299,71 @@ impl TronType {
             False
         }
     }
-    pub fn is_falsy(&self) -> TronType {
-        match self {
-            Object(_) => False,
-            Number(x) => {
-                if *x == 0.0 as f32 {
-                    True
-                } else {
-                    False
-                }
-            }
-            StringValue(s) => {
-                if s.len() == 0 {
-                    True
-                } else {
-                    False
-                }
-            }
-            ArrayValue(x) => {
-                if x.len() == 0 {
-                    True
-                } else {
-                    False
-                }
-            }
-            True => False,
-            False => True,
-            Null => True,
-            Callable(_) => True,
-        }
-    }
+    /// The `is_truthy()` method decides whether a value counts as true in conditions and logical operators.
+    ///
+    /// `false`, `null`, `0`, `NaN`, `""`, `[]` and `{}` are falsy. Every other value, including functions, is truthy.
+    /// The same rules are used by `if`, `while`, `!` and the logical operators.
+    ///
+    /// ### Last Updated: (v3.2.0)
     pub fn is_truthy(&self) -> TronType {
+        let truthy = match self {
+            Object(fields) => !fields.is_empty(),
+            Number(x) => *x != 0.0 && !x.is_nan(),
+            #[cfg(feature = "bigint")]
+            BigInt(x) => !x.is_zero(),
+            #[cfg(feature = "bigint")]
+            Decimal(x) => !x.is_zero(),
+            Set(x) => !x.members().is_empty(),
+            Deque(x) => !x.borrow().is_empty(),
+            Buffer(x) => !x.borrow().is_empty(),
+            StringValue(s) => !s.is_empty(),
+            ArrayValue(x) => !x.is_empty(),
+            True => true,
+            False => false,
+            Null => false,
+            Callable(_) => true,
+        };
+        TronType::from_bool(truthy)
+    }
+    /// The `is_condition_true()` method checks a value used as a condition (`if`, `while`, `!` and the logical operators).
+    ///
+    /// With the `--strict-bool` flag only `true` and `false` are accepted and any other value throws `E4022`,
+    /// otherwise the usual truthiness rules of `is_truthy()` apply, and `--warn-coercions` reports the conversion
+    /// with `W4001`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn is_condition_true(&self, context: &str, line: usize, environment: &Environment) -> bool {
         match self {
-            Object(_) => True,
-            Number(x) => {
-                if *x == 0.0 as f32 {
-                    False
-                } else {
-                    True
-                }
-            }
-
-            StringValue(s) => {
-                if s.len() == 0 {
-                    False
-                } else {
-                    True
-                }
-            }
-            ArrayValue(x) => {
-                if x.len() == 0 {
-                    False
-                } else {
-                    True
+            True => true,
+            False => false,
+            value => {
+                let settings = environment.settings.borrow();
+                if settings.strict_bool {
+                    TronError::throw(
+                        "E4022",
+                        line,
+                        vec![context.to_string(), value.to_type().to_string()],
+                    );
+                } else if settings.warn_coercions {
+                    TronError::warn(
+                        "W4001",
+                        line,
+                        vec![value.to_type().to_string(), context.to_string()],
+                    );
                 }
+                value.is_truthy() == True
             }
-            True => True,
-            False => False,
-            Null => False,
-            Callable(_) => False,
         }
     }
 }
+use crate::environment::gc;
+use crate::library::context::NativeContext;
+use crate::utils::suggest::did_you_mean;
+use crate::utils::{crash, deterministic, hints, sources};
 use crate::{scanner::Statement, utils::TronError, Interpreter, LiteralValue, Token, TokenType};
 
 use super::Environment;
+use crate::settings::Settings;
 #[derive(Clone)]
 pub enum Expression {
     Object {
@@ -242,28 +377,33 @@ pub enum Expression {
     },
     Array {
         id: usize,
-        elements: Vec<Box<Expression>>,
+        elements: Vec<Rc<Expression>>,
+    },
+    Index {
+        id: usize,
+        object: Rc<Expression>,
+        index: Rc<Expression>,
     },
     Assign {
         id: usize,
         name: Token,
-        value: Box<Expression>,
+        value: Rc<Expression>,
     },
     Binary {
         id: usize,
-        left: Box<Expression>,
+        left: Rc<Expression>,
         operator: Token,
-        right: Box<Expression>,
+        right: Rc<Expression>,
     },
     Call {
         id: usize,
-        callee: Box<Expression>,
+        callee: Rc<Expression>,
         paren: Token,
         arguments: Vec<Expression>,
     },
     Grouping {
         id: usize,
-        expression: Box<Expression>,
+        expression: Rc<Expression>,
     },
     Literal {
         id: usize,
@@ -271,14 +411,14 @@ pub enum Expression {
     },
     Logical {
         id: usize,
-        left: Box<Expression>,
+        left: Rc<Expression>,
         operator: Token,
-        right: Box<Expression>,
+        right: Rc<Expression>,
     },
     Unary {
         id: usize,
         operator: Token,
-        right: Box<Expression>,
+        right: Rc<Expression>,
     },
     Variable {
         id: usize,
@@ -287,15 +427,29 @@ pub enum Expression {
     Function {
         id: usize,
         name: Token,
-        params: Vec<(Token, Token)>,
-        body: Vec<Box<Statement>>,
+        params: Rc<[(Token, Token)]>,
+        body: Rc<[Rc<Statement>]>,
         output_type: Token,
-    }
+    },
+    Template {
+        id: usize,
+        parts: Vec<Expression>,
+    },
+    Comparison {
+        id: usize,
+        operands: Vec<Expression>,
+        operators: Vec<Token>,
+    },
+    OptionalGet {
+        id: usize,
+        object: Rc<Expression>,
+        key: Token,
+    },
 }
 
 impl std::fmt::Debug for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.get_id(), self.to_string())
+        write!(f, "{}: {}", self.get_id(), self)
     }
 }
 impl Hash for Expression {
@@ -328,6 +482,11 @@ impl Expression {
             } => *id,
             Expression::Object { id, properties: _ } => *id,
             Expression::Array { id, elements: _ } => *id,
+            Expression::Index {
+                id,
+                object: _,
+                index: _,
+            } => *id,
             Expression::Assign {
                 id,
                 name: _,
@@ -359,12 +518,23 @@ impl Expression {
                 right: _,
             } => *id,
             Expression::Variable { id, name: _ } => *id,
+            Expression::Template { id, parts: _ } => *id,
+            Expression::Comparison {
+                id,
+                operands: _,
+                operators: _,
+            } => *id,
+            Expression::OptionalGet {
+                id,
+                object: _,
+                key: _,
+            } => *id,
         }
     }
 }
-impl Expression {
-    pub fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let string = match self {
             Expression::Function {
                 id: _,
                 name,
@@ -384,7 +554,7 @@ impl Expression {
             Expression::Object { id: _, properties } => {
                 let properties_str = properties
                     .iter()
-                    .map(|(key, value)| format!("{}: {}", key.lexeme, value.to_string()))
+                    .map(|(key, value)| format!("{}: {}", key.lexeme, value))
                     .collect::<Vec<_>>()
                     .join(", ");
                 format!("{{{}}}", properties_str)
@@ -393,7 +563,7 @@ impl Expression {
                 id: _,
                 key,
                 name: _,
-            } => format!("{}", key.lexeme), //
+            } => key.lexeme.to_string(),
             Expression::Array { id: _, elements } => {
                 let elements_str = elements
                     .iter()
@@ -402,41 +572,36 @@ impl Expression {
                     .join(", ");
                 format!("[{}]", elements_str)
             }
+            Expression::Index {
+                id: _,
+                object,
+                index,
+            } => format!("({}[{}])", object, index),
             Expression::Assign { id: _, name, value } => {
-                format!("({name:?} = {}", value.to_string())
+                format!("({name:?} = {}", value)
             }
             Expression::Binary {
                 id: _,
                 left,
                 operator,
                 right,
-            } => format!(
-                "({} {} {})",
-                operator.lexeme,
-                left.to_string(),
-                right.to_string()
-            ),
+            } => format!("({} {} {})", operator.lexeme, left, right),
             Expression::Call {
                 id: _,
                 callee,
                 paren: _,
                 arguments,
-            } => format!("({} {:?})", (*callee).to_string(), arguments),
+            } => format!("({} {:?})", (*callee), arguments),
             Expression::Grouping { id: _, expression } => {
-                format!("(group {})", (*expression).to_string())
+                format!("(group {})", (*expression))
             }
-            Expression::Literal { id: _, value } => format!("{}", value.to_string()),
+            Expression::Literal { id: _, value } => value.to_string(),
             Expression::Logical {
                 id: _,
                 left,
                 operator,
                 right,
-            } => format!(
-                "({} {} {})",
-                operator.to_string(),
-                left.to_string(),
-                right.to_string()
-            ),
+            } => format!("({} {} {})", operator, left, right),
             Expression::Unary {
                 id: _,
                 operator,
@@ -447,10 +612,35 @@ impl Expression {
                 format!("({}({}))", operator_str, right_str)
             }
             Expression::Variable { id: _, name } => format!("(let {})", name.lexeme),
-        }
+            Expression::Template { id: _, parts } => {
+                let parts_str = parts
+                    .iter()
+                    .map(|part| part.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("(template {})", parts_str)
+            }
+            Expression::Comparison {
+                id: _,
+                operands,
+                operators,
+            } => {
+                let mut chain = operands[0].to_string();
+                for (operator, operand) in operators.iter().zip(operands.iter().skip(1)) {
+                    chain.push_str(&format!(" {} {}", operator.lexeme, operand));
+                }
+                format!("(chain {})", chain)
+            }
+            Expression::OptionalGet { id: _, object, key } => {
+                format!("({}?.{})", object, key.lexeme)
+            }
+        };
+        f.write_str(&string)
     }
-
+}
+impl Expression {
     pub fn evaluate(&self, environment: Environment) -> Result<TronType, String> {
+        crash::evaluating(self.get_id());
         match self {
             Expression::Function {
                 id: _,
@@ -460,39 +650,40 @@ impl Expression {
                 output_type,
             } => {
                 let function_impl = FunctionImpl {
-                    name: name.clone().lexeme,
+                    name: name.lexeme.to_string(),
                     arity: params.len(),
                     parent_env: environment.clone(),
                     params: params.clone(),
                     body: body.clone(),
                     output_type: output_type.clone(),
+                    doc: None,
+                    file: sources::current(),
                 };
-                let callback_env = environment.enclose();
-
-                let int = Interpreter::with_env(callback_env.clone());
-                let callable = int.make_function(&Statement::FunctionStatement {
-                    name: name.clone(),
-                    params: function_impl.clone().params,
-                    body: function_impl.clone().body,
-                    output_type: function_impl.clone().output_type,
-                    line: name.line_number,
-                });
-                let fun = TronType::Callable(CallableImpl::Function(callable));
-                environment.define(function_impl.clone().name.clone(), fun);
+                let callable = FunctionImpl {
+                    parent_env: environment.enclose(),
+                    ..function_impl.clone()
+                };
+                if name.token_type == TokenType::Identifier {
+                    gc::track(&callable.parent_env);
+                    let fun = TronType::Callable(CallableImpl::Function(callable));
+                    environment.define(name.lexeme.clone(), fun);
+                } else {
+                    gc::track(&environment);
+                }
                 Ok(TronType::Callable(CallableImpl::Function(function_impl)))
             }
             Expression::Object { id: _, properties } => {
                 let mut fields = HashMap::new();
                 for (key, value_expr) in properties {
                     let value = value_expr.evaluate(environment.clone())?;
-                    fields.insert(key.lexeme.clone(), value);
+                    fields.insert(key.lexeme.to_string(), value);
                 }
                 Ok(TronType::Object(fields))
             }
             Expression::ObjectCall { id, key, name } => {
                 let object = environment.get(&name.lexeme, *id);
                 match object {
-                    Some(TronType::Object(fields)) => match fields.get(&key.lexeme) {
+                    Some(TronType::Object(fields)) => match fields.get(&*key.lexeme) {
                         Some(value) => Ok(value.clone()),
                         None => Err(format!("Key '{}' not found in object", key.lexeme)),
                     },
@@ -500,70 +691,50 @@ impl Expression {
                 }
             }
             Expression::Array { id: _, elements } => {
-                if elements.len() == 2 {
-                    let array = elements[0].evaluate(environment.clone())?;
-                    let index = elements[1].evaluate(environment.clone())?;
-                    if let TronType::Number(index_num) = index {
-                        if let TronType::ArrayValue(arr) = array {
-                            let idx = index_num as usize;
-                            return arr.get(idx).cloned().ok_or_else(|| {
-                                TronError::throw("E4009", 0, vec![]);
-                                "".to_string()
-                            });
-                        }
-                    }
-
-                    TronError::throw("E4010", 0, vec![]);
-                    Ok(TronType::Null)
-                } else {
-                    let mut array_elements = Vec::new();
-                    for element_expr in elements.iter() {
-                        let evaluated = element_expr.evaluate(environment.clone())?;
-                        array_elements.push(evaluated);
+                let mut array_elements = Vec::new();
+                for element_expr in elements.iter() {
+                    let evaluated = element_expr.evaluate(environment.clone())?;
+                    array_elements.push(evaluated);
+                }
+                Ok(TronType::ArrayValue(array_elements))
+            }
+            Expression::Index {
+                id: _,
+                object,
+                index,
+            } => {
+                let array = object.evaluate(environment.clone())?;
+                let index = index.evaluate(environment.clone())?;
+                if let TronType::Number(index_num) = index {
+                    if let TronType::ArrayValue(arr) = array {
+                        let idx = index_num as usize;
+                        return arr.get(idx).cloned().ok_or_else(|| {
+                            TronError::throw("E4009", 0, vec![]);
+                            "".to_string()
+                        });
                     }
-                    Ok(TronType::ArrayValue(array_elements))
                 }
+                TronError::throw("E4010", 0, vec![]);
+                Ok(TronType::Null)
             }
             Expression::Assign { id: _, name, value } => {
                 if name.lexeme.chars().next().unwrap().is_uppercase() {
-                    TronError::throw("E4012", name.line_number, vec![]);
+                    TronError::throw_at("E4012", name.line_number, name.column, vec![]);
                 }
                 let new_value = (*value).evaluate(environment.clone())?;
                 let assign_success =
                     environment.assign(&name.lexeme, new_value.clone(), self.get_id());
-                let type_annotation = environment.get_value_type(&name.lexeme);
-                match type_annotation {
-                    Some(expected_type) => match (expected_type.as_str(), &new_value) {
-                        ("number", TronType::Number(_)) => {}
-                        ("string", TronType::StringValue(_)) => {}
-                        ("array", TronType::ArrayValue(_)) => {}
-                        ("object", TronType::Object(_)) => {}
-                        ("bool", TronType::True) | ("bool", TronType::False) => {}
-                        ("null", TronType::Null) => {}
-                        _ => TronError::throw(
-                            "E4003",
-                            0,
-                            vec![
-                                "variable".to_string(),
-                                name.lexeme.to_string(),
-                                expected_type,
-                                new_value.to_type().to_string(),
-                            ],
-                        ),
-                    },
-                    None => {}
-                }
                 if assign_success {
                     Ok(new_value)
                 } else {
-                    TronError::throw("E4011", name.line_number, vec![name.lexeme.to_string()]);
+                    undefined_variable(name, &environment);
                     Ok(TronType::Null)
                 }
             }
             Expression::Call {
                 id: _,
                 callee,
-                paren: _,
+                paren,
                 arguments,
             } => {
                 let callable: TronType = (*callee).evaluate(environment.clone())?;
@@ -576,10 +747,21 @@ impl Expression {
                         for argument in arguments {
                             evaluated_arguments.push(argument.evaluate(environment.clone())?);
                         }
-                        Ok((nativefun.function)(&evaluated_arguments))
+                        let context =
+                            NativeContext::new(environment, paren.line_number, paren.column);
+                        Ok(context.invoke(&nativefun, &evaluated_arguments))
                     }
-                    _ => {
-                        TronError::throw("E4013", 0, vec![]);
+                    value => {
+                        TronError::throw_at("E4013", paren.line_number, paren.column, vec![]);
+                        if let Expression::Variable { id, name } = callee.as_ref() {
+                            // a field of an object was found, or evaluating the callee would have failed
+                            let declared = name.lexeme.contains('.')
+                                || environment.get(&name.lexeme, *id).is_some();
+                            let type_name = value.to_type();
+                            let (hint, args) =
+                                hints::not_callable(&name.lexeme, declared.then_some(type_name));
+                            hints::show(hint, args);
+                        }
                         Ok(TronType::Null)
                     }
                 }
@@ -603,13 +785,21 @@ impl Expression {
                     match environment.get(&name.lexeme, self.get_id()) {
                         Some(value) => Ok(value.clone()),
                         None => {
-                            TronError::throw("E4011", name.line_number, vec![name.clone().lexeme]);
+                            undefined_variable(name, &environment);
                             Ok(TronType::Null)
                         }
                     }
                 }
             }
             Expression::Literal { id: _, value } => Ok((*value).clone()),
+            Expression::Template { id: _, parts } => {
+                let mut rendered = String::new();
+                for part in parts {
+                    let value = part.evaluate(environment.clone())?;
+                    rendered.push_str(&value.to_plain_string());
+                }
+                Ok(StringValue(rendered))
+            }
             Expression::Logical {
                 id: _,
                 left,
@@ -618,42 +808,65 @@ impl Expression {
             } => match operator.token_type {
                 TokenType::Or => {
                     let lhs_value = left.evaluate(environment.clone())?;
-                    let lhs_true = lhs_value.is_truthy();
-                    if lhs_true == True {
+                    if lhs_value.is_condition_true("or", operator.line_number, &environment) {
                         Ok(lhs_value)
                     } else {
-                        right.evaluate(environment.clone())
+                        let rhs_value = right.evaluate(environment.clone())?;
+                        rhs_value.is_condition_true("or", operator.line_number, &environment);
+                        Ok(rhs_value)
                     }
                 }
+                // `xor` needs both sides; it returns the only truthy operand, or `false` if both or neither are truthy
                 TokenType::Xor => {
                     let lhs_value = left.evaluate(environment.clone())?;
-                    let lhs_true = lhs_value.is_truthy();
-                    if lhs_true == True {
-                        Ok(False)
-                    } else {
-                        Ok(True)
+                    let rhs_value = right.evaluate(environment.clone())?;
+                    match (
+                        lhs_value.is_condition_true("xor", operator.line_number, &environment),
+                        rhs_value.is_condition_true("xor", operator.line_number, &environment),
+                    ) {
+                        (true, false) => Ok(lhs_value),
+                        (false, true) => Ok(rhs_value),
+                        _ => Ok(False),
                     }
                 }
+                // `nor` always returns a bool and skips the right side as soon as the left one is truthy
                 TokenType::Nor => {
                     let lhs_value = left.evaluate(environment.clone())?;
-                    let lhs_true = lhs_value.is_truthy();
-                    if lhs_true == False {
-                        Ok(True)
-                    } else {
-                        Ok(False)
+                    if lhs_value.is_condition_true("nor", operator.line_number, &environment) {
+                        return Ok(False);
                     }
+                    let rhs_value = right.evaluate(environment.clone())?;
+                    Ok(TronType::from_bool(!rhs_value.is_condition_true(
+                        "nor",
+                        operator.line_number,
+                        &environment,
+                    )))
                 }
                 TokenType::And => {
                     let lhs_value = left.evaluate(environment.clone())?;
-                    let lhs_true = lhs_value.is_truthy();
-                    if lhs_true == False {
-                        Ok(lhs_true)
+                    if lhs_value.is_condition_true("and", operator.line_number, &environment) {
+                        let rhs_value = right.evaluate(environment.clone())?;
+                        rhs_value.is_condition_true("and", operator.line_number, &environment);
+                        Ok(rhs_value)
                     } else {
+                        Ok(lhs_value)
+                    }
+                }
+                TokenType::QuestionQuestion => {
+                    let lhs_value = left.evaluate(environment.clone())?;
+                    if lhs_value == TronType::Null {
                         right.evaluate(environment.clone())
+                    } else {
+                        Ok(lhs_value)
                     }
                 }
-                operator => {
-                    TronError::throw("E4016", 0, vec![operator.to_string()]);
+                other => {
+                    TronError::throw_at(
+                        "E4016",
+                        operator.line_number,
+                        operator.column,
+                        vec![other.to_string()],
+                    );
                     Ok(TronType::Null)
                 }
             },
@@ -663,34 +876,58 @@ impl Expression {
                 operator,
                 right,
             } => {
-                let right = right.evaluate(environment)?;
+                let right = right.evaluate(environment.clone())?;
                 match (&right, operator.token_type) {
                     // minus
                     (Number(x), TokenType::Minus) => Ok(Number(-x)),
+                    #[cfg(feature = "bigint")]
+                    (BigInt(x), TokenType::Minus) => Ok(BigInt(-x)),
+                    #[cfg(feature = "bigint")]
+                    (Decimal(x), TokenType::Minus) => Ok(Decimal(-x)),
                     (True, TokenType::Minus) => Ok(False),
                     (False, TokenType::Minus) => Ok(True),
                     (e, TokenType::Minus) => {
-                        TronError::throw("E4015", 0, vec!["minus".to_string(), e.to_string()]);
+                        TronError::throw_at(
+                            "E4015",
+                            operator.line_number,
+                            operator.column,
+                            vec!["minus".to_string(), e.to_string()],
+                        );
                         Ok(TronType::Null)
                     }
                     (Number(x), TokenType::Increment) => Ok(Number(x + 1.0)),
                     (Number(x), TokenType::Decrement) => Ok(Number(x - 1.0)),
                     (e, TokenType::Increment) => {
-                        TronError::throw("E4015", 0, vec!["increment".to_string(), e.to_string()]);
+                        TronError::throw_at(
+                            "E4015",
+                            operator.line_number,
+                            operator.column,
+                            vec!["increment".to_string(), e.to_string()],
+                        );
 
                         Ok(TronType::Null)
                     }
                     (e, TokenType::Decrement) => {
-                        TronError::throw("E4015", 0, vec!["decrement".to_string(), e.to_string()]);
-                        Ok(TronType::Null)
-                    }
-                    (e, TokenType::Percent) => {
-                        TronError::throw("E4015", 0, vec!["percent".to_string(), e.to_string()]);
+                        TronError::throw_at(
+                            "E4015",
+                            operator.line_number,
+                            operator.column,
+                            vec!["decrement".to_string(), e.to_string()],
+                        );
                         Ok(TronType::Null)
                     }
-                    (any, TokenType::Bang) => Ok(any.is_falsy()),
+                    (any, TokenType::Bang) => Ok(TronType::from_bool(!any.is_condition_true(
+                        "not",
+                        operator.line_number,
+                        &environment,
+                    ))),
                     (e, f) => {
-                        TronError::throw("E4015", 0, vec![f.to_string(), e.to_string()]);
+                        TronError::throw_at(
+                            "E4015",
+                            operator.line_number,
+                            operator.column,
+                            vec![f.to_string(), e.to_string()],
+                        );
                         Ok(TronType::Null)
                     }
                 }
@@ -703,147 +940,656 @@ impl Expression {
             } => {
                 let left = left.evaluate(environment.clone())?;
                 let right = right.evaluate(environment.clone())?;
-                match (&left, operator.token_type, &right) {
-                    (Number(x), TokenType::Plus, Number(y)) => Ok(Number(x + y)),
-                    (StringValue(x), TokenType::Plus, Number(y)) => {
-                        Ok(StringValue(format!("{}{}", x, y.to_string())))
-                    }
-                    (Number(x), TokenType::Plus, StringValue(y)) => {
-                        Ok(StringValue(format!("{}{}", x.to_string(), y)))
-                    }
-                    (StringValue(x), TokenType::Plus, StringValue(y)) => {
-                        Ok(StringValue(format!("{}{}", x.to_string(), y)))
-                    }
-                    (Number(x), TokenType::Minus, Number(y)) => Ok(Number(x - y)),
-                    (Number(x), TokenType::Star, Number(y)) => Ok(Number(x * y)),
-                    (Number(x), TokenType::Slash, Number(y)) => Ok(Number(x / y)),
-                    (Number(x), TokenType::Greater, Number(y)) => Ok(TronType::from_bool(x > y)),
-                    (StringValue(x), TokenType::Greater, StringValue(y)) => {
-                        Ok(TronType::from_bool(x.len() > y.len()))
-                    }
-                    (Number(x), TokenType::GreaterEqual, Number(y)) => {
-                        Ok(TronType::from_bool(x >= y))
-                    }
-                    (StringValue(x), TokenType::GreaterEqual, StringValue(y)) => {
-                        Ok(TronType::from_bool(x.len() >= y.len()))
-                    }
-                    (Number(x), TokenType::Less, Number(y)) => Ok(TronType::from_bool(x < y)),
-                    (StringValue(x), TokenType::Less, StringValue(y)) => {
-                        Ok(TronType::from_bool(x.len() < y.len()))
-                    }
-                    (Number(x), TokenType::LessEqual, Number(y)) => Ok(TronType::from_bool(x <= y)),
-                    (StringValue(x), TokenType::LessEqual, StringValue(y)) => {
-                        Ok(TronType::from_bool(x.len() <= y.len()))
-                    }
-                    (StringValue(_), e, Number(_)) => {
-                        TronError::throw(
-                            "E4015",
-                            0,
-                            vec!["string and number".to_string(), e.to_string()],
-                        );
-                        Ok(TronType::Null)
-                    }
-                    (Number(_), e, StringValue(_)) => {
-                        TronError::throw(
-                            "E4015",
-                            0,
-                            vec!["number and string".to_string(), e.to_string()],
-                        );
-                        Ok(TronType::Null)
+                binary_operation(&left, operator, &right, &environment.settings.borrow())
+            }
+            Expression::OptionalGet { id: _, object, key } => {
+                match object.evaluate(environment.clone())? {
+                    TronType::Object(fields) => {
+                        Ok(fields.get(&*key.lexeme).cloned().unwrap_or(TronType::Null))
                     }
-                    (x, TokenType::BangEqual, y) => Ok(TronType::from_bool(x != y)),
-                    (x, TokenType::EqualEqual, y) => Ok(TronType::from_bool(x == y)),
-                    (f, e, c) => {
-                        TronError::throw(
-                            "E4015",
-                            0,
-                            vec![
-                                format!("{} and {}", f.to_string(), c.to_string()).to_string(),
-                                e.to_string(),
-                            ],
-                        );
-                        Ok(TronType::Null)
+                    _ => Ok(TronType::Null),
+                }
+            }
+            Expression::Comparison {
+                id: _,
+                operands,
+                operators,
+            } => {
+                let settings = environment.settings.borrow().clone();
+                let mut left = operands[0].evaluate(environment.clone())?;
+                for (operator, operand) in operators.iter().zip(operands.iter().skip(1)) {
+                    let right = operand.evaluate(environment.clone())?;
+                    let result = binary_operation(&left, operator, &right, &settings)?;
+                    if result.is_truthy() != True {
+                        return Ok(False);
                     }
+                    left = right;
                 }
+                Ok(True)
+            }
+        }
+    }
+}
+/// The `binary_operation` function applies a binary operator to two already evaluated values.
+///
+/// It is shared by plain binary expressions and chained comparisons, so that `a < b < c` compares
+/// every pair exactly like `a < b` would.
+///
+/// # Parameters
+///
+/// - `left`: The value on the left side of the operator.
+/// - `operator`: The operator token, its line and column are reported when the operands don't support it.
+/// - `right`: The value on the right side of the operator.
+/// - `settings`: The settings of the program: `--strict-types` disables the implicit string coercion of `+`, and
+///   `--warn-coercions` reports it.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn binary_operation(
+    left: &TronType,
+    operator: &Token,
+    right: &TronType,
+    settings: &Settings,
+) -> Result<TronType, String> {
+    match (left, operator.token_type, right) {
+        (Number(x), TokenType::Plus, Number(y)) => Ok(Number(x + y)),
+        (StringValue(x), TokenType::Plus, StringValue(y)) => Ok(StringValue(format!("{}{}", x, y))),
+        (StringValue(x), TokenType::Plus, y) if !settings.strict_types => {
+            warn_coercion(operator, y, settings);
+            Ok(StringValue(format!("{}{}", x, y.to_plain_string())))
+        }
+        (x, TokenType::Plus, StringValue(y)) if !settings.strict_types => {
+            warn_coercion(operator, x, settings);
+            Ok(StringValue(format!("{}{}", x.to_plain_string(), y)))
+        }
+        (ArrayValue(x), TokenType::Plus, ArrayValue(y)) => {
+            Ok(ArrayValue(x.iter().chain(y.iter()).cloned().collect()))
+        }
+        (Number(x), TokenType::Minus, Number(y)) => Ok(Number(x - y)),
+        (Number(x), TokenType::Star, Number(y)) => Ok(Number(x * y)),
+        (StringValue(x), TokenType::Star, Number(y))
+        | (Number(y), TokenType::Star, StringValue(x)) => {
+            match repeat_count(*y, x.len(), operator, "a string") {
+                Some(times) => Ok(StringValue(x.repeat(times))),
+                None => Ok(TronType::Null),
             }
         }
+        (ArrayValue(x), TokenType::Star, Number(y))
+        | (Number(y), TokenType::Star, ArrayValue(x)) => {
+            match repeat_count(*y, x.len(), operator, "an array") {
+                Some(times) => Ok(ArrayValue(
+                    std::iter::repeat_n(x.iter().cloned(), times)
+                        .flatten()
+                        .collect(),
+                )),
+                None => Ok(TronType::Null),
+            }
+        }
+        (Number(_), TokenType::Slash | TokenType::Percent, Number(y))
+            if *y == 0.0 && !settings.allow_infinity =>
+        {
+            let operation = match operator.token_type {
+                TokenType::Slash => "division",
+                _ => "remainder",
+            };
+            TronError::throw_at(
+                "E4024",
+                operator.line_number,
+                operator.column,
+                vec![operation.to_string()],
+            );
+            Ok(TronType::Null)
+        }
+        (Number(x), TokenType::Slash, Number(y)) => Ok(Number(x / y)),
+        // the remainder has the sign of the dividend, `-7 % 3` is -1
+        (Number(x), TokenType::Percent, Number(y)) => Ok(Number(x % y)),
+        #[cfg(feature = "bigint")]
+        (Decimal(_), _, Decimal(_) | BigInt(_) | Number(_))
+        | (BigInt(_) | Number(_), _, Decimal(_))
+            if is_arithmetic_or_order(operator) =>
+        {
+            Ok(decimal_operation(left, operator, right))
+        }
+        #[cfg(feature = "bigint")]
+        (BigInt(_), _, BigInt(_) | Number(_)) | (Number(_), _, BigInt(_))
+            if is_arithmetic_or_order(operator) =>
+        {
+            Ok(bigint_operation(left, operator, right))
+        }
+        (Number(x), TokenType::Greater, Number(y)) => Ok(TronType::from_bool(x > y)),
+        (StringValue(x), TokenType::Greater, StringValue(y)) => {
+            Ok(TronType::from_bool(x.len() > y.len()))
+        }
+        (Number(x), TokenType::GreaterEqual, Number(y)) => Ok(TronType::from_bool(x >= y)),
+        (StringValue(x), TokenType::GreaterEqual, StringValue(y)) => {
+            Ok(TronType::from_bool(x.len() >= y.len()))
+        }
+        (Number(x), TokenType::Less, Number(y)) => Ok(TronType::from_bool(x < y)),
+        (StringValue(x), TokenType::Less, StringValue(y)) => {
+            Ok(TronType::from_bool(x.len() < y.len()))
+        }
+        (Number(x), TokenType::LessEqual, Number(y)) => Ok(TronType::from_bool(x <= y)),
+        (StringValue(x), TokenType::LessEqual, StringValue(y)) => {
+            Ok(TronType::from_bool(x.len() <= y.len()))
+        }
+        (StringValue(_), e, Number(_)) => {
+            TronError::throw_at(
+                "E4015",
+                operator.line_number,
+                operator.column,
+                vec!["string and number".to_string(), e.to_string()],
+            );
+            Ok(TronType::Null)
+        }
+        (Number(_), e, StringValue(_)) => {
+            TronError::throw_at(
+                "E4015",
+                operator.line_number,
+                operator.column,
+                vec!["number and string".to_string(), e.to_string()],
+            );
+            Ok(TronType::Null)
+        }
+        (Callable(first), TokenType::GreaterGreater, Callable(second)) => {
+            Ok(compose(first, second))
+        }
+        (x, TokenType::BangEqual, y) => Ok(TronType::from_bool(x != y)),
+        (x, TokenType::EqualEqual, y) => Ok(TronType::from_bool(x == y)),
+        (f, e, c) => {
+            TronError::throw_at(
+                "E4015",
+                operator.line_number,
+                operator.column,
+                vec![format!("{} and {}", f, c).to_string(), e.to_string()],
+            );
+            Ok(TronType::Null)
+        }
+    }
+}
+// the longest string or array `*` builds, a longer one is most likely a mistake and would run out of memory
+const MAX_REPEATED_LENGTH: usize = 1 << 30;
+
+// how many times `*` repeats a string or an array of `length`, a negative count repeats it no times, or `None` after
+// reporting a count that isn't a whole number or makes the result too long
+fn repeat_count(count: f32, length: usize, operator: &Token, kind: &str) -> Option<usize> {
+    let times = count.max(0.0);
+    if count.is_finite()
+        && times.fract() == 0.0
+        && times as f64 * length as f64 <= MAX_REPEATED_LENGTH as f64
+    {
+        return Some(times as usize);
+    }
+    TronError::throw_at(
+        "E4039",
+        operator.line_number,
+        operator.column,
+        vec![kind.to_string(), Number(count).to_string()],
+    );
+    None
+}
+// the operators big integers and decimals implement, besides `==` and `!=` that every value does
+#[cfg(feature = "bigint")]
+fn is_arithmetic_or_order(operator: &Token) -> bool {
+    matches!(
+        operator.token_type,
+        TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual
+    )
+}
+// a number, big integer or decimal as a decimal, `None` for any other value and for infinity and `NaN`
+#[cfg(feature = "bigint")]
+fn to_decimal(value: &TronType) -> Option<crate::library::math::decimal::Decimal> {
+    use crate::library::math::decimal::Decimal as Fixed;
+    match value {
+        Decimal(x) => Some(x.clone()),
+        BigInt(x) => Some(Fixed::from_bigint(x.clone())),
+        Number(x) => Fixed::from_f32(*x),
+        _ => None,
     }
 }
+/// Applies an arithmetic or comparison operator to decimals, for `binary_operation()`.
+///
+/// A number or big integer mixed with a decimal is converted to one, so `@decimal("19.99") * 3` is a decimal. The
+/// number is converted to the digits it's shown with, `0.1` is exactly `0.1`. Dividing by 0 throws `E4038`, which
+/// `--allow-infinity` doesn't change since there is no infinite decimal. Mixing in an infinite number or `NaN` throws
+/// `E4015`.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(feature = "bigint")]
+fn decimal_operation(left: &TronType, operator: &Token, right: &TronType) -> TronType {
+    let (Some(x), Some(y)) = (to_decimal(left), to_decimal(right)) else {
+        TronError::throw_at(
+            "E4015",
+            operator.line_number,
+            operator.column,
+            vec![
+                format!(
+                    "decimal and infinite number ({} and {})",
+                    left.to_string(),
+                    right.to_string()
+                ),
+                operator.token_type.to_string(),
+            ],
+        );
+        return TronType::Null;
+    };
+    let quotient = match operator.token_type {
+        TokenType::Plus => return Decimal(&x + &y),
+        TokenType::Minus => return Decimal(&x - &y),
+        TokenType::Star => return Decimal(&x * &y),
+        TokenType::Slash => x.div(&y),
+        TokenType::Percent => x.rem(&y),
+        TokenType::Greater => return TronType::from_bool(x > y),
+        TokenType::GreaterEqual => return TronType::from_bool(x >= y),
+        TokenType::Less => return TronType::from_bool(x < y),
+        _ => return TronType::from_bool(x <= y),
+    };
+    match quotient {
+        Some(quotient) => Decimal(quotient),
+        None => {
+            let operation = match operator.token_type {
+                TokenType::Slash => "division",
+                _ => "remainder",
+            };
+            TronError::throw_at(
+                "E4038",
+                operator.line_number,
+                operator.column,
+                vec![operation.to_string(), "decimal".to_string()],
+            );
+            TronType::Null
+        }
+    }
+}
+/// Applies an arithmetic or comparison operator to big integers, for `binary_operation()`.
+///
+/// A number mixed with a big integer is converted to one, so `@bigint(2) * 3` is a big integer too. The number has to
+/// be an integer itself, a fraction like `0.5` can't be added to a big integer without losing it, which throws
+/// `E4015`. Division rounds toward 0, like the integer division of most languages, and dividing by 0 throws `E4038`,
+/// which `--allow-infinity` doesn't change since there is no infinite big integer.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(feature = "bigint")]
+fn bigint_operation(left: &TronType, operator: &Token, right: &TronType) -> TronType {
+    use crate::library::math::bigint::BigInt as Integer;
+    let to_integer = |value: &TronType| match value {
+        BigInt(x) => Some(x.clone()),
+        Number(x) => Integer::from_f32(*x),
+        _ => None,
+    };
+    let (Some(x), Some(y)) = (to_integer(left), to_integer(right)) else {
+        TronError::throw_at(
+            "E4015",
+            operator.line_number,
+            operator.column,
+            vec![
+                format!(
+                    "bigint and fractional number ({} and {})",
+                    left.to_string(),
+                    right.to_string()
+                ),
+                operator.token_type.to_string(),
+            ],
+        );
+        return TronType::Null;
+    };
+    match operator.token_type {
+        TokenType::Plus => BigInt(&x + &y),
+        TokenType::Minus => BigInt(&x - &y),
+        TokenType::Star => BigInt(&x * &y),
+        TokenType::Slash | TokenType::Percent => match x.div_rem(&y) {
+            Some((quotient, _)) if operator.token_type == TokenType::Slash => BigInt(quotient),
+            Some((_, remainder)) => BigInt(remainder),
+            None => {
+                let operation = match operator.token_type {
+                    TokenType::Slash => "division",
+                    _ => "remainder",
+                };
+                TronError::throw_at(
+                    "E4038",
+                    operator.line_number,
+                    operator.column,
+                    vec![operation.to_string(), "bigint".to_string()],
+                );
+                TronType::Null
+            }
+        },
+        TokenType::Greater => TronType::from_bool(x > y),
+        TokenType::GreaterEqual => TronType::from_bool(x >= y),
+        TokenType::Less => TronType::from_bool(x < y),
+        _ => TronType::from_bool(x <= y),
+    }
+}
+/// Composes two functions for `>>`: the result takes the arguments of `first` and passes what it returns to `second`.
+///
+/// The composition is a native named after both functions, like `trim >> upper`, so it can be composed again, passed
+/// around and called like any other function. An error of either call is reported as `E4023`.
+///
+/// ### Last Updated: (v3.2.0)
+fn compose(first: &CallableImpl, second: &CallableImpl) -> TronType {
+    let name = format!("{} >> {}", first.name(), second.name());
+    let arity = first.arity();
+    let (first, second) = (Callable(first.clone()), Callable(second.clone()));
+    let composed_name = name.clone();
+    Callable(CallableImpl::StdFunction(StdFunctionImpl {
+        name,
+        arity,
+        function: Rc::new(move |context: &NativeContext, args: &Vec<TronType>| {
+            context
+                .call(&first, args.clone())
+                .and_then(|value| context.call(&second, vec![value]))
+                .unwrap_or_else(|msg| {
+                    context.throw("E4023", vec![composed_name.clone(), msg]);
+                    TronType::Null
+                })
+        }),
+    }))
+}
+/// Reports `W4002` with `--warn-coercions` when `+` converts a value that isn't a string to a string.
+///
+/// ### Last Updated: (v3.2.0)
+fn warn_coercion(operator: &Token, value: &TronType, settings: &Settings) {
+    if settings.warn_coercions {
+        TronError::warn_at(
+            "W4002",
+            operator.line_number,
+            operator.column,
+            vec![value.to_type().to_string()],
+        );
+    }
+}
+/// Reports `E4011` for a name that isn't defined, suggesting the closest name visible from `environment` (a variable of
+/// an enclosing scope, a global or a native) when there's one.
+///
+/// ### Last Updated: (v3.2.0)
+fn undefined_variable(name: &Token, environment: &Environment) {
+    let names = environment.visible_names();
+    let mut args = vec![name.lexeme.to_string()];
+    args.extend(did_you_mean(&name.lexeme, names.iter().map(|n| &**n)));
+    TronError::throw_at("E4011", name.line_number, name.column, args);
+}
 pub fn run_tron_function(
     tronfun: FunctionImpl,
     arguments: &Vec<Expression>,
     eval_env: Environment,
 ) -> Result<TronType, String> {
-    if arguments.len() != tronfun.arity {
+    let mut arg_vals = vec![];
+    for arg in arguments {
+        let val = arg.evaluate(eval_env.clone())?;
+        arg_vals.push(val);
+    }
+    call_tron_function(tronfun, arg_vals)
+}
+/// Runs a Tron function with arguments that are already evaluated, in a new environment enclosing the one the function
+/// was declared in.
+///
+/// # Return Value
+///
+/// The value the function returns, `null` when it ends without a `return`, or an error message when the number of
+/// arguments doesn't match its parameters.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn call_tron_function(
+    tronfun: FunctionImpl,
+    arg_vals: Vec<TronType>,
+) -> Result<TronType, String> {
+    if arg_vals.len() != tronfun.arity {
         return Err(format!(
             "Callable {} expected {} arguments but got {}",
             tronfun.name,
             tronfun.arity,
-            arguments.len()
+            arg_vals.len()
         ));
     }
-    let mut arg_vals = vec![];
-    for arg in arguments {
-        let val = arg.evaluate(eval_env.clone())?;
-        arg_vals.push(val);
-    }
     let fun_env = tronfun.parent_env.enclose();
     for (i, val) in arg_vals.iter().enumerate() {
         if i < tronfun.params.len() {
-            let (param_name_token, param_type_token) = &tronfun.params[i];
-            let param_name_lexeme = &param_name_token.lexeme;
-
-            let param_type_lexeme = &param_type_token.lexeme;
-
-            match (param_type_lexeme.as_str(), val) {
-                ("number", TronType::Number(_)) => {}
-                ("string", TronType::StringValue(_)) => {}
-                ("array", TronType::ArrayValue(_)) => {}
-                ("object", TronType::Object(_)) => {}
-                ("bool", TronType::True) | ("bool", TronType::False) => {}
-                ("null", TronType::Null) => {}
-                _ => {
-                    TronError::throw(
-                        "E4002",
-                        0,
-                        vec![
-                            tronfun.name.to_string(),
-                            param_name_lexeme.to_string(),
-                            val.to_type().to_string(),
-                            param_type_lexeme.to_string(),
-                        ],
-                    );
-                }
-            }
-
-            fun_env.define(param_name_lexeme.clone(), val.clone());
+            // the parameter types are only checked by `tron check`
+            let (param_name_token, _) = &tronfun.params[i];
+            fun_env.define(param_name_token.lexeme.clone(), val.clone());
         } else {
             TronError::throw("E4014", 0, vec![]);
         }
     }
     let mut int = Interpreter::with_env(fun_env);
+    let caller_file = sources::switch(tronfun.file);
+    crash::enter_call(&tronfun.name);
+    let mut value = TronType::Null;
     for stmt in tronfun.body.iter() {
-        let result = int.interpret(vec![stmt.as_ref()]);
-        if let Err(_e) = result {
-            TronError::throw("E4006", 0, vec![]);
-        } else if let Some(value) = int.specials.get("return") {
-            let output_type_lexeme = &tronfun.output_type.lexeme;
-            let value_clone = value.clone();
-            let value_clone_type = value_clone.to_type();
-            let value_clone_string = value_clone.to_string();
-            if !(output_type_lexeme == value_clone_type
-                || output_type_lexeme.clone() == value_clone_string)
-            {
-                TronError::throw("E4017", 0, vec![]);
+        match int.interpret(vec![stmt.as_ref()]) {
+            // `return` ends the function from any block or loop of its body
+            Err(signal) if signal == "return" => {
+                value = int.specials.remove("return").unwrap_or(TronType::Null);
+                break;
             }
-            return Ok(value.clone());
+            Err(_e) => TronError::throw("E4006", 0, vec![]),
+            Ok(()) => {}
         }
     }
+    crash::leave_call();
+    sources::leave(caller_file);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    // runs a script and returns the interpreter, with the globals it left behind
+    fn run(source: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.execute(source).unwrap();
+        interpreter
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> TronType {
+        interpreter.get_global(name).unwrap()
+    }
+
+    // `left operator right`, with the settings of a program
+    fn operate(
+        left: TronType,
+        operator: TokenType,
+        right: TronType,
+        settings: &Settings,
+    ) -> TronType {
+        let operator = Token {
+            token_type: operator,
+            lexeme: "operator".into(),
+            literal: None,
+            line_number: 1,
+            column: 1,
+        };
+        binary_operation(&left, &operator, &right, settings).unwrap()
+    }
+
+    #[test]
+    fn a_chained_comparison_checks_every_pair() {
+        let interpreter = run("let x = 5;
+            let inside = 0 < x < 10;
+            let above = 0 < 11 < 10;
+            let below = 0 < -1 < 10;
+            let descending = 10 >= x > 1 >= 1;");
+        assert_eq!(global(&interpreter, "inside"), True);
+        assert_eq!(global(&interpreter, "above"), False);
+        assert_eq!(global(&interpreter, "below"), False);
+        assert_eq!(global(&interpreter, "descending"), True);
+    }
+
+    #[test]
+    fn a_chained_comparison_evaluates_each_operand_once_and_stops_early() {
+        let interpreter = run("let calls = 0;
+            fn middle() { calls = calls + 1; return 5; }
+            let once = 0 < middle() <= 5;
+            let after_once = calls;
+            let skipped = 10 < 0 < middle();");
+        assert_eq!(global(&interpreter, "once"), True);
+        assert_eq!(global(&interpreter, "after_once"), Number(1.0));
+        assert_eq!(global(&interpreter, "skipped"), False);
+        assert_eq!(global(&interpreter, "calls"), Number(1.0));
+    }
+
+    #[test]
+    fn and_and_or_return_one_of_their_operands() {
+        let interpreter = run("let input = \"\";
+            let name = input || \"guest\";
+            let given = \"ada\" || \"guest\";
+            let first_falsy = 0 & \"never\";
+            let last = 1 & \"both\";");
+        assert_eq!(
+            global(&interpreter, "name"),
+            StringValue("guest".to_string())
+        );
+        assert_eq!(
+            global(&interpreter, "given"),
+            StringValue("ada".to_string())
+        );
+        assert_eq!(global(&interpreter, "first_falsy"), Number(0.0));
+        assert_eq!(
+            global(&interpreter, "last"),
+            StringValue("both".to_string())
+        );
+    }
 
-    let output_type_lexeme = &tronfun.output_type.lexeme;
-    if output_type_lexeme != "null" {
-        TronError::throw("E4017", 0, vec![]);
+    #[test]
+    fn and_and_or_short_circuit() {
+        let interpreter = run("let calls = 0;
+            fn touch() { calls = calls + 1; return true; }
+            let a = true || touch();
+            let b = false & touch();
+            let c = 1 nor touch();");
+        assert_eq!(global(&interpreter, "calls"), Number(0.0));
+        assert_eq!(global(&interpreter, "c"), False);
     }
 
-    Ok(TronType::Null)
+    #[test]
+    fn xor_returns_the_only_truthy_operand_and_nor_a_bool() {
+        let interpreter = run("let left = \"a\" xor 0;
+            let right = null xor 2;
+            let both = 1 xor 2;
+            let neither = 0 xor \"\";
+            let nor_falsy = 0 nor null;
+            let nor_truthy = 0 nor 3;");
+        assert_eq!(global(&interpreter, "left"), StringValue("a".to_string()));
+        assert_eq!(global(&interpreter, "right"), Number(2.0));
+        assert_eq!(global(&interpreter, "both"), False);
+        assert_eq!(global(&interpreter, "neither"), False);
+        assert_eq!(global(&interpreter, "nor_falsy"), True);
+        assert_eq!(global(&interpreter, "nor_truthy"), False);
+    }
+
+    #[test]
+    fn null_coalescing_only_replaces_null() {
+        let interpreter = run("let calls = 0;
+            fn touch() { calls = calls + 1; return 1; }
+            let replaced = null ?? \"fallback\";
+            let zero = 0 ?? 1;
+            let empty = \"\" ?? \"fallback\";
+            let kept = \"x\" ?? touch();");
+        assert_eq!(
+            global(&interpreter, "replaced"),
+            StringValue("fallback".to_string())
+        );
+        assert_eq!(global(&interpreter, "zero"), Number(0.0));
+        assert_eq!(global(&interpreter, "empty"), StringValue(String::new()));
+        assert_eq!(global(&interpreter, "kept"), StringValue("x".to_string()));
+        assert_eq!(global(&interpreter, "calls"), Number(0.0));
+    }
+
+    #[test]
+    fn optional_chaining_returns_null_instead_of_failing() {
+        let interpreter = run("let user = { name: \"ada\", address: null };
+            let name = user?.name;
+            let missing = user?.age;
+            let nested = user?.address?.city;
+            let not_an_object = 5?.name;
+            let fallback = user?.age ?? \"unknown\";");
+        assert_eq!(global(&interpreter, "name"), StringValue("ada".to_string()));
+        assert_eq!(global(&interpreter, "missing"), Null);
+        assert_eq!(global(&interpreter, "nested"), Null);
+        assert_eq!(global(&interpreter, "not_an_object"), Null);
+        assert_eq!(
+            global(&interpreter, "fallback"),
+            StringValue("unknown".to_string())
+        );
+    }
+
+    #[test]
+    fn dividing_by_zero_is_an_error() {
+        let settings = Settings::default();
+        for operator in [TokenType::Slash, TokenType::Percent] {
+            assert_eq!(operate(Number(1.0), operator, Number(0.0), &settings), Null);
+            assert_eq!(
+                operate(Number(0.0), operator, Number(-0.0), &settings),
+                Null
+            );
+        }
+        assert_eq!(
+            operate(Number(7.0), TokenType::Slash, Number(2.0), &settings),
+            Number(3.5)
+        );
+    }
+
+    #[test]
+    fn allow_infinity_keeps_the_float_results_of_dividing_by_zero() {
+        let settings = Settings {
+            allow_infinity: true,
+            ..Settings::default()
+        };
+        assert_eq!(
+            operate(Number(1.0), TokenType::Slash, Number(0.0), &settings),
+            Number(f32::INFINITY)
+        );
+        assert_eq!(
+            operate(Number(-1.0), TokenType::Slash, Number(0.0), &settings),
+            Number(f32::NEG_INFINITY)
+        );
+        for (left, operator) in [(0.0, TokenType::Slash), (1.0, TokenType::Percent)] {
+            match operate(Number(left), operator, Number(0.0), &settings) {
+                Number(result) => assert!(result.is_nan()),
+                result => panic!("expected NaN, got {:?}", result),
+            }
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn decimals_mix_with_numbers_exactly() {
+        use crate::library::math::decimal::Decimal as Fixed;
+        let settings = Settings::default();
+        let decimal = |string: &str| Decimal(Fixed::parse(string).unwrap());
+        assert_eq!(
+            operate(decimal("0.1"), TokenType::Plus, Number(0.2), &settings),
+            decimal("0.3")
+        );
+        assert_eq!(
+            operate(Number(3.0), TokenType::Star, decimal("19.99"), &settings),
+            decimal("59.97")
+        );
+        assert_eq!(
+            operate(decimal("0.1"), TokenType::Less, Number(0.2), &settings),
+            True
+        );
+        // an infinite number has no decimal, and 0 can't divide
+        assert_eq!(
+            operate(
+                decimal("1"),
+                TokenType::Plus,
+                Number(f32::INFINITY),
+                &settings
+            ),
+            Null
+        );
+        assert_eq!(
+            operate(decimal("1"), TokenType::Slash, decimal("0"), &settings),
+            Null
+        );
+    }
 }
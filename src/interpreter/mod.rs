@@ -1,24 +1,46 @@
 use crate::environment::*;
 use crate::expressions::*;
+use crate::library::context::NativeContext;
+use crate::library::signal::check_interrupt;
 use crate::library::standard_library;
 use crate::parser::*;
+use crate::project::{display, import_path};
 use crate::resolver::*;
 use crate::scanner::*;
-use crate::utils::TronError;
+use crate::utils::{sources, TronError};
 use std::collections::HashMap;
+use std::rc::Rc;
 pub mod expressions;
 
+/// What a loop does after its body ran: start the next iteration or stop.
+#[derive(Debug, PartialEq)]
+enum LoopFlow {
+    Next,
+    Break,
+}
 #[derive(Debug)]
 pub struct Interpreter {
     pub specials: HashMap<String, TronType>,
     pub environment: Environment,
+    // set by the REPL, top level expression statements print their value
+    pub interactive: bool,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
+    /// Creates an interpreter with the standard library defined in its global environment.
+    ///
+    /// ### Last Updated: (v3.2.0)
     pub fn new() -> Self {
         let mut interpreter = Self {
             specials: HashMap::new(),
             environment: Environment::new(HashMap::new()),
+            interactive: false,
         };
         standard_library(&mut interpreter.environment);
 
@@ -31,68 +53,171 @@ impl Interpreter {
         Self {
             specials: HashMap::new(),
             environment: env,
+            interactive: false,
+        }
+    }
+    /// Evaluates a single expression in the current environment and returns its value.
+    ///
+    /// The expression sees every variable and function defined by the code interpreted so far, and the ones it
+    /// assigns stay assigned, which is what the REPL, a debugger or a program embedding Tron need to inspect a running
+    /// program. Nothing is printed, even in interactive mode.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: The source of the expression, e.g. `x + 1`. A trailing `;` is allowed.
+    ///
+    /// # Return Value
+    ///
+    /// The value of the expression, or an error message when it can't be scanned, parsed or resolved, or when its
+    /// evaluation fails. The errors are reported like the ones of a script.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut interpreter = Interpreter::new();
+    /// let value = interpreter.eval_expr("@pow(2, 10)")?;
+    /// assert!(interpreter.eval_expr("let x = 2;").is_err()); // declarations are statements
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn eval_expr(&mut self, source: &str) -> Result<TronType, String> {
+        let tokens = Scanner::new(source).scan_tokens()?;
+        let line = tokens.first().map_or(0, |token| token.line_number);
+        let mut parser = Parser::new(tokens).with_first_id(self.environment.next_id());
+        let expression = parser.parse_expression()?;
+        // the resolver works on statements, the ids of the copy are the same
+        let statement = Statement::Expression {
+            expression: expression.clone(),
+            line,
+        };
+        let locals = Resolver::new().resolve(&vec![&statement], &mut self.environment)?;
+        self.resolve(locals);
+        expression.evaluate(self.environment.clone())
+    }
+    /// Defines a global variable, replacing the value of an existing one with the same name.
+    ///
+    /// This is how a program embedding Tron passes data into a script: a global set before the script runs is visible
+    /// to it like one it declared itself, without writing a native to return it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut interpreter = Interpreter::new();
+    /// interpreter.set_global("limit", TronType::Number(10.0));
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn set_global(&mut self, name: &str, value: TronType) {
+        self.environment.define_global(name, value);
+    }
+    /// Returns the value of a global variable or function, or `None` when there is none with that name.
+    ///
+    /// This is how a program embedding Tron reads the results of a script after it ran.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn get_global(&self, name: &str) -> Option<TronType> {
+        self.environment.get_global(name)
+    }
+    /// Calls a global function, declared by a script or native, with arguments given by the host program.
+    ///
+    /// A program embedding Tron can interpret a script that declares hooks, like `fn on_event(name: string) {}`, and
+    /// then call them as many times as it needs, with the globals the script left behind.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: The name of the function, with the `@` of a native.
+    /// - `args`: The values of the arguments, in the order of the parameters.
+    ///
+    /// # Return Value
+    ///
+    /// The value the function returns, or an error message when there is no function with that name or the number of
+    /// arguments doesn't match its parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// interpreter.execute(&fs::read_to_string("hooks.tron")?)?;
+    /// let reply = interpreter.call_function("on_event", vec![TronType::StringValue("start".to_string())])?;
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn call_function(&mut self, name: &str, args: Vec<TronType>) -> Result<TronType, String> {
+        match self.get_global(name) {
+            Some(TronType::Callable(CallableImpl::Function(function))) => {
+                call_tron_function(function, args)
+            }
+            Some(TronType::Callable(CallableImpl::StdFunction(native))) => {
+                let context = NativeContext::new(self.environment.clone(), 0, 0);
+                Ok(context.invoke(&native, &args))
+            }
+            Some(value) => Err(format!("{} is a {}, not a function", name, value.to_type())),
+            None => Err(format!("function {} is not defined", name)),
         }
     }
     pub fn interpret(&mut self, stmts: Vec<&Statement>) -> Result<(), String> {
         for stmt in stmts {
+            check_interrupt(&self.environment);
             match stmt {
-                Statement::ExpressionStatement {
+                Statement::Expression {
                     expression,
                     line: _,
                 } => {
-                    expression.evaluate(self.environment.clone())?;
+                    let value = expression.evaluate(self.environment.clone())?;
+                    // assignments and calls that return nothing aren't echoed
+                    if self.interactive
+                        && value != TronType::Null
+                        && !matches!(expression, Expression::Assign { .. })
+                    {
+                        println!("{:?}", value);
+                    }
                 }
-                Statement::UseStatement { expression, line } => {
+                Statement::Use { expression, line } => {
                     let value = expression.evaluate(self.environment.clone())?;
                     let path = std::env::current_dir().unwrap();
-                    let path_buf =
-                        path.join(value.to_string().trim_matches('"').trim_start_matches('/'));
-                    match value.to_string().as_str() {
-                        // "\"#math\"" => include_math_natives(&mut self.environment),
-                        _ => {
-                            if std::path::Path::new(&path_buf).exists() {
-                                let lib_contents = std::fs::read_to_string(&path_buf)
-                                    .map_err(|e| e.to_string())?;
-                                self.execute_lib(&lib_contents)?;
-                            } else {
-                                TronError::throw("E4005", *line, vec![value.to_string()]);
-                            }
-                        }
+                    let path_buf = import_path(&path, &value.to_string());
+                    if std::path::Path::new(&path_buf).exists() {
+                        let lib_contents =
+                            std::fs::read_to_string(&path_buf).map_err(|e| e.to_string())?;
+                        // the diagnostics of the library point at its file, and the `use` leading to it
+                        let importer = sources::enter(display(&path, &path_buf), Some(*line));
+                        let result = self.execute(&lib_contents);
+                        sources::leave(importer);
+                        result?;
+                    } else {
+                        TronError::throw("E4005", *line, vec![value.to_string()]);
                     }
                 }
-                Statement::VariableStatement {
-                    name,
-                    value_type: _,
-                    value,
-                    line: _,
-                } => {
+                // type annotations are only checked by `tron check`
+                Statement::Variable { name, value, .. } => {
                     let value = value.evaluate(self.environment.clone())?;
-                    let value_clone = value.clone();
-                    self.environment.define(name.lexeme.clone(), value_clone);
+                    self.environment.define(name.lexeme.clone(), value);
                 }
-                Statement::BlockStatement {
+                Statement::Block {
                     statements,
                     line: _,
                 } => {
                     let new_environment = self.environment.enclose();
                     let old_environment = self.environment.clone();
                     self.environment = new_environment;
+                    // only the statements typed at the prompt are echoed, not the ones of their blocks
+                    let interactive = std::mem::replace(&mut self.interactive, false);
                     let block_result =
                         self.interpret((*statements).iter().map(|b| b.as_ref()).collect());
+                    self.interactive = interactive;
                     self.environment = old_environment;
                     block_result?;
                 }
-                Statement::IfStatement {
+                Statement::If {
                     conditions,
                     then_branch,
                     elif_branches,
                     else_branch,
-                    line: _,
+                    line,
                 } => {
                     let mut all_true = true;
                     for condition in conditions {
                         let truth_value = condition.evaluate(self.environment.clone())?;
-                        if truth_value.is_truthy() != TronType::True {
+                        if !truth_value.is_condition_true("condition", *line, &self.environment) {
                             all_true = false;
                             break;
                         }
@@ -106,7 +231,11 @@ impl Interpreter {
                             for elif_predicate in elif_predicates {
                                 let elif_truth_value =
                                     elif_predicate.evaluate(self.environment.clone())?;
-                                if elif_truth_value.is_truthy() != TronType::True {
+                                if !elif_truth_value.is_condition_true(
+                                    "condition",
+                                    *line,
+                                    &self.environment,
+                                ) {
                                     all_true = false;
                                     break;
                                 }
@@ -124,43 +253,50 @@ impl Interpreter {
                         }
                     }
                 }
-                Statement::WhileStatement {
+                Statement::While {
                     conditions,
                     body,
-                    line: _,
+                    increment,
+                    label,
+                    line,
                 } => {
-                    let mut all_true = true;
-                    for condition in conditions {
-                        let truth_value = condition.evaluate(self.environment.clone())?;
-                        if truth_value.is_truthy() != TronType::True {
-                            all_true = false;
+                    while self.conditions_hold(conditions, *line)? {
+                        let result = self.interpret(vec![body.as_ref()]);
+                        if Self::loop_flow(result, label)? == LoopFlow::Break {
                             break;
                         }
-                    }
-                    while all_true {
-                        self.interpret(vec![body.as_ref()])?;
-                        all_true = true;
-                        for condition in conditions {
-                            let truth_value = condition.evaluate(self.environment.clone())?;
-                            if truth_value.is_truthy() != TronType::True {
-                                all_true = false;
-                                break;
-                            }
+                        if let Some(increment) = increment {
+                            increment.evaluate(self.environment.clone())?;
                         }
                     }
                 }
-                Statement::FunctionStatement {
+                Statement::DoWhile {
+                    body,
+                    conditions,
+                    label,
+                    line,
+                } => loop {
+                    let result = self.interpret(vec![body.as_ref()]);
+                    if Self::loop_flow(result, label)? == LoopFlow::Break
+                        || !self.conditions_hold(conditions, *line)?
+                    {
+                        break;
+                    }
+                },
+                Statement::Function {
                     name,
                     params: _,
                     body: _,
                     output_type: _,
+                    doc: _,
                     line: _,
                 } => {
                     let callable = self.make_function(stmt);
+                    gc::track(&callable.parent_env);
                     let fun = TronType::Callable(CallableImpl::Function(callable));
                     self.environment.define(name.lexeme.clone(), fun);
                 }
-                Statement::ReturnStatement {
+                Statement::Return {
                     keyword: _,
                     value,
                     line: _,
@@ -171,12 +307,23 @@ impl Interpreter {
                     } else {
                         eval_val = TronType::Null;
                     }
+                    // passed up like `break`, through the blocks and loops around it, to the function call
                     self.specials.insert("return".to_string(), eval_val);
+                    return Err("return".to_string());
+                }
+                Statement::Break { label, .. } => {
+                    return Err(match label {
+                        Some(label) => format!("break:{}", label.lexeme),
+                        None => "break".to_string(),
+                    });
                 }
-                Statement::BreakStatement { .. } => {
-                    return Err("break".to_string());
+                Statement::Continue { label, .. } => {
+                    return Err(match label {
+                        Some(label) => format!("continue:{}", label.lexeme),
+                        None => "continue".to_string(),
+                    });
                 }
-                Statement::SwitchStatement {
+                Statement::Switch {
                     condition,
                     case_branches,
                     default_branch,
@@ -200,59 +347,272 @@ impl Interpreter {
         }
         Ok(())
     }
+    /// Decides what a loop does after running its body.
+    ///
+    /// `break` and `continue` are passed up as the `"break"` and `"continue"` errors, or `"break:label"` and
+    /// `"continue:label"` when they name a loop. Signals meant for this loop are consumed here; every other error,
+    /// including signals for an outer labeled loop and the `"return"` of the function around the loop, is passed on.
+    fn loop_flow(result: Result<(), String>, label: &Option<Token>) -> Result<LoopFlow, String> {
+        let signal = match result {
+            Ok(()) => return Ok(LoopFlow::Next),
+            Err(signal) => signal,
+        };
+        let (kind, target) = match signal.split_once(':') {
+            Some((kind, target)) => (kind, Some(target)),
+            None => (signal.as_str(), None),
+        };
+        let own_label = label.as_ref().map(|label| &*label.lexeme);
+        match (kind, target) {
+            ("break", None) => Ok(LoopFlow::Break),
+            ("continue", None) => Ok(LoopFlow::Next),
+            ("break", Some(target)) if Some(target) == own_label => Ok(LoopFlow::Break),
+            ("continue", Some(target)) if Some(target) == own_label => Ok(LoopFlow::Next),
+            _ => Err(signal),
+        }
+    }
+    /// Evaluates the conditions of a loop, which are all required to be true (`while a, b { ... }`).
+    ///
+    /// Stops at the first false condition, so the remaining ones aren't evaluated.
+    fn conditions_hold(
+        &mut self,
+        conditions: &Vec<Expression>,
+        line: usize,
+    ) -> Result<bool, String> {
+        for condition in conditions {
+            let truth_value = condition.evaluate(self.environment.clone())?;
+            if !truth_value.is_condition_true("condition", line, &self.environment) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
     fn make_function(&self, fn_stmt: &Statement) -> FunctionImpl {
-        if let Statement::FunctionStatement {
+        if let Statement::Function {
             name,
             params,
             body,
             output_type,
+            doc,
             line: _,
         } = fn_stmt
         {
-            let arity = params.len();
-            let params: Vec<(Token, Token)> = params
-                .iter()
-                .map(|(name, type_token)| (name.clone(), type_token.clone()))
-                .collect();
-            let body: Vec<Box<Statement>> = body.iter().map(|b| (*b).clone()).collect();
-            let name_clone = name.lexeme.clone();
-            let output_type_clone = output_type.clone();
-            let parent_env = self.environment.clone();
-            let callable_impl = FunctionImpl {
-                name: name_clone,
-                arity,
-                parent_env,
-                params,
-                body,
-                output_type: output_type_clone,
-            };
-            callable_impl
+            FunctionImpl {
+                name: name.lexeme.to_string(),
+                arity: params.len(),
+                parent_env: self.environment.clone(),
+                params: params.clone(),
+                body: body.clone(),
+                output_type: output_type.clone(),
+                doc: doc.clone(),
+                file: sources::current(),
+            }
         } else {
             TronError::throw("E4006", 0, vec![]);
             FunctionImpl {
                 name: "err".to_string(),
                 arity: 0,
                 parent_env: self.environment.clone(),
-                params: vec![],
-                body: vec![],
+                params: Rc::from([]),
+                body: Rc::from([]),
                 output_type: Token {
                     token_type: TokenType::Null,
-                    lexeme: "".to_string(),
+                    lexeme: "".into(),
                     literal: None,
                     line_number: 0,
+                    column: 0,
                 },
+                doc: None,
+                file: sources::current(),
             }
         }
     }
-    fn execute_lib(&mut self, lib_contents: &str) -> Result<(), String> {
+    /// Scans, parses, resolves and interprets a whole script in the current environment.
+    ///
+    /// This is how a library imported with `use` runs, and how a program embedding Tron runs a script before reading
+    /// its globals or calling its functions. The `#strict` directive of the script applies to its resolution.
+    ///
+    /// # Return Value
+    ///
+    /// An error message when the script has a syntax error, doesn't resolve, or fails while running. The errors are
+    /// reported like the ones of `tron run`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut interpreter = Interpreter::new();
+    /// interpreter.execute("fn on_event(name: string): string { return \"got \" + name; }")?;
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn execute(&mut self, lib_contents: &str) -> Result<(), String> {
         let scanner = Scanner::new(lib_contents);
-        let tokens = scanner.scan_tokens().map_err(|e| e.to_string())?;
-        let mut parser = Parser::new(tokens);
+        let (tokens, directives) = scanner
+            .scan_tokens_with_directives()
+            .map_err(|e| e.to_string())?;
+        let strict = self.environment.settings.borrow().strict || is_strict(&directives);
+        let mut parser = Parser::new(tokens).with_strict(strict);
         let stmts = parser.parse().map_err(|e| e.to_string())?;
         let stmts_refs: Vec<&Statement> = stmts.iter().collect();
-        let resolver = Resolver::new();
+        let resolver = Resolver::new().with_directives(directives);
         let locals = resolver.resolve(&stmts.iter().collect(), &mut self.environment)?;
         self.resolve(locals);
         self.interpret(stmts_refs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // runs a script and returns the interpreter, with the globals it left behind
+    fn run(source: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.execute(source).unwrap();
+        interpreter
+    }
+
+    #[test]
+    fn return_ends_the_loop_and_the_function() {
+        let interpreter = run("fn f(n: number): number {
+                while true {
+                    if n > 3 { return n; }
+                    n = n + 1;
+                }
+            }
+            let result: number = f(0);");
+        assert_eq!(
+            interpreter.get_global("result"),
+            Some(TronType::Number(4.0))
+        );
+    }
+
+    #[test]
+    fn return_ends_a_for_loop_and_a_switch() {
+        let interpreter = run("fn f(): number {
+                for let i: number = 0; i < 10; i = i + 1 {
+                    switch i {
+                        case 4 { return i; }
+                    }
+                }
+                return -1;
+            }
+            let result: number = f();");
+        assert_eq!(
+            interpreter.get_global("result"),
+            Some(TronType::Number(4.0))
+        );
+    }
+
+    // the code `tron check` reports as unreachable with W3007
+    #[test]
+    fn code_after_return_never_runs() {
+        let interpreter = run("let ran: bool = false;
+            fn f(): number {
+                if true {
+                    return 1;
+                    ran = true;
+                }
+                ran = true;
+                return 2;
+            }
+            let result: number = f();");
+        assert_eq!(
+            interpreter.get_global("result"),
+            Some(TronType::Number(1.0))
+        );
+        assert_eq!(interpreter.get_global("ran"), Some(TronType::False));
+    }
+
+    #[test]
+    fn code_after_break_never_runs() {
+        let interpreter = run("let ran: bool = false;
+            while true {
+                break;
+                ran = true;
+            }");
+        assert_eq!(interpreter.get_global("ran"), Some(TronType::False));
+    }
+
+    #[test]
+    fn return_without_a_value_returns_null() {
+        let interpreter = run("fn f(): null {
+                while true { return; }
+            }
+            let result: null = f();");
+        assert_eq!(interpreter.get_global("result"), Some(TronType::Null));
+    }
+
+    #[test]
+    fn a_syntax_error_stops_the_whole_script() {
+        // 256 arguments is one more than a call takes
+        let arguments: Vec<String> = (0..256).map(|index| index.to_string()).collect();
+        let source = format!(
+            "let ran: bool = true;\nfn f(): null {{ ran = true; }}\nf({});",
+            arguments.join(", ")
+        );
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&source).is_err());
+        assert_eq!(interpreter.get_global("ran"), None);
+    }
+
+    #[test]
+    fn a_script_sees_the_globals_of_the_host_and_the_host_reads_its_results() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("limit", TronType::Number(3.0));
+        interpreter
+            .execute("let doubled: number = limit * 2;")
+            .unwrap();
+        assert_eq!(
+            interpreter.get_global("doubled"),
+            Some(TronType::Number(6.0))
+        );
+        assert_eq!(
+            interpreter.eval_expr("doubled + limit").unwrap(),
+            TronType::Number(9.0)
+        );
+    }
+
+    #[test]
+    fn the_host_calls_the_functions_of_a_script_by_name() {
+        let mut interpreter = run("let calls: number = 0;
+            fn on_event(name: string): string {
+                calls = calls + 1;
+                return \"got \" + name;
+            }");
+        for _ in 0..2 {
+            let reply = interpreter
+                .call_function("on_event", vec![TronType::StringValue("start".into())])
+                .unwrap();
+            assert_eq!(reply, TronType::StringValue("got start".into()));
+        }
+        assert_eq!(interpreter.get_global("calls"), Some(TronType::Number(2.0)));
+        assert!(interpreter.call_function("calls", vec![]).is_err());
+        assert!(interpreter.call_function("missing", vec![]).is_err());
+    }
+
+    #[test]
+    fn repeating_takes_whole_counts_only() {
+        let interpreter = run("let line = \"-\" * 3;
+            let zeros = [0] * 2;
+            let none = \"ab\" * -1;
+            let half = \"ab\" * 2.5;
+            let endless = [0] * 1e39;
+            let huge = \"ab\" * 1e30;");
+        assert_eq!(
+            interpreter.get_global("line"),
+            Some(TronType::StringValue("---".to_string()))
+        );
+        // arrays are never equal, their elements are
+        match interpreter.get_global("zeros") {
+            Some(TronType::ArrayValue(zeros)) => assert_eq!(zeros, vec![TronType::Number(0.0); 2]),
+            zeros => panic!("expected an array, got {:?}", zeros),
+        }
+        assert_eq!(
+            interpreter.get_global("none"),
+            Some(TronType::StringValue(String::new()))
+        );
+        for name in ["half", "endless", "huge"] {
+            assert_eq!(interpreter.get_global(name), Some(TronType::Null));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! The Tron interpreter as a library, for the programs embedding Tron and the tools working on its syntax tree.
+//!
+//! An embedding program creates an `Interpreter`, passes data in with `Interpreter::set_global()`, runs a script with
+//! `Interpreter::execute()` and reads the results back with `Interpreter::get_global()`, `Interpreter::eval_expr()` or
+//! `Interpreter::call_function()`. Linters, metrics and codemods walk the `Statement`s and the `Expression`s of a
+//! program with the `visitor::Visitor` trait.
+//!
+//! The rest of the crate is the `tron` command line, which the binary runs through `main()`.
+//!
+//! ### Last Updated: (v3.2.0)
+mod cache;
+mod commands;
+mod environment;
+pub mod interpreter;
+mod library;
+mod lints;
+mod parser;
+mod project;
+mod resolver;
+mod scanner;
+mod settings;
+mod typecheck;
+mod utils;
+pub mod visitor;
+use crate::commands::bench::cli_bench;
+use crate::commands::build::cli_build;
+use crate::commands::check::cli_check;
+use crate::commands::doc::cli_doc;
+use crate::commands::graph::cli_graph;
+use crate::commands::help::cli_help;
+use crate::commands::init::cli_init;
+use crate::commands::install::{cli_add, cli_install};
+use crate::commands::repl::cli_repl;
+use crate::commands::run::{cli_eval, cli_run, cli_run_project};
+use crate::commands::update::cli_update;
+use crate::commands::upgrade::cli_upgrade;
+use crate::commands::version::cli_version;
+use crate::{interpreter::*, parser::*, resolver::*, scanner::*};
+use cache::CompiledProgram;
+use library::clean_up;
+use library::plugin::load_plugin;
+use project::display;
+use settings::Settings;
+use std::{env, fs, path::Path, process::exit};
+use utils::{crash, sources, TronError};
+
+pub use interpreter::{expressions::TronType, Interpreter};
+pub use scanner::{Statement, Token};
+
+fn panic(message: &str) {
+    eprintln!("\x1B[31m{}\x1B[0m \n", message);
+    exit(1);
+}
+
+fn run_file(path: &str, settings: Settings) -> Result<(), String> {
+    let current_dir = std::env::current_dir().unwrap();
+    let path = current_dir.join(path);
+    match fs::read_to_string(&path) {
+        Err(_msg) => {
+            TronError::throw("E0001", 0, vec![]);
+            Ok(())
+        }
+        Ok(contents) => {
+            sources::enter(display(&current_dir, &path), None);
+            let cache = settings.cache.then(|| cache::cache_path(&path));
+            run(&contents, settings, cache.as_deref())
+        }
+    }
+}
+
+fn run(contents: &str, settings: Settings, cache: Option<&Path>) -> Result<(), String> {
+    let mut interpreter = Interpreter::new();
+    for plugin in &settings.plugins {
+        if let Err(msg) = load_plugin(plugin, &mut interpreter.environment) {
+            TronError::throw("E0009", 0, vec![plugin.clone(), msg.clone()]);
+            return Err(msg);
+        }
+    }
+    interpreter.environment.set_settings(settings);
+    crash::watch(&interpreter.environment);
+    // `--strict` rejects programs that compile without it, they are cached apart
+    let strict = interpreter.environment.settings.borrow().strict;
+    let program = match cache.and_then(|path| cache::load(path, contents, strict)) {
+        Some(program) => {
+            if program.strict {
+                use_strict_mode(&interpreter);
+            }
+            program
+        }
+        None => {
+            let reported = TronError::reported();
+            let program = compile(contents, &mut interpreter)?;
+            match cache {
+                Some(path) if TronError::reported() == reported => {
+                    cache::store(path, contents, strict, &program)
+                }
+                _ => {}
+            }
+            program
+        }
+    };
+    interpreter.resolve(program.locals);
+    let result = interpreter.interpret(program.statements.iter().collect());
+    clean_up();
+    result
+}
+
+fn compile(contents: &str, interpreter: &mut Interpreter) -> Result<CompiledProgram, String> {
+    let scanner = Scanner::new(contents);
+    let (tokens, directives) = scanner.scan_tokens_with_directives()?;
+    let strict = is_strict(&directives);
+    if strict {
+        use_strict_mode(interpreter);
+    }
+    let strict_syntax = interpreter.environment.settings.borrow().strict;
+    let mut parser = Parser::new(tokens).with_strict(strict_syntax);
+    let statements = parser.parse()?;
+    let resolver = Resolver::new().with_directives(directives);
+    let locals = resolver.resolve(&statements.iter().collect(), &mut interpreter.environment)?;
+    Ok(CompiledProgram {
+        statements,
+        locals,
+        strict,
+    })
+}
+
+// `#strict` turns on the same settings as `--strict`
+fn use_strict_mode(interpreter: &Interpreter) {
+    let mut settings = interpreter.environment.settings.borrow_mut();
+    settings.strict = true;
+    settings.strict_types = true;
+}
+
+/// The `tron` command line, which is all the `tron` binary runs.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn main() {
+    let args: Vec<String> = env::args().collect();
+    let path = std::env::current_dir().unwrap();
+    if args.len() == 1 {
+        TronError::throw("E0002", 0, vec![]);
+        exit(64);
+    }
+    crash::install();
+    // a panic was reported by the crash reporter, the children and temporary files of the script are still cleaned up
+    if let Err(payload) = std::panic::catch_unwind(|| dispatch(&args, path)) {
+        clean_up();
+        exit(match crash::is_broken_pipe(&*payload) {
+            true => 141,
+            false => 101,
+        });
+    }
+}
+
+fn dispatch(args: &[String], path: std::path::PathBuf) {
+    let command = args[1].as_str();
+    match command {
+        "version" => cli_version(&args[2..]),
+        "update" => cli_update(),
+        "upgrade" => cli_upgrade(),
+        "help" => cli_help(),
+        "run" => match args.get(2) {
+            Some(file) if !file.starts_with("--") => cli_run(file, &args[3..], path),
+            _ => cli_run_project(&args[2..], path),
+        },
+        "-e" => cli_eval(&args[2..], path),
+        "repl" => cli_repl(&args[2..], path),
+        "init" => cli_init(&args[2..], path),
+        "build" => cli_build(path),
+        "check" => cli_check(&args[2..], path),
+        "add" => cli_add(&args[2..], path),
+        "install" => cli_install(path),
+        "bench" => cli_bench(&args[2..], path),
+        "doc" => cli_doc(&args[2..], path),
+        "graph" => cli_graph(&args[2..], path),
+        _ => TronError::throw("E0002", 0, vec![]),
+    }
+}
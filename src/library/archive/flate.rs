@@ -0,0 +1,475 @@
+/// The base lengths of the length codes 257 to 285 of DEFLATE, RFC 1951 section 3.2.5.
+///
+/// ### Last Updated: (v3.2.0)
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// The number of extra bits of the length codes 257 to 285.
+///
+/// ### Last Updated: (v3.2.0)
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// The base distances of the distance codes 0 to 29.
+///
+/// ### Last Updated: (v3.2.0)
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// The number of extra bits of the distance codes 0 to 29.
+///
+/// ### Last Updated: (v3.2.0)
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order the code lengths of the code length alphabet come in, in a dynamic block.
+///
+/// ### Last Updated: (v3.2.0)
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+/// How far back a match can start.
+///
+/// ### Last Updated: (v3.2.0)
+const WINDOW: usize = 32768;
+
+/// Computes the CRC-32 of zip and gzip, the one with the polynomial `0xEDB88320`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut crc = i as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+        *entry = crc;
+    }
+    !data.iter().fold(!0u32, |crc, byte| {
+        table[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+// reads the bits of a DEFLATE stream, from the lowest of every byte
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.count < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("the compressed data ends too early")?;
+            self.buffer |= (byte as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let bits = self.buffer & ((1u32 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(bits)
+    }
+    // the bits left in the current byte are skipped before a stored block
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+    fn decode(&mut self, huffman: &Huffman) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= self.bits(1)? as i32;
+            let count = huffman.counts[length] as i32;
+            if code - count < first {
+                return Ok(huffman.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("the compressed data has an invalid code".to_string())
+    }
+}
+
+// a canonical Huffman code, as the number of codes of every length and the symbols sorted by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+    fn fixed() -> (Self, Self) {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+        (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+    }
+}
+
+/// Decompresses a raw DEFLATE stream, RFC 1951.
+///
+/// # Return Value
+///
+/// The decompressed bytes with the number of compressed bytes the stream took, which is where the trailer of a gzip
+/// file starts, or an error message when the data isn't valid.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn inflate(data: &[u8]) -> Result<(Vec<u8>, usize), String> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut output = vec![];
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let header = data
+                    .get(reader.position..reader.position + 4)
+                    .ok_or("the compressed data ends too early")?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if length != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err("a stored block has a corrupted length".to_string());
+                }
+                let start = reader.position + 4;
+                let block = data
+                    .get(start..start + length)
+                    .ok_or("the compressed data ends too early")?;
+                output.extend_from_slice(block);
+                reader.position = start + length;
+            }
+            1 => {
+                let (literals, distances) = Huffman::fixed();
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err("the compressed data has an invalid block type".to_string()),
+        }
+        if last {
+            return Ok((output, reader.position));
+        }
+    }
+}
+
+// reads the codes a dynamic block starts with
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for i in 0..code_length_count {
+        code_lengths[CODE_LENGTH_ORDER[i]] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+    let mut lengths = vec![];
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match reader.decode(&code_lengths)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or("a code length repeats nothing")?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(length, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err("the code lengths of a block overflow".to_string());
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = reader.decode(literals)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err("the compressed data has an invalid length".to_string());
+                }
+                let length =
+                    LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                let index = reader.decode(distances)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err("the compressed data has an invalid distance".to_string());
+                }
+                let distance = DISTANCE_BASE[index] as usize
+                    + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > output.len() {
+                    return Err("the compressed data refers to data before its start".to_string());
+                }
+                // copied a byte at a time, a match can overlap the bytes it produces
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+// writes the bits of a DEFLATE stream, from the lowest of every byte
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.output.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+    // Huffman codes are written from their highest bit
+    fn code(&mut self, code: u32, length: u32) {
+        self.bits(code.reverse_bits() >> (32 - length), length);
+    }
+    fn literal(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xC0 + symbol - 280, 8),
+        }
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.output.push(self.buffer as u8);
+        }
+        self.output
+    }
+}
+
+/// Compresses bytes into a raw DEFLATE stream, as a single block with the fixed codes.
+///
+/// Repeated sequences are found with a hash of their first three bytes and a chain of the earlier positions with the
+/// same hash, which is a fraction of what zlib does but shrinks text and code well.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    const HASH_SIZE: usize = 1 << 15;
+    const MAX_CHAIN: usize = 64;
+    let hash = |i: usize| {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize)
+            & (HASH_SIZE - 1)
+    };
+    let mut writer = BitWriter {
+        output: vec![],
+        buffer: 0,
+        count: 0,
+    };
+    // the last block, with the fixed codes
+    writer.bits(1, 1);
+    writer.bits(1, 2);
+    let mut head = vec![usize::MAX; HASH_SIZE];
+    let mut previous = vec![usize::MAX; data.len()];
+    let insert = |i: usize, head: &mut [usize], previous: &mut [usize]| {
+        if i + 2 < data.len() {
+            let h = hash(i);
+            previous[i] = head[h];
+            head[h] = i;
+        }
+    };
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_length, mut best_distance) = (0, 0);
+        if i + 2 < data.len() {
+            let mut candidate = head[hash(i)];
+            let mut chain = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN {
+                let limit = (data.len() - i).min(258);
+                let length = (0..limit)
+                    .take_while(|&k| data[candidate + k] == data[i + k])
+                    .count();
+                if length > best_length {
+                    (best_length, best_distance) = (length, i - candidate);
+                    if length == limit {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+                chain += 1;
+            }
+        }
+        if best_length >= 3 {
+            let index = LENGTH_BASE
+                .iter()
+                .rposition(|base| *base as usize <= best_length)
+                .unwrap();
+            writer.literal(257 + index as u32);
+            writer.bits(
+                (best_length - LENGTH_BASE[index] as usize) as u32,
+                LENGTH_EXTRA[index] as u32,
+            );
+            let index = DISTANCE_BASE
+                .iter()
+                .rposition(|base| *base as usize <= best_distance)
+                .unwrap();
+            writer.code(index as u32, 5);
+            writer.bits(
+                (best_distance - DISTANCE_BASE[index] as usize) as u32,
+                DISTANCE_EXTRA[index] as u32,
+            );
+            for k in i..i + best_length {
+                insert(k, &mut head, &mut previous);
+            }
+            i += best_length;
+        } else {
+            writer.literal(data[i] as u32);
+            insert(i, &mut head, &mut previous);
+            i += 1;
+        }
+    }
+    writer.literal(256);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // compresses and decompresses `data`, checking the stream is read to its end
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let compressed = deflate(data);
+        let (output, read) = inflate(&compressed).unwrap();
+        assert_eq!(read, compressed.len());
+        output
+    }
+
+    #[test]
+    fn computes_the_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    // the streams below were compressed by zlib, as raw DEFLATE
+    #[test]
+    fn inflates_a_stored_block() {
+        let stream = bytes("010c00f3ff73746f72656420626c6f636b");
+        assert_eq!(
+            inflate(&stream).unwrap(),
+            (b"stored block".to_vec(), stream.len())
+        );
+    }
+
+    #[test]
+    fn inflates_a_fixed_block() {
+        let stream = bytes("4b4c2a4a4c4e4c4904520aa9b841090a4884817c10c80382cc4c00");
+        let text = b"abracadabra eeeeeeeeeeeeeeeeeeeeeeeeeeettttttttttttttaaaaaaaaaooooonnnnii";
+        assert_eq!(inflate(&stream).unwrap(), (text.to_vec(), stream.len()));
+    }
+
+    #[test]
+    fn inflates_a_dynamic_block() {
+        let stream = bytes(
+            "2dcc3112002008c4c0b78a2828f2ff56b8b9ad3319ee2272ca6c57352256796d679ad928de04d52dda02d52ed9\
+             0c95707726aae06e3d54c6dd705493bbaba81e773b51397772502977f101",
+        );
+        // runs of 1 to 5 letters
+        let text: Vec<u8> = (0..60)
+            .flat_map(|i| std::iter::repeat_n(b'a' + (i * 7 % 13) as u8, i % 5 + 1))
+            .collect();
+        assert_eq!(inflate(&stream).unwrap(), (text, stream.len()));
+    }
+
+    #[test]
+    fn rejects_a_broken_stream() {
+        assert!(inflate(&[]).is_err());
+        // a stored block whose length and its complement don't match
+        assert!(inflate(&bytes("010c00f3fe73746f72656420626c6f636b")).is_err());
+        // the reserved block type
+        assert!(inflate(&[0x07]).is_err());
+    }
+
+    #[test]
+    fn round_trips_empty_input() {
+        assert_eq!(inflate(&deflate(b"")).unwrap().0, b"");
+    }
+
+    #[test]
+    fn round_trips_text_and_binary() {
+        let text = b"the quick brown fox jumps over the lazy dog, ".repeat(50);
+        assert_eq!(round_trip(&text), text);
+        assert!(deflate(&text).len() < text.len() / 10);
+        let every_byte: Vec<u8> = (0..=255).collect();
+        assert_eq!(round_trip(&every_byte), every_byte);
+        // longer than the longest match, 258 bytes
+        assert_eq!(round_trip(&[0; 1000]), [0; 1000]);
+    }
+
+    #[test]
+    fn round_trips_matches_across_the_window() {
+        // a pseudo-random block repeated further than the 32 KiB window reaches
+        let mut state = 1u32;
+        let block: Vec<u8> = (0..40_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let data = [block.as_slice(), block.as_slice(), &block[..100]].concat();
+        assert_eq!(round_trip(&data), data);
+    }
+}
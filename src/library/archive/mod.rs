@@ -0,0 +1,540 @@
+mod flate;
+
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use flate::{crc32, deflate, inflate};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::exit;
+use std::time::UNIX_EPOCH;
+
+/// A file or directory to put in a zip archive.
+///
+/// # Fields
+///
+/// - `name`: The name of the entry, with `/` between its components and at the end of a directory.
+/// - `path`: Where the file is read from.
+///
+/// ### Last Updated: (v3.2.0)
+struct Entry {
+    name: String,
+    path: PathBuf,
+}
+
+/// The `archive_library` function declares the natives of the standard library that create and extract archives,
+/// with the `archive` feature, so packaging scripts don't depend on the `zip` and `tar` programs of the system.
+///
+/// - `@zip_create(path, files)`: creates the zip archive `path` with the files and directories of the array `files`,
+///   directories with everything in them, and returns the number of entries it has.
+/// - `@zip_extract(path, dir)`: extracts the zip archive `path` into the directory `dir` and returns the names of its
+///   entries.
+/// - `@tar_extract(path, dir)`: extracts the tar archive `path`, compressed with gzip or not, into the directory `dir`
+///   and returns the names of its entries.
+///
+/// An entry is named by the path it was given with, or by its file name when the path is absolute. Files are
+/// compressed with DEFLATE, or stored as they are when that doesn't make them smaller. Extracting creates `dir` when it
+/// doesn't exist, and refuses entries whose names would put them outside of it, like `../x` or `/etc/x`. The links of
+/// a tar archive are skipped. An archive that can't be read or written throws `E4034` and the native returns `null`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn archive_library(environment: &mut Environment) {
+    declare_native(
+        "@zip_create".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let path = string("@zip_create", &args[0], "first", context);
+            let TronType::ArrayValue(files) = &args[1] else {
+                throw_type("@zip_create", "array", "second", context)
+            };
+            let mut paths = vec![];
+            for file in files {
+                match file {
+                    TronType::StringValue(file) => paths.push(file.clone()),
+                    _ => throw_type("@zip_create", "array of strings", "second", context),
+                }
+            }
+            match zip_create(&path, &paths) {
+                Ok(count) => TronType::Number(count as f32),
+                Err(reason) => fail("@zip_create", reason, context),
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@zip_extract".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let path = string("@zip_extract", &args[0], "first", context);
+            let dir = string("@zip_extract", &args[1], "second", context);
+            match fs::read(&path)
+                .map_err(|error| format!("failed to read {}: {}", path, error))
+                .and_then(|archive| zip_extract(&archive, Path::new(&dir)))
+            {
+                Ok(names) => names_array(names),
+                Err(reason) => fail("@zip_extract", reason, context),
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@tar_extract".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let path = string("@tar_extract", &args[0], "first", context);
+            let dir = string("@tar_extract", &args[1], "second", context);
+            match fs::read(&path)
+                .map_err(|error| format!("failed to read {}: {}", path, error))
+                .and_then(|archive| tar_extract(&archive, Path::new(&dir)))
+            {
+                Ok(names) => names_array(names),
+                Err(reason) => fail("@tar_extract", reason, context),
+            }
+        },
+        environment,
+    );
+}
+
+fn string(name: &str, value: &TronType, place: &str, context: &NativeContext) -> String {
+    match value {
+        TronType::StringValue(string) => string.clone(),
+        _ => throw_type(name, "string", place, context),
+    }
+}
+
+fn throw_type(name: &str, expected: &str, place: &str, context: &NativeContext) -> ! {
+    context.throw(
+        "E4021",
+        vec![name.to_string(), expected.to_string(), place.to_string()],
+    );
+    exit(1)
+}
+
+fn fail(name: &str, reason: String, context: &NativeContext) -> TronType {
+    context.throw("E4034", vec![name.to_string(), reason]);
+    TronType::Null
+}
+
+fn names_array(names: Vec<String>) -> TronType {
+    TronType::ArrayValue(names.into_iter().map(TronType::StringValue).collect())
+}
+
+// the entries of the archive for the paths given, directories with everything in them, sorted by name
+fn entries(paths: &[String]) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    for path in paths {
+        let source = PathBuf::from(path);
+        let name = match source.is_absolute() {
+            true => source.file_name().map(PathBuf::from).unwrap_or_default(),
+            false => source
+                .components()
+                .filter(|c| *c != Component::CurDir)
+                .collect(),
+        };
+        if name.as_os_str().is_empty() || name.components().any(|c| c == Component::ParentDir) {
+            return Err(format!("{} can't be named in an archive", path));
+        }
+        collect(&source, name_of(&name), &mut entries)?;
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries.dedup_by(|a, b| a.name == b.name);
+    Ok(entries)
+}
+
+// a relative path as the name of an entry, with `/` between its components
+fn name_of(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn collect(path: &Path, name: String, entries: &mut Vec<Entry>) -> Result<(), String> {
+    let metadata = fs::metadata(path)
+        .map_err(|error| format!("failed to read {}: {}", path.display(), error))?;
+    if !metadata.is_dir() {
+        entries.push(Entry {
+            name,
+            path: path.to_path_buf(),
+        });
+        return Ok(());
+    }
+    let children = fs::read_dir(path)
+        .map_err(|error| format!("failed to read {}: {}", path.display(), error))?;
+    for child in children {
+        let child = child.map_err(|error| error.to_string())?;
+        let child_name = format!("{}/{}", name, child.file_name().to_string_lossy());
+        collect(&child.path(), child_name, entries)?;
+    }
+    entries.push(Entry {
+        name: format!("{}/", name),
+        path: path.to_path_buf(),
+    });
+    Ok(())
+}
+
+// writes a zip archive, its central directory listing the local headers and data written before it
+fn zip_create(path: &str, paths: &[String]) -> Result<usize, String> {
+    let entries = entries(paths)?;
+    if entries.len() > u16::MAX as usize {
+        return Err("an archive can have at most 65535 entries".to_string());
+    }
+    let mut archive: Vec<u8> = vec![];
+    let mut directory: Vec<u8> = vec![];
+    for entry in &entries {
+        let metadata = fs::metadata(&entry.path)
+            .map_err(|error| format!("failed to read {}: {}", entry.path.display(), error))?;
+        let data = match metadata.is_dir() {
+            true => vec![],
+            false => fs::read(&entry.path)
+                .map_err(|error| format!("failed to read {}: {}", entry.path.display(), error))?,
+        };
+        let compressed = deflate(&data);
+        let (method, stored) = match compressed.len() < data.len() {
+            true => (8u16, compressed),
+            false => (0u16, data.clone()),
+        };
+        if archive.len() > u32::MAX as usize || data.len() > u32::MAX as usize {
+            return Err("an archive can't be larger than 4 GB".to_string());
+        }
+        let (time, date) = dos_time(&metadata);
+        let offset = archive.len() as u32;
+        // the fields the local header and the central directory share, from the version needed to the name length
+        let mut shared = vec![];
+        shared.extend_from_slice(&20u16.to_le_bytes());
+        // the names are UTF-8
+        shared.extend_from_slice(&0x0800u16.to_le_bytes());
+        shared.extend_from_slice(&method.to_le_bytes());
+        shared.extend_from_slice(&time.to_le_bytes());
+        shared.extend_from_slice(&date.to_le_bytes());
+        shared.extend_from_slice(&crc32(&data).to_le_bytes());
+        shared.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+        shared.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        shared.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        archive.extend_from_slice(&shared);
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive.extend_from_slice(entry.name.as_bytes());
+        archive.extend_from_slice(&stored);
+        directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        // made on Unix, so the permissions in the external attributes are read back
+        directory.extend_from_slice(&(3u16 << 8 | 20).to_le_bytes());
+        directory.extend_from_slice(&shared);
+        // no extra field, comment, or disk number, and no internal attributes
+        directory.extend_from_slice(&[0; 8]);
+        directory.extend_from_slice(&external_attributes(&metadata).to_le_bytes());
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(entry.name.as_bytes());
+    }
+    let directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    fs::write(path, archive).map_err(|error| format!("failed to write {}: {}", path, error))?;
+    Ok(entries.len())
+}
+
+// the modification time of a file as the time and date fields of zip, which count from 1980
+fn dos_time(metadata: &fs::Metadata) -> (u16, u16) {
+    let seconds = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs());
+    let days = (seconds / 86400) as i64;
+    let seconds = seconds % 86400;
+    // the civil date of a day since 1970, from Howard Hinnant's `civil_from_days`
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    if year < 1980 {
+        return (0, 1 << 5 | 1);
+    }
+    let time = ((seconds / 3600) << 11) | ((seconds % 3600 / 60) << 5) | (seconds % 60 / 2);
+    let date = ((year - 1980) as u64) << 9 | (month as u64) << 5 | day as u64;
+    (time as u16, date as u16)
+}
+
+// the Unix permissions of an entry, in the high half, with the MS-DOS directory flag
+fn external_attributes(metadata: &fs::Metadata) -> u32 {
+    #[cfg(unix)]
+    let mode = std::os::unix::fs::PermissionsExt::mode(&metadata.permissions());
+    #[cfg(not(unix))]
+    let mode = if metadata.is_dir() { 0o40755 } else { 0o100644 };
+    mode << 16 | if metadata.is_dir() { 0x10 } else { 0 }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| "the archive is truncated".to_string())
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| "the archive is truncated".to_string())
+}
+
+// reads the central directory at the end of the archive, and every entry from its local header
+fn zip_extract(archive: &[u8], dir: &Path) -> Result<Vec<String>, String> {
+    // the end of the central directory is followed by a comment of at most 65535 bytes
+    let search_start = archive.len().saturating_sub(22 + 65535);
+    let end = (search_start..archive.len().saturating_sub(21))
+        .rev()
+        .find(|&i| archive[i..i + 4] == 0x06054b50u32.to_le_bytes())
+        .ok_or("the file isn't a zip archive")?;
+    let count = u16_at(archive, end + 10)? as usize;
+    let mut position = u32_at(archive, end + 16)? as usize;
+    let mut names = vec![];
+    for _ in 0..count {
+        if u32_at(archive, position)? != 0x02014b50 {
+            return Err("the central directory of the archive is corrupted".to_string());
+        }
+        let made_on_unix = u16_at(archive, position + 4)? >> 8 == 3;
+        let flags = u16_at(archive, position + 8)?;
+        let method = u16_at(archive, position + 10)?;
+        let crc = u32_at(archive, position + 16)?;
+        let compressed_size = u32_at(archive, position + 20)? as usize;
+        let size = u32_at(archive, position + 24)? as usize;
+        let name_length = u16_at(archive, position + 28)? as usize;
+        let extra_length = u16_at(archive, position + 30)? as usize;
+        let comment_length = u16_at(archive, position + 32)? as usize;
+        let attributes = u32_at(archive, position + 38)?;
+        let offset = u32_at(archive, position + 42)? as usize;
+        let name = archive
+            .get(position + 46..position + 46 + name_length)
+            .ok_or("the archive is truncated")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        position += 46 + name_length + extra_length + comment_length;
+        if flags & 1 != 0 {
+            return Err(format!("{} is encrypted", name));
+        }
+        let target = safe_path(dir, &name)?;
+        if name.ends_with('/') {
+            fs::create_dir_all(&target).map_err(|error| write_error(&target, error))?;
+            names.push(name);
+            continue;
+        }
+        if u32_at(archive, offset)? != 0x04034b50 {
+            return Err(format!("the local header of {} is corrupted", name));
+        }
+        let start = offset
+            + 30
+            + u16_at(archive, offset + 26)? as usize
+            + u16_at(archive, offset + 28)? as usize;
+        let stored = archive
+            .get(start..start + compressed_size)
+            .ok_or("the archive is truncated")?;
+        let data = match method {
+            0 => stored.to_vec(),
+            8 => {
+                inflate(stored)
+                    .map_err(|reason| format!("{}: {}", name, reason))?
+                    .0
+            }
+            _ => {
+                return Err(format!(
+                    "{} is compressed with the unsupported method {}",
+                    name, method
+                ))
+            }
+        };
+        if data.len() != size || crc32(&data) != crc {
+            return Err(format!("{} is corrupted", name));
+        }
+        write_file(&target, &data)?;
+        #[cfg(unix)]
+        if made_on_unix && attributes >> 16 != 0 {
+            use std::os::unix::fs::PermissionsExt;
+            let permissions = fs::Permissions::from_mode(attributes >> 16 & 0o777);
+            fs::set_permissions(&target, permissions)
+                .map_err(|error| write_error(&target, error))?;
+        }
+        #[cfg(not(unix))]
+        let _ = (made_on_unix, attributes);
+        names.push(name);
+    }
+    Ok(names)
+}
+
+// extracts a tar archive, after decompressing it when it's a gzip file
+fn tar_extract(archive: &[u8], dir: &Path) -> Result<Vec<String>, String> {
+    let archive = match archive.starts_with(&[0x1f, 0x8b]) {
+        true => gunzip(archive)?,
+        false => archive.to_vec(),
+    };
+    let mut names = vec![];
+    let mut position = 0;
+    // the name given by a GNU long name entry or a pax header, for the entry after it
+    let mut long_name: Option<String> = None;
+    while position < archive.len() {
+        let header = archive
+            .get(position..position + 512)
+            .ok_or("the file isn't a tar archive")?;
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+        // the sum of the bytes of the header, counting its checksum field as spaces
+        let sum: usize = header
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| match (148..156).contains(&i) {
+                true => b' ' as usize,
+                false => *byte as usize,
+            })
+            .sum();
+        if octal(&header[148..156]) != Ok(sum) {
+            return Err("the file isn't a tar archive".to_string());
+        }
+        let size = octal(&header[124..136])?;
+        let data = archive
+            .get(position + 512..position + 512 + size)
+            .ok_or("the archive is truncated")?;
+        position += 512 + size.div_ceil(512) * 512;
+        let kind = header[156];
+        let name = match long_name.take() {
+            Some(name) => name,
+            None => {
+                let name = text(&header[..100]);
+                let prefix = match &header[257..262] == b"ustar" {
+                    true => text(&header[345..500]),
+                    false => String::new(),
+                };
+                match prefix.is_empty() {
+                    true => name,
+                    false => format!("{}/{}", prefix, name),
+                }
+            }
+        };
+        match kind {
+            b'L' => long_name = Some(text(data)),
+            b'x' => long_name = pax_path(data).or(long_name),
+            b'0' | b'\0' | b'7' => {
+                let target = safe_path(dir, &name)?;
+                write_file(&target, data)?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let mode = octal(&header[100..108])? as u32 & 0o777;
+                    if mode != 0 {
+                        fs::set_permissions(&target, fs::Permissions::from_mode(mode))
+                            .map_err(|error| write_error(&target, error))?;
+                    }
+                }
+                names.push(name);
+            }
+            b'5' => {
+                let target = safe_path(dir, &name)?;
+                fs::create_dir_all(&target).map_err(|error| write_error(&target, error))?;
+                names.push(name);
+            }
+            // links, devices and global pax headers
+            _ => {}
+        }
+    }
+    Ok(names)
+}
+
+// the `path` record of a pax header, made of `<length> <key>=<value>\n` records
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data).lines().find_map(|record| {
+        let (_, field) = record.split_once(' ')?;
+        field.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+// a text field of a tar header, which ends at its first zero
+fn text(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+// a number field of a tar header, written in octal
+fn octal(field: &[u8]) -> Result<usize, String> {
+    let digits = text(field);
+    let digits = digits.trim_matches(|c: char| c == ' ' || c == '\0');
+    match digits.is_empty() {
+        true => Ok(0),
+        false => usize::from_str_radix(digits, 8)
+            .map_err(|_| "the archive has a corrupted header".to_string()),
+    }
+}
+
+// the content of a gzip file, RFC 1952
+fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 18 || data[2] != 8 {
+        return Err("the gzip file isn't compressed with DEFLATE".to_string());
+    }
+    let flags = data[3];
+    let mut position = 10;
+    if flags & 4 != 0 {
+        position += 2 + u16_at(data, position)? as usize;
+    }
+    // the original name and a comment, ending with a zero
+    for flag in [8, 16] {
+        if flags & flag != 0 {
+            let length = data
+                .get(position..)
+                .and_then(|rest| rest.iter().position(|byte| *byte == 0))
+                .ok_or("the gzip header is truncated")?;
+            position += length + 1;
+        }
+    }
+    if flags & 2 != 0 {
+        position += 2;
+    }
+    let (content, length) = inflate(data.get(position..).ok_or("the gzip header is truncated")?)?;
+    let trailer = position + length;
+    if u32_at(data, trailer)? != crc32(&content)
+        || u32_at(data, trailer + 4)? != content.len() as u32
+    {
+        return Err("the gzip file is corrupted".to_string());
+    }
+    Ok(content)
+}
+
+// where an entry goes in the directory, refusing names that would put it outside
+fn safe_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let outside = || format!("{} would be extracted outside of {}", name, dir.display());
+    if name.starts_with(['/', '\\']) {
+        return Err(outside());
+    }
+    let mut path = dir.to_path_buf();
+    for component in name.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            // a parent, or a drive on Windows
+            component if component == ".." || component.contains(':') => return Err(outside()),
+            component => path.push(component),
+        }
+    }
+    Ok(path)
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| write_error(parent, error))?;
+    }
+    fs::write(path, data).map_err(|error| write_error(path, error))
+}
+
+fn write_error(path: &Path, error: std::io::Error) -> String {
+    format!("failed to write {}: {}", path.display(), error)
+}
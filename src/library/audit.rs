@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::ExitStatus;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    // the audit log, `None` when commands aren't audited
+    static LOG: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Audits the shell commands of the script into the file `path`, with `--audit-cmds <path>`, for deployment pipelines
+/// that have to keep a trace of what ran.
+///
+/// Every command `@cmd` runs or `@spawn_detached` starts appends a line to the log, which is kept across runs:
+///
+/// ```text
+/// 2026-10-16T09:30:12Z exit=0 duration=15ms sh -c "make deploy"
+/// 2026-10-16T09:30:13Z exit=2 duration=4ms sh -c "test -f release.tar"
+/// 2026-10-16T09:30:13Z spawned=4521 duration=0ms sh -c "./worker"
+/// ```
+///
+/// The time is UTC, the status is `exit=<code>`, `signal` when the command was killed, `spawned=<pid>` for a detached
+/// command and `failed="<reason>"` when it couldn't start, and the command is the shell with its arguments, quoted
+/// when they have spaces.
+///
+/// # Return Value
+///
+/// An error message when the log can't be opened.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn audit_to(path: &str) -> Result<(), String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| error.to_string())?;
+    LOG.with(|log| *log.borrow_mut() = Some(path.to_string()));
+    Ok(())
+}
+
+/// The way a command audited with `audited()` ended.
+///
+/// ### Last Updated: (v3.2.0)
+pub enum Outcome<'a> {
+    Exited(ExitStatus),
+    Spawned(u32),
+    Failed(&'a str),
+}
+
+/// Appends a command to the audit log, when there's one.
+///
+/// # Parameters
+///
+/// - `args`: The program and its arguments, like `["sh", "-c", "make"]`.
+/// - `duration`: How long the command ran.
+/// - `outcome`: How it ended.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn audited(args: &[&str], duration: Duration, outcome: Outcome) {
+    let Some(path) = LOG.with(|log| log.borrow().clone()) else {
+        return;
+    };
+    let status = match outcome {
+        Outcome::Exited(status) => match status.code() {
+            Some(code) => format!("exit={}", code),
+            None => "signal".to_string(),
+        },
+        Outcome::Spawned(pid) => format!("spawned={}", pid),
+        Outcome::Failed(reason) => format!("failed={:?}", reason),
+    };
+    let command = args
+        .iter()
+        .map(
+            |arg| match arg.contains([' ', '\n', '\t', '"']) || arg.is_empty() {
+                true => format!("{:?}", arg),
+                false => arg.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = format!(
+        "{} {} duration={}ms {}\n",
+        utc_now(),
+        status,
+        duration.as_millis(),
+        command
+    );
+    let written = OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut log| log.write_all(line.as_bytes()));
+    if let Err(error) = written {
+        crate::utils::TronError::throw("E0010", 0, vec![path, error.to_string()]);
+    }
+}
+
+// the current time as `2026-10-16T09:30:12Z`
+fn utc_now() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    // the civil date of a number of days since 1970, from Howard Hinnant's algorithm
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh log in the temp directory, audited on this thread
+    fn log(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("tron-audit-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        audit_to(path.to_str().unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn commands_are_logged_with_their_outcome() {
+        let path = log("outcome");
+        audited(
+            &["sh", "-c", "./worker"],
+            Duration::from_millis(3),
+            Outcome::Spawned(4521),
+        );
+        audited(&["missing"], Duration::ZERO, Outcome::Failed("not found"));
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" spawned=4521 duration=3ms sh -c ./worker"));
+        assert!(lines[1].ends_with(" failed=\"not found\" duration=0ms missing"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn arguments_with_spaces_or_quotes_are_quoted() {
+        let path = log("quoting");
+        audited(
+            &["sh", "-c", "make \"deploy\"", ""],
+            Duration::ZERO,
+            Outcome::Spawned(1),
+        );
+        let line = std::fs::read_to_string(&path).unwrap();
+        assert!(line.ends_with(" sh -c \"make \\\"deploy\\\"\" \"\"\n"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn the_log_is_kept_across_runs() {
+        let path = log("kept");
+        audited(&["true"], Duration::ZERO, Outcome::Spawned(1));
+        audit_to(path.to_str().unwrap()).unwrap();
+        audited(&["true"], Duration::ZERO, Outcome::Spawned(2));
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn an_unopenable_log_is_an_error() {
+        assert!(audit_to("/nonexistent-directory/audit.log").is_err());
+    }
+
+    #[test]
+    fn times_are_utc_timestamps() {
+        let time = utc_now();
+        assert_eq!(time.len(), 20);
+        assert!(time.starts_with("20") && time.ends_with('Z'));
+        assert_eq!(&time[10..11], "T");
+    }
+}
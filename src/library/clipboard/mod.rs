@@ -0,0 +1,145 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::env;
+use std::io::Write;
+use std::process::{exit, Command, Stdio};
+
+/// The programs that read and write the clipboard on this system, with their arguments.
+///
+/// # Fields
+///
+/// - `get`: The program printing the content of the clipboard.
+/// - `set`: The program replacing the content of the clipboard with its input.
+///
+/// ### Last Updated: (v3.2.0)
+struct Tools {
+    get: (&'static str, &'static [&'static str]),
+    set: (&'static str, &'static [&'static str]),
+}
+
+/// The `clipboard_library` function declares the natives of the standard library that use the clipboard, with the
+/// `clipboard` feature.
+///
+/// - `@clipboard_get()`: returns the text in the clipboard.
+/// - `@clipboard_set(text)`: puts `text` in the clipboard.
+///
+/// The clipboard belongs to the desktop, so these go through the programs it comes with: `pbpaste` and `pbcopy` on
+/// macOS, PowerShell on Windows, and `wl-paste` and `wl-copy` or `xclip` elsewhere, depending on whether a Wayland or
+/// X11 display is set. On a system without a desktop, like a server or a container, or when the program is missing,
+/// they throw `E4031` and return `null` rather than stopping the script.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn clipboard_library(environment: &mut Environment) {
+    declare_native(
+        "@clipboard_get".to_string(),
+        0,
+        |context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            match clipboard_get() {
+                Ok(text) => TronType::StringValue(text),
+                Err(reason) => {
+                    context.throw("E4031", vec!["@clipboard_get".to_string(), reason]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@clipboard_set".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::StringValue(text) = &args[0] else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@clipboard_set".to_string(),
+                        "string".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            match clipboard_set(text) {
+                Ok(()) => TronType::Null,
+                Err(reason) => {
+                    context.throw("E4031", vec!["@clipboard_set".to_string(), reason]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+}
+
+// the clipboard programs of this system, or why there are none
+fn tools() -> Result<Tools, String> {
+    if cfg!(target_os = "macos") {
+        return Ok(Tools {
+            get: ("pbpaste", &[]),
+            set: ("pbcopy", &[]),
+        });
+    }
+    if cfg!(windows) {
+        return Ok(Tools {
+            get: (
+                "powershell",
+                &["-NoProfile", "-Command", "Get-Clipboard -Raw"],
+            ),
+            set: (
+                "powershell",
+                &["-NoProfile", "-Command", "$input | Set-Clipboard"],
+            ),
+        });
+    }
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        return Ok(Tools {
+            get: ("wl-paste", &["--no-newline"]),
+            set: ("wl-copy", &[]),
+        });
+    }
+    if env::var_os("DISPLAY").is_some() {
+        return Ok(Tools {
+            get: ("xclip", &["-selection", "clipboard", "-o"]),
+            set: ("xclip", &["-selection", "clipboard"]),
+        });
+    }
+    Err("no display is set, this system has no desktop".to_string())
+}
+
+fn clipboard_get() -> Result<String, String> {
+    let (program, arguments) = tools()?.get;
+    let output = Command::new(program)
+        .args(arguments)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|error| format!("failed to run {}: {}", program, error))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn clipboard_set(text: &str) -> Result<(), String> {
+    let (program, arguments) = tools()?.set;
+    let mut child = Command::new(program)
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| format!("failed to run {}: {}", program, error))?;
+    if let Some(mut input) = child.stdin.take() {
+        input
+            .write_all(text.as_bytes())
+            .map_err(|error| format!("failed to write to {}: {}", program, error))?;
+    }
+    let status = child
+        .wait()
+        .map_err(|error| format!("failed to run {}: {}", program, error))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(format!("{} exited with {}", program, status)),
+    }
+}
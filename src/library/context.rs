@@ -0,0 +1,89 @@
+use super::effects;
+use crate::environment::Environment;
+use crate::expressions::{call_tron_function, CallableImpl, StdFunctionImpl, TronType};
+use crate::utils::TronError;
+
+/// The context a native function is called in, passed to it next to its arguments.
+///
+/// Natives that only transform their arguments ignore it. The ones that need more than their arguments use it to look
+/// up globals and the natives registry, read the settings of the program through `environment.settings`, call back
+/// into a function they were given, or report an error at the line and column of the call instead of line 0.
+///
+/// # Fields
+///
+/// - `environment`: The environment the native is called from.
+/// - `line`: The line of the call, 0 when the native is called from Rust.
+/// - `column`: The column of the call, 0 when it isn't known.
+///
+/// ### Last Updated: (v3.2.0)
+pub struct NativeContext {
+    pub environment: Environment,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl NativeContext {
+    /// Creates the context of a call made at `line` and `column` from `environment`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn new(environment: Environment, line: usize, column: usize) -> Self {
+        Self {
+            environment,
+            line,
+            column,
+        }
+    }
+
+    /// Calls a Tron function or a native with arguments that are already evaluated, like a native taking a callback
+    /// (e.g. the comparator of a sort) needs to.
+    ///
+    /// # Return Value
+    ///
+    /// The value the function returns, or an error message when the value isn't callable or the number of arguments
+    /// doesn't match the parameters of a Tron function.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn call(&self, callable: &TronType, args: Vec<TronType>) -> Result<TronType, String> {
+        match callable {
+            TronType::Callable(CallableImpl::Function(function)) => {
+                call_tron_function(function.clone(), args)
+            }
+            TronType::Callable(CallableImpl::StdFunction(native)) => Ok(self.invoke(native, &args)),
+            value => Err(format!("a {} is not callable", value.to_type())),
+        }
+    }
+
+    /// Calls a native in this context once its arity is checked.
+    ///
+    /// A call with too few or too many arguments is reported as `E4018`, `E4019` or `E4020` and ends the program, so
+    /// natives don't have to check the number of their arguments themselves. In `--ask` mode, a call the user doesn't
+    /// allow is reported as `E4037` and returns `null`, see `effects::ask()`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn invoke(&self, native: &StdFunctionImpl, args: &Vec<TronType>) -> TronType {
+        let arity = native.arity;
+        if !arity.accepts(args.len()) {
+            let (error_code, count) = match arity.max {
+                Some(max) if max == arity.min => ("E4020", max),
+                Some(max) if args.len() > max => ("E4019", max),
+                _ => ("E4018", arity.min),
+            };
+            self.throw(error_code, vec![native.name.clone(), count.to_string()]);
+        }
+        if let Some((permission, false)) = effects::permit(&native.name, args) {
+            self.throw(
+                "E4037",
+                vec![native.name.clone(), permission.action().to_string()],
+            );
+            return TronType::Null;
+        }
+        (native.function)(self, args)
+    }
+
+    /// Reports an error at the call of the native, see `TronError::throw()`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn throw(&self, error_code: &str, args: Vec<String>) {
+        TronError::throw_at(error_code, self.line, self.column, args);
+    }
+}
@@ -0,0 +1,665 @@
+use super::context::NativeContext;
+use super::declare_native;
+use super::registry::NativeRegistry;
+use crate::environment::Environment;
+use crate::expressions::{Arity, CallableImpl, FunctionImpl, TronType};
+use crate::scanner::Token;
+use std::process::exit;
+
+/// The signature and description of every native of the standard library, as `(name, signature, doc)`.
+const NATIVE_DOCS: [(&str, &str, &str); 110] = [
+    (
+        "clock",
+        "clock(): number",
+        "Prints the milliseconds since the Unix epoch and returns them as seconds.",
+    ),
+    (
+        "@print",
+        "@print(...values: any): null",
+        "Prints every argument on its own line.",
+    ),
+    (
+        "@panic",
+        "@panic(...values: any): null",
+        "Prints every argument in red to stderr, and exits when called without arguments.",
+    ),
+    (
+        "@shift",
+        "@shift(array: array): array",
+        "Returns a copy of the array without its first element.",
+    ),
+    (
+        "@pop",
+        "@pop(array: array): array",
+        "Returns a copy of the array without its last element.",
+    ),
+    (
+        "@join",
+        "@join(array: array, separator: string): string",
+        "Joins the strings and numbers of the array with the separator.",
+    ),
+    (
+        "@push",
+        "@push(array: array, value: any): array",
+        "Returns a copy of the array with the value added at the end.",
+    ),
+    (
+        "@unshift",
+        "@unshift(array: array, value: any): array",
+        "Returns a copy of the array with the value added at the start.",
+    ),
+    (
+        "@peek_first",
+        "@peek_first(xs: array | deque): any",
+        "Returns the first element of an array or deque, null when it's empty.",
+    ),
+    (
+        "@peek_last",
+        "@peek_last(xs: array | deque): any",
+        "Returns the last element of an array or deque, null when it's empty.",
+    ),
+    (
+        "@deque",
+        "@deque(xs: array): deque",
+        "Returns a deque of the elements of xs. A deque is changed in place and shared by the variables holding it.",
+    ),
+    (
+        "@push_front",
+        "@push_front(d: deque, value: any): deque",
+        "Adds the value at the start of the deque, in constant time, and returns the deque.",
+    ),
+    (
+        "@push_back",
+        "@push_back(d: deque, value: any): deque",
+        "Adds the value at the end of the deque, in constant time, and returns the deque.",
+    ),
+    (
+        "@pop_front",
+        "@pop_front(d: deque): any",
+        "Removes the first element of the deque and returns it, null when it's empty.",
+    ),
+    (
+        "@pop_back",
+        "@pop_back(d: deque): any",
+        "Removes the last element of the deque and returns it, null when it's empty.",
+    ),
+    (
+        "@deque_values",
+        "@deque_values(d: deque): array",
+        "Returns the elements of the deque as an array.",
+    ),
+    (
+        "@length",
+        "@length(value: string | array | set | deque): number",
+        "Returns the number of characters of a string, the number of elements of an array or deque or the members of a set.",
+    ),
+    (
+        "@ask",
+        "@ask(prompt: string): string",
+        "Prints the prompt and returns the line read from stdin, including its newline.",
+    ),
+    (
+        "@typeof",
+        "@typeof(value: any): string",
+        "Returns the name of the type of the value.",
+    ),
+    (
+        "@if",
+        "@if(condition: bool, then: any, else: any): any",
+        "Returns `then` when the condition is true and `else` when it is false.",
+    ),
+    (
+        "@sleep",
+        "@sleep(milliseconds: number): number",
+        "Pauses the program for the given number of milliseconds and returns it.",
+    ),
+    (
+        "@cmd",
+        "@cmd(script: string | array): string",
+        "Runs a shell script and returns its output. A multi-line string stops at the first failing line, and an array \
+         of commands is joined with `&&`.",
+    ),
+    ("@sin", "@sin(x: number): number", "Returns the sine of x, in radians."),
+    ("@cos", "@cos(x: number): number", "Returns the cosine of x, in radians."),
+    ("@tan", "@tan(x: number): number", "Returns the tangent of x, in radians."),
+    (
+        "@round",
+        "@round(x: number): number",
+        "Rounds x to the nearest integer, half-way cases away from zero.",
+    ),
+    (
+        "@floor",
+        "@floor(x: number): number",
+        "Returns the largest integer less than or equal to x.",
+    ),
+    (
+        "@ceil",
+        "@ceil(x: number): number",
+        "Returns the smallest integer greater than or equal to x.",
+    ),
+    (
+        "@pow",
+        "@pow(base: number | bigint, exponent: number): number | bigint",
+        "Raises the base to the exponent. A bigint base needs a whole exponent that isn't negative.",
+    ),
+    (
+        "@root",
+        "@root(x: number, n: number): number",
+        "Returns the n-th root of x.",
+    ),
+    (
+        "@bigint",
+        "@bigint(x: string | number): bigint",
+        "Converts a string of digits or a whole number to an integer of any size, which keeps every digit through arithmetic. Needs the `bigint` feature.",
+    ),
+    (
+        "@decimal",
+        "@decimal(x: string | number | bigint): decimal",
+        "Converts x to a decimal, which adds, subtracts and multiplies exactly. A division is rounded to 20 places. Needs the `bigint` feature.",
+    ),
+    (
+        "@round_decimal",
+        "@round_decimal(x: decimal, places: number): decimal",
+        "Rounds x to the given number of digits after the point, half away from 0. Needs the `bigint` feature.",
+    ),
+    (
+        "@format_decimal",
+        "@format_decimal(x: decimal, places: number): string",
+        "Shows x rounded to the given number of places, with its thousands separated by commas, like `1,234.50`. Needs the `bigint` feature.",
+    ),
+    (
+        "@random",
+        "@random(): number",
+        "Returns a random number from 0 up to but not including 1, the same sequence on every run with --deterministic.",
+    ),
+    (
+        "@matrix",
+        "@matrix(rows: number, columns: number, fill: number): array",
+        "Returns a matrix of the given size, an array of rows with every element set to fill.",
+    ),
+    (
+        "@identity",
+        "@identity(n: number): array",
+        "Returns the identity matrix of size n, with 1 on its diagonal and 0 everywhere else.",
+    ),
+    (
+        "@transpose",
+        "@transpose(m: array): array",
+        "Swaps the rows and columns of a matrix. A vector becomes a matrix of a single column.",
+    ),
+    (
+        "@dot",
+        "@dot(a: array, b: array): number | array",
+        "Returns the dot product of two vectors, or the product of two matrices or of a matrix and a vector.",
+    ),
+    (
+        "@add_elements",
+        "@add_elements(a: array, b: array | number): array",
+        "Adds the elements at the same position of two vectors or matrices of the same shape, or a number to every element.",
+    ),
+    (
+        "@sub_elements",
+        "@sub_elements(a: array, b: array | number): array",
+        "Subtracts the elements of b from the elements at the same position of a, or a number from every element.",
+    ),
+    (
+        "@mul_elements",
+        "@mul_elements(a: array, b: array | number): array",
+        "Multiplies the elements at the same position of two vectors or matrices of the same shape, or every element by a number.",
+    ),
+    (
+        "@keys",
+        "@keys(object: object): array",
+        "Returns the keys of an object, sorted.",
+    ),
+    (
+        "@values",
+        "@values(object: object): array",
+        "Returns the values of an object, in the order of their keys.",
+    ),
+    (
+        "@entries",
+        "@entries(object: object): array",
+        "Returns the [key, value] pairs of an object sorted by key, to loop over with `for entry in @entries(object)`.",
+    ),
+    (
+        "@has_key",
+        "@has_key(object: object, key: string): bool",
+        "Returns whether an object has the given key.",
+    ),
+    (
+        "@merge",
+        "@merge(a: object, b: object): object",
+        "Returns an object with the keys of both, the values of b replacing the ones of a.",
+    ),
+    (
+        "@remove_key",
+        "@remove_key(object: object, key: string): object",
+        "Returns a copy of an object without the given key.",
+    ),
+    (
+        "@set",
+        "@set(xs: array): set",
+        "Returns a set of the distinct elements of xs, in the order they first appear.",
+    ),
+    (
+        "@set_add",
+        "@set_add(s: set, x: any): set",
+        "Returns a copy of s with x added. Arrays, objects and functions can't be members.",
+    ),
+    (
+        "@set_has",
+        "@set_has(s: set, x: any): bool",
+        "Returns whether x is a member of s, without searching through the members one by one.",
+    ),
+    (
+        "@set_union",
+        "@set_union(a: set, b: set): set",
+        "Returns the members of a or b.",
+    ),
+    (
+        "@set_intersect",
+        "@set_intersect(a: set, b: set): set",
+        "Returns the members of both a and b.",
+    ),
+    (
+        "@set_diff",
+        "@set_diff(a: set, b: set): set",
+        "Returns the members of a that aren't in b.",
+    ),
+    (
+        "@set_values",
+        "@set_values(s: set): array",
+        "Returns the members of s as an array, in the order they were added.",
+    ),
+    (
+        "@sum",
+        "@sum(xs: array): number",
+        "Returns the sum of an array of numbers, 0 when it's empty.",
+    ),
+    (
+        "@mean",
+        "@mean(xs: array): number",
+        "Returns the average of an array of numbers.",
+    ),
+    (
+        "@median",
+        "@median(xs: array): number",
+        "Returns the middle number of an array once sorted, or the average of the two middle ones.",
+    ),
+    (
+        "@stddev",
+        "@stddev(xs: array): number",
+        "Returns the population standard deviation of an array of numbers.",
+    ),
+    (
+        "@percentile",
+        "@percentile(xs: array, p: number): number",
+        "Returns the number below which p percent (0 to 100) of the numbers fall, interpolated between the closest two.",
+    ),
+    (
+        "@is_nan",
+        "@is_nan(x: number): bool",
+        "Returns whether x is `NaN`, which isn't equal to anything, itself included.",
+    ),
+    (
+        "@is_finite",
+        "@is_finite(x: number): bool",
+        "Returns whether x is neither `Infinity`, `-Infinity` nor `NaN`.",
+    ),
+    (
+        "@chars",
+        "@chars(text: string): array",
+        "Returns an array with every character of the string.",
+    ),
+    (
+        "@char_at",
+        "@char_at(text: string, index: number): string",
+        "Returns the character at the index, or null when the index is out of range.",
+    ),
+    (
+        "@code_point",
+        "@code_point(text: string): number",
+        "Returns the unicode code point of the first character of the string, or null for an empty string.",
+    ),
+    (
+        "@ord",
+        "@ord(character: string): number",
+        "Returns the code point of a single character.",
+    ),
+    (
+        "@chr",
+        "@chr(code: number): string",
+        "Returns the character with the given code point.",
+    ),
+    (
+        "@is_digit",
+        "@is_digit(text: string): bool",
+        "Returns whether every character of a non-empty string is an ASCII digit.",
+    ),
+    (
+        "@is_alpha",
+        "@is_alpha(text: string): bool",
+        "Returns whether every character of a non-empty string is alphabetic.",
+    ),
+    (
+        "@is_whitespace",
+        "@is_whitespace(text: string): bool",
+        "Returns whether every character of a non-empty string is whitespace.",
+    ),
+    (
+        "@memo",
+        "@memo(function: function): function",
+        "Returns a function that remembers the result of every call by its arguments, calls with an array, an object or a \
+         function as an argument aren't remembered.",
+    ),
+    (
+        "@buffer",
+        "@buffer(): buffer",
+        "Returns an empty buffer to build a string in. Appending to it doesn't copy what it holds, unlike `s = s + piece`.",
+    ),
+    (
+        "@buffer_push",
+        "@buffer_push(b: buffer, value: any): buffer",
+        "Appends the value to the buffer, the way `+` would show it in a string, and returns the buffer.",
+    ),
+    (
+        "@buffer_to_string",
+        "@buffer_to_string(b: buffer): string",
+        "Returns the string built in the buffer so far.",
+    ),
+    (
+        "@render",
+        "@render(template: string, data: object): string",
+        "Fills a mustache-style template: {{name}} (HTML-escaped), {{{name}}} (as it is), {{#items}}...{{/items}} loops and conditions, {{^name}}...{{/name}} when falsy.",
+    ),
+    (
+        "@markdown",
+        "@markdown(text: string): string",
+        "Converts Markdown to HTML: headings, paragraphs, quotes, lists, fenced code, rules, **strong**, *emphasis*, \
+         `code`, links and images. HTML in the text is escaped. Needs the `markdown` feature.",
+    ),
+    (
+        "@clear_screen",
+        "@clear_screen(): null",
+        "Clears the terminal and moves the cursor to the top left corner. Does nothing when the output isn't a terminal.",
+    ),
+    (
+        "@move_cursor",
+        "@move_cursor(row: number, column: number): null",
+        "Moves the cursor to row and column, counted from 1. Does nothing when the output isn't a terminal.",
+    ),
+    (
+        "@styled",
+        "@styled(text: any, style: string): string",
+        "Returns text in colors and styles like \"red\" or \"bold bright_green\". Returns it unchanged when the output \
+         isn't a terminal.",
+    ),
+    (
+        "@confirm",
+        "@confirm(prompt: string): bool",
+        "Asks a yes or no question until the answer is y, yes, n or no. Returns false when the input ends.",
+    ),
+    (
+        "@select",
+        "@select(prompt: string, options: array): any",
+        "Shows the options numbered from 1 and returns the one the user picks. Returns null when the input ends.",
+    ),
+    (
+        "@password",
+        "@password(prompt: string): string",
+        "Reads a line without showing what's typed.",
+    ),
+    (
+        "@clipboard_get",
+        "@clipboard_get(): string",
+        "Returns the text in the clipboard, or null on a system without a desktop. Needs the `clipboard` feature.",
+    ),
+    (
+        "@clipboard_set",
+        "@clipboard_set(text: string): null",
+        "Puts text in the clipboard. Needs the `clipboard` feature.",
+    ),
+    (
+        "@os",
+        "@os(): string",
+        "Returns the operating system, like \"linux\", \"macos\" or \"windows\".",
+    ),
+    (
+        "@arch",
+        "@arch(): string",
+        "Returns the architecture of the processor, like \"x86_64\" or \"aarch64\".",
+    ),
+    (
+        "@hostname",
+        "@hostname(): string",
+        "Returns the name of the machine, or null when it can't be found.",
+    ),
+    (
+        "@cpu_count",
+        "@cpu_count(): number",
+        "Returns the number of processors the script can use.",
+    ),
+    (
+        "@home_dir",
+        "@home_dir(): string",
+        "Returns the home directory of the user, or null when it isn't set.",
+    ),
+    (
+        "@on_interrupt",
+        "@on_interrupt(handler: function): null",
+        "Calls handler when Ctrl+C is pressed, then ends the program with the exit code 130. Without a handler, Ctrl+C \
+         ends the program right away.",
+    ),
+    (
+        "@serve",
+        "@serve(port: number, handler: function, host?: string): null",
+        "Serves HTTP on port, calling handler with every request ({method, path, query, headers, body}) and sending \
+         the response it returns ({status, headers, body}). Listens on 127.0.0.1 unless host is given, \"0.0.0.0\" \
+         listens on every interface. Needs the `server` feature.",
+    ),
+    (
+        "@ws_connect",
+        "@ws_connect(url: string): number",
+        "Connects to a ws:// WebSocket server and returns the handle of the connection.",
+    ),
+    (
+        "@ws_send",
+        "@ws_send(ws: number, text: string): null",
+        "Sends text as a message on the connection.",
+    ),
+    (
+        "@ws_recv",
+        "@ws_recv(ws: number, timeout?: number): string",
+        "Waits for the next message, or returns null once timeout milliseconds passed.",
+    ),
+    (
+        "@ws_close",
+        "@ws_close(ws: number): null",
+        "Closes the connection.",
+    ),
+    (
+        "@pid",
+        "@pid(): number",
+        "Returns the pid of the script.",
+    ),
+    (
+        "@spawn_detached",
+        "@spawn_detached(command: string | array): number",
+        "Starts a command in the background like @cmd would run it, and returns its pid. It's stopped when the script \
+         ends.",
+    ),
+    (
+        "@is_running",
+        "@is_running(pid: number): bool",
+        "Returns whether the process is still running.",
+    ),
+    (
+        "@kill",
+        "@kill(pid: number): bool",
+        "Stops the process, returning whether it was running.",
+    ),
+    (
+        "@temp_file",
+        "@temp_file(suffix?: string): string",
+        "Creates an empty temporary file, its name ending with suffix, and returns its path. It's removed when the \
+         script ends.",
+    ),
+    (
+        "@temp_dir",
+        "@temp_dir(): string",
+        "Creates an empty temporary directory and returns its path. It's removed with its content when the script ends.",
+    ),
+    (
+        "@download",
+        "@download(url: string, path: string, sha256?: string): string",
+        "Downloads url to path, resuming an interrupted download, checks it against sha256 when it's given, and \
+         returns its SHA-256 checksum.",
+    ),
+    (
+        "@file_sha256",
+        "@file_sha256(path: string): string",
+        "Returns the SHA-256 checksum of the file path as lowercase hex.",
+    ),
+    (
+        "@image_size",
+        "@image_size(path: string): object",
+        "Returns the width, height and format (png, jpeg, gif, bmp or webp) of an image, reading only its header.",
+    ),
+    (
+        "@zip_create",
+        "@zip_create(path: string, files: array): number",
+        "Creates the zip archive path with the files and directories of files and returns the number of entries.",
+    ),
+    (
+        "@zip_extract",
+        "@zip_extract(path: string, dir: string): array",
+        "Extracts the zip archive path into dir and returns the names of its entries.",
+    ),
+    (
+        "@tar_extract",
+        "@tar_extract(path: string, dir: string): array",
+        "Extracts the tar archive path, gzipped or not, into dir and returns the names of its entries.",
+    ),
+    (
+        "@sort_by",
+        "@sort_by(xs: array, key: function): array",
+        "Returns the elements of xs sorted by the keys the function returns for them. Elements with equal keys keep their order.",
+    ),
+    (
+        "@gc_stats",
+        "@gc_stats(): object",
+        "Returns the number of scopes alive (`scopes`), of cycle collections run (`collections`) and of scopes they \
+         freed (`freed`).",
+    ),
+    (
+        "@help",
+        "@help(name: string | function): null",
+        "Prints the signature and documentation of a native or of a function, e.g. `@help(\"push\")`.",
+    ),
+];
+
+/// The `help_library` function declares `@help` and documents every native of the standard library in the registry.
+///
+/// It has to be called after the other natives are declared, since the registry only documents natives that exist.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn help_library(environment: &mut Environment) {
+    declare_native(
+        "@help".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            if args.len() != 1 {
+                context.throw("E4018", vec!["@help".to_string(), 1.to_string()]);
+                exit(1);
+            }
+            let globals = &context.environment;
+            let natives = globals.natives.borrow();
+            let help = match &args[0] {
+                TronType::StringValue(name) => {
+                    describe_native(&natives, name).or_else(|| match globals.get_global(name) {
+                        Some(TronType::Callable(CallableImpl::Function(function))) => {
+                            Some(describe_function(&function))
+                        }
+                        _ => None,
+                    })
+                }
+                TronType::Callable(CallableImpl::Function(function)) => {
+                    Some(describe_function(function))
+                }
+                TronType::Callable(CallableImpl::StdFunction(function)) => {
+                    describe_native(&natives, &function.name)
+                }
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@help".to_string(),
+                            "string or function".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            match help {
+                Some(help) => println!("{}", help),
+                None => println!("no help found for {:?}", args[0]),
+            }
+            TronType::Null
+        },
+        environment,
+    );
+    let mut natives = environment.natives.borrow_mut();
+    for (name, signature, doc) in NATIVE_DOCS {
+        natives.document(name, signature, doc);
+    }
+}
+
+/// Returns the help text of a native, looked up with or without its `@` prefix.
+///
+/// Natives without documentation, like the ones of plugins, only show their arity.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn describe_native(natives: &NativeRegistry, name: &str) -> Option<String> {
+    let prefixed = format!("@{}", name);
+    let name = [name, prefixed.as_str()]
+        .into_iter()
+        .find(|name| natives.contains(name))?;
+    match natives.doc(name) {
+        Some(doc) => Some(format!("{}\n    {}", doc.signature, doc.doc)),
+        None => {
+            let arity = natives
+                .natives()
+                .into_iter()
+                .find(|(native, _)| &**native == name)
+                .map_or(Arity::exactly(0), |(_, arity)| arity);
+            Some(format!(
+                "{}\n    native function taking {} argument(s), no documentation",
+                name, arity
+            ))
+        }
+    }
+}
+
+/// Returns the help text of a user function: its signature and its doc comment.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn describe_function(function: &FunctionImpl) -> String {
+    let signature = signature(&function.name, &function.params, &function.output_type);
+    match &function.doc {
+        Some(doc) => format!("{}\n    {}", signature, doc.replace('\n', "\n    ")),
+        None => format!("{}\n    no documentation", signature),
+    }
+}
+
+/// Formats the signature of a function as it is declared, e.g. `add(a: number, b: number): number`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn signature(name: &str, params: &[(Token, Token)], output_type: &Token) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .map(|(name, param_type)| format!("{}: {}", name.lexeme, param_type.lexeme))
+        .collect();
+    format!("{}({}): {}", name, params.join(", "), output_type.lexeme)
+}
@@ -0,0 +1,390 @@
+use super::context::NativeContext;
+use super::declare_native;
+use super::effects;
+use super::signal::check_interrupt;
+use crate::environment::*;
+use crate::expressions::*;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::process::{exit, Command, Stdio};
+
+/// The exit code of `curl` when the server can't send the rest of a file, so the download starts over.
+///
+/// ### Last Updated: (v3.2.0)
+const CANNOT_RESUME: i32 = 33;
+
+/// The `download_library` function declares the natives of the standard library that download and verify files, for
+/// installer scripts.
+///
+/// - `@download(url, path, sha256)`: downloads `url` to `path` and returns the SHA-256 checksum of the file, as
+///   lowercase hex. When `sha256` is given, the file has to match it.
+/// - `@file_sha256(path)`: the SHA-256 checksum of the file `path`, as lowercase hex.
+///
+/// The file is downloaded to `path` with `.part` at the end of its name, and only moved to `path` once it's complete
+/// and verified, so `path` never holds half of a file or a file that doesn't match. A download that was interrupted
+/// goes on from where it stopped the next time, when the server can send the rest of the file, and a `path` that
+/// already matches `sha256` isn't downloaded again. A file that doesn't match is removed.
+///
+/// The transfer is made by `curl`, which comes with macOS, Windows and most Linux distributions, so HTTPS works without
+/// the interpreter carrying TLS, and redirects are followed. A download that fails throws `E4035` and the native
+/// returns `null`, and a file that can't be read throws `E0011`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn download_library(environment: &mut Environment) {
+    declare_native(
+        "@download".to_string(),
+        Arity::between(2, 3),
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let url = string("@download", &args[0], "first", context);
+            let path = string("@download", &args[1], "second", context);
+            let expected = match args.get(2) {
+                Some(TronType::Null) | None => None,
+                Some(TronType::StringValue(hash)) if is_sha256(hash) => Some(hash.to_lowercase()),
+                Some(_) => throw_type("@download", "SHA-256 hex string", "third", context),
+            };
+            // the contents of the file are only kept in memory for the cassette
+            let downloaded = match effects::is_live() {
+                true => download(&url, &path, expected.as_deref()),
+                false => effects::perform("@download", &url, || {
+                    download(&url, &path, expected.as_deref())?;
+                    fs::read(&path).map_err(|error| format!("failed to read {}: {}", path, error))
+                })
+                .and_then(|contents| replace(&path, &contents, expected.as_deref())),
+            };
+            check_interrupt(&context.environment);
+            match downloaded {
+                Ok(hash) => TronType::StringValue(hash),
+                Err(reason) => {
+                    context.throw("E4035", vec!["@download".to_string(), url, reason]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@file_sha256".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let path = string("@file_sha256", &args[0], "first", context);
+            let hash = effects::perform("@file_sha256", &path, || {
+                file_sha256(Path::new(&path))
+                    .map(String::into_bytes)
+                    .map_err(|error| error.to_string())
+            });
+            match hash {
+                Ok(hash) => TronType::StringValue(String::from_utf8_lossy(&hash).into_owned()),
+                Err(message) => {
+                    context.throw("E0011", vec![path, message]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+}
+
+fn string(name: &str, value: &TronType, place: &str, context: &NativeContext) -> String {
+    match value {
+        TronType::StringValue(string) => string.clone(),
+        _ => throw_type(name, "string", place, context),
+    }
+}
+
+fn throw_type(name: &str, expected: &str, place: &str, context: &NativeContext) -> ! {
+    context.throw(
+        "E4021",
+        vec![name.to_string(), expected.to_string(), place.to_string()],
+    );
+    exit(1)
+}
+
+// puts the contents of a download at `path` when they were replayed from a cassette, returning their checksum
+fn replace(path: &str, contents: &[u8], expected: Option<&str>) -> Result<String, String> {
+    let mut sha256 = Sha256::new();
+    sha256.update(contents);
+    let hash = hex(sha256.finish());
+    if let Some(expected) = expected.filter(|expected| *expected != hash) {
+        return Err(format!(
+            "the file has the checksum {} instead of {}",
+            hash, expected
+        ));
+    }
+    if fs::read(path).ok().as_deref() != Some(contents) {
+        fs::write(path, contents)
+            .map_err(|error| format!("failed to write {}: {}", path, error))?;
+    }
+    Ok(hash)
+}
+
+fn is_sha256(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Downloads `url` to `path` like `@download` does, through a `.part` file resumed from where an earlier download
+/// stopped, and returns the SHA-256 checksum of the file.
+///
+/// # Parameters
+///
+/// - `url`: The URL of the file.
+/// - `path`: Where the file goes once it's complete and matches `expected`.
+/// - `expected`: The SHA-256 checksum the file must have, as lowercase hex, if it's known.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn download(url: &str, path: &str, expected: Option<&str>) -> Result<String, String> {
+    if let Some(expected) = expected {
+        if file_sha256(Path::new(path)).is_ok_and(|hash| hash == expected) {
+            return Ok(expected.to_string());
+        }
+    }
+    let part = format!("{}.part", path);
+    let mut result = curl(url, &part, Path::new(&part).exists());
+    if matches!(result, Err((Some(CANNOT_RESUME), _))) {
+        let _ = fs::remove_file(&part);
+        result = curl(url, &part, false);
+    }
+    let hash = match (result, file_sha256(Path::new(&part))) {
+        (Ok(()), Ok(hash)) => hash,
+        // the `.part` file was complete already, and the server refused to send more of it
+        (Err(_), Ok(hash)) if expected == Some(hash.as_str()) => hash,
+        (Ok(()), Err(error)) => return Err(format!("failed to read {}: {}", part, error)),
+        (Err((_, message)), _) => return Err(message),
+    };
+    if let Some(expected) = expected.filter(|expected| *expected != hash) {
+        let _ = fs::remove_file(&part);
+        return Err(format!(
+            "the file has the checksum {} instead of {}",
+            hash, expected
+        ));
+    }
+    fs::rename(&part, path).map_err(|error| format!("failed to write {}: {}", path, error))?;
+    Ok(hash)
+}
+
+// runs curl, failing on HTTP errors and following redirects, with the exit code and the message it failed with
+fn curl(url: &str, output: &str, resume: bool) -> Result<(), (Option<i32>, String)> {
+    let mut command = Command::new("curl");
+    command.args([
+        "--fail",
+        "--silent",
+        "--show-error",
+        "--location",
+        "--retry",
+        "3",
+    ]);
+    if resume {
+        command.args(["--continue-at", "-"]);
+    }
+    let result = command
+        .args(["--output", output, "--", url])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .output()
+        .map_err(|error| (None, format!("failed to run curl: {}", error)))?;
+    match result.status.success() {
+        true => Ok(()),
+        false => Err((result.status.code(), curl_error(&result.stderr))),
+    }
+}
+
+/// Returns the text at `url`, like the version number a server publishes, with the transfer made by `curl` as for
+/// `download()`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn fetch_text(url: &str) -> Result<String, String> {
+    let result = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--",
+            url,
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|error| format!("failed to run curl: {}", error))?;
+    match result.status.success() {
+        true => Ok(String::from_utf8_lossy(&result.stdout).into_owned()),
+        false => Err(curl_error(&result.stderr)),
+    }
+}
+
+// the message curl printed last, `The requested URL returned error: 404` for `curl: (22) The requested URL ...`
+fn curl_error(stderr: &[u8]) -> String {
+    let message = String::from_utf8_lossy(stderr);
+    let message = message.lines().last().unwrap_or_default();
+    let message = message
+        .split_once(") ")
+        .map_or(message, |(_, message)| message);
+    message.trim().to_string()
+}
+
+// the SHA-256 checksum of a file as lowercase hex, reading it in chunks
+fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut chunk = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&chunk[..read]);
+    }
+    Ok(hex(hasher.finish()))
+}
+
+fn hex(digest: [u8; 32]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// SHA-256, FIPS 180-4, written in-tree under the dependency policy of `Cargo.toml`.
+///
+/// # Fields
+///
+/// - `state`: The hash of the blocks so far.
+/// - `block`: The bytes of the block being filled.
+/// - `length`: The number of bytes hashed.
+///
+/// ### Last Updated: (v3.2.0)
+struct Sha256 {
+    state: [u32; 8],
+    block: Vec<u8>,
+    length: u64,
+}
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+impl Sha256 {
+    fn new() -> Self {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            let taken = (64 - self.block.len()).min(data.len());
+            self.block.extend_from_slice(&data[..taken]);
+            data = &data[taken..];
+            if self.block.len() == 64 {
+                compress(&mut self.state, &self.block);
+                self.block.clear();
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bits = self.length * 8;
+        self.update(&[0x80]);
+        while self.block.len() != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+        let mut hash = [0; 32];
+        for (i, word) in self.state.iter().enumerate() {
+            hash[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        hash
+    }
+}
+
+// runs the 64 rounds of SHA-256 on a block, adding the result to the state
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut words = [0u32; 64];
+    for i in 0..16 {
+        words[i] = u32::from_be_bytes([
+            block[i * 4],
+            block[i * 4 + 1],
+            block[i * 4 + 2],
+            block[i * 4 + 3],
+        ]);
+    }
+    for i in 16..64 {
+        let s0 =
+            words[i - 15].rotate_right(7) ^ words[i - 15].rotate_right(18) ^ (words[i - 15] >> 3);
+        let s1 =
+            words[i - 2].rotate_right(17) ^ words[i - 2].rotate_right(19) ^ (words[i - 2] >> 10);
+        words[i] = words[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(words[i - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(choice)
+            .wrapping_add(ROUND_CONSTANTS[i])
+            .wrapping_add(words[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(majority);
+        (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256(chunks: &[&[u8]]) -> String {
+        let mut hasher = Sha256::new();
+        for chunk in chunks {
+            hasher.update(chunk);
+        }
+        hex(hasher.finish())
+    }
+
+    // the examples of FIPS 180-4, from the NIST examples with intermediate values
+    #[test]
+    fn hashes_the_fips_180_4_examples() {
+        assert_eq!(
+            sha256(&[b""]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256(&[b"abc"]),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // 56 bytes, so the padding takes a second block
+        assert_eq!(
+            sha256(&[b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"]),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            sha256(&[&[b'a'; 1_000_000]]),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn hashes_the_same_in_pieces() {
+        let message = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        let (first, rest) = message.split_at(13);
+        let (second, third) = rest.split_at(40);
+        assert_eq!(sha256(&[first, second, third]), sha256(&[message]));
+    }
+}
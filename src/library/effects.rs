@@ -0,0 +1,491 @@
+use crate::expressions::TronType;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{stdin, IsTerminal, Write};
+
+/// What a native needs to be allowed to do in `--ask` mode.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    Run,
+    Write,
+    Net,
+}
+
+impl Permission {
+    /// Describes the permission for the prompt and for `E4037`, like `run commands`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn action(self) -> &'static str {
+        match self {
+            Permission::Run => "run commands",
+            Permission::Write => "write files",
+            Permission::Net => "access the network",
+        }
+    }
+}
+
+// the natives `--ask` asks about, `@download` writing the file it downloads as well
+const GUARDED: [(&str, Permission); 9] = [
+    ("@cmd", Permission::Run),
+    ("@spawn_detached", Permission::Run),
+    ("@kill", Permission::Run),
+    ("@download", Permission::Net),
+    ("@ws_connect", Permission::Net),
+    ("@serve", Permission::Net),
+    ("@zip_create", Permission::Write),
+    ("@zip_extract", Permission::Write),
+    ("@tar_extract", Permission::Write),
+];
+
+/// A result of the outside world a native got, as kept in a cassette.
+///
+/// # Fields
+///
+/// - `native`: The native that got it, like `@cmd`.
+/// - `request`: What the native asked for, like the command or the URL, which tells the effects of a native apart.
+/// - `result`: The bytes the native got, or the message it failed with.
+/// - `replayed`: Whether the effect was replayed already, so the same request made twice gets the results in the
+///   order they were recorded.
+///
+/// ### Last Updated: (v3.2.0)
+struct Effect {
+    native: String,
+    request: String,
+    result: Result<Vec<u8>, String>,
+    replayed: bool,
+}
+
+/// What the effects layer does with the effects the natives perform.
+///
+/// ### Last Updated: (v3.2.0)
+enum Mode {
+    Live,
+    Record(String),
+    Replay(String, Vec<Effect>),
+}
+
+thread_local! {
+    static MODE: RefCell<Mode> = const { RefCell::new(Mode::Live) };
+    // the answers remembered for the session in `--ask` mode, `None` when natives don't ask
+    static ANSWERS: RefCell<Option<BTreeMap<Permission, bool>>> = const { RefCell::new(None) };
+    // whether the commands are only shown, with `--dry-run`
+    static DRY_RUN: Cell<bool> = const { Cell::new(false) };
+}
+
+const HEADER: &str =
+    "# Tron cassette, recorded with `tron run --record` and replayed with `tron run --replay`\n";
+
+/// Records the effects of the natives into the cassette `path`, with `--record <path>`, replacing what it held.
+///
+/// The natives that reach outside of the script go through the effects layer, see `perform()`: `@cmd` runs commands,
+/// `@download` downloads files, and `@file_sha256` and `@image_size` read files. What they get is appended to the
+/// cassette as soon as they get it, so the cassette is complete even when the script fails.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn record(path: &str) -> Result<(), String> {
+    fs::write(path, HEADER).map_err(|error| error.to_string())?;
+    MODE.with(|mode| *mode.borrow_mut() = Mode::Record(path.to_string()));
+    Ok(())
+}
+
+/// Replays the effects recorded in the cassette `path` instead of performing them, with `--replay <path>`, so a script
+/// that runs commands and downloads files can be tested offline and gets the same results on every run.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn replay(path: &str) -> Result<(), String> {
+    let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let effects = parse(&contents).map_err(|line| format!("invalid line {}", line))?;
+    MODE.with(|mode| *mode.borrow_mut() = Mode::Replay(path.to_string(), effects));
+    Ok(())
+}
+
+/// Turns `--ask` mode on, where the natives that run commands, write files or access the network ask the user first,
+/// like Deno does:
+///
+/// ```text
+/// ⚠ @cmd wants to run commands: rm -rf build
+///   Allow? [y]es, [n]o, [A]lways or [N]ever for the session:
+/// ```
+///
+/// `y` and `n` answer for this call only, `A` and `N` for every later call that needs the same permission. A denied
+/// call throws `E4037` and the native returns `null`. Without a terminal to ask in, every call is denied.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn ask() {
+    ANSWERS.with(|answers| *answers.borrow_mut() = Some(BTreeMap::new()));
+}
+
+/// Returns the permission a call of a native needs and whether it's granted, asking the user in `--ask` mode when it
+/// wasn't answered for the session yet. `None` when the native doesn't need one or natives don't ask.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn permit(native: &str, args: &[TronType]) -> Option<(Permission, bool)> {
+    let (_, permission) = GUARDED.iter().find(|(name, _)| *name == native)?;
+    // a command that's only shown doesn't need to be allowed
+    if *permission == Permission::Run && native != "@kill" && DRY_RUN.with(Cell::get) {
+        return None;
+    }
+    let remembered = ANSWERS.with(|answers| {
+        answers
+            .borrow()
+            .as_ref()
+            .map(|answers| answers.get(permission).copied())
+    })?;
+    if let Some(allowed) = remembered {
+        return Some((*permission, allowed));
+    }
+    let target = args
+        .first()
+        .map(|arg| format!(": {}", arg))
+        .unwrap_or_default();
+    eprintln!(
+        "\x1B[33m⚠\x1B[0m {} wants to {}{}",
+        native,
+        permission.action(),
+        target
+    );
+    if !stdin().is_terminal() {
+        eprintln!("  denied, there's no terminal to ask in");
+        return Some((*permission, false));
+    }
+    loop {
+        eprint!("  Allow? [y]es, [n]o, [A]lways or [N]ever for the session: ");
+        let mut answer = String::new();
+        if stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return Some((*permission, false));
+        }
+        let (allowed, remember) = match answer.trim() {
+            "y" | "yes" => (true, false),
+            "n" | "no" => (false, false),
+            "A" => (true, true),
+            "N" => (false, true),
+            _ => continue,
+        };
+        if remember {
+            ANSWERS.with(|answers| {
+                if let Some(answers) = answers.borrow_mut().as_mut() {
+                    answers.insert(*permission, allowed);
+                }
+            });
+        }
+        return Some((*permission, allowed));
+    }
+}
+
+/// Turns dry-run mode on, with `--dry-run`, to preview a deploy script safely: `@cmd` and `@spawn_detached` show the
+/// command they would run, on the standard error like `set -x` does, instead of running it. `@cmd` returns an empty
+/// output as if the command succeeded, and `@spawn_detached` the pid 0.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn dry_run() {
+    DRY_RUN.with(|dry_run| dry_run.set(true));
+}
+
+/// Shows the command a native would run in dry-run mode.
+///
+/// # Return Value
+///
+/// Whether the command has to be skipped, `false` when it runs normally.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn preview(native: &str, command: &str) -> bool {
+    if !DRY_RUN.with(Cell::get) {
+        return false;
+    }
+    eprintln!("\x1B[2m[dry run]\x1B[0m {}: {}", native, command);
+    true
+}
+
+/// Returns whether the effects are performed without a cassette, the way scripts normally run.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn is_live() -> bool {
+    MODE.with(|mode| matches!(*mode.borrow(), Mode::Live))
+}
+
+/// Performs an effect of a native through the effects layer: live, recorded into the cassette, or replayed from it.
+///
+/// # Parameters
+///
+/// - `native`: The name of the native.
+/// - `request`: What the native asks for, the same on every run of the script, like the command it runs.
+/// - `effect`: Performs the effect, returning what the native gets or the message it fails with.
+///
+/// # Return Value
+///
+/// The result of the effect. When replaying, the first result recorded for the request that wasn't replayed yet, and
+/// an error when there's none.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn perform(
+    native: &str,
+    request: &str,
+    effect: impl FnOnce() -> Result<Vec<u8>, String>,
+) -> Result<Vec<u8>, String> {
+    let replayed = MODE.with(|mode| match &mut *mode.borrow_mut() {
+        Mode::Replay(path, effects) => Some(
+            match effects.iter_mut().find(|effect| {
+                !effect.replayed && effect.native == native && effect.request == request
+            }) {
+                Some(effect) => {
+                    effect.replayed = true;
+                    effect.result.clone()
+                }
+                None => Err(format!("{} has no recording of {:?}", path, request)),
+            },
+        ),
+        _ => None,
+    });
+    if let Some(result) = replayed {
+        return result;
+    }
+    let result = effect();
+    let Some(path) = MODE.with(|mode| match &*mode.borrow() {
+        Mode::Record(path) => Some(path.clone()),
+        _ => None,
+    }) else {
+        return result;
+    };
+    let mut entry = format!("\n[{}]\nrequest = {}\n", native, quote(request.as_bytes()));
+    match &result {
+        Ok(bytes) => entry.push_str(&format!("ok = {}\n", quote(bytes))),
+        Err(message) => entry.push_str(&format!("error = {}\n", quote(message.as_bytes()))),
+    }
+    let appended = OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(entry.as_bytes()));
+    if let Err(error) = appended {
+        crate::utils::TronError::throw("E0010", 0, vec![path, error.to_string()]);
+    }
+    result
+}
+
+// `"..."` with `"`, `\` and the control characters escaped, and the bytes that aren't UTF-8 as `\xff`
+fn quote(bytes: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                c if c.is_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+                c => quoted.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            quoted.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// the bytes of a string `quote()` wrote
+fn unquote(quoted: &str) -> Option<Vec<u8>> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut bytes = vec![];
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next()? {
+            'n' => bytes.push(b'\n'),
+            'r' => bytes.push(b'\r'),
+            't' => bytes.push(b'\t'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+            }
+            c => bytes.push(c as u8),
+        }
+    }
+    Some(bytes)
+}
+
+// the effects of a cassette in the order they were recorded, or the number of the first invalid line
+fn parse(contents: &str) -> Result<Vec<Effect>, usize> {
+    let mut effects: Vec<Effect> = vec![];
+    let mut request = None;
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(native) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            request = Some((native.to_string(), None));
+            continue;
+        }
+        let invalid = index + 1;
+        let (key, value) = line.split_once(" = ").ok_or(invalid)?;
+        let value = unquote(value).ok_or(invalid)?;
+        let (native, requested) = request.as_mut().ok_or(invalid)?;
+        let result = match (key, requested.take()) {
+            ("request", None) => {
+                *requested = Some(String::from_utf8_lossy(&value).into_owned());
+                continue;
+            }
+            ("ok", Some(requested)) => (requested, Ok(value)),
+            ("error", Some(requested)) => {
+                (requested, Err(String::from_utf8_lossy(&value).into_owned()))
+            }
+            _ => return Err(invalid),
+        };
+        effects.push(Effect {
+            native: native.clone(),
+            request: result.0,
+            result: result.1,
+            replayed: false,
+        });
+    }
+    Ok(effects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a cassette in the temporary directory, unique to the test
+    fn cassette(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("tron-{}-{}.cassette", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn quotes_any_bytes_and_reads_them_back() {
+        let bytes = b"say \"hi\"\\\n\tok\x01\xff\xfe caf\xc3\xa9";
+        let quoted = quote(bytes);
+        assert_eq!(quoted, "\"say \\\"hi\\\"\\\\\\n\\tok\\x01\\xff\\xfe café\"");
+        assert_eq!(unquote(&quoted), Some(bytes.to_vec()));
+        assert_eq!(unquote("no quotes"), None);
+    }
+
+    #[test]
+    fn parses_the_effects_of_a_cassette_in_order() {
+        let effects = parse(
+            "# a comment\n\n[@cmd]\nrequest = \"ls\"\nok = \"a\\nb\"\n\n[@download]\nrequest = \"url\"\nerror = \"404\"\n",
+        )
+        .unwrap();
+        assert_eq!(effects.len(), 2);
+        assert_eq!(
+            (effects[0].native.as_str(), effects[0].request.as_str()),
+            ("@cmd", "ls")
+        );
+        assert_eq!(effects[0].result, Ok(b"a\nb".to_vec()));
+        assert_eq!(effects[1].result, Err("404".to_string()));
+    }
+
+    #[test]
+    fn reports_the_first_invalid_line() {
+        assert_eq!(parse("[@cmd]\nok = \"a\"\n").err(), Some(2));
+        assert_eq!(parse("request = \"ls\"\n").err(), Some(1));
+        assert_eq!(parse("[@cmd]\nrequest = ls\n").err(), Some(2));
+    }
+
+    #[test]
+    fn replays_what_was_recorded() {
+        let path = cassette("replay");
+        record(&path).unwrap();
+        assert_eq!(
+            perform("@cmd", "date", || Ok(b"first".to_vec())),
+            Ok(b"first".to_vec())
+        );
+        assert_eq!(
+            perform("@cmd", "date", || Ok(b"second".to_vec())),
+            Ok(b"second".to_vec())
+        );
+        assert_eq!(
+            perform("@cmd", "fail", || Err("exit 1".to_string())),
+            Err("exit 1".to_string())
+        );
+        replay(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        // the effect isn't performed again, and the same request gets its results in the order they were recorded
+        let unreachable = || -> Result<Vec<u8>, String> { panic!("performed while replaying") };
+        assert_eq!(perform("@cmd", "date", unreachable), Ok(b"first".to_vec()));
+        assert_eq!(perform("@cmd", "date", unreachable), Ok(b"second".to_vec()));
+        assert_eq!(
+            perform("@cmd", "fail", unreachable),
+            Err("exit 1".to_string())
+        );
+        assert!(perform("@cmd", "date", unreachable).is_err());
+        assert!(!is_live());
+    }
+
+    // `--ask` mode with the answers already given for the session
+    fn ask_with(answers: &[(Permission, bool)]) {
+        ask();
+        ANSWERS.with(|remembered| {
+            remembered
+                .borrow_mut()
+                .as_mut()
+                .unwrap()
+                .extend(answers.iter().copied())
+        });
+    }
+
+    #[test]
+    fn natives_dont_ask_without_ask_mode() {
+        assert_eq!(permit("@cmd", &[]), None);
+    }
+
+    #[test]
+    fn remembered_answers_apply_to_every_native_of_the_permission() {
+        ask_with(&[(Permission::Run, true), (Permission::Net, false)]);
+        assert_eq!(permit("@cmd", &[]), Some((Permission::Run, true)));
+        assert_eq!(permit("@kill", &[]), Some((Permission::Run, true)));
+        assert_eq!(permit("@download", &[]), Some((Permission::Net, false)));
+        assert_eq!(permit("@serve", &[]), Some((Permission::Net, false)));
+        // natives that don't reach outside never ask
+        assert_eq!(permit("@print", &[]), None);
+    }
+
+    #[test]
+    fn commands_that_are_only_shown_dont_ask() {
+        ask_with(&[(Permission::Run, false)]);
+        dry_run();
+        assert_eq!(permit("@cmd", &[]), None);
+        assert_eq!(permit("@spawn_detached", &[]), None);
+        // killing a process isn't previewed
+        assert_eq!(permit("@kill", &[]), Some((Permission::Run, false)));
+    }
+
+    #[test]
+    fn commands_run_unless_previewed() {
+        assert!(!preview("@cmd", "make deploy"));
+        dry_run();
+        assert!(preview("@cmd", "make deploy"));
+        assert!(preview("@spawn_detached", "./worker"));
+    }
+
+    #[test]
+    fn previewed_commands_dont_run() {
+        let marker = std::env::temp_dir().join(format!("tron-dry-run-{}", std::process::id()));
+        dry_run();
+        let mut interpreter = crate::interpreter::Interpreter::new();
+        interpreter
+            .execute(&format!(
+                "let output = @cmd(\"touch {}\"); let pid = @spawn_detached(\"touch {}\");",
+                marker.display(),
+                marker.display()
+            ))
+            .unwrap();
+        assert_eq!(
+            interpreter.get_global("output"),
+            Some(TronType::StringValue(String::new()))
+        );
+        assert_eq!(interpreter.get_global("pid"), Some(TronType::Number(0.0)));
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,185 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::process::exit;
+use std::rc::Rc;
+
+/// An argument of a memoized call, as a key of its cache.
+///
+/// Only values compared by their content can be keys: arrays and objects aren't compared by `==` and functions only by
+/// their name, so a call with one of them as an argument isn't cached. Numbers are keyed by their bits, which keeps
+/// `NaN` from never matching itself.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MemoKey {
+    Number(u32),
+    String(String),
+    Bool(bool),
+    Null,
+}
+
+impl MemoKey {
+    fn from_value(value: &TronType) -> Option<Self> {
+        match value {
+            TronType::Number(number) => Some(MemoKey::Number(number.to_bits())),
+            TronType::StringValue(string) => Some(MemoKey::String(string.clone())),
+            TronType::True => Some(MemoKey::Bool(true)),
+            TronType::False => Some(MemoKey::Bool(false)),
+            TronType::Null => Some(MemoKey::Null),
+            _ => None,
+        }
+    }
+}
+
+/// The `function_library` function declares the natives of the standard library that work on functions.
+///
+/// - `@memo(function)`: returns a function that caches the results of `function` by its arguments.
+/// - `@sort_by(array, key)`: returns the elements of `array` sorted by the keys `key` returns for them.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn function_library(environment: &mut Environment) {
+    declare_native(
+        "@memo".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            match &args[0] {
+                TronType::Callable(function) => memoize(function),
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@memo".to_string(),
+                            "function".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@sort_by".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::ArrayValue(elements) = &args[0] else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@sort_by".to_string(),
+                        "array".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            sort_by(elements, &args[1], context)
+        },
+        environment,
+    );
+}
+
+/// Sorts an array by the keys a function returns for its elements, for `@sort_by`.
+///
+/// The key of every element is computed once, before sorting. Numbers, big integers and decimals are ordered by value,
+/// strings alphabetically (by code point), `false` before `true`, and arrays element by element, so a key like
+/// `[person.last, person.first]` sorts by last name and then by first name. Keys that can't be ordered against each
+/// other, like a number and a string, throw `E4027`.
+///
+/// The sort is stable: elements with equal keys keep the order they had, so sorting by one key and then by another
+/// keeps the first order among the elements the second one can't tell apart.
+///
+/// ### Last Updated: (v3.2.0)
+fn sort_by(elements: &[TronType], key: &TronType, context: &NativeContext) -> TronType {
+    let mut keyed = Vec::with_capacity(elements.len());
+    for element in elements {
+        match context.call(key, vec![element.clone()]) {
+            Ok(key) => keyed.push((key, element.clone())),
+            Err(msg) => {
+                context.throw("E4023", vec!["@sort_by".to_string(), msg]);
+                return TronType::Null;
+            }
+        }
+    }
+    // the first pair of keys that can't be ordered, reported once the sort is over
+    let mut unordered = None;
+    // `sort_by` is a stable merge sort
+    keyed.sort_by(|(a, _), (b, _)| {
+        compare_keys(a, b).unwrap_or_else(|| {
+            unordered.get_or_insert((a.to_type().to_string(), b.to_type().to_string()));
+            Ordering::Equal
+        })
+    });
+    if let Some((a, b)) = unordered {
+        context.throw("E4027", vec!["@sort_by".to_string(), a, b]);
+        return TronType::Null;
+    }
+    TronType::ArrayValue(keyed.into_iter().map(|(_, element)| element).collect())
+}
+
+// the order of two sort keys, `None` when they can't be ordered
+fn compare_keys(a: &TronType, b: &TronType) -> Option<Ordering> {
+    match (a, b) {
+        (TronType::Number(x), TronType::Number(y)) => Some(x.total_cmp(y)),
+        (TronType::StringValue(x), TronType::StringValue(y)) => Some(x.cmp(y)),
+        #[cfg(feature = "bigint")]
+        (TronType::BigInt(x), TronType::BigInt(y)) => Some(x.cmp(y)),
+        #[cfg(feature = "bigint")]
+        (TronType::Decimal(x), TronType::Decimal(y)) => Some(x.cmp(y)),
+        (TronType::True | TronType::False, TronType::True | TronType::False) => {
+            Some((a == &TronType::True).cmp(&(b == &TronType::True)))
+        }
+        (TronType::ArrayValue(x), TronType::ArrayValue(y)) => {
+            for (x, y) in x.iter().zip(y) {
+                match compare_keys(x, y)? {
+                    Ordering::Equal => continue,
+                    order => return Some(order),
+                }
+            }
+            Some(x.len().cmp(&y.len()))
+        }
+        _ => None,
+    }
+}
+
+/// Wraps a function with a cache of its results, keyed by the arguments of each call.
+///
+/// A recursive function only benefits from the cache when it calls the memoized function, so it has to be declared as
+/// `let fib = @memo(fn(n) { ... });` with its recursive calls going through `fib`.
+///
+/// ### Last Updated: (v3.2.0)
+fn memoize(function: &CallableImpl) -> TronType {
+    let name = format!("@memo({})", function.name());
+    let arity = function.arity();
+    let function = TronType::Callable(function.clone());
+    let cache: RefCell<HashMap<Vec<MemoKey>, TronType>> = RefCell::new(HashMap::new());
+    let memoized_name = name.clone();
+    TronType::Callable(CallableImpl::StdFunction(StdFunctionImpl {
+        name,
+        arity,
+        function: Rc::new(move |context: &NativeContext, args: &Vec<TronType>| {
+            let key: Option<Vec<MemoKey>> = args.iter().map(MemoKey::from_value).collect();
+            // the borrow ends before the call, which can call the memoized function again
+            let cached = key
+                .as_ref()
+                .and_then(|key| cache.borrow().get(key).cloned());
+            if let Some(value) = cached {
+                return value;
+            }
+            let value = context.call(&function, args.clone()).unwrap_or_else(|msg| {
+                context.throw("E4023", vec![memoized_name.clone(), msg]);
+                TronType::Null
+            });
+            if let Some(key) = key {
+                cache.borrow_mut().insert(key, value.clone());
+            }
+            value
+        }),
+    }))
+}
@@ -0,0 +1,191 @@
+use super::context::NativeContext;
+use super::declare_native;
+use super::effects;
+use crate::environment::*;
+use crate::expressions::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::process::exit;
+
+/// The `image_library` function declares the natives of the standard library that read image metadata, with the
+/// `image` feature, for asset pipeline scripts.
+///
+/// - `@image_size(path)`: the `width` and `height` of the image `path` in pixels, and its `format`, one of `"png"`,
+///   `"jpeg"`, `"gif"`, `"bmp"` and `"webp"`, as an object.
+///
+/// Only the header of the image is read, so large images are probed as quickly as small ones, and nothing is decoded.
+/// A file that can't be read or isn't an image in one of these formats throws `E4036` and the native returns `null`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn image_library(environment: &mut Environment) {
+    declare_native(
+        "@image_size".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::StringValue(path) = &args[0] else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@image_size".to_string(),
+                        "string".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            // as `<width> <height> <format>` in a cassette
+            let probed = effects::perform("@image_size", path, || {
+                let (width, height, format) = File::open(path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|file| probe(&mut BufReader::new(file)))?;
+                Ok(format!("{} {} {}", width, height, format).into_bytes())
+            })
+            .and_then(|probed| {
+                let probed = String::from_utf8_lossy(&probed).into_owned();
+                match probed.split(' ').collect::<Vec<_>>()[..] {
+                    [width, height, format] => Ok((
+                        width.parse().unwrap_or_default(),
+                        height.parse().unwrap_or_default(),
+                        format.to_string(),
+                    )),
+                    _ => Err(format!("the recorded size {} is invalid", probed)),
+                }
+            });
+            match probed {
+                Ok((width, height, format)) => TronType::Object(HashMap::from([
+                    ("width".to_string(), TronType::Number(width)),
+                    ("height".to_string(), TronType::Number(height)),
+                    ("format".to_string(), TronType::StringValue(format)),
+                ])),
+                Err(reason) => {
+                    context.throw(
+                        "E4036",
+                        vec!["@image_size".to_string(), path.clone(), reason],
+                    );
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+}
+
+// the width, height and format of an image, from the magic bytes at its start
+fn probe(reader: &mut (impl Read + Seek)) -> Result<(u32, u32, &'static str), String> {
+    let mut header = [0; 30];
+    let length = read_up_to(reader, &mut header)?;
+    let header = &header[..length];
+    let le16 = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]) as u32;
+    let be32 = |at: usize| {
+        u32::from_be_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    let le24 = |at: usize| u32::from_le_bytes([header[at], header[at + 1], header[at + 2], 0]);
+    let le32 = |at: usize| {
+        u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    let truncated = || "the image is truncated".to_string();
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // the IHDR chunk comes first
+        if length < 24 || &header[12..16] != b"IHDR" {
+            return Err(truncated());
+        }
+        return Ok((be32(16), be32(20), "png"));
+    }
+    if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        if length < 10 {
+            return Err(truncated());
+        }
+        return Ok((le16(6), le16(8), "gif"));
+    }
+    if header.starts_with(b"BM") {
+        if length < 26 {
+            return Err(truncated());
+        }
+        // the OS/2 header of 12 bytes has 16-bit sizes, the others signed 32-bit ones, negative for top-down images
+        return match le32(14) {
+            12 => Ok((le16(18), le16(20), "bmp")),
+            _ => Ok((le32(18), (le32(22) as i32).unsigned_abs(), "bmp")),
+        };
+    }
+    if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+        if length < 30 {
+            return Err(truncated());
+        }
+        return match &header[12..16] {
+            b"VP8 " => Ok((le16(26) & 0x3fff, le16(28) & 0x3fff, "webp")),
+            b"VP8L" => {
+                let bits = le32(21);
+                Ok(((bits & 0x3fff) + 1, (bits >> 14 & 0x3fff) + 1, "webp"))
+            }
+            b"VP8X" => Ok((le24(24) + 1, le24(27) + 1, "webp")),
+            _ => Err("the WebP image has an unknown encoding".to_string()),
+        };
+    }
+    if header.starts_with(&[0xff, 0xd8]) {
+        reader
+            .seek(SeekFrom::Start(2))
+            .map_err(|error| error.to_string())?;
+        return jpeg_size(reader).map(|(width, height)| (width, height, "jpeg"));
+    }
+    Err("the file isn't a PNG, JPEG, GIF, BMP or WebP image".to_string())
+}
+
+// walks the segments of a JPEG image until the start of a frame, which has its size
+fn jpeg_size(reader: &mut (impl Read + Seek)) -> Result<(u32, u32), String> {
+    let mut byte = [0; 1];
+    loop {
+        read_exact(reader, &mut byte)?;
+        if byte[0] != 0xff {
+            return Err("the JPEG image is corrupted".to_string());
+        }
+        // markers may be padded with any number of 0xff
+        while byte[0] == 0xff {
+            read_exact(reader, &mut byte)?;
+        }
+        let marker = byte[0];
+        // the markers without a segment
+        if (0xd0..=0xd8).contains(&marker) || marker == 0x01 {
+            continue;
+        }
+        if marker == 0xd9 {
+            return Err("the JPEG image has no frame".to_string());
+        }
+        let mut length = [0; 2];
+        read_exact(reader, &mut length)?;
+        let length = u16::from_be_bytes(length) as i64;
+        // every start of frame marker but the ones of DHT, JPG and DAC
+        if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+            let mut frame = [0; 5];
+            read_exact(reader, &mut frame)?;
+            let height = u16::from_be_bytes([frame[1], frame[2]]) as u32;
+            let width = u16::from_be_bytes([frame[3], frame[4]]) as u32;
+            return Ok((width, height));
+        }
+        if length < 2 {
+            return Err("the JPEG image has no frame".to_string());
+        }
+        reader
+            .seek(SeekFrom::Current(length - 2))
+            .map_err(|error| error.to_string())?;
+    }
+}
+
+// reads as much of `buffer` as the file has
+fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, String> {
+    let mut length = 0;
+    while length < buffer.len() {
+        match reader.read(&mut buffer[length..]) {
+            Ok(0) => break,
+            Ok(read) => length += read,
+            Err(error) => return Err(error.to_string()),
+        }
+    }
+    Ok(length)
+}
+
+fn read_exact(reader: &mut impl Read, buffer: &mut [u8]) -> Result<(), String> {
+    reader
+        .read_exact(buffer)
+        .map_err(|_| "the image is truncated".to_string())
+}
@@ -0,0 +1,252 @@
+use num_bigint::Sign;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// An integer of any size, the value of `@bigint()`.
+///
+/// Numbers are 32 bit floats, so integers above 2^24 silently lose their last digits. A `BigInt` keeps every digit,
+/// for the arithmetic of combinatorics or cryptography scripts, at the cost of being slower than a number.
+///
+/// The arithmetic is done by the `num-bigint` crate, which the `bigint` feature brings in. This type only adds the
+/// conversions from the values of Tron and the division of `/` and `%`, which doesn't panic on 0. The default integer
+/// is 0.
+///
+/// # Example
+///
+/// ```
+/// let x = BigInt::parse("123456789012345678901234567890").unwrap();
+/// assert_eq!((&x * &x).to_string(), "15241578753238836750495351562536198787501905199875019052100");
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BigInt(num_bigint::BigInt);
+
+impl BigInt {
+    /// Parses a decimal integer with an optional sign, like `-123` or `+42`. Underscores can separate the digits, like
+    /// in `1_000_000`.
+    ///
+    /// # Return Value
+    ///
+    /// The integer, or `None` when the string isn't one.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn parse(string: &str) -> Option<Self> {
+        let string = string.trim();
+        let (negative, digits) = match string.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, string.strip_prefix('+').unwrap_or(string)),
+        };
+        let digits: String = digits.chars().filter(|&c| c != '_').collect();
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let magnitude: num_bigint::BigInt = digits.parse().ok()?;
+        Some(Self(if negative { -magnitude } else { magnitude }))
+    }
+    /// Converts a number to an integer, when it has no fractional part and is finite.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn from_f32(number: f32) -> Option<Self> {
+        if !number.is_finite() || number.fract() != 0.0 {
+            return None;
+        }
+        // a float is its 24 bit mantissa times a power of 2, up to about 3.4e38, more than an i128 holds
+        let bits = number.to_bits();
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = match exponent {
+            0 => (bits & 0x7f_ffff) << 1,
+            _ => (bits & 0x7f_ffff) | 0x80_0000,
+        };
+        let magnitude = num_bigint::BigInt::from(mantissa);
+        // the shift to the right only drops zeros, the number has no fractional part
+        let magnitude = match exponent - 150 {
+            shift if shift >= 0 => magnitude << shift,
+            shift => magnitude >> -shift,
+        };
+        Some(Self(if number < 0.0 { -magnitude } else { magnitude }))
+    }
+    /// Converts an unsigned integer of Rust.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn from_u32(value: u32) -> Self {
+        Self(value.into())
+    }
+    /// Whether the integer is below 0.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn is_negative(&self) -> bool {
+        self.0.sign() == Sign::Minus
+    }
+    /// Whether the integer is 0.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn is_zero(&self) -> bool {
+        self.0.sign() == Sign::NoSign
+    }
+    /// Divides by `divisor`, with the quotient rounded toward 0 and the remainder having the sign of the dividend,
+    /// like the `/` and `%` of integers in most languages: `-7 / 2` is `-3` and `-7 % 2` is `-1`.
+    ///
+    /// # Return Value
+    ///
+    /// The quotient and the remainder, or `None` when `divisor` is 0.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        Some((Self(&self.0 / &divisor.0), Self(&self.0 % &divisor.0)))
+    }
+    /// Raises the integer to the power `exponent`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn pow(&self, exponent: u32) -> BigInt {
+        Self(self.0.pow(exponent))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt(-&self.0)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        BigInt(&self.0 + &other.0)
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        BigInt(&self.0 - &other.0)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt(&self.0 * &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(string: &str) -> BigInt {
+        BigInt::parse(string).unwrap()
+    }
+
+    // the quotient and the remainder of `a / b` and `a % b`, as strings
+    fn div_rem(a: &str, b: &str) -> (String, String) {
+        let (quotient, remainder) = int(a).div_rem(&int(b)).unwrap();
+        (quotient.to_string(), remainder.to_string())
+    }
+
+    #[test]
+    fn division_rounds_toward_zero_and_the_remainder_has_the_sign_of_the_dividend() {
+        assert_eq!(div_rem("7", "2"), ("3".to_string(), "1".to_string()));
+        assert_eq!(div_rem("-7", "2"), ("-3".to_string(), "-1".to_string()));
+        assert_eq!(div_rem("7", "-2"), ("-3".to_string(), "1".to_string()));
+        assert_eq!(div_rem("-7", "-2"), ("3".to_string(), "-1".to_string()));
+        assert_eq!(
+            div_rem("-100000000000000000000000000001", "10"),
+            (
+                "-10000000000000000000000000000".to_string(),
+                "-1".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn division_by_zero_has_no_result() {
+        assert!(int("5").div_rem(&int("0")).is_none());
+        assert!(int("0").div_rem(&int("-0")).is_none());
+    }
+
+    #[test]
+    fn zero_has_no_sign() {
+        let zero = &int("5") - &int("5");
+        assert!(zero.is_zero() && !zero.is_negative());
+        assert_eq!(zero, BigInt::default());
+        assert_eq!(int("-0").to_string(), "0");
+        assert_eq!((-&zero).to_string(), "0");
+        assert_eq!((&int("-3") * &zero).to_string(), "0");
+        assert_eq!(div_rem("-4", "2"), ("-2".to_string(), "0".to_string()));
+    }
+
+    #[test]
+    fn carries_and_borrows_reach_the_next_digits() {
+        assert_eq!((&int("999999999") + &int("1")).to_string(), "1000000000");
+        assert_eq!(
+            (&int("999999999999999999") + &int("1")).to_string(),
+            "1000000000000000000"
+        );
+        assert_eq!(
+            (&int("1000000000000000000") - &int("1")).to_string(),
+            "999999999999999999"
+        );
+        assert_eq!((&int("1") - &int("1000000000")).to_string(), "-999999999");
+        assert_eq!(
+            (&int("999999999") * &int("999999999")).to_string(),
+            "999999998000000001"
+        );
+        assert_eq!(
+            (&int("-99999999999999999999") * &int("99999999999999999999")).to_string(),
+            "-9999999999999999999800000000000000000001"
+        );
+        assert_eq!(
+            int("2").pow(100).to_string(),
+            "1267650600228229401496703205376"
+        );
+    }
+
+    #[test]
+    fn parses_signs_and_underscores_only() {
+        assert_eq!(int("+42").to_string(), "42");
+        assert_eq!(int(" -1_000_000 ").to_string(), "-1000000");
+        assert!(BigInt::parse("").is_none());
+        assert!(BigInt::parse("-").is_none());
+        assert!(BigInt::parse("--1").is_none());
+        assert!(BigInt::parse("1.5").is_none());
+        assert!(BigInt::parse("0x10").is_none());
+    }
+
+    #[test]
+    fn converts_whole_numbers_only() {
+        assert_eq!(BigInt::from_f32(-16777216.0), Some(int("-16777216")));
+        assert_eq!(
+            BigInt::from_f32(1e30).map(|x| x.to_string().len()),
+            Some(31)
+        );
+        assert!(BigInt::from_f32(0.5).is_none());
+        assert!(BigInt::from_f32(f32::INFINITY).is_none());
+        assert!(BigInt::from_f32(f32::NAN).is_none());
+    }
+
+    #[test]
+    fn converts_numbers_above_the_i128_range() {
+        // i128::MAX is about 1.7e38, the largest floats are above it
+        assert_eq!(
+            BigInt::from_f32(f32::MAX),
+            Some(int("340282346638528859811704183484516925440"))
+        );
+        assert_eq!(
+            BigInt::from_f32(-3e38),
+            Some(int("-300000000549775575777803994281145270272"))
+        );
+        assert_eq!(BigInt::from_f32(0.0), Some(BigInt::default()));
+        assert_eq!(BigInt::from_f32(3.0), Some(int("3")));
+    }
+}
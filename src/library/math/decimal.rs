@@ -0,0 +1,337 @@
+use super::bigint::BigInt;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// the number of decimal places a quotient is computed to when it doesn't end before, `1 / 3` is 0.333... with 20 3s
+const DIVISION_PLACES: u32 = 20;
+
+/// A decimal number of any size, the value of `@decimal()`.
+///
+/// Numbers are binary floats, so `0.1 + 0.2` isn't exactly `0.3` and amounts of money drift a cent at a time. A
+/// `Decimal` stores the digits themselves, as an integer and the number of digits after the point, so sums and
+/// products of decimals are exact. Only a division that doesn't end, like `1 / 3`, is rounded, to 20 places.
+///
+/// # Fields
+///
+/// - `coefficient`: The digits of the number without the point, `-1234` for `-12.34`.
+/// - `scale`: The number of digits after the point, `2` for `-12.34`.
+///
+/// # Example
+///
+/// ```
+/// let price = Decimal::parse("19.99").unwrap();
+/// assert_eq!((&price * &Decimal::parse("3").unwrap()).to_string(), "59.97");
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    coefficient: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Parses a decimal number with an optional sign and fractional part, like `-12.50` or `+3`. Underscores can
+    /// separate the digits, like in `1_000.00`.
+    ///
+    /// # Return Value
+    ///
+    /// The number, or `None` when the string isn't one.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn parse(string: &str) -> Option<Self> {
+        let string = string.trim();
+        let (integer, fraction) = string.split_once('.').unwrap_or((string, ""));
+        // `1.` and `.5` are accepted, but not `.` or `-.` alone
+        if !fraction.bytes().all(|b| b.is_ascii_digit() || b == b'_')
+            || !integer.bytes().any(|b| b.is_ascii_digit()) && fraction.is_empty()
+        {
+            return None;
+        }
+        let coefficient = match integer {
+            "" | "-" | "+" => BigInt::parse(&format!("{}0{}", integer, fraction))?,
+            _ => BigInt::parse(&format!("{}{}", integer, fraction))?,
+        };
+        let scale = fraction.bytes().filter(|&b| b != b'_').count() as u32;
+        Some(Self { coefficient, scale })
+    }
+    /// Converts a number to the decimal it's shown as, so `@decimal(0.1)` is exactly `0.1` rather than the binary
+    /// float closest to it.
+    ///
+    /// # Return Value
+    ///
+    /// The decimal, or `None` when the number is infinite or `NaN`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn from_f32(number: f32) -> Option<Self> {
+        if !number.is_finite() {
+            return None;
+        }
+        // the shortest digits that read back as the same float, without an exponent
+        Self::parse(&number.to_string())
+    }
+    /// Converts an integer to a decimal without a fractional part.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn from_bigint(integer: BigInt) -> Self {
+        Self {
+            coefficient: integer,
+            scale: 0,
+        }
+    }
+    /// Whether the number is 0.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn is_zero(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+    /// Divides by `divisor`. The quotient is exact when it ends within 20 places, otherwise it's rounded to 20 places,
+    /// half away from 0, and the trailing zeros are dropped either way: `1 / 4` is `0.25`.
+    ///
+    /// # Return Value
+    ///
+    /// The quotient, or `None` when `divisor` is 0.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn div(&self, divisor: &Decimal) -> Option<Decimal> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let scale = DIVISION_PLACES.max(self.scale);
+        // (a / 10^p) / (b / 10^q) = (a * 10^(scale + q - p) / b) / 10^scale
+        let dividend = &self.coefficient * &power_of_ten(scale + divisor.scale - self.scale);
+        let (quotient, remainder) = dividend.div_rem(&divisor.coefficient)?;
+        let quotient = round_half_away(quotient, &remainder, &divisor.coefficient);
+        Some(Self::normalized(quotient, scale))
+    }
+    /// Returns the remainder of dividing by `divisor`, with the sign of the dividend like the `%` of numbers.
+    ///
+    /// # Return Value
+    ///
+    /// The remainder, or `None` when `divisor` is 0.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn rem(&self, divisor: &Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(divisor.scale);
+        let (_, remainder) = self.rescaled(scale).div_rem(&divisor.rescaled(scale))?;
+        Some(Self {
+            coefficient: remainder,
+            scale,
+        })
+    }
+    /// Rounds the number to `places` digits after the point, half away from 0 like prices are usually rounded, and
+    /// pads it with zeros when it has fewer: `2.345` is `2.35` and `2` is `2.00` with 2 places.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn round(&self, places: u32) -> Decimal {
+        if places >= self.scale {
+            return Self {
+                coefficient: self.rescaled(places),
+                scale: places,
+            };
+        }
+        let unit = power_of_ten(self.scale - places);
+        // the unit isn't 0, so the division always succeeds
+        let (quotient, remainder) = self.coefficient.div_rem(&unit).unwrap_or_default();
+        Self {
+            coefficient: round_half_away(quotient, &remainder, &unit),
+            scale: places,
+        }
+    }
+    /// Returns the same number without the trailing zeros of its fractional part, `1.50` as `1.5`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn trimmed(&self) -> Decimal {
+        Self::normalized(self.coefficient.clone(), self.scale)
+    }
+    // the coefficient of the same number with `scale` digits after the point, which can't be below the current one
+    fn rescaled(&self, scale: u32) -> BigInt {
+        &self.coefficient * &power_of_ten(scale - self.scale)
+    }
+    // drops the trailing zeros of the fractional part
+    fn normalized(mut coefficient: BigInt, mut scale: u32) -> Self {
+        let ten = BigInt::from_u32(10);
+        while scale > 0 {
+            match coefficient.div_rem(&ten) {
+                Some((quotient, remainder)) if remainder.is_zero() => {
+                    coefficient = quotient;
+                    scale -= 1;
+                }
+                _ => break,
+            }
+        }
+        Self { coefficient, scale }
+    }
+}
+
+fn power_of_ten(exponent: u32) -> BigInt {
+    BigInt::from_u32(10).pow(exponent)
+}
+
+// moves a truncated quotient one unit away from 0 when the remainder is at least half the divisor
+fn round_half_away(quotient: BigInt, remainder: &BigInt, divisor: &BigInt) -> BigInt {
+    let magnitude = |value: &BigInt| match value.is_negative() {
+        true => -value,
+        false => value.clone(),
+    };
+    let twice = &magnitude(remainder) + &magnitude(remainder);
+    if twice < magnitude(divisor) {
+        return quotient;
+    }
+    let unit = BigInt::from_u32(1);
+    // a quotient of 0 has no sign, the result is negative when the remainder (of the dividend's sign) and divisor differ
+    if quotient.is_negative()
+        || quotient.is_zero() && remainder.is_negative() != divisor.is_negative()
+    {
+        &quotient - &unit
+    } else {
+        &quotient + &unit
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = match self.coefficient.is_negative() {
+            true => (-&self.coefficient).to_string(),
+            false => self.coefficient.to_string(),
+        };
+        if self.coefficient.is_negative() {
+            write!(f, "-")?;
+        }
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}", digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}.{}", integer, fraction)
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.rescaled(scale).cmp(&other.rescaled(scale))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// `1.5` and `1.50` are the same number
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+    fn neg(self) -> Decimal {
+        Decimal {
+            coefficient: -&self.coefficient,
+            scale: self.scale,
+        }
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+    fn add(self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        Decimal {
+            coefficient: &self.rescaled(scale) + &other.rescaled(scale),
+            scale,
+        }
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+    fn sub(self, other: &Decimal) -> Decimal {
+        self + &-other
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal {
+            coefficient: &self.coefficient * &other.coefficient,
+            scale: self.scale + other.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(string: &str) -> Decimal {
+        Decimal::parse(string).unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_decimals() {
+        assert_eq!(decimal("-12.50").to_string(), "-12.50");
+        assert_eq!(decimal("+3").to_string(), "3");
+        assert_eq!(decimal("1_000.00").to_string(), "1000.00");
+        assert_eq!(decimal(".5").to_string(), "0.5");
+        assert_eq!(decimal("-0.05").to_string(), "-0.05");
+        for invalid in ["", ".", "-.", "1.2.3", "abc", "1.x"] {
+            assert!(Decimal::parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn adds_and_multiplies_exactly() {
+        assert_eq!((&decimal("0.1") + &decimal("0.2")).to_string(), "0.3");
+        assert_eq!((&decimal("19.99") * &decimal("3")).to_string(), "59.97");
+        assert_eq!((&decimal("1.00") - &decimal("1.01")).to_string(), "-0.01");
+        assert_eq!(decimal("1.5"), decimal("1.50"));
+        assert!(decimal("-2") < decimal("0.1"));
+    }
+
+    #[test]
+    fn divides_to_20_places() {
+        assert_eq!(decimal("1").div(&decimal("4")).unwrap().to_string(), "0.25");
+        assert_eq!(
+            decimal("1").div(&decimal("3")).unwrap().to_string(),
+            "0.33333333333333333333"
+        );
+        assert_eq!(
+            decimal("2").div(&decimal("-3")).unwrap().to_string(),
+            "-0.66666666666666666667"
+        );
+        assert!(decimal("1").div(&decimal("0.00")).is_none());
+        assert_eq!(
+            decimal("-7.5").rem(&decimal("2")).unwrap().to_string(),
+            "-1.5"
+        );
+    }
+
+    #[test]
+    fn rounds_half_away_from_zero() {
+        assert_eq!(decimal("2.345").round(2).to_string(), "2.35");
+        assert_eq!(decimal("-2.345").round(2).to_string(), "-2.35");
+        assert_eq!(decimal("2.344").round(2).to_string(), "2.34");
+        assert_eq!(decimal("-0.004").round(2).to_string(), "0.00");
+        assert_eq!(decimal("-0.005").round(2).to_string(), "-0.01");
+        assert_eq!(decimal("2").round(2).to_string(), "2.00");
+        assert_eq!(decimal("1.500").trimmed().to_string(), "1.5");
+    }
+
+    #[test]
+    fn converts_numbers_as_they_are_shown() {
+        assert_eq!(Decimal::from_f32(0.1).unwrap().to_string(), "0.1");
+        assert_eq!(Decimal::from_f32(-2.5).unwrap().to_string(), "-2.5");
+        assert!(Decimal::from_f32(f32::NAN).is_none());
+        assert!(Decimal::from_f32(f32::INFINITY).is_none());
+    }
+}
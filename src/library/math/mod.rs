@@ -0,0 +1,264 @@
+#[cfg(feature = "bigint")]
+pub mod bigint;
+#[cfg(feature = "bigint")]
+pub mod decimal;
+
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use crate::utils::deterministic;
+#[cfg(feature = "bigint")]
+use bigint::BigInt;
+#[cfg(feature = "bigint")]
+use decimal::Decimal;
+use std::process::exit;
+
+/// The `math_library` function declares the numeric constants and the natives of the standard library that inspect
+/// numbers.
+///
+/// - `Infinity` and `NaN`: the results of operations that overflow or have no numeric value, as constants to compare
+///   with or to start a minimum search from. They start with a capital letter, so they can't be reassigned.
+/// - `@is_nan(x)`: whether `x` is `NaN`, which `==` can't tell since `NaN` isn't equal to itself.
+/// - `@is_finite(x)`: whether `x` is neither infinite nor `NaN`.
+/// - `@random()`: a random number from 0 up to but not including 1, the same on every run with `--deterministic`.
+///
+/// The big integers and decimals of `bigint_library()` come with the `bigint` feature.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn math_library(environment: &mut Environment) {
+    environment.define("Infinity", TronType::Number(f32::INFINITY));
+    environment.define("NaN", TronType::Number(f32::NAN));
+    declare_number_predicate("@is_nan", f32::is_nan, environment);
+    declare_number_predicate("@is_finite", f32::is_finite, environment);
+    #[cfg(feature = "bigint")]
+    bigint_library(environment);
+    declare_native(
+        "@random".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            // the 24 bits a number holds exactly, so the result never rounds up to 1
+            TronType::Number((deterministic::random() >> 40) as f32 / (1 << 24) as f32)
+        },
+        environment,
+    );
+}
+
+/// The `bigint_library` function declares the natives of the exact number types, big integers and decimals, which are
+/// built on the `num-bigint` crate and only come with the `bigint` feature.
+///
+/// - `@bigint(x)`: converts a string of digits or an integer number to a big integer, which keeps every digit through
+///   `+`, `-`, `*`, `/` and `%` where a number loses them above 2^24.
+/// - `@decimal(x)`: converts a string like `"19.99"`, a number or a big integer to a decimal, which adds and multiplies
+///   exactly where `0.1 + 0.2` isn't `0.3` with numbers.
+/// - `@round_decimal(x, places)`: rounds a decimal to `places` digits after the point, half away from 0.
+/// - `@format_decimal(x, places)`: shows a decimal rounded to `places` digits with its thousands separated, like
+///   `1,234.50`.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(feature = "bigint")]
+fn bigint_library(environment: &mut Environment) {
+    declare_native(
+        "@bigint".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let integer = match &args[0] {
+                TronType::BigInt(integer) => Some(integer.clone()),
+                TronType::StringValue(digits) => BigInt::parse(digits),
+                TronType::Number(number) => BigInt::from_f32(*number),
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@bigint".to_string(),
+                            "string or number".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            match integer {
+                Some(integer) => TronType::BigInt(integer),
+                // a fraction or a string that isn't an integer
+                None => {
+                    context.throw("E4008", vec![args[0].to_string()]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@decimal".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let decimal = match &args[0] {
+                TronType::Decimal(decimal) => Some(decimal.clone()),
+                TronType::StringValue(digits) => Decimal::parse(digits),
+                TronType::Number(number) => Decimal::from_f32(*number),
+                TronType::BigInt(integer) => Some(Decimal::from_bigint(integer.clone())),
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@decimal".to_string(),
+                            "string or number".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            match decimal {
+                Some(decimal) => TronType::Decimal(decimal),
+                // infinity, `NaN`, or a string that isn't a number
+                None => {
+                    context.throw("E4008", vec![args[0].to_string()]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@round_decimal".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let (decimal, places) = decimal_and_places("@round_decimal", context, args);
+            TronType::Decimal(decimal.round(places))
+        },
+        environment,
+    );
+    declare_native(
+        "@format_decimal".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let (decimal, places) = decimal_and_places("@format_decimal", context, args);
+            TronType::StringValue(group_thousands(&decimal.round(places).to_string()))
+        },
+        environment,
+    );
+}
+
+// the arguments of `@round_decimal` and `@format_decimal`, a decimal (or a number converted to one) and a number of
+// places that isn't negative
+#[cfg(feature = "bigint")]
+fn decimal_and_places(name: &str, context: &NativeContext, args: &[TronType]) -> (Decimal, u32) {
+    let decimal = match &args[0] {
+        TronType::Decimal(decimal) => Some(decimal.clone()),
+        TronType::Number(number) => Decimal::from_f32(*number),
+        TronType::BigInt(integer) => Some(Decimal::from_bigint(integer.clone())),
+        _ => None,
+    };
+    let Some(decimal) = decimal else {
+        context.throw(
+            "E4021",
+            vec![name.to_string(), "decimal".to_string(), "first".to_string()],
+        );
+        exit(1)
+    };
+    match &args[1] {
+        TronType::Number(places) if *places >= 0.0 && places.fract() == 0.0 => {
+            (decimal, *places as u32)
+        }
+        _ => {
+            context.throw(
+                "E4021",
+                vec![
+                    name.to_string(),
+                    "whole number".to_string(),
+                    "second".to_string(),
+                ],
+            );
+            exit(1)
+        }
+    }
+}
+
+// `-1234567.50` as `-1,234,567.50`
+#[cfg(feature = "bigint")]
+fn group_thousands(decimal: &str) -> String {
+    let (sign, digits) = match decimal.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", decimal),
+    };
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, format!(".{}", fraction)),
+        None => (digits, String::new()),
+    };
+    let mut grouped = String::new();
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+// declares a native taking a number and returning whether it satisfies `predicate`
+fn declare_number_predicate(
+    name: &'static str,
+    predicate: fn(f32) -> bool,
+    environment: &mut Environment,
+) {
+    declare_native(
+        name.to_string(),
+        1,
+        move |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            match &args[0] {
+                TronType::Number(number) => TronType::from_bool(predicate(*number)),
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![name.to_string(), "number".to_string(), "first".to_string()],
+                    );
+                    exit(1)
+                }
+            }
+        },
+        environment,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    #[test]
+    fn infinity_and_nan_can_be_told_apart() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .execute(
+                "let nan = @is_nan(NaN);
+                let number_is_nan = @is_nan(1);
+                let infinity_is_finite = @is_finite(Infinity);
+                let minus_infinity_is_finite = @is_finite(-Infinity);
+                let nan_is_finite = @is_finite(NaN);
+                let number_is_finite = @is_finite(1.5e-3);
+                let nan_equals_itself = NaN == NaN;
+                let largest = Infinity > 3e38;",
+            )
+            .unwrap();
+        let expected = [
+            ("nan", true),
+            ("number_is_nan", false),
+            ("infinity_is_finite", false),
+            ("minus_infinity_is_finite", false),
+            ("nan_is_finite", false),
+            ("number_is_finite", true),
+            ("nan_equals_itself", false),
+            ("largest", true),
+        ];
+        for (name, value) in expected {
+            assert_eq!(
+                interpreter.get_global(name),
+                Some(TronType::from_bool(value)),
+                "{}",
+                name
+            );
+        }
+    }
+}
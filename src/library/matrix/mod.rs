@@ -0,0 +1,295 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::process::exit;
+
+/// A numeric array the matrix natives work on, read from the nested arrays of a program.
+///
+/// The numbers are stored in a single `Vec`, row after row, so the inner loops of the natives run over plain floats
+/// instead of going through a `TronType` for every element.
+///
+/// # Variants
+///
+/// - `Vector`: An array of numbers, like `[1, 2, 3]`.
+/// - `Matrix`: An array of rows of the same length, like `[[1, 2], [3, 4]]`.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+enum Tensor {
+    Vector(Vec<f32>),
+    Matrix {
+        rows: usize,
+        columns: usize,
+        values: Vec<f32>,
+    },
+}
+
+impl Tensor {
+    // `None` for an array with something else than numbers, or with rows of different lengths
+    fn from_value(value: &TronType) -> Option<Self> {
+        let TronType::ArrayValue(elements) = value else {
+            return None;
+        };
+        match elements.first() {
+            Some(TronType::ArrayValue(first)) => {
+                let columns = first.len();
+                let mut values = Vec::with_capacity(elements.len() * columns);
+                for row in elements {
+                    match Tensor::from_value(row)? {
+                        Tensor::Vector(row) if row.len() == columns => values.extend(row),
+                        _ => return None,
+                    }
+                }
+                Some(Tensor::Matrix {
+                    rows: elements.len(),
+                    columns,
+                    values,
+                })
+            }
+            _ => elements
+                .iter()
+                .map(|element| match element {
+                    TronType::Number(number) => Some(*number),
+                    _ => None,
+                })
+                .collect::<Option<Vec<f32>>>()
+                .map(Tensor::Vector),
+        }
+    }
+    fn to_value(&self) -> TronType {
+        let numbers = |values: &[f32]| values.iter().map(|x| TronType::Number(*x)).collect();
+        match self {
+            Tensor::Vector(values) => TronType::ArrayValue(numbers(values)),
+            // rows without columns, which `chunks()` can't split
+            Tensor::Matrix {
+                rows, columns: 0, ..
+            } => TronType::ArrayValue(vec![TronType::ArrayValue(vec![]); *rows]),
+            Tensor::Matrix {
+                columns, values, ..
+            } => TronType::ArrayValue(
+                values
+                    .chunks(*columns)
+                    .map(|row| TronType::ArrayValue(numbers(row)))
+                    .collect(),
+            ),
+        }
+    }
+    // `3` for a vector of 3 numbers, `2x3` for a matrix of 2 rows and 3 columns
+    fn shape(&self) -> String {
+        match self {
+            Tensor::Vector(values) => values.len().to_string(),
+            Tensor::Matrix { rows, columns, .. } => format!("{}x{}", rows, columns),
+        }
+    }
+    fn values(&self) -> &[f32] {
+        match self {
+            Tensor::Vector(values) | Tensor::Matrix { values, .. } => values,
+        }
+    }
+    // the same shape with other values, which have to be as many
+    fn with_values(&self, values: Vec<f32>) -> Self {
+        match self {
+            Tensor::Vector(_) => Tensor::Vector(values),
+            Tensor::Matrix { rows, columns, .. } => Tensor::Matrix {
+                rows: *rows,
+                columns: *columns,
+                values,
+            },
+        }
+    }
+}
+
+/// The `matrix_library` function declares the natives of the standard library that work on vectors (arrays of
+/// numbers) and matrices (arrays of rows of the same length).
+///
+/// - `@matrix(rows, columns, fill)`: returns a matrix of `rows` rows and `columns` columns, every element `fill`.
+/// - `@identity(n)`: returns the identity matrix of size `n`.
+/// - `@transpose(m)`: swaps the rows and columns of a matrix, a vector becomes a single column.
+/// - `@dot(a, b)`: the dot product of two vectors, or the product of matrices and vectors.
+/// - `@add_elements(a, b)`, `@sub_elements(a, b)` and `@mul_elements(a, b)`: apply `+`, `-` or `*` to the elements at
+///   the same position of two vectors or matrices of the same shape, or to every element and a number.
+///
+/// The natives that take two arrays throw `E4025` when their shapes don't fit together.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn matrix_library(environment: &mut Environment) {
+    declare_native(
+        "@matrix".to_string(),
+        3,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let rows = size("@matrix", &args[0], "first", context);
+            let columns = size("@matrix", &args[1], "second", context);
+            let TronType::Number(fill) = args[2] else {
+                throw_type("@matrix", "number", "third", context)
+            };
+            Tensor::Matrix {
+                rows,
+                columns,
+                values: vec![fill; rows * columns],
+            }
+            .to_value()
+        },
+        environment,
+    );
+    declare_native(
+        "@identity".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let n = size("@identity", &args[0], "first", context);
+            let mut values = vec![0.0; n * n];
+            for i in 0..n {
+                values[i * n + i] = 1.0;
+            }
+            Tensor::Matrix {
+                rows: n,
+                columns: n,
+                values,
+            }
+            .to_value()
+        },
+        environment,
+    );
+    declare_native(
+        "@transpose".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let (rows, columns, values) = match tensor("@transpose", &args[0], "first", context) {
+                Tensor::Vector(values) => (1, values.len(), values),
+                Tensor::Matrix {
+                    rows,
+                    columns,
+                    values,
+                } => (rows, columns, values),
+            };
+            let mut transposed = Vec::with_capacity(values.len());
+            for column in 0..columns {
+                for row in 0..rows {
+                    transposed.push(values[row * columns + column]);
+                }
+            }
+            Tensor::Matrix {
+                rows: columns,
+                columns: rows,
+                values: transposed,
+            }
+            .to_value()
+        },
+        environment,
+    );
+    declare_native(
+        "@dot".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let a = tensor("@dot", &args[0], "first", context);
+            let b = tensor("@dot", &args[1], "second", context);
+            // a vector is a row on the left of a matrix and a column on its right
+            let (rows, inner, columns, right_inner) = match (&a, &b) {
+                (Tensor::Vector(x), Tensor::Vector(y)) if x.len() == y.len() => {
+                    return TronType::Number(x.iter().zip(y).map(|(x, y)| x * y).sum());
+                }
+                (Tensor::Vector(x), Tensor::Vector(y)) => (1, x.len(), 1, y.len()),
+                (Tensor::Vector(x), Tensor::Matrix { rows, columns, .. }) => {
+                    (1, x.len(), *columns, *rows)
+                }
+                (Tensor::Matrix { rows, columns, .. }, Tensor::Vector(y)) => {
+                    (*rows, *columns, 1, y.len())
+                }
+                (
+                    Tensor::Matrix { rows, columns, .. },
+                    Tensor::Matrix {
+                        rows: right_rows,
+                        columns: right_columns,
+                        ..
+                    },
+                ) => (*rows, *columns, *right_columns, *right_rows),
+            };
+            if inner != right_inner {
+                return throw_shapes("@dot", &a, &b, context);
+            }
+            let (x, y) = (a.values(), b.values());
+            let mut product = vec![0.0; rows * columns];
+            // row by row of `y`, which reads both arrays in the order they're stored
+            for row in 0..rows {
+                for k in 0..inner {
+                    let factor = x[row * inner + k];
+                    for column in 0..columns {
+                        product[row * columns + column] += factor * y[k * columns + column];
+                    }
+                }
+            }
+            match (a, b) {
+                (Tensor::Matrix { .. }, Tensor::Matrix { .. }) => Tensor::Matrix {
+                    rows,
+                    columns,
+                    values: product,
+                },
+                _ => Tensor::Vector(product),
+            }
+            .to_value()
+        },
+        environment,
+    );
+    declare_elementwise("@add_elements", |x, y| x + y, environment);
+    declare_elementwise("@sub_elements", |x, y| x - y, environment);
+    declare_elementwise("@mul_elements", |x, y| x * y, environment);
+}
+
+// declares a native applying `operation` to the elements at the same position of two arrays, or to every element of an
+// array and a number
+fn declare_elementwise(
+    name: &'static str,
+    operation: fn(f32, f32) -> f32,
+    environment: &mut Environment,
+) {
+    declare_native(
+        name.to_string(),
+        2,
+        move |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let a = tensor(name, &args[0], "first", context);
+            let values: Vec<f32> = match &args[1] {
+                TronType::Number(y) => a.values().iter().map(|x| operation(*x, *y)).collect(),
+                other => {
+                    let b = tensor(name, other, "second", context);
+                    if a.shape() != b.shape() {
+                        return throw_shapes(name, &a, &b, context);
+                    }
+                    a.values()
+                        .iter()
+                        .zip(b.values())
+                        .map(|(x, y)| operation(*x, *y))
+                        .collect()
+                }
+            };
+            a.with_values(values).to_value()
+        },
+        environment,
+    );
+}
+
+fn tensor(name: &str, value: &TronType, position: &str, context: &NativeContext) -> Tensor {
+    match Tensor::from_value(value) {
+        Some(tensor) => tensor,
+        None => throw_type(name, "numeric array or matrix", position, context),
+    }
+}
+
+fn size(name: &str, value: &TronType, position: &str, context: &NativeContext) -> usize {
+    match value {
+        TronType::Number(size) if *size >= 0.0 && size.fract() == 0.0 => *size as usize,
+        _ => throw_type(name, "whole number", position, context),
+    }
+}
+
+fn throw_type(name: &str, expected: &str, position: &str, context: &NativeContext) -> ! {
+    context.throw(
+        "E4021",
+        vec![name.to_string(), expected.to_string(), position.to_string()],
+    );
+    exit(1)
+}
+
+fn throw_shapes(name: &str, a: &Tensor, b: &Tensor, context: &NativeContext) -> TronType {
+    context.throw("E4025", vec![name.to_string(), a.shape(), b.shape()]);
+    TronType::Null
+}
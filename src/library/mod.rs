@@ -1,18 +1,103 @@
 use crate::environment::*;
 use crate::expressions::*;
 use crate::panic;
-use crate::utils::TronError;
-use std::io as std_io;
+use crate::utils::{deterministic, TronError};
+#[cfg(feature = "archive")]
+use archive::archive_library;
+use audit::Outcome;
+#[cfg(feature = "clipboard")]
+use clipboard::clipboard_library;
+use context::NativeContext;
+use docs::help_library;
+use download::download_library;
+use function::function_library;
+#[cfg(feature = "image")]
+use image::image_library;
+use math::math_library;
+use matrix::matrix_library;
+use object::object_library;
+use process::process_library;
+use prompt::prompt_library;
+use queue::queue_library;
+#[cfg(feature = "server")]
+use server::server_library;
+use set::set_library;
+use signal::signal_library;
+use stats::stats_library;
+use std::collections::HashMap;
 use std::process::exit;
 use std::process::Command;
 use std::rc::Rc;
+use std::time::Instant;
+use string::buffer::buffer_library;
+#[cfg(feature = "markdown")]
+use string::markdown::markdown_library;
+use string::template::template_library;
+use string::{segments, string_library};
+use system::system_library;
+use temp::temp_library;
+use terminal::terminal_library;
+use websocket::websocket_library;
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod audit;
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod context;
+pub mod docs;
+pub mod download;
+pub mod effects;
+pub mod function;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod math;
+pub mod matrix;
+pub mod object;
+pub mod plugin;
+pub mod process;
+pub mod prompt;
+pub mod queue;
+pub mod registry;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod set;
+pub mod signal;
+pub mod stats;
+pub mod string;
+pub mod system;
+pub mod temp;
+pub mod terminal;
+pub mod websocket;
 
 pub fn declare_function(
     name: String,
-    args: usize,
+    args: impl Into<Arity>,
     fun: impl Fn(&Vec<TronType>) -> TronType + 'static,
     environment: &mut Environment,
 ) {
+    declare_native(
+        name,
+        args,
+        move |_: &NativeContext, args: &Vec<TronType>| fun(args),
+        environment,
+    )
+}
+
+/// Declares a native that is also given the `NativeContext` of its call, for natives that need more than their
+/// arguments: the globals, the settings, a callback to call or the location of the call to report an error at.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn declare_native(
+    name: String,
+    args: impl Into<Arity>,
+    fun: impl Fn(&NativeContext, &Vec<TronType>) -> TronType + 'static,
+    environment: &mut Environment,
+) {
+    let args = args.into();
+    environment
+        .natives
+        .borrow_mut()
+        .register(name.as_str(), args);
     environment.define(
         name.clone(),
         TronType::Callable(CallableImpl::StdFunction(StdFunctionImpl {
@@ -23,17 +108,102 @@ pub fn declare_function(
     )
 }
 
+/// Builds the shell script run by `@cmd` from its argument.
+///
+/// - A single-line string is run as it is.
+/// - A multi-line string is treated like a heredoc: leading and trailing blank lines are dropped,
+///   the common indentation is stripped and the script stops at the first failing line.
+/// - An array of strings is joined with `&&`, so every command only runs if the previous one succeeded.
+///
+/// Returns the script together with a flag telling whether the shell has to run in fail-fast (`-e`) mode,
+/// or `None` if the argument can't be turned into a command.
+fn command_script(command: &TronType) -> Option<(String, bool)> {
+    match command {
+        TronType::StringValue(command) if command.contains('\n') => {
+            let lines: Vec<&str> = command
+                .lines()
+                .skip_while(|line| line.trim().is_empty())
+                .collect();
+            let end = lines
+                .iter()
+                .rposition(|line| !line.trim().is_empty())
+                .map_or(0, |i| i + 1);
+            let indent = lines[..end]
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or(0);
+            let script = lines[..end]
+                .iter()
+                .map(|line| line.get(indent..).unwrap_or("").trim_end())
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some((script, true))
+        }
+        TronType::StringValue(command) => Some((command.clone(), false)),
+        TronType::ArrayValue(commands) => {
+            let mut parts = Vec::new();
+            for command in commands {
+                match command {
+                    TronType::StringValue(command) => parts.push(command.trim().to_string()),
+                    _ => return None,
+                }
+            }
+            Some((parts.join(" && "), false))
+        }
+        _ => None,
+    }
+}
+
+/// Releases what the natives of a script hold once it ends, normally or with Ctrl+C: the processes started by
+/// `@spawn_detached` are stopped and the paths created by `@temp_file` and `@temp_dir` are removed.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn clean_up() {
+    process::kill_children();
+    temp::remove_temporaries();
+}
+
 pub fn standard_library(environment: &mut Environment) {
+    string_library(environment);
+    buffer_library(environment);
+    template_library(environment);
+    #[cfg(feature = "markdown")]
+    markdown_library(environment);
+    function_library(environment);
+    math_library(environment);
+    matrix_library(environment);
+    stats_library(environment);
+    object_library(environment);
+    set_library(environment);
+    queue_library(environment);
+    terminal_library(environment);
+    prompt_library(environment);
+    system_library(environment);
+    signal_library(environment);
+    process_library(environment);
+    temp_library(environment);
+    #[cfg(feature = "server")]
+    server_library(environment);
+    websocket_library(environment);
+    download_library(environment);
+    #[cfg(feature = "image")]
+    image_library(environment);
+    #[cfg(feature = "archive")]
+    archive_library(environment);
+    #[cfg(feature = "clipboard")]
+    clipboard_library(environment);
     declare_function(
         "@print".to_string(),
-        1,
+        Arity::at_least(0),
         |args: &Vec<TronType>| {
-            if args.len() > 0 {
+            if !args.is_empty() {
                 for arg in args {
                     println!("{:?}", arg);
                 }
             } else {
-                println!("");
+                println!();
             }
             TronType::Null
         },
@@ -41,9 +211,9 @@ pub fn standard_library(environment: &mut Environment) {
     );
     declare_function(
         "@panic".to_string(),
-        1,
+        Arity::at_least(0),
         |args: &Vec<TronType>| -> TronType {
-            if args.len() > 0 {
+            if !args.is_empty() {
                 for arg in args {
                     eprintln!("\x1B[31m{:?}\x1B[0m \n", arg);
                 }
@@ -189,8 +359,10 @@ pub fn standard_library(environment: &mut Environment) {
         |args: &Vec<TronType>| -> TronType {
             if args.len() == 1 {
                 match &args[0] {
-                    TronType::StringValue(n) => TronType::Number(n.len() as f32),
+                    TronType::StringValue(n) => TronType::Number(segments(n).len() as f32),
                     TronType::ArrayValue(n) => TronType::Number(n.len() as f32),
+                    TronType::Set(n) => TronType::Number(n.members().len() as f32),
+                    TronType::Deque(n) => TronType::Number(n.borrow().len() as f32),
                     _ => {
                         TronError::throw(
                             "E4021",
@@ -218,10 +390,10 @@ pub fn standard_library(environment: &mut Environment) {
             if args.len() == 1 {
                 match &args[0] {
                     TronType::StringValue(n) => {
-                        println!("{}", n.to_string());
+                        println!("{}", n);
                         let mut input = String::new();
-                        std_io::stdin().read_line(&mut input).unwrap();
-                        TronType::StringValue(format!("{}", input))
+                        deterministic::read_line(&mut input).unwrap();
+                        TronType::StringValue(input)
                     }
                     _ => {
                         TronError::throw(
@@ -322,33 +494,48 @@ pub fn standard_library(environment: &mut Environment) {
         1,
         |args: &Vec<TronType>| -> TronType {
             if args.len() == 1 {
-                match &args[0] {
-                    TronType::StringValue(command) => {
-                        let output = Command::new("sh").arg("-c").arg(command).output();
-                        match output {
-                            Ok(output) => {
-                                if output.status.success() {
-                                    let stdout = String::from_utf8_lossy(&output.stdout);
-                                    TronType::StringValue(stdout.to_string())
-                                } else {
-                                    let stderr = String::from_utf8_lossy(&output.stderr);
-                                    TronError::throw("E4004", 0, vec![stderr.to_string()]);
-                                    exit(1)
+                match command_script(&args[0]) {
+                    Some((script, fail_fast)) => {
+                        if effects::preview("@cmd", &script) {
+                            return TronType::StringValue(String::new());
+                        }
+                        let output = effects::perform("@cmd", &script, || {
+                            let shell = match fail_fast {
+                                true => vec!["sh", "-e", "-c", &script],
+                                false => vec!["sh", "-c", &script],
+                            };
+                            let start = Instant::now();
+                            let output = Command::new(shell[0]).args(&shell[1..]).output();
+                            let outcome = match &output {
+                                Ok(output) => Outcome::Exited(output.status),
+                                Err(error) => Outcome::Failed(&error.to_string()),
+                            };
+                            audit::audited(&shell, start.elapsed(), outcome);
+                            match output {
+                                Ok(output) if output.status.success() => Ok(output.stdout),
+                                Ok(output) => {
+                                    Err(String::from_utf8_lossy(&output.stderr).into_owned())
                                 }
+                                Err(error) => Err(error.to_string()),
                             }
-                            Err(error) => {
-                                TronError::throw("E4004", 0, vec![error.to_string()]);
+                        });
+                        match output {
+                            Ok(stdout) => {
+                                TronType::StringValue(String::from_utf8_lossy(&stdout).into_owned())
+                            }
+                            Err(message) => {
+                                TronError::throw("E4004", 0, vec![message]);
                                 exit(1)
                             }
                         }
                     }
-                    _ => {
+                    None => {
                         TronError::throw(
                             "E4021",
                             0,
                             vec![
                                 "@cmd".to_string(),
-                                "string".to_string(),
+                                "string or array".to_string(),
                                 "first".to_string(),
                             ],
                         );
@@ -538,6 +725,12 @@ pub fn standard_library(environment: &mut Environment) {
                     (TronType::Number(base), TronType::Number(exp)) => {
                         TronType::Number(base.powf(*exp))
                     }
+                    #[cfg(feature = "bigint")]
+                    (TronType::BigInt(base), TronType::Number(exp))
+                        if *exp >= 0.0 && exp.fract() == 0.0 =>
+                    {
+                        TronType::BigInt(base.pow(*exp as u32))
+                    }
                     _ => {
                         TronError::throw(
                             "E4021",
@@ -584,4 +777,21 @@ pub fn standard_library(environment: &mut Environment) {
         },
         environment,
     );
+    declare_function(
+        "@gc_stats".to_string(),
+        0,
+        |_args: &Vec<TronType>| -> TronType {
+            let stats = gc::stats();
+            TronType::Object(HashMap::from([
+                ("scopes".to_string(), TronType::Number(stats.scopes as f32)),
+                (
+                    "collections".to_string(),
+                    TronType::Number(stats.collections as f32),
+                ),
+                ("freed".to_string(), TronType::Number(stats.freed as f32)),
+            ]))
+        },
+        environment,
+    );
+    help_library(environment);
 }
@@ -0,0 +1,141 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::collections::HashMap;
+use std::process::exit;
+
+/// The `object_library` function declares the natives of the standard library that work on objects.
+///
+/// - `@keys(object)`: the keys of `object`.
+/// - `@values(object)`: the values of `object`, in the order of their keys.
+/// - `@entries(object)`: the `[key, value]` pairs of `object`, to loop over with `for entry in @entries(object)`.
+/// - `@has_key(object, key)`: whether `object` has the key `key`.
+/// - `@merge(a, b)`: an object with the keys of both, the values of `b` replacing the ones of `a`.
+/// - `@remove_key(object, key)`: a copy of `object` without the key `key`.
+///
+/// Objects don't keep the order their keys were written in, so the natives returning arrays sort them by key, which
+/// makes the same object always come out the same way. Like every native, they never change the objects they get,
+/// `@merge` and `@remove_key` return a new one.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn object_library(environment: &mut Environment) {
+    declare_native(
+        "@keys".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let fields = sorted_fields("@keys", &args[0], context);
+            TronType::ArrayValue(
+                fields
+                    .into_iter()
+                    .map(|(key, _)| TronType::StringValue(key.clone()))
+                    .collect(),
+            )
+        },
+        environment,
+    );
+    declare_native(
+        "@values".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let fields = sorted_fields("@values", &args[0], context);
+            TronType::ArrayValue(fields.into_iter().map(|(_, value)| value.clone()).collect())
+        },
+        environment,
+    );
+    declare_native(
+        "@entries".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let fields = sorted_fields("@entries", &args[0], context);
+            TronType::ArrayValue(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| {
+                        TronType::ArrayValue(vec![
+                            TronType::StringValue(key.clone()),
+                            value.clone(),
+                        ])
+                    })
+                    .collect(),
+            )
+        },
+        environment,
+    );
+    declare_native(
+        "@has_key".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let fields = fields("@has_key", &args[0], "first", context);
+            let key = key("@has_key", &args[1], context);
+            TronType::from_bool(fields.contains_key(key))
+        },
+        environment,
+    );
+    declare_native(
+        "@merge".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let mut merged = fields("@merge", &args[0], "first", context).clone();
+            let other = fields("@merge", &args[1], "second", context);
+            merged.extend(
+                other
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+            TronType::Object(merged)
+        },
+        environment,
+    );
+    declare_native(
+        "@remove_key".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let mut fields = fields("@remove_key", &args[0], "first", context).clone();
+            fields.remove(key("@remove_key", &args[1], context));
+            TronType::Object(fields)
+        },
+        environment,
+    );
+}
+
+fn fields<'a>(
+    name: &str,
+    value: &'a TronType,
+    position: &str,
+    context: &NativeContext,
+) -> &'a HashMap<String, TronType> {
+    match value {
+        TronType::Object(fields) => fields,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "object".to_string(), position.to_string()],
+            );
+            exit(1)
+        }
+    }
+}
+
+fn sorted_fields<'a>(
+    name: &str,
+    value: &'a TronType,
+    context: &NativeContext,
+) -> Vec<(&'a String, &'a TronType)> {
+    let mut fields: Vec<_> = fields(name, value, "first", context).iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+    fields
+}
+
+fn key<'a>(name: &str, value: &'a TronType, context: &NativeContext) -> &'a str {
+    match value {
+        TronType::StringValue(key) => key,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "string".to_string(), "second".to_string()],
+            );
+            exit(1)
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::declare_function;
+use crate::environment::Environment;
+use crate::expressions::TronType;
+use std::ffi::{c_char, c_void, CStr, CString};
+
+/// A value passed between Tron and a plugin, the `TronValue` struct of `tron_plugin.h`.
+///
+/// Only null, numbers, strings and bools can cross the boundary; arrays, objects and functions are passed as null.
+/// Numbers are widened from `f32` to `f64` exactly on the way in and rounded back to `f32` on the way out.
+///
+/// ### Last Updated: (v3.2.0)
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PluginValue {
+    kind: u8,
+    number: f64,
+    string: *const c_char,
+    boolean: u8,
+}
+
+const KIND_NULL: u8 = 0;
+const KIND_NUMBER: u8 = 1;
+const KIND_STRING: u8 = 2;
+const KIND_BOOL: u8 = 3;
+const ENTRY_POINT: &CStr = c"tron_plugin_register";
+
+type PluginFunction = extern "C" fn(*const PluginValue, usize, *mut PluginValue);
+type RegisterFunction = extern "C" fn(*mut c_void, *const c_char, usize, PluginFunction);
+type EntryPoint = unsafe extern "C" fn(*mut c_void, RegisterFunction);
+
+impl PluginValue {
+    const NULL: PluginValue = PluginValue {
+        kind: KIND_NULL,
+        number: 0.0,
+        string: std::ptr::null(),
+        boolean: 0,
+    };
+}
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{c_char, c_int, c_void};
+    pub const RTLD_NOW: c_int = 2;
+    extern "C" {
+        pub fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+        pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        pub fn dlerror() -> *mut c_char;
+    }
+}
+
+/// Loads a plugin (a shared library following `tron_plugin.h`) and declares its functions as natives.
+///
+/// The library stays loaded until the program exits, since its functions can be called at any time.
+///
+/// # Return Value
+///
+/// The number of functions the plugin registered, or an error message when the library can't be loaded or doesn't
+/// export `tron_plugin_register`.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(unix)]
+pub fn load_plugin(path: &str, environment: &mut Environment) -> Result<usize, String> {
+    let c_path = CString::new(path).map_err(|e| e.to_string())?;
+    let mut natives: Vec<(String, usize, PluginFunction)> = vec![];
+    // SAFETY: the library is never closed, so the function pointers it registers stay valid, and the entry point is
+    // called with the signature documented in tron_plugin.h.
+    unsafe {
+        let handle = dl::dlopen(c_path.as_ptr(), dl::RTLD_NOW);
+        if handle.is_null() {
+            return Err(last_error());
+        }
+        let symbol = dl::dlsym(handle, ENTRY_POINT.as_ptr());
+        if symbol.is_null() {
+            return Err(last_error());
+        }
+        let entry: EntryPoint = std::mem::transmute::<*mut c_void, EntryPoint>(symbol);
+        entry(
+            &mut natives as *mut Vec<(String, usize, PluginFunction)> as *mut c_void,
+            register,
+        );
+    }
+    let count = natives.len();
+    for (name, arity, function) in natives {
+        declare_function(
+            format!("@{}", name),
+            arity,
+            move |args: &Vec<TronType>| call(function, args),
+            environment,
+        );
+    }
+    Ok(count)
+}
+
+#[cfg(not(unix))]
+pub fn load_plugin(_path: &str, _environment: &mut Environment) -> Result<usize, String> {
+    Err("plugins are only supported on unix platforms".to_string())
+}
+
+#[cfg(unix)]
+fn last_error() -> String {
+    // SAFETY: dlerror returns null or a valid C string owned by the loader.
+    unsafe {
+        let error = dl::dlerror();
+        if error.is_null() {
+            "unknown error".to_string()
+        } else {
+            CStr::from_ptr(error).to_string_lossy().to_string()
+        }
+    }
+}
+
+// called by the plugin for each of its functions, `ctx` is the list the functions are collected in
+extern "C" fn register(
+    ctx: *mut c_void,
+    name: *const c_char,
+    arity: usize,
+    function: PluginFunction,
+) {
+    if ctx.is_null() || name.is_null() {
+        return;
+    }
+    // SAFETY: `ctx` is the list passed to the entry point by `load_plugin`, which `tron_plugin.h` only lets plugins use
+    // until the entry point returns, and `name` is a C string.
+    let (natives, name) = unsafe {
+        (
+            &mut *(ctx as *mut Vec<(String, usize, PluginFunction)>),
+            CStr::from_ptr(name).to_string_lossy().to_string(),
+        )
+    };
+    natives.push((name, arity, function));
+}
+
+fn call(function: PluginFunction, args: &[TronType]) -> TronType {
+    // the strings have to outlive the call, so they are kept here while the values point into them
+    let strings: Vec<Option<CString>> = args
+        .iter()
+        .map(|arg| match arg {
+            TronType::StringValue(s) => CString::new(s.as_str()).ok(),
+            _ => None,
+        })
+        .collect();
+    let values: Vec<PluginValue> = args
+        .iter()
+        .zip(&strings)
+        .map(|(arg, string)| match (arg, string) {
+            // widening a float to a double is exact
+            (TronType::Number(n), _) => PluginValue {
+                kind: KIND_NUMBER,
+                number: *n as f64,
+                ..PluginValue::NULL
+            },
+            (TronType::StringValue(_), Some(string)) => PluginValue {
+                kind: KIND_STRING,
+                string: string.as_ptr(),
+                ..PluginValue::NULL
+            },
+            (TronType::True, _) | (TronType::False, _) => PluginValue {
+                kind: KIND_BOOL,
+                boolean: (*arg == TronType::True) as u8,
+                ..PluginValue::NULL
+            },
+            _ => PluginValue::NULL,
+        })
+        .collect();
+    let mut out = PluginValue::NULL;
+    function(values.as_ptr(), values.len(), &mut out);
+    match out.kind {
+        // rounded to the nearest float, the digits a double has beyond that are lost, see `tron_plugin.h`
+        KIND_NUMBER => TronType::Number(out.number as f32),
+        KIND_STRING if !out.string.is_null() => {
+            // SAFETY: plugins return strings as C strings that stay valid until they are copied here.
+            let string = unsafe { CStr::from_ptr(out.string) };
+            TronType::StringValue(string.to_string_lossy().to_string())
+        }
+        KIND_BOOL => TronType::from_bool(out.boolean != 0),
+        _ => TronType::Null,
+    }
+}
@@ -0,0 +1,51 @@
+/*
+ * C interface of Tron plugins, loaded with `tron run main.tron --plugin ./libfoo.so`.
+ *
+ * A plugin exports `tron_plugin_register`, which calls `register_native` once for every
+ * function it provides. The functions are available to Tron code with an `@` prefix.
+ *
+ *     static void shout(const TronValue *args, size_t argc, TronValue *out) { ... }
+ *
+ *     void tron_plugin_register(void *ctx, TronRegister register_native) {
+ *         register_native(ctx, "shout", 1, shout);
+ *     }
+ *
+ * `ctx` is only valid while `tron_plugin_register` runs: pass it to `register_native` from
+ * there and don't keep it, registering a function after `tron_plugin_register` returned is
+ * undefined behavior.
+ *
+ * Strings passed to a function are only valid during the call. Strings returned in `out`
+ * are copied right after the call, so they can point to a static or thread-local buffer.
+ *
+ * Tron numbers are 32-bit floats. They reach a plugin as exact doubles, but a number
+ * returned in `out` is rounded to the nearest float, keeping about 7 significant digits:
+ * integers above 16777216 (2^24) and fractions like 0.1 don't come back exactly.
+ */
+#ifndef TRON_PLUGIN_H
+#define TRON_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+enum {
+    TRON_NULL = 0,
+    TRON_NUMBER = 1,
+    TRON_STRING = 2,
+    TRON_BOOL = 3,
+};
+
+/* arrays, objects and functions are passed as TRON_NULL, `number` holds a float widened to
+ * a double, see above for the precision of returned numbers */
+typedef struct {
+    uint8_t kind;
+    double number;
+    const char *string;
+    uint8_t boolean;
+} TronValue;
+
+typedef void (*TronNative)(const TronValue *args, size_t argc, TronValue *out);
+typedef void (*TronRegister)(void *ctx, const char *name, size_t arity, TronNative function);
+
+void tron_plugin_register(void *ctx, TronRegister register_native);
+
+#endif
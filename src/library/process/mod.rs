@@ -0,0 +1,194 @@
+use super::audit::{audited, Outcome};
+use super::command_script;
+use super::context::NativeContext;
+use super::declare_native;
+use super::effects;
+use crate::environment::*;
+use crate::expressions::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::process::{exit, Child, Command, Stdio};
+use std::time::Duration;
+
+thread_local! {
+    // the processes started by `@spawn_detached` that weren't waited for yet, by pid
+    static CHILDREN: RefCell<HashMap<u32, Child>> = RefCell::new(HashMap::new());
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_int;
+    pub const SIGTERM: c_int = 15;
+    extern "C" {
+        pub fn kill(pid: c_int, signal: c_int) -> c_int;
+    }
+}
+
+/// The `process_library` function declares the natives of the standard library that manage processes, for
+/// long-lived subprocesses a script has to watch or stop, where `@cmd` waits for the command to finish.
+///
+/// - `@pid()`: the pid of the script.
+/// - `@spawn_detached(command)`: starts `command` in the background, without its input and output, and returns its
+///   pid. The command is the same as the one of `@cmd`, a string, a multi-line script or an array of commands.
+/// - `@is_running(pid)`: whether the process is still running.
+/// - `@kill(pid)`: stops the process and returns whether it was running.
+///
+/// The interpreter keeps the processes it started, so they're waited for once they finish instead of staying zombies,
+/// and the ones still running when the script ends or is interrupted with Ctrl+C are stopped. `@kill` and `@is_running`
+/// also work with the pid of a process the script didn't start, on Unix.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn process_library(environment: &mut Environment) {
+    declare_native(
+        "@pid".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            TronType::Number(std::process::id() as f32)
+        },
+        environment,
+    );
+    declare_native(
+        "@spawn_detached".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let Some((script, fail_fast)) = command_script(&args[0]) else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@spawn_detached".to_string(),
+                        "string or array".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            if effects::preview("@spawn_detached", &script) {
+                return TronType::Number(0.0);
+            }
+            let args = match fail_fast {
+                true => vec!["sh", "-e", "-c", &script],
+                false => vec!["sh", "-c", &script],
+            };
+            let mut shell = Command::new(args[0]);
+            // in a group of its own, which Ctrl+C in the terminal doesn't reach and `@kill` stops as a whole
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+            let child = shell
+                .args(&args[1..])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            match child {
+                Ok(child) => {
+                    let pid = child.id();
+                    audited(&args, Duration::ZERO, Outcome::Spawned(pid));
+                    CHILDREN.with(|children| children.borrow_mut().insert(pid, child));
+                    TronType::Number(pid as f32)
+                }
+                Err(error) => {
+                    audited(&args, Duration::ZERO, Outcome::Failed(&error.to_string()));
+                    context.throw("E4004", vec![error.to_string()]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@is_running".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let pid = pid("@is_running", &args[0], context);
+            let tracked = CHILDREN.with(|children| {
+                let mut children = children.borrow_mut();
+                let running = match children.get_mut(&pid)?.try_wait() {
+                    Ok(None) => true,
+                    // finished, and now waited for
+                    _ => false,
+                };
+                if !running {
+                    children.remove(&pid);
+                }
+                Some(running)
+            });
+            TronType::from_bool(tracked.unwrap_or_else(|| signal(pid, 0)))
+        },
+        environment,
+    );
+    declare_native(
+        "@kill".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let pid = pid("@kill", &args[0], context);
+            let child = CHILDREN.with(|children| children.borrow_mut().remove(&pid));
+            let killed = match child {
+                Some(mut child) => {
+                    let running = matches!(child.try_wait(), Ok(None));
+                    stop(&mut child);
+                    running
+                }
+                #[cfg(unix)]
+                None => signal(pid, sys::SIGTERM),
+                #[cfg(not(unix))]
+                None => false,
+            };
+            TronType::from_bool(killed)
+        },
+        environment,
+    );
+}
+
+/// Stops the processes started by `@spawn_detached` that are still running and waits for them.
+///
+/// Called when the script ends, since a process nobody can reach anymore would keep running forever.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn kill_children() {
+    let children = CHILDREN.with(|children| std::mem::take(&mut *children.borrow_mut()));
+    for (_, mut child) in children {
+        stop(&mut child);
+    }
+}
+
+// stops a process started by `@spawn_detached` with the commands the shell started, and waits for it
+fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    signal_group(child.id(), sys::SIGTERM);
+    if matches!(child.try_wait(), Ok(None)) {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+// sends a signal to a process the script didn't start, 0 only checks that it exists
+#[cfg(unix)]
+fn signal(pid: u32, signal: std::ffi::c_int) -> bool {
+    // SAFETY: `kill` has no preconditions, a pid that doesn't exist is an error it returns.
+    unsafe { sys::kill(pid as std::ffi::c_int, signal) == 0 }
+}
+
+// sends a signal to the process group led by `pid`
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: std::ffi::c_int) -> bool {
+    // SAFETY: as in `signal`, a negative pid names a process group.
+    unsafe { sys::kill(-(pid as std::ffi::c_int), signal) == 0 }
+}
+
+#[cfg(not(unix))]
+fn signal(_pid: u32, _signal: i32) -> bool {
+    false
+}
+
+fn pid(name: &str, value: &TronType, context: &NativeContext) -> u32 {
+    match value {
+        TronType::Number(pid) if *pid >= 1.0 && pid.fract() == 0.0 => *pid as u32,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "pid".to_string(), "first".to_string()],
+            );
+            exit(1)
+        }
+    }
+}
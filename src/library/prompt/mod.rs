@@ -0,0 +1,138 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use crate::utils::deterministic;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::process::exit;
+
+/// The `prompt_library` function declares the natives of the standard library that ask the user for input, asking
+/// again until the answer is valid.
+///
+/// - `@confirm(prompt)`: asks a yes or no question, returning `true` for `y` or `yes` and `false` for `n` or `no`, in
+///   any case.
+/// - `@select(prompt, options)`: shows the elements of the array `options` numbered from 1 and returns the one whose
+///   number the user enters.
+/// - `@password(prompt)`: reads a line without showing what's typed, returning it without the line break.
+///
+/// When the input ends before a valid answer, `@confirm` returns `false`, `@select` returns `null` and `@password`
+/// returns what was read so far, so a script reading from a file or a pipe doesn't wait forever.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn prompt_library(environment: &mut Environment) {
+    declare_native(
+        "@confirm".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let prompt = string("@confirm", &args[0], context);
+            loop {
+                let Some(answer) = ask(&format!("{} [y/n] ", prompt)) else {
+                    return TronType::False;
+                };
+                match answer.trim().to_lowercase().as_str() {
+                    "y" | "yes" => return TronType::True,
+                    "n" | "no" => return TronType::False,
+                    _ => println!("Please answer y or n."),
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@select".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let prompt = string("@select", &args[0], context);
+            let options = match &args[1] {
+                TronType::ArrayValue(options) if !options.is_empty() => options,
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@select".to_string(),
+                            "non-empty array".to_string(),
+                            "second".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            println!("{}", prompt);
+            for (i, option) in options.iter().enumerate() {
+                println!("  {}) {}", i + 1, option.to_plain_string());
+            }
+            loop {
+                let Some(answer) = ask(&format!("[1-{}] ", options.len())) else {
+                    return TronType::Null;
+                };
+                match answer.trim().parse::<usize>() {
+                    Ok(choice) if (1..=options.len()).contains(&choice) => {
+                        return options[choice - 1].clone()
+                    }
+                    _ => println!("Please enter a number from 1 to {}.", options.len()),
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@password".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let prompt = string("@password", &args[0], context);
+            let hidden = set_echo(false);
+            let answer = ask(&prompt);
+            if hidden {
+                set_echo(true);
+                // the line break typed by the user wasn't shown either
+                println!();
+            }
+            TronType::StringValue(answer.unwrap_or_default())
+        },
+        environment,
+    );
+}
+
+// shows a prompt and reads a line without its line break, `None` once the input has ended
+fn ask(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    let _ = stdout().flush();
+    let mut line = String::new();
+    match deterministic::read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end_matches(['\n', '\r']).to_string()),
+    }
+}
+
+// turns the echo of the terminal on or off with `stty`, returning whether it could, which it can't when the input
+// isn't a terminal
+#[cfg(unix)]
+fn set_echo(on: bool) -> bool {
+    if !stdin().is_terminal() {
+        return false;
+    }
+    std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(std::process::Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+// there's no `stty` to hide the input with, so it's shown
+#[cfg(not(unix))]
+fn set_echo(_on: bool) -> bool {
+    false
+}
+
+fn string(name: &str, value: &TronType, context: &NativeContext) -> String {
+    match value {
+        TronType::StringValue(string) => string.clone(),
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "string".to_string(), "first".to_string()],
+            );
+            exit(1)
+        }
+    }
+}
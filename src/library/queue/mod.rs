@@ -0,0 +1,237 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::process::exit;
+use std::rc::Rc;
+
+/// A double-ended queue, the value of `@deque()`.
+///
+/// Unlike arrays, which natives copy to return a changed one, a deque is changed in place and shared by every variable
+/// holding it, so adding or removing an element at either end takes the same time however long it is. That makes it
+/// the queue of a breadth-first search, where `@shift` and `@unshift` would copy the whole array every time.
+///
+/// ### Last Updated: (v3.2.0)
+pub type Deque = Rc<RefCell<VecDeque<TronType>>>;
+
+thread_local! {
+    // the pairs of deques being compared and the deques being printed, innermost last, so a deque that contains
+    // itself is only walked once
+    static COMPARING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(vec![]) };
+    static PRINTING: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+}
+
+/// Returns whether two deques are equal, the same deque or deques with equal elements in the same order.
+///
+/// A deque can contain itself, since it's shared, so a pair of deques met again while their elements are compared is
+/// taken as equal instead of being compared forever.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn deques_equal(x: &Deque, y: &Deque) -> bool {
+    if Rc::ptr_eq(x, y) {
+        return true;
+    }
+    let pair = (Rc::as_ptr(x) as usize, Rc::as_ptr(y) as usize);
+    if COMPARING.with(|comparing| comparing.borrow().contains(&pair)) {
+        return true;
+    }
+    COMPARING.with(|comparing| comparing.borrow_mut().push(pair));
+    let equal = *x.borrow() == *y.borrow();
+    COMPARING.with(|comparing| comparing.borrow_mut().pop());
+    equal
+}
+
+/// Returns how a deque prints, `deque(1, 2)`, with `deque(...)` for a deque inside itself.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn display_deque(deque: &Deque) -> String {
+    let id = Rc::as_ptr(deque) as usize;
+    if PRINTING.with(|printing| printing.borrow().contains(&id)) {
+        return "deque(...)".to_string();
+    }
+    PRINTING.with(|printing| printing.borrow_mut().push(id));
+    let elements: Vec<String> = deque
+        .borrow()
+        .iter()
+        .map(|element| element.to_string())
+        .collect();
+    PRINTING.with(|printing| printing.borrow_mut().pop());
+    format!("deque({})", elements.join(", "))
+}
+
+/// The `queue_library` function declares the natives of the standard library that work on the ends of arrays and on
+/// deques.
+///
+/// - `@unshift(array, x)`: a copy of `array` with `x` added at the start, the opposite of `@shift`.
+/// - `@peek_first(xs)` and `@peek_last(xs)`: the first or last element of an array or a deque, `null` when it's empty.
+/// - `@deque(array)`: a deque with the elements of `array`.
+/// - `@push_front(deque, x)` and `@push_back(deque, x)`: add `x` at the start or end of `deque`, returning it.
+/// - `@pop_front(deque)` and `@pop_back(deque)`: remove the first or last element of `deque` and return it, `null`
+///   when it's empty.
+/// - `@deque_values(deque)`: the elements of `deque` as an array, to loop over them with `for x in ...`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn queue_library(environment: &mut Environment) {
+    declare_native(
+        "@unshift".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::ArrayValue(elements) = &args[0] else {
+                throw_type("@unshift", "array", context)
+            };
+            let mut unshifted = Vec::with_capacity(elements.len() + 1);
+            unshifted.push(args[1].clone());
+            unshifted.extend(elements.iter().cloned());
+            TronType::ArrayValue(unshifted)
+        },
+        environment,
+    );
+    declare_native(
+        "@peek_first".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let first = match &args[0] {
+                TronType::ArrayValue(elements) => elements.first().cloned(),
+                TronType::Deque(deque) => deque.borrow().front().cloned(),
+                _ => throw_type("@peek_first", "array or deque", context),
+            };
+            first.unwrap_or(TronType::Null)
+        },
+        environment,
+    );
+    declare_native(
+        "@peek_last".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let last = match &args[0] {
+                TronType::ArrayValue(elements) => elements.last().cloned(),
+                TronType::Deque(deque) => deque.borrow().back().cloned(),
+                _ => throw_type("@peek_last", "array or deque", context),
+            };
+            last.unwrap_or(TronType::Null)
+        },
+        environment,
+    );
+    declare_native(
+        "@deque".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::ArrayValue(elements) = &args[0] else {
+                throw_type("@deque", "array", context)
+            };
+            TronType::Deque(Rc::new(RefCell::new(elements.iter().cloned().collect())))
+        },
+        environment,
+    );
+    declare_native(
+        "@push_front".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            deque("@push_front", &args[0], context)
+                .borrow_mut()
+                .push_front(args[1].clone());
+            args[0].clone()
+        },
+        environment,
+    );
+    declare_native(
+        "@push_back".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            deque("@push_back", &args[0], context)
+                .borrow_mut()
+                .push_back(args[1].clone());
+            args[0].clone()
+        },
+        environment,
+    );
+    declare_native(
+        "@pop_front".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let first = deque("@pop_front", &args[0], context)
+                .borrow_mut()
+                .pop_front();
+            first.unwrap_or(TronType::Null)
+        },
+        environment,
+    );
+    declare_native(
+        "@pop_back".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let last = deque("@pop_back", &args[0], context)
+                .borrow_mut()
+                .pop_back();
+            last.unwrap_or(TronType::Null)
+        },
+        environment,
+    );
+    declare_native(
+        "@deque_values".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let elements = deque("@deque_values", &args[0], context)
+                .borrow()
+                .iter()
+                .cloned()
+                .collect();
+            TronType::ArrayValue(elements)
+        },
+        environment,
+    );
+}
+
+fn deque<'a>(name: &str, value: &'a TronType, context: &NativeContext) -> &'a Deque {
+    match value {
+        TronType::Deque(deque) => deque,
+        _ => throw_type(name, "deque", context),
+    }
+}
+
+fn throw_type(name: &str, expected: &str, context: &NativeContext) -> ! {
+    context.throw(
+        "E4021",
+        vec![name.to_string(), expected.to_string(), "first".to_string()],
+    );
+    exit(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deque(elements: Vec<TronType>) -> Deque {
+        Rc::new(RefCell::new(elements.into()))
+    }
+
+    #[test]
+    fn a_deque_containing_itself_equals_itself() {
+        let d = deque(vec![TronType::Number(1.0)]);
+        d.borrow_mut().push_back(TronType::Deque(d.clone()));
+        assert!(deques_equal(&d, &d));
+        assert_eq!(TronType::Deque(d.clone()), TronType::Deque(d.clone()));
+        // two different deques that both contain themselves
+        let e = deque(vec![TronType::Number(1.0)]);
+        e.borrow_mut().push_back(TronType::Deque(e.clone()));
+        assert!(deques_equal(&d, &e));
+        e.borrow_mut().push_front(TronType::Null);
+        assert!(!deques_equal(&d, &e));
+    }
+
+    #[test]
+    fn a_deque_containing_itself_prints_a_marker() {
+        let d = deque(vec![TronType::Number(1.0)]);
+        d.borrow_mut().push_back(TronType::Deque(d.clone()));
+        assert_eq!(
+            TronType::Deque(d.clone()).to_string(),
+            "deque(1, deque(...))"
+        );
+        // a deque printed twice side by side isn't a cycle
+        let inner = deque(vec![TronType::Number(2.0)]);
+        let outer = deque(vec![TronType::Deque(inner.clone()), TronType::Deque(inner)]);
+        assert_eq!(display_deque(&outer), "deque(deque(2), deque(2))");
+    }
+}
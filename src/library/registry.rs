@@ -0,0 +1,75 @@
+use crate::expressions::Arity;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The `NativeRegistry` struct keeps track of every native function of a program.
+///
+/// Natives are registered when they are declared (by `declare_function()`, the built-in `clock` and plugins), so the
+/// registry can't drift from the functions that actually exist. The resolver queries it to reject redefinitions of natives.
+/// The standard library also documents its natives here, for `@help()` and `tron doc`.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Default)]
+pub struct NativeRegistry {
+    natives: HashMap<Rc<str>, Arity>,
+    docs: HashMap<Rc<str>, NativeDoc>,
+}
+
+/// The documentation of a native function.
+///
+/// # Fields
+///
+/// - `signature`: The signature of the native, e.g. `@push(array: array, value: any): array`.
+/// - `doc`: What the native does.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+pub struct NativeDoc {
+    pub signature: Rc<str>,
+    pub doc: Rc<str>,
+}
+
+impl NativeRegistry {
+    /// Records a native function with its arity.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn register(&mut self, name: impl Into<Rc<str>>, arity: Arity) {
+        self.natives.insert(name.into(), arity);
+    }
+    /// Returns whether `name` is a native function.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn contains(&self, name: &str) -> bool {
+        self.natives.contains_key(name)
+    }
+    /// Attaches a signature and a description to a registered native, natives that don't exist are ignored.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn document(&mut self, name: &str, signature: &str, doc: &str) {
+        if let Some((name, _)) = self.natives.get_key_value(name) {
+            let doc = NativeDoc {
+                signature: signature.into(),
+                doc: doc.into(),
+            };
+            self.docs.insert(name.clone(), doc);
+        }
+    }
+    /// Returns the documentation of a native, when it has any.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn doc(&self, name: &str) -> Option<&NativeDoc> {
+        self.docs.get(name)
+    }
+    /// Returns every native with its arity, sorted by name.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn natives(&self) -> Vec<(Rc<str>, Arity)> {
+        let mut natives: Vec<(Rc<str>, Arity)> = self
+            .natives
+            .iter()
+            .map(|(name, arity)| (name.clone(), *arity))
+            .collect();
+        natives.sort_by(|a, b| a.0.cmp(&b.0));
+        natives
+    }
+}
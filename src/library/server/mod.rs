@@ -0,0 +1,395 @@
+use super::context::NativeContext;
+use super::declare_native;
+use super::signal::check_interrupt;
+use crate::environment::*;
+use crate::expressions::*;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::exit;
+use std::thread;
+use std::time::Duration;
+
+/// The largest request body `@serve` reads, larger ones are answered with `413`.
+///
+/// ### Last Updated: (v3.2.0)
+const MAX_BODY: usize = 10 * 1024 * 1024;
+
+/// The address `@serve` listens on when no host is given, so a script only reachable from the machine running it
+/// doesn't become reachable from the network by accident.
+///
+/// ### Last Updated: (v3.2.0)
+const DEFAULT_HOST: &str = "127.0.0.1";
+
+/// The `server_library` function declares the natives of the standard library that serve HTTP, with the `server`
+/// feature.
+///
+/// - `@serve(port, handler, host)`: listens on `port` of `host` and calls `handler` with every request, never
+///   returning unless the port can't be listened on. Without `host`, it listens on `127.0.0.1`, only for requests
+///   from the same machine, and `"0.0.0.0"` listens on every interface.
+///
+/// The request is an object with the `method`, the `path` without its query string, the `query` parameters as an
+/// object, the `headers` as an object with lowercase names, and the `body` as a string. The handler returns the
+/// response, an object with a `status` (200 when it's left out), `headers` and a `body`, or any other value, which is
+/// sent as the body of a `200` response. Since the keys of an object literal are names, the `_` of a header name is
+/// sent as a `-`, so `{content_type: "text/html"}` sets `Content-Type`.
+///
+/// The server is small and blocking, for demo webhooks and local tools rather than production: it answers one request
+/// at a time and closes the connection after it. A handler failing is reported and answered with `500`, and a request
+/// that isn't HTTP with `400`. `@on_interrupt` still runs on Ctrl+C while the server waits for requests.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn server_library(environment: &mut Environment) {
+    declare_native(
+        "@serve".to_string(),
+        Arity::between(2, 3),
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let port = match &args[0] {
+                TronType::Number(port) if (0.0..=65535.0).contains(port) && port.fract() == 0.0 => {
+                    *port as u16
+                }
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@serve".to_string(),
+                            "port number".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            if !matches!(args[1], TronType::Callable(_)) {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@serve".to_string(),
+                        "function".to_string(),
+                        "second".to_string(),
+                    ],
+                );
+                exit(1)
+            }
+            let host = match args.get(2) {
+                Some(TronType::Null) | None => DEFAULT_HOST.to_string(),
+                Some(TronType::StringValue(host)) => host.clone(),
+                Some(_) => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@serve".to_string(),
+                            "string".to_string(),
+                            "third".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            let listener = match listen(&host, port) {
+                Ok(listener) => listener,
+                Err(error) => {
+                    context.throw(
+                        "E4032",
+                        vec!["@serve".to_string(), port.to_string(), error.to_string()],
+                    );
+                    return TronType::Null;
+                }
+            };
+            loop {
+                check_interrupt(&context.environment);
+                match listener.accept() {
+                    Ok((stream, _)) => respond(stream, &args[1], context),
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(20))
+                    }
+                    Err(_) => {}
+                }
+            }
+        },
+        environment,
+    );
+}
+
+// polled rather than blocking in `accept`, which would hold off Ctrl+C handlers until the next request
+fn listen(host: &str, port: u16) -> std::io::Result<TcpListener> {
+    let listener = TcpListener::bind((host, port))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+// reads one request, calls the handler with it and writes its response, a connection that fails is dropped
+fn respond(stream: TcpStream, handler: &TronType, context: &NativeContext) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let Ok(writer) = stream.try_clone() else {
+        return;
+    };
+    let response = match read_request(&mut BufReader::new(stream)) {
+        Ok(request) => match context.call(handler, vec![request]) {
+            Ok(response) => response,
+            Err(msg) => {
+                context.throw("E4023", vec!["@serve".to_string(), msg]);
+                error_response(500)
+            }
+        },
+        Err(status) => error_response(status),
+    };
+    let _ = write_response(writer, &response);
+}
+
+// the request as an object, or the status to answer a request that can't be read with
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<TronType, u16> {
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|_| 400u16)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(400);
+    };
+    if !version.starts_with("HTTP/") {
+        return Err(400);
+    }
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|_| 400u16)? == 0 {
+            return Err(400);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').ok_or(400u16)?;
+        headers.insert(
+            name.trim().to_lowercase(),
+            TronType::StringValue(value.trim().to_string()),
+        );
+    }
+    let length = match headers.get("content-length") {
+        Some(TronType::StringValue(length)) => length.parse::<usize>().map_err(|_| 400u16)?,
+        _ => 0,
+    };
+    if length > MAX_BODY {
+        return Err(413);
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|_| 400u16)?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), TronType::StringValue(decode(value)))
+        })
+        .collect();
+    let request = HashMap::from([
+        (
+            "method".to_string(),
+            TronType::StringValue(method.to_string()),
+        ),
+        ("path".to_string(), TronType::StringValue(decode(path))),
+        ("query".to_string(), TronType::Object(query)),
+        ("headers".to_string(), TronType::Object(headers)),
+        (
+            "body".to_string(),
+            TronType::StringValue(String::from_utf8_lossy(&body).into_owned()),
+        ),
+    ]);
+    Ok(TronType::Object(request))
+}
+
+// decodes the `%XX` escapes of a URL and the `+` standing for spaces in a query
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn error_response(status: u16) -> TronType {
+    TronType::Object(HashMap::from([
+        ("status".to_string(), TronType::Number(status as f32)),
+        (
+            "body".to_string(),
+            TronType::StringValue(reason(status).to_string()),
+        ),
+    ]))
+}
+
+fn write_response(mut stream: TcpStream, response: &TronType) -> std::io::Result<()> {
+    let (status, headers, body) = match response {
+        TronType::Object(fields) => {
+            let status = match fields.get("status") {
+                Some(TronType::Number(status)) => *status as u16,
+                _ => 200,
+            };
+            let headers = match fields.get("headers") {
+                Some(TronType::Object(headers)) => headers.clone(),
+                _ => HashMap::new(),
+            };
+            let body = fields
+                .get("body")
+                .map_or(String::new(), TronType::to_plain_string);
+            (status, headers, body)
+        }
+        value => (200, HashMap::new(), value.to_plain_string()),
+    };
+    let mut head = format!("HTTP/1.1 {} {}\r\n", status, reason(status));
+    let mut headers: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| (name.replace('_', "-"), value.to_plain_string()))
+        .collect();
+    headers.sort();
+    for (name, value) in &headers {
+        let lower = name.to_lowercase();
+        // the server sets these itself, from the body it sends
+        if lower != "content-length" && lower != "connection" {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+    {
+        head.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Shutdown;
+
+    // a connected pair of streams, the client's end and the server's
+    fn connection() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind((DEFAULT_HOST, 0)).unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (client, server)
+    }
+
+    fn field<'a>(object: &'a TronType, name: &str) -> &'a TronType {
+        match object {
+            TronType::Object(fields) => &fields[name],
+            _ => panic!("expected an object, got {:?}", object),
+        }
+    }
+
+    #[test]
+    fn listens_on_the_loopback_interface_by_default() {
+        let listener = listen(DEFAULT_HOST, 0).unwrap();
+        assert!(listener.local_addr().unwrap().ip().is_loopback());
+    }
+
+    #[test]
+    fn reads_a_request_and_writes_its_response() {
+        let (mut client, server) = connection();
+        client
+            .write_all(
+                b"POST /hooks/new%20item?tag=a+b&empty HTTP/1.1\r\nHost: localhost\r\n\
+                  X-Token: secret\r\nContent-Length: 5\r\n\r\nhello",
+            )
+            .unwrap();
+        let writer = server.try_clone().unwrap();
+        let request = read_request(&mut BufReader::new(server)).unwrap();
+        let text = |value: &TronType| value.to_plain_string();
+        assert_eq!(text(field(&request, "method")), "POST");
+        assert_eq!(text(field(&request, "path")), "/hooks/new item");
+        assert_eq!(text(field(&request, "body")), "hello");
+        let query = field(&request, "query");
+        assert_eq!(text(field(query, "tag")), "a b");
+        assert_eq!(text(field(query, "empty")), "");
+        assert_eq!(text(field(field(&request, "headers"), "x-token")), "secret");
+
+        let response = TronType::Object(HashMap::from([
+            ("status".to_string(), TronType::Number(201.0)),
+            (
+                "headers".to_string(),
+                TronType::Object(HashMap::from([(
+                    "content_type".to_string(),
+                    TronType::StringValue("application/json".to_string()),
+                )])),
+            ),
+            (
+                "body".to_string(),
+                TronType::StringValue("{\"ok\":true}".to_string()),
+            ),
+        ]));
+        write_response(writer, &response).unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+        let mut answer = String::new();
+        client.read_to_string(&mut answer).unwrap();
+        assert_eq!(
+            answer,
+            "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\nContent-Length: 11\r\n\
+             Connection: close\r\n\r\n{\"ok\":true}"
+        );
+    }
+
+    #[test]
+    fn answers_a_request_that_isnt_http_with_400() {
+        let (mut client, server) = connection();
+        client.write_all(b"hello\r\n\r\n").unwrap();
+        assert_eq!(read_request(&mut BufReader::new(server)), Err(400));
+    }
+
+    #[test]
+    fn answers_a_body_too_large_with_413() {
+        let (mut client, server) = connection();
+        let head = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        client.write_all(head.as_bytes()).unwrap();
+        assert_eq!(read_request(&mut BufReader::new(server)), Err(413));
+    }
+}
@@ -0,0 +1,297 @@
+use super::context::NativeContext;
+use super::declare_native;
+#[cfg(feature = "bigint")]
+use super::math::bigint::BigInt;
+use crate::environment::*;
+use crate::expressions::*;
+use std::collections::HashMap;
+use std::process::exit;
+
+/// A value as a member of a set, compared by its content.
+///
+/// Only values compared by their content can be members: arrays, objects and functions aren't. Numbers are keyed by
+/// their bits, with `-0` keyed as `0` and every `NaN` as the same one, so `NaN` can be found in a set even though it
+/// isn't equal to itself. Decimals are keyed by their digits without trailing zeros, so `1.5` and `1.50` are the same
+/// member.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SetKey {
+    Number(u32),
+    String(String),
+    Bool(bool),
+    Null,
+    #[cfg(feature = "bigint")]
+    BigInt(BigInt),
+    #[cfg(feature = "bigint")]
+    Decimal(String),
+}
+
+impl SetKey {
+    fn from_value(value: &TronType) -> Option<Self> {
+        match value {
+            TronType::Number(number) if number.is_nan() => Some(SetKey::Number(f32::NAN.to_bits())),
+            TronType::Number(number) => Some(SetKey::Number((number + 0.0).to_bits())),
+            TronType::StringValue(string) => Some(SetKey::String(string.clone())),
+            TronType::True => Some(SetKey::Bool(true)),
+            TronType::False => Some(SetKey::Bool(false)),
+            TronType::Null => Some(SetKey::Null),
+            #[cfg(feature = "bigint")]
+            TronType::BigInt(integer) => Some(SetKey::BigInt(integer.clone())),
+            #[cfg(feature = "bigint")]
+            TronType::Decimal(decimal) => Some(SetKey::Decimal(decimal.trimmed().to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// A collection of distinct values, the value of `@set()`.
+///
+/// The members are kept in the order they were added, so a set is always shown and turned back into an array the same
+/// way, and found through a hash map, so `@set_has` doesn't go through them one by one like a search in an array does.
+///
+/// # Fields
+///
+/// - `members`: The members, in the order they were added.
+/// - `index`: The position of every member in `members`, by its key.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Default)]
+pub struct Set {
+    members: Vec<TronType>,
+    index: HashMap<SetKey, usize>,
+}
+
+impl Set {
+    /// Adds a value to the set when it isn't a member already.
+    ///
+    /// # Return Value
+    ///
+    /// `false` when the value can't be a member, an array, an object or a function.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn insert(&mut self, value: TronType) -> bool {
+        let Some(key) = SetKey::from_value(&value) else {
+            return false;
+        };
+        if !self.index.contains_key(&key) {
+            self.index.insert(key, self.members.len());
+            self.members.push(value);
+        }
+        true
+    }
+    /// Whether a value is a member of the set.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn contains(&self, value: &TronType) -> bool {
+        SetKey::from_value(value).is_some_and(|key| self.index.contains_key(&key))
+    }
+    /// Returns the members of the set, in the order they were added.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn members(&self) -> &[TronType] {
+        &self.members
+    }
+    // the members of this set `other` contains, or doesn't contain
+    fn filter(&self, other: &Set, contained: bool) -> Set {
+        let mut set = Set::default();
+        for member in &self.members {
+            if other.contains(member) == contained {
+                set.insert(member.clone());
+            }
+        }
+        set
+    }
+}
+
+// two sets are equal when they have the same members, whatever the order they were added in
+impl PartialEq for Set {
+    fn eq(&self, other: &Self) -> bool {
+        self.members.len() == other.members.len()
+            && self.members.iter().all(|member| other.contains(member))
+    }
+}
+
+/// The `set_library` function declares the natives of the standard library that work on sets.
+///
+/// - `@set(array)`: a set of the distinct elements of `array`.
+/// - `@set_add(set, x)`: a copy of `set` with `x` added.
+/// - `@set_has(set, x)`: whether `x` is a member of `set`.
+/// - `@set_union(a, b)`: the members of `a` or `b`.
+/// - `@set_intersect(a, b)`: the members of both `a` and `b`.
+/// - `@set_diff(a, b)`: the members of `a` that aren't in `b`.
+/// - `@set_values(set)`: the members of `set` as an array, to loop over them with `for x in @set_values(set)`.
+///
+/// Numbers, strings, booleans, `null`, big integers and decimals can be members, adding an array, an object or a
+/// function throws `E4028`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn set_library(environment: &mut Environment) {
+    declare_native(
+        "@set".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::ArrayValue(elements) = &args[0] else {
+                context.throw(
+                    "E4021",
+                    vec!["@set".to_string(), "array".to_string(), "first".to_string()],
+                );
+                exit(1)
+            };
+            let mut set = Set::default();
+            for element in elements {
+                insert("@set", &mut set, element, context);
+            }
+            TronType::Set(set)
+        },
+        environment,
+    );
+    declare_native(
+        "@set_add".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let mut set = set("@set_add", &args[0], "first", context).clone();
+            insert("@set_add", &mut set, &args[1], context);
+            TronType::Set(set)
+        },
+        environment,
+    );
+    declare_native(
+        "@set_has".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            TronType::from_bool(set("@set_has", &args[0], "first", context).contains(&args[1]))
+        },
+        environment,
+    );
+    declare_native(
+        "@set_union".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let mut union = set("@set_union", &args[0], "first", context).clone();
+            for member in set("@set_union", &args[1], "second", context).members() {
+                union.insert(member.clone());
+            }
+            TronType::Set(union)
+        },
+        environment,
+    );
+    declare_native(
+        "@set_intersect".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let a = set("@set_intersect", &args[0], "first", context);
+            let b = set("@set_intersect", &args[1], "second", context);
+            TronType::Set(a.filter(b, true))
+        },
+        environment,
+    );
+    declare_native(
+        "@set_diff".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let a = set("@set_diff", &args[0], "first", context);
+            let b = set("@set_diff", &args[1], "second", context);
+            TronType::Set(a.filter(b, false))
+        },
+        environment,
+    );
+    declare_native(
+        "@set_values".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            TronType::ArrayValue(
+                set("@set_values", &args[0], "first", context)
+                    .members()
+                    .to_vec(),
+            )
+        },
+        environment,
+    );
+}
+
+fn set<'a>(name: &str, value: &'a TronType, position: &str, context: &NativeContext) -> &'a Set {
+    match value {
+        TronType::Set(set) => set,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "set".to_string(), position.to_string()],
+            );
+            exit(1)
+        }
+    }
+}
+
+fn insert(name: &str, set: &mut Set, value: &TronType, context: &NativeContext) {
+    if !set.insert(value.clone()) {
+        context.throw("E4028", vec![name.to_string(), value.to_type().to_string()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[f32]) -> Set {
+        let mut set = Set::default();
+        for value in values {
+            set.insert(TronType::Number(*value));
+        }
+        set
+    }
+
+    #[test]
+    fn keeps_distinct_members_in_the_order_they_were_added() {
+        let mut set = numbers(&[3.0, 1.0, 3.0, 2.0, 1.0]);
+        assert_eq!(
+            set.members(),
+            [
+                TronType::Number(3.0),
+                TronType::Number(1.0),
+                TronType::Number(2.0)
+            ]
+        );
+        assert!(set.insert(TronType::StringValue("3".to_string())));
+        assert_eq!(set.members().len(), 4);
+    }
+
+    #[test]
+    fn finds_zero_and_nan_by_their_value() {
+        let set = numbers(&[-0.0, f32::NAN]);
+        assert_eq!(set.members().len(), 2);
+        assert!(set.contains(&TronType::Number(0.0)));
+        assert!(set.contains(&TronType::Number(f32::NAN)));
+        assert!(!set.contains(&TronType::Null));
+    }
+
+    #[test]
+    fn only_takes_values_compared_by_their_content() {
+        let mut set = Set::default();
+        assert!(!set.insert(TronType::ArrayValue(vec![])));
+        assert!(!set.insert(TronType::Object(HashMap::new())));
+        assert!(set.insert(TronType::True));
+        assert!(set.insert(TronType::Null));
+        assert!(!set.contains(&TronType::ArrayValue(vec![])));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn decimals_are_members_without_their_trailing_zeros() {
+        use crate::library::math::decimal::Decimal;
+        let mut set = Set::default();
+        set.insert(TronType::Decimal(Decimal::parse("1.5").unwrap()));
+        assert!(set.contains(&TronType::Decimal(Decimal::parse("1.50").unwrap())));
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a = numbers(&[1.0, 2.0, 3.0]);
+        let b = numbers(&[2.0, 3.0, 4.0]);
+        assert_eq!(a.filter(&b, true), numbers(&[2.0, 3.0]));
+        assert_eq!(a.filter(&b, false), numbers(&[1.0]));
+        // equal whatever the order the members were added in
+        assert_eq!(numbers(&[3.0, 2.0]), numbers(&[2.0, 3.0]));
+        assert_ne!(numbers(&[1.0, 2.0]), numbers(&[1.0, 2.0, 3.0]));
+    }
+}
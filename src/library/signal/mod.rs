@@ -0,0 +1,102 @@
+use super::clean_up;
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::cell::RefCell;
+use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// set by the signal handler, and read by the interpreter between statements
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // the function given to `@on_interrupt`
+    static HANDLER: RefCell<Option<TronType>> = const { RefCell::new(None) };
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_int;
+    pub const SIGINT: c_int = 2;
+    pub const SIG_DFL: usize = 0;
+    extern "C" {
+        pub fn signal(signum: c_int, handler: usize) -> usize;
+    }
+}
+
+// only flags the interrupt, since a signal handler can't run Tron code, and puts the default action back so a
+// second Ctrl+C stops a handler that hangs
+#[cfg(unix)]
+extern "C" fn interrupted(_signum: std::ffi::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    // SAFETY: `signal` is async-signal-safe, and SIG_DFL is a valid disposition.
+    unsafe {
+        sys::signal(sys::SIGINT, sys::SIG_DFL);
+    }
+}
+
+/// The `signal_library` function declares the natives of the standard library that handle signals.
+///
+/// - `@on_interrupt(function)`: calls `function` without arguments when the user presses Ctrl+C, then ends the
+///   program with the exit code 130.
+///
+/// Without a handler, Ctrl+C ends the program right away, like it always did. With one, a long-running watcher or
+/// server can flush its files or print a summary before it stops. The handler runs once the statement running when
+/// Ctrl+C was pressed finishes, so a native blocking in `@sleep` or `@ask` finishes first. Pressing Ctrl+C again while
+/// the handler runs ends the program right away. Calling `@on_interrupt` again replaces the handler.
+///
+/// Signals are only handled on Unix, elsewhere Ctrl+C always ends the program right away.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn signal_library(environment: &mut Environment) {
+    declare_native(
+        "@on_interrupt".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            if !matches!(args[0], TronType::Callable(_)) {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@on_interrupt".to_string(),
+                        "function".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            }
+            HANDLER.with(|handler| *handler.borrow_mut() = Some(args[0].clone()));
+            #[cfg(unix)]
+            // SAFETY: `interrupted` only touches an atomic and calls `signal`, both async-signal-safe.
+            unsafe {
+                sys::signal(
+                    sys::SIGINT,
+                    interrupted as extern "C" fn(std::ffi::c_int) as usize,
+                );
+            }
+            TronType::Null
+        },
+        environment,
+    );
+}
+
+/// Runs the handler given to `@on_interrupt` and ends the program, when Ctrl+C was pressed since the last check.
+///
+/// The interpreter calls this before every statement, which is as soon as a handler can safely run Tron code.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn check_interrupt(environment: &Environment) {
+    if !INTERRUPTED.load(Ordering::Relaxed) {
+        return;
+    }
+    // cleared so the statements of the handler don't run it again
+    INTERRUPTED.store(false, Ordering::Relaxed);
+    if let Some(handler) = HANDLER.with(|handler| handler.borrow_mut().take()) {
+        let context = NativeContext::new(environment.clone(), 0, 0);
+        if let Err(msg) = context.call(&handler, vec![]) {
+            context.throw("E4023", vec!["@on_interrupt".to_string(), msg]);
+        }
+    }
+    clean_up();
+    exit(130)
+}
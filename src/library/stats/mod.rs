@@ -0,0 +1,141 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::process::exit;
+
+/// The `stats_library` function declares the natives of the standard library that summarize arrays of numbers.
+///
+/// - `@sum(xs)`: the sum of the numbers, 0 for an empty array.
+/// - `@mean(xs)`: their average.
+/// - `@median(xs)`: the middle number once sorted, or the average of the two middle ones.
+/// - `@stddev(xs)`: the population standard deviation, how far the numbers are from their mean.
+/// - `@percentile(xs, p)`: the number below which `p` percent of the numbers fall, interpolated between the two
+///   closest ones, so `@percentile(xs, 50)` is the median.
+///
+/// Every native but `@sum` throws `E4026` for an empty array, which has no average or middle.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn stats_library(environment: &mut Environment) {
+    declare_native(
+        "@sum".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            // a fold rather than `sum()`, which starts from -0 for floats
+            let xs = numbers("@sum", &args[0], context);
+            TronType::Number(xs.iter().fold(0.0, |sum, x| sum + x))
+        },
+        environment,
+    );
+    declare_native(
+        "@mean".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            match non_empty("@mean", &args[0], context) {
+                Some(xs) => TronType::Number(mean(&xs)),
+                None => TronType::Null,
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@median".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            match non_empty("@median", &args[0], context) {
+                Some(xs) => TronType::Number(percentile(xs, 50.0)),
+                None => TronType::Null,
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@stddev".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let Some(xs) = non_empty("@stddev", &args[0], context) else {
+                return TronType::Null;
+            };
+            let mean = mean(&xs);
+            let variance =
+                xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / xs.len() as f32;
+            TronType::Number(variance.sqrt())
+        },
+        environment,
+    );
+    declare_native(
+        "@percentile".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let p = match args[1] {
+                TronType::Number(p) if (0.0..=100.0).contains(&p) => p,
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@percentile".to_string(),
+                            "number from 0 to 100".to_string(),
+                            "second".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            match non_empty("@percentile", &args[0], context) {
+                Some(xs) => TronType::Number(percentile(xs, p)),
+                None => TronType::Null,
+            }
+        },
+        environment,
+    );
+}
+
+fn mean(xs: &[f32]) -> f32 {
+    xs.iter().sum::<f32>() / xs.len() as f32
+}
+
+// linear interpolation between the closest ranks, like most spreadsheets do, `xs` can't be empty
+fn percentile(mut xs: Vec<f32>, p: f32) -> f32 {
+    xs.sort_by(f32::total_cmp);
+    let rank = p / 100.0 * (xs.len() - 1) as f32;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    xs[low] + (xs[high] - xs[low]) * (rank - low as f32)
+}
+
+// the numbers of an array, or `E4021` when it isn't one
+fn numbers(name: &str, value: &TronType, context: &NativeContext) -> Vec<f32> {
+    let numbers = match value {
+        TronType::ArrayValue(elements) => elements
+            .iter()
+            .map(|element| match element {
+                TronType::Number(number) => Some(*number),
+                _ => None,
+            })
+            .collect::<Option<Vec<f32>>>(),
+        _ => None,
+    };
+    match numbers {
+        Some(numbers) => numbers,
+        None => {
+            context.throw(
+                "E4021",
+                vec![
+                    name.to_string(),
+                    "numeric array".to_string(),
+                    "first".to_string(),
+                ],
+            );
+            exit(1)
+        }
+    }
+}
+
+// the numbers of an array, or `None` after reporting `E4026` when there are none
+fn non_empty(name: &str, value: &TronType, context: &NativeContext) -> Option<Vec<f32>> {
+    let numbers = numbers(name, value, context);
+    if numbers.is_empty() {
+        context.throw("E4026", vec![name.to_string()]);
+        return None;
+    }
+    Some(numbers)
+}
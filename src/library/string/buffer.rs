@@ -0,0 +1,131 @@
+use crate::environment::*;
+use crate::expressions::*;
+use crate::library::context::NativeContext;
+use crate::library::declare_native;
+use std::cell::RefCell;
+use std::process::exit;
+use std::rc::Rc;
+
+/// A string being built, the value of `@buffer()`.
+///
+/// `s = s + piece` copies `s` every time, so building a string of `n` pieces in a loop takes time in `n²`. A buffer is
+/// changed in place and shared by every variable holding it, like a deque, and appending to it only copies the piece,
+/// so the same loop with `@buffer_push` takes time in `n`. `tron bench --strings` compares the two.
+///
+/// ### Last Updated: (v3.2.0)
+pub type Buffer = Rc<RefCell<String>>;
+
+/// The `buffer_library` function declares the natives of the standard library that build strings.
+///
+/// - `@buffer()`: returns an empty buffer.
+/// - `@buffer_push(buffer, value)`: appends `value` to `buffer`, the way `+` would show it in a string, and returns
+///   the buffer.
+/// - `@buffer_to_string(buffer)`: returns the string built so far.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn buffer_library(environment: &mut Environment) {
+    declare_native(
+        "@buffer".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            TronType::Buffer(Rc::new(RefCell::new(String::new())))
+        },
+        environment,
+    );
+    declare_native(
+        "@buffer_push".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let buffer = buffer("@buffer_push", &args[0], context);
+            match &args[1] {
+                // borrowed before the buffer is, which pushing a buffer to itself needs
+                TronType::Buffer(other) => {
+                    let piece = other.borrow().clone();
+                    buffer.borrow_mut().push_str(&piece);
+                }
+                value => buffer.borrow_mut().push_str(&value.to_plain_string()),
+            }
+            args[0].clone()
+        },
+        environment,
+    );
+    declare_native(
+        "@buffer_to_string".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            TronType::StringValue(
+                buffer("@buffer_to_string", &args[0], context)
+                    .borrow()
+                    .clone(),
+            )
+        },
+        environment,
+    );
+}
+
+fn buffer<'a>(name: &str, value: &'a TronType, context: &NativeContext) -> &'a Buffer {
+    match value {
+        TronType::Buffer(buffer) => buffer,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "buffer".to_string(), "first".to_string()],
+            );
+            exit(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    // runs a script and returns the string it left in `result`
+    fn result_of(source: &str) -> TronType {
+        let mut interpreter = Interpreter::new();
+        interpreter.execute(source).unwrap();
+        interpreter.get_global("result").unwrap()
+    }
+
+    #[test]
+    fn appends_values_the_way_plus_shows_them() {
+        assert_eq!(
+            result_of(
+                "let b = @buffer();
+                @buffer_push(@buffer_push(b, \"n = \"), 1.5);
+                @buffer_push(b, true);
+                @buffer_push(b, null);
+                let result = @buffer_to_string(b);"
+            ),
+            TronType::StringValue("n = 1.5truenull".to_string())
+        );
+    }
+
+    #[test]
+    fn a_buffer_is_shared_by_every_variable_holding_it() {
+        assert_eq!(
+            result_of(
+                "let b = @buffer();
+                let same = b;
+                fn add(buffer) { @buffer_push(buffer, \"x\"); }
+                add(same);
+                @buffer_push(same, \"y\");
+                let result = @buffer_to_string(b);"
+            ),
+            TronType::StringValue("xy".to_string())
+        );
+    }
+
+    #[test]
+    fn a_buffer_can_be_pushed_to_itself() {
+        assert_eq!(
+            result_of(
+                "let b = @buffer_push(@buffer(), \"ab\");
+                @buffer_push(b, b);
+                let result = @buffer_to_string(b);"
+            ),
+            TronType::StringValue("abab".to_string())
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use crate::environment::*;
+use crate::expressions::*;
+use crate::library::context::NativeContext;
+use crate::library::declare_native;
+use std::process::exit;
+
+/// The `markdown_library` function declares the natives of the standard library that convert Markdown, with the
+/// `markdown` feature.
+///
+/// - `@markdown(text)`: converts Markdown to HTML.
+///
+/// The common subset of Markdown is supported: `#` headings, paragraphs, `>` quotes, `-`, `*`, `+` and `1.` lists
+/// (nested by indenting), fenced code blocks with an optional language, `---` rules, and the inline `**strong**`,
+/// `*emphasis*`, `` `code` ``, `[links](url)` and `![images](src)`. A line ending with two spaces breaks the line.
+/// Any HTML in the text is escaped rather than passed through, so the output is safe to put in a page.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn markdown_library(environment: &mut Environment) {
+    declare_native(
+        "@markdown".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            match &args[0] {
+                TronType::StringValue(text) => TronType::StringValue(to_html(text)),
+                _ => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@markdown".to_string(),
+                            "string".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            }
+        },
+        environment,
+    );
+}
+
+/// Converts Markdown to HTML, for `@markdown`.
+///
+/// The text is read a block at a time, a block being a heading, a paragraph, a quote, a list, a code block or a rule.
+/// Quotes and list items that span several lines are converted again on their own, which is how they nest.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn to_html(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        if trimmed.is_empty() || starts_block(line) {
+            flush_paragraph(&mut paragraph, &mut html);
+        }
+        if trimmed.is_empty() {
+            i += 1;
+        } else if let Some(language) = trimmed.strip_prefix("```") {
+            let start = i + 1;
+            i = start;
+            while i < lines.len() && !lines[i].trim().starts_with("```") {
+                i += 1;
+            }
+            let code = lines[start..i].join("\n");
+            match language.trim() {
+                "" => html.push_str("<pre><code>"),
+                language => html.push_str(&format!(
+                    "<pre><code class=\"language-{}\">",
+                    escape(language)
+                )),
+            }
+            html.push_str(&escape(&code));
+            if !code.is_empty() {
+                html.push('\n');
+            }
+            html.push_str("</code></pre>\n");
+            // the closing fence, when there is one
+            i += 1;
+        } else if let Some((level, title)) = heading(trimmed) {
+            html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, inline(title)));
+            i += 1;
+        } else if is_rule(trimmed) {
+            html.push_str("<hr>\n");
+            i += 1;
+        } else if trimmed.starts_with('>') {
+            let mut quoted = vec![];
+            while i < lines.len() && lines[i].trim_start().starts_with('>') {
+                let line = &lines[i].trim_start()[1..];
+                quoted.push(line.strip_prefix(' ').unwrap_or(line));
+                i += 1;
+            }
+            html.push_str(&format!(
+                "<blockquote>\n{}</blockquote>\n",
+                to_html(&quoted.join("\n"))
+            ));
+        } else if let Some((ordered, _)) = list_item(line) {
+            i = list(&lines, i, ordered, &mut html);
+        } else {
+            // the spaces at the end are kept, two of them break the line
+            paragraph.push(line.trim_start());
+            i += 1;
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut html);
+    html
+}
+
+// whether a line starts a block that ends the paragraph before it
+fn starts_block(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with("```")
+        || trimmed.starts_with('>')
+        || heading(trimmed).is_some()
+        || is_rule(trimmed)
+        || list_item(line).is_some()
+}
+
+fn flush_paragraph(paragraph: &mut Vec<&str>, html: &mut String) {
+    if !paragraph.is_empty() {
+        html.push_str(&format!(
+            "<p>{}</p>\n",
+            inline(paragraph.join("\n").trim_end())
+        ));
+        paragraph.clear();
+    }
+}
+
+// `## Title` as its level and title
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let title = line[level..]
+        .strip_prefix(' ')
+        .or(Some("").filter(|_| level == line.len()))?;
+    (1..=6)
+        .contains(&level)
+        .then(|| (level, title.trim().trim_end_matches('#').trim_end()))
+}
+
+// `---`, `***` or `___`, with spaces allowed between the characters
+fn is_rule(line: &str) -> bool {
+    let characters: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    characters.len() >= 3
+        && matches!(characters[0], '-' | '*' | '_')
+        && characters.iter().all(|&c| c == characters[0])
+}
+
+// whether a line is a list item, ordered or not, and the text after its marker
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    let trimmed = line.trim_start();
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+    {
+        return Some((false, text));
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let text = trimmed[digits..]
+        .strip_prefix(". ")
+        .or_else(|| trimmed[digits..].strip_prefix(") "))?;
+    (digits > 0).then_some((true, text))
+}
+
+// renders the items of a list starting at line `start`, returning the line after the list
+fn list(lines: &[&str], start: usize, ordered: bool, html: &mut String) -> usize {
+    let indent = indentation(lines[start]);
+    html.push_str(if ordered { "<ol>\n" } else { "<ul>\n" });
+    let mut i = start;
+    while i < lines.len() {
+        // an item of the same list, at the same indentation
+        match list_item(lines[i]) {
+            Some((kind, text)) if kind == ordered && indentation(lines[i]) == indent => {
+                let mut item = vec![text.to_string()];
+                i += 1;
+                // the lines indented under the item belong to it, like a nested list
+                while i < lines.len()
+                    && !lines[i].trim().is_empty()
+                    && indentation(lines[i]) > indent
+                {
+                    item.push(lines[i].trim_start().to_string());
+                    i += 1;
+                }
+                match item.len() {
+                    1 => html.push_str(&format!("<li>{}</li>\n", inline(&item[0]))),
+                    _ => {
+                        let nested = to_html(&item[1..].join("\n"));
+                        html.push_str(&format!("<li>{}\n{}</li>\n", inline(&item[0]), nested));
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+    html.push_str(if ordered { "</ol>\n" } else { "</ul>\n" });
+    i
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// converts the spans of a block: code, images, links, strong and emphasized text, and line breaks
+fn inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut html = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest: String = chars[i..].iter().collect();
+        if c == '\\' && chars.get(i + 1).is_some_and(|c| c.is_ascii_punctuation()) {
+            html.push_str(&escape(&chars[i + 1].to_string()));
+            i += 2;
+        } else if c == '`' {
+            match find(&chars, i + 1, "`") {
+                Some(end) => {
+                    let code: String = chars[i + 1..end].iter().collect();
+                    html.push_str(&format!("<code>{}</code>", escape(&code)));
+                    i = end + 1;
+                }
+                None => {
+                    html.push('`');
+                    i += 1;
+                }
+            }
+        } else if let Some((label, target, length)) = link(&rest, c == '!') {
+            if c == '!' {
+                html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\">",
+                    escape(&target),
+                    escape(&label)
+                ));
+            } else {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape(&target),
+                    inline(&label)
+                ));
+            }
+            i += length;
+        } else if let Some((tag, delimiter)) = emphasis(&chars, i) {
+            match find(&chars, i + delimiter.len(), delimiter)
+                .filter(|&end| end > i + delimiter.len())
+            {
+                Some(end) => {
+                    let inner: String = chars[i + delimiter.len()..end].iter().collect();
+                    html.push_str(&format!("<{0}>{1}</{0}>", tag, inline(&inner)));
+                    i = end + delimiter.len();
+                }
+                None => {
+                    html.push_str(delimiter);
+                    i += delimiter.len();
+                }
+            }
+        } else if c == '\n' && html.ends_with("  ") {
+            html.truncate(html.trim_end_matches(' ').len());
+            html.push_str("<br>\n");
+            i += 1;
+        } else {
+            html.push_str(&escape(&c.to_string()));
+            i += 1;
+        }
+    }
+    html
+}
+
+// the tag and delimiter of a strong or emphasized span opening at `i`, `_` only opens one at the start of a word so
+// names like `snake_case` are left alone
+fn emphasis(chars: &[char], i: usize) -> Option<(&'static str, &'static str)> {
+    let c = chars[i];
+    if c == '_' && i > 0 && chars[i - 1].is_alphanumeric() {
+        return None;
+    }
+    let double = chars.get(i + 1) == Some(&c);
+    match (c, double) {
+        ('*', true) => Some(("strong", "**")),
+        ('_', true) => Some(("strong", "__")),
+        ('*', false) => Some(("em", "*")),
+        ('_', false) => Some(("em", "_")),
+        _ => None,
+    }
+}
+
+// the position of the next `delimiter` from `start`
+fn find(chars: &[char], start: usize, delimiter: &str) -> Option<usize> {
+    let delimiter: Vec<char> = delimiter.chars().collect();
+    (start..chars.len()).find(|&i| chars[i..].starts_with(&delimiter))
+}
+
+// `[label](target)` or `![label](target)` at the start of `text`, with the number of characters it spans
+fn link(text: &str, image: bool) -> Option<(String, String, usize)> {
+    let rest = if image {
+        text.strip_prefix("![")?
+    } else {
+        text.strip_prefix('[')?
+    };
+    let label_end = rest.find("](")?;
+    let target_end = rest[label_end + 2..].find(')')? + label_end + 2;
+    let label = &rest[..label_end];
+    let target = rest[label_end + 2..target_end].trim();
+    let opening = if image { 2 } else { 1 };
+    let length = opening + rest[..=target_end].chars().count();
+    Some((label.to_string(), target.to_string(), length))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_headings() {
+        assert_eq!(
+            to_html("# Title\n### Section ###\n####### seven"),
+            "<h1>Title</h1>\n<h3>Section</h3>\n<p>####### seven</p>\n"
+        );
+    }
+
+    #[test]
+    fn converts_paragraphs_and_line_breaks() {
+        assert_eq!(
+            to_html("one\ntwo  \nthree\n\nfour"),
+            "<p>one\ntwo<br>\nthree</p>\n<p>four</p>\n"
+        );
+    }
+
+    #[test]
+    fn converts_lists_and_nests_them_by_indentation() {
+        assert_eq!(
+            to_html("- a\n- b\n  1. c\n  2. d\n\n3) e"),
+            "<ul>\n<li>a</li>\n<li>b\n<ol>\n<li>c</li>\n<li>d</li>\n</ol>\n</li>\n</ul>\n\
+             <ol>\n<li>e</li>\n</ol>\n"
+        );
+    }
+
+    #[test]
+    fn converts_emphasis_and_code() {
+        assert_eq!(
+            to_html("**strong** *em* __also strong__ _em_ `a * b` snake_case_name"),
+            "<p><strong>strong</strong> <em>em</em> <strong>also strong</strong> <em>em</em> \
+             <code>a * b</code> snake_case_name</p>\n"
+        );
+    }
+
+    #[test]
+    fn converts_links_and_images() {
+        assert_eq!(
+            to_html("see [the *docs*](https://example.com/a?b=1&c=2) ![logo](logo.png)"),
+            "<p>see <a href=\"https://example.com/a?b=1&amp;c=2\">the <em>docs</em></a> \
+             <img src=\"logo.png\" alt=\"logo\"></p>\n"
+        );
+    }
+
+    #[test]
+    fn converts_quotes_code_blocks_and_rules() {
+        assert_eq!(
+            to_html("> quoted\n> # heading\n\n```rust\nlet a = 1 < 2;\n```\n---"),
+            "<blockquote>\n<p>quoted</p>\n<h1>heading</h1>\n</blockquote>\n\
+             <pre><code class=\"language-rust\">let a = 1 &lt; 2;\n</code></pre>\n<hr>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_html_in_the_text() {
+        assert_eq!(
+            to_html("<script>alert(\"x\")</script> & \\*not em\\*"),
+            "<p>&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; *not em*</p>\n"
+        );
+        assert_eq!(
+            to_html("# <b>bold</b>"),
+            "<h1>&lt;b&gt;bold&lt;/b&gt;</h1>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_in_link_targets() {
+        assert_eq!(
+            to_html("[x](https://a.b/\"onmouseover=\"alert(1))"),
+            "<p><a href=\"https://a.b/&quot;onmouseover=&quot;alert(1\">x</a>)</p>\n"
+        );
+        assert_eq!(
+            to_html("![\"><script>](a.png)"),
+            "<p><img src=\"a.png\" alt=\"&quot;&gt;&lt;script&gt;\"></p>\n"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+pub mod buffer;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod template;
+
+use super::declare_function;
+use crate::environment::*;
+use crate::expressions::*;
+use crate::utils::TronError;
+use std::process::exit;
+
+/// The `is_extending` function tells whether a character attaches to the character before it
+/// instead of starting a new user-perceived character.
+///
+/// This covers combining diacritical marks, variation selectors, emoji skin tone modifiers,
+/// tag characters and the zero width joiner itself.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(feature = "graphemes")]
+fn is_extending(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200D
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0020..=0xE007F
+    )
+}
+/// The `is_regional_indicator` function tells whether a character is one of the regional indicator symbols
+/// that are paired up into flag emojis.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(feature = "graphemes")]
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+/// The `segments` function splits a string into the characters a user would see on the screen.
+///
+/// With the `graphemes` feature enabled, combining marks, emoji modifiers and zero width joiner sequences
+/// stay attached to their base character, flags made of two regional indicators are kept together and
+/// `\r\n` counts as a single character. Without the feature the string is split into unicode scalar values.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(feature = "graphemes")]
+pub fn segments(value: &str) -> Vec<String> {
+    let mut segments: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut pairs_open = false;
+    for c in value.chars() {
+        let joins = match previous {
+            None => false,
+            Some(prev) => {
+                is_extending(c)
+                    || prev == '\u{200D}'
+                    || (prev == '\r' && c == '\n')
+                    || (pairs_open && is_regional_indicator(prev) && is_regional_indicator(c))
+            }
+        };
+        if joins {
+            segments.last_mut().unwrap().push(c);
+            if is_regional_indicator(c) {
+                pairs_open = false;
+            }
+        } else {
+            segments.push(c.to_string());
+            pairs_open = is_regional_indicator(c);
+        }
+        previous = Some(c);
+    }
+    segments
+}
+/// The `segments` function splits a string into its unicode scalar values.
+///
+/// Enable the `graphemes` feature to keep combining marks and emoji sequences together.
+///
+/// ### Last Updated: (v3.2.0)
+#[cfg(not(feature = "graphemes"))]
+pub fn segments(value: &str) -> Vec<String> {
+    value.chars().map(|c| c.to_string()).collect()
+}
+/// The `classify` function implements the character classification functions (`@is_digit`, `@is_alpha`, ...).
+///
+/// Returns `true` if the string isn't empty and every character in it matches the predicate, and throws
+/// the usual argument errors if the function wasn't called with a single string.
+///
+/// ### Last Updated: (v3.2.0)
+fn classify(name: &str, args: &[TronType], predicate: fn(char) -> bool) -> TronType {
+    if args.len() == 1 {
+        match &args[0] {
+            TronType::StringValue(s) => {
+                if !s.is_empty() && s.chars().all(predicate) {
+                    TronType::True
+                } else {
+                    TronType::False
+                }
+            }
+            _ => {
+                TronError::throw(
+                    "E4021",
+                    0,
+                    vec![name.to_string(), "string".to_string(), "first".to_string()],
+                );
+                exit(1)
+            }
+        }
+    } else {
+        TronError::throw("E4018", 0, vec![name.to_string(), 1.to_string()]);
+        exit(1);
+    }
+}
+/// The `string_library` function declares the unicode-aware string functions of the standard library.
+///
+/// - `@chars(string)`: returns an array with every character of the string.
+/// - `@char_at(string, index)`: returns the character at the given index, or `null` if the index is out of range.
+/// - `@code_point(string)`: returns the unicode code point of the first character of the string.
+/// - `@ord(string)`: returns the code point of a single character.
+/// - `@chr(number)`: returns the character with the given code point.
+/// - `@is_digit(string)`, `@is_alpha(string)`, `@is_whitespace(string)`: tell whether every character
+///   of a non-empty string is an ASCII digit, an alphabetic character or whitespace.
+///
+/// All of them work on characters instead of bytes, so non-ASCII text can't be split in the middle of a character.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn string_library(environment: &mut Environment) {
+    declare_function(
+        "@chars".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType {
+            if args.len() == 1 {
+                match &args[0] {
+                    TronType::StringValue(s) => TronType::ArrayValue(
+                        segments(s).into_iter().map(TronType::StringValue).collect(),
+                    ),
+                    _ => {
+                        TronError::throw(
+                            "E4021",
+                            0,
+                            vec![
+                                "@chars".to_string(),
+                                "string".to_string(),
+                                "first".to_string(),
+                            ],
+                        );
+                        exit(1)
+                    }
+                }
+            } else {
+                TronError::throw("E4018", 0, vec!["@chars".to_string(), 1.to_string()]);
+                exit(1);
+            }
+        },
+        environment,
+    );
+    declare_function(
+        "@char_at".to_string(),
+        2,
+        |args: &Vec<TronType>| -> TronType {
+            if args.len() == 2 {
+                match (&args[0], &args[1]) {
+                    (TronType::StringValue(s), TronType::Number(i)) => {
+                        if *i < 0.0 || i.fract() != 0.0 {
+                            return TronType::Null;
+                        }
+                        match segments(s).into_iter().nth(*i as usize) {
+                            Some(c) => TronType::StringValue(c),
+                            None => TronType::Null,
+                        }
+                    }
+                    (TronType::StringValue(_), _) => {
+                        TronError::throw(
+                            "E4021",
+                            0,
+                            vec![
+                                "@char_at".to_string(),
+                                "number".to_string(),
+                                "second".to_string(),
+                            ],
+                        );
+                        exit(1)
+                    }
+                    _ => {
+                        TronError::throw(
+                            "E4021",
+                            0,
+                            vec![
+                                "@char_at".to_string(),
+                                "string".to_string(),
+                                "first".to_string(),
+                            ],
+                        );
+                        exit(1)
+                    }
+                }
+            } else {
+                TronError::throw("E4018", 0, vec!["@char_at".to_string(), 2.to_string()]);
+                exit(1);
+            }
+        },
+        environment,
+    );
+    declare_function(
+        "@code_point".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType {
+            if args.len() == 1 {
+                match &args[0] {
+                    TronType::StringValue(s) => match s.chars().next() {
+                        Some(c) => TronType::Number(c as u32 as f32),
+                        None => TronType::Null,
+                    },
+                    _ => {
+                        TronError::throw(
+                            "E4021",
+                            0,
+                            vec![
+                                "@code_point".to_string(),
+                                "string".to_string(),
+                                "first".to_string(),
+                            ],
+                        );
+                        exit(1)
+                    }
+                }
+            } else {
+                TronError::throw("E4018", 0, vec!["@code_point".to_string(), 1.to_string()]);
+                exit(1);
+            }
+        },
+        environment,
+    );
+    declare_function(
+        "@ord".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType {
+            if args.len() == 1 {
+                match &args[0] {
+                    TronType::StringValue(s) if s.chars().count() == 1 => {
+                        TronType::Number(s.chars().next().unwrap() as u32 as f32)
+                    }
+                    _ => {
+                        TronError::throw(
+                            "E4021",
+                            0,
+                            vec![
+                                "@ord".to_string(),
+                                "single character string".to_string(),
+                                "first".to_string(),
+                            ],
+                        );
+                        exit(1)
+                    }
+                }
+            } else {
+                TronError::throw("E4018", 0, vec!["@ord".to_string(), 1.to_string()]);
+                exit(1);
+            }
+        },
+        environment,
+    );
+    declare_function(
+        "@chr".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType {
+            if args.len() == 1 {
+                let character = match &args[0] {
+                    TronType::Number(n) if *n >= 0.0 && n.fract() == 0.0 => {
+                        char::from_u32(*n as u32)
+                    }
+                    _ => None,
+                };
+                match character {
+                    Some(c) => TronType::StringValue(c.to_string()),
+                    None => {
+                        TronError::throw(
+                            "E4021",
+                            0,
+                            vec![
+                                "@chr".to_string(),
+                                "valid code point number".to_string(),
+                                "first".to_string(),
+                            ],
+                        );
+                        exit(1)
+                    }
+                }
+            } else {
+                TronError::throw("E4018", 0, vec!["@chr".to_string(), 1.to_string()]);
+                exit(1);
+            }
+        },
+        environment,
+    );
+    declare_function(
+        "@is_digit".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType { classify("@is_digit", args, |c| c.is_ascii_digit()) },
+        environment,
+    );
+    declare_function(
+        "@is_alpha".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType { classify("@is_alpha", args, char::is_alphabetic) },
+        environment,
+    );
+    declare_function(
+        "@is_whitespace".to_string(),
+        1,
+        |args: &Vec<TronType>| -> TronType {
+            classify("@is_whitespace", args, char::is_whitespace)
+        },
+        environment,
+    );
+}
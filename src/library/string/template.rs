@@ -0,0 +1,312 @@
+use crate::environment::*;
+use crate::expressions::*;
+use crate::library::context::NativeContext;
+use crate::library::declare_native;
+use std::process::exit;
+
+/// A part of a template parsed by `@render`.
+///
+/// # Variants
+///
+/// - `Text`: Text copied as it is.
+/// - `Value`: A `{{name}}` tag replaced by a value, HTML-escaped unless the tag has three braces, `{{{name}}}`.
+/// - `Section`: The parts between `{{#name}}` and `{{/name}}`, rendered once per element when the value is an array,
+///   once when it's any other truthy value, and never otherwise. An inverted section, `{{^name}}`, is rendered only
+///   when the value is falsy or an empty array.
+///
+/// ### Last Updated: (v3.2.0)
+enum Node {
+    Text(String),
+    Value {
+        path: String,
+        escape: bool,
+    },
+    Section {
+        path: String,
+        inverted: bool,
+        children: Vec<Node>,
+    },
+}
+
+/// The `template_library` function declares the natives of the standard library that fill templates.
+///
+/// - `@render(template, data)`: fills a mustache-style template with the values of the object `data`.
+///
+/// `{{name}}` is replaced by the value of `name`, and `{{user.name}}` by the value of `name` in the object `user`.
+/// Values are HTML-escaped, `{{{name}}}` inserts one as it is, and a missing value or `null` is an empty string.
+/// `{{#items}} ... {{/items}}` repeats its content for every element of `items`, where `{{.}}` is the element and
+/// names are looked up in the element first when it's an object, then in the data around it.
+/// `{{#admin}} ... {{/admin}}` only renders its content when `admin` is truthy, and `{{^admin}} ... {{/admin}}` when
+/// it's not. `{{! a comment }}` is left out.
+///
+/// A template whose tags aren't closed, or closed in the wrong order, throws `E4029`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn template_library(environment: &mut Environment) {
+    declare_native(
+        "@render".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::StringValue(template) = &args[0] else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@render".to_string(),
+                        "string".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            match parse(template) {
+                Ok(nodes) => {
+                    let mut output = String::new();
+                    render(&nodes, &mut vec![&args[1]], &mut output);
+                    TronType::StringValue(output)
+                }
+                Err(msg) => {
+                    context.throw("E4029", vec!["@render".to_string(), msg]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+}
+
+// parses a whole template, every section has to be closed
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    let mut rest = template;
+    let (nodes, closed) = parse_nodes(&mut rest)?;
+    match closed {
+        Some(name) => Err(format!(
+            "{{{{/{}}}}} closes a section that isn't open",
+            name
+        )),
+        None => Ok(nodes),
+    }
+}
+
+// parses nodes until the end of the template or a closing tag, returning the name the tag closes
+fn parse_nodes(rest: &mut &str) -> Result<(Vec<Node>, Option<String>), String> {
+    let mut nodes = vec![];
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            nodes.push(Node::Text(rest[..start].to_string()));
+        }
+        let triple = rest[start..].starts_with("{{{");
+        let (open, close) = if triple { ("{{{", "}}}") } else { ("{{", "}}") };
+        let tag_start = start + open.len();
+        let Some(length) = rest[tag_start..].find(close) else {
+            return Err(format!(
+                "the tag at `{}` isn't closed",
+                preview(&rest[start..])
+            ));
+        };
+        let tag = rest[tag_start..tag_start + length].trim();
+        *rest = &rest[tag_start + length + close.len()..];
+        if triple {
+            nodes.push(Node::Value {
+                path: tag.to_string(),
+                escape: false,
+            });
+            continue;
+        }
+        match tag.chars().next() {
+            Some('!') => {}
+            Some('/') => return Ok((nodes, Some(tag[1..].trim().to_string()))),
+            Some(sigil @ ('#' | '^')) => {
+                let path = tag[1..].trim().to_string();
+                let (children, closed) = parse_nodes(rest)?;
+                if closed.as_deref() != Some(path.as_str()) {
+                    return Err(format!(
+                        "{{{{{}{}}}}} isn't closed by {{{{/{}}}}}",
+                        sigil, path, path
+                    ));
+                }
+                nodes.push(Node::Section {
+                    path,
+                    inverted: sigil == '^',
+                    children,
+                });
+            }
+            _ => nodes.push(Node::Value {
+                path: tag.to_string(),
+                escape: true,
+            }),
+        }
+    }
+    if !rest.is_empty() {
+        nodes.push(Node::Text(rest.to_string()));
+        *rest = "";
+    }
+    Ok((nodes, None))
+}
+
+// the start of the template at an error, for the message
+fn preview(template: &str) -> String {
+    template.chars().take(20).collect()
+}
+
+fn render<'a>(nodes: &'a [Node], scopes: &mut Vec<&'a TronType>, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value { path, escape } => match lookup(path, scopes) {
+                Some(TronType::Null) | None => {}
+                Some(value) if *escape => output.push_str(&escape_html(&value.to_plain_string())),
+                Some(value) => output.push_str(&value.to_plain_string()),
+            },
+            Node::Section {
+                path,
+                inverted,
+                children,
+            } => {
+                let value = lookup(path, scopes);
+                let truthy = value.is_some_and(|value| value.is_truthy() == TronType::True);
+                match value {
+                    _ if *inverted && !truthy => render(children, scopes, output),
+                    _ if *inverted => {}
+                    Some(TronType::ArrayValue(elements)) => {
+                        for element in elements {
+                            scopes.push(element);
+                            render(children, scopes, output);
+                            scopes.pop();
+                        }
+                    }
+                    Some(value) if truthy => {
+                        scopes.push(value);
+                        render(children, scopes, output);
+                        scopes.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+// the value of `.`, `name` or `name.field`, the first name is looked up from the innermost scope out
+fn lookup<'a>(path: &str, scopes: &[&'a TronType]) -> Option<&'a TronType> {
+    if path == "." {
+        return scopes.last().copied();
+    }
+    let mut names = path.split('.');
+    let first = names.next()?;
+    let mut value = scopes.iter().rev().find_map(|scope| match scope {
+        TronType::Object(fields) => fields.get(first),
+        _ => None,
+    })?;
+    for name in names {
+        value = match value {
+            TronType::Object(fields) => fields.get(name)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn object(fields: Vec<(&str, TronType)>) -> TronType {
+        TronType::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    fn text(text: &str) -> TronType {
+        TronType::StringValue(text.to_string())
+    }
+
+    fn render_template(template: &str, data: &TronType) -> String {
+        let nodes = parse(template).unwrap();
+        let mut output = String::new();
+        render(&nodes, &mut vec![data], &mut output);
+        output
+    }
+
+    #[test]
+    fn replaces_values_and_escapes_them() {
+        let data = object(vec![
+            ("name", text("<Ada>")),
+            ("user", object(vec![("age", TronType::Number(36.0))])),
+            ("nothing", TronType::Null),
+        ]);
+        assert_eq!(
+            render_template(
+                "{{name}} {{{name}}} {{ user.age }} [{{nothing}}{{missing}}]{{! note }}",
+                &data
+            ),
+            "&lt;Ada&gt; <Ada> 36 []"
+        );
+    }
+
+    #[test]
+    fn repeats_sections_for_every_element() {
+        let data = object(vec![
+            (
+                "items",
+                TronType::ArrayValue(vec![
+                    object(vec![("name", text("a"))]),
+                    object(vec![("name", text("b"))]),
+                ]),
+            ),
+            ("title", text("list")),
+            ("numbers", TronType::ArrayValue(vec![TronType::Number(1.0)])),
+        ]);
+        // the outer scopes stay visible inside a section
+        assert_eq!(
+            render_template("{{#items}}{{title}}:{{name}} {{/items}}", &data),
+            "list:a list:b "
+        );
+        assert_eq!(
+            render_template("{{#numbers}}<{{.}}>{{/numbers}}", &data),
+            "<1>"
+        );
+    }
+
+    #[test]
+    fn renders_conditions_and_inverted_sections() {
+        let data = object(vec![
+            ("admin", TronType::True),
+            ("guest", TronType::False),
+            ("items", TronType::ArrayValue(vec![])),
+        ]);
+        assert_eq!(
+            render_template(
+                "{{#admin}}admin{{/admin}}{{#guest}}guest{{/guest}}{{^items}} none{{/items}}",
+                &data
+            ),
+            "admin none"
+        );
+    }
+
+    #[test]
+    fn rejects_tags_that_arent_closed() {
+        assert!(parse("{{name").is_err());
+        assert!(parse("{{#a}}x").is_err());
+        assert!(parse("{{#a}}{{#b}}{{/a}}{{/b}}").is_err());
+        assert!(parse("x{{/a}}").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::thread;
+
+/// The `system_library` function declares the natives of the standard library that describe the system a script runs
+/// on, so scripts can branch per platform without running `uname`.
+///
+/// - `@os()`: the operating system, like `"linux"`, `"macos"` or `"windows"`.
+/// - `@arch()`: the architecture of the processor, like `"x86_64"` or `"aarch64"`.
+/// - `@hostname()`: the name of the machine, `null` when it can't be found.
+/// - `@cpu_count()`: the number of processors the script can use, at least 1.
+/// - `@home_dir()`: the home directory of the user, `null` when it isn't set.
+///
+/// `@os()` and `@arch()` are the values Rust names the platforms with, the ones of `std::env::consts`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn system_library(environment: &mut Environment) {
+    declare_native(
+        "@os".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            TronType::StringValue(env::consts::OS.to_string())
+        },
+        environment,
+    );
+    declare_native(
+        "@arch".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            TronType::StringValue(env::consts::ARCH.to_string())
+        },
+        environment,
+    );
+    declare_native(
+        "@hostname".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            hostname().map_or(TronType::Null, TronType::StringValue)
+        },
+        environment,
+    );
+    declare_native(
+        "@cpu_count".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            let count = thread::available_parallelism().map_or(1, |count| count.get());
+            TronType::Number(count as f32)
+        },
+        environment,
+    );
+    declare_native(
+        "@home_dir".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            let home = env::var("HOME").or_else(|_| env::var("USERPROFILE"));
+            match home {
+                Ok(home) if !home.is_empty() => TronType::StringValue(home),
+                _ => TronType::Null,
+            }
+        },
+        environment,
+    );
+}
+
+// the kernel's name for the machine on Linux, the variable Windows sets, then the `hostname` program
+fn hostname() -> Option<String> {
+    let name = fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .or_else(|| {
+            let output = Command::new("hostname").output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+        })?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
@@ -0,0 +1,117 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use crate::utils::deterministic;
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process::exit;
+
+thread_local! {
+    // the files and directories created by `@temp_file` and `@temp_dir`, removed when the script ends
+    static TEMPORARIES: RefCell<Vec<PathBuf>> = const { RefCell::new(vec![]) };
+    static COUNTER: Cell<u32> = const { Cell::new(0) };
+}
+
+/// The `temp_library` function declares the natives of the standard library that create temporary files and
+/// directories, for build and test scripts.
+///
+/// - `@temp_file(suffix)`: creates an empty file in the temporary directory of the system and returns its path. The
+///   name ends with `suffix` when it's given, like `".json"`.
+/// - `@temp_dir()`: creates an empty directory in the temporary directory of the system and returns its path.
+///
+/// Every name is new, so two scripts running at the same time never get the same one. The files and directories are
+/// removed when the script ends normally or is interrupted with Ctrl+C, with everything that was put in the
+/// directories, so a script doesn't have to clean up after itself. A path that can't be created throws `E0010` and
+/// the native returns `null`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn temp_library(environment: &mut Environment) {
+    declare_native(
+        "@temp_file".to_string(),
+        Arity::between(0, 1),
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let suffix = match args.first() {
+                Some(TronType::StringValue(suffix)) => suffix.clone(),
+                None => String::new(),
+                Some(_) => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@temp_file".to_string(),
+                            "string".to_string(),
+                            "first".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            create(&suffix, context, |path| {
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(path)
+                    .map(|_| ())
+            })
+        },
+        environment,
+    );
+    declare_native(
+        "@temp_dir".to_string(),
+        0,
+        |context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            create("", context, |path| fs::create_dir(path))
+        },
+        environment,
+    );
+}
+
+/// Removes the files and directories created by `@temp_file` and `@temp_dir`.
+///
+/// Called when the script ends, the ones removed by the script already are skipped.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn remove_temporaries() {
+    let paths = TEMPORARIES.with(|paths| std::mem::take(&mut *paths.borrow_mut()));
+    for path in paths {
+        let _ = match path.is_dir() {
+            true => fs::remove_dir_all(&path),
+            false => fs::remove_file(&path),
+        };
+    }
+}
+
+// creates a new path with `make`, trying other names while the ones picked exist already
+fn create(
+    suffix: &str,
+    context: &NativeContext,
+    make: impl Fn(&PathBuf) -> std::io::Result<()>,
+) -> TronType {
+    loop {
+        let path = env::temp_dir().join(format!("tron-{}{}", unique_name(), suffix));
+        match make(&path) {
+            Ok(()) => {
+                TEMPORARIES.with(|paths| paths.borrow_mut().push(path.clone()));
+                return TronType::StringValue(path.to_string_lossy().into_owned());
+            }
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => {}
+            Err(error) => {
+                context.throw(
+                    "E0010",
+                    vec![path.to_string_lossy().into_owned(), error.to_string()],
+                );
+                return TronType::Null;
+            }
+        }
+    }
+}
+
+// the pid tells scripts apart, the counter the paths of a script, and the random part makes the names hard to guess
+fn unique_name() -> String {
+    let count = COUNTER.with(|counter| counter.replace(counter.get() + 1));
+    let random = deterministic::random() as u32;
+    format!("{}-{}-{:08x}", std::process::id(), count, random)
+}
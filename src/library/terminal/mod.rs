@@ -0,0 +1,124 @@
+use super::context::NativeContext;
+use super::declare_native;
+use crate::environment::*;
+use crate::expressions::*;
+use std::io::{stdout, IsTerminal, Write};
+use std::process::exit;
+
+/// The styles `@styled` knows, with their SGR codes.
+///
+/// ### Last Updated: (v3.2.0)
+const STYLES: [(&str, u8); 20] = [
+    ("bold", 1),
+    ("dim", 2),
+    ("italic", 3),
+    ("underline", 4),
+    ("black", 30),
+    ("red", 31),
+    ("green", 32),
+    ("yellow", 33),
+    ("blue", 34),
+    ("magenta", 35),
+    ("cyan", 36),
+    ("white", 37),
+    ("bright_black", 90),
+    ("bright_red", 91),
+    ("bright_green", 92),
+    ("bright_yellow", 93),
+    ("bright_blue", 94),
+    ("bright_magenta", 95),
+    ("bright_cyan", 96),
+    ("bright_white", 97),
+];
+
+/// The `terminal_library` function declares the natives of the standard library that control the terminal, to draw
+/// simple interfaces.
+///
+/// - `@clear_screen()`: clears the screen and moves the cursor to the top left corner.
+/// - `@move_cursor(row, column)`: moves the cursor to `row` and `column`, counted from 1.
+/// - `@styled(text, style)`: returns `text` in a color or style, like `"red"`, `"bold"` or `"bold bright_green"`.
+///
+/// When the standard output isn't a terminal, because it's redirected to a file or piped to another program, the
+/// escape codes would end up in the output as they are, so `@clear_screen` and `@move_cursor` do nothing and `@styled`
+/// returns the text unchanged. A style it doesn't know throws `E4030` either way, so a typo isn't only found on a
+/// terminal.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn terminal_library(environment: &mut Environment) {
+    declare_native(
+        "@clear_screen".to_string(),
+        0,
+        |_context: &NativeContext, _args: &Vec<TronType>| -> TronType {
+            write_escape("\x1B[2J\x1B[H");
+            TronType::Null
+        },
+        environment,
+    );
+    declare_native(
+        "@move_cursor".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let row = position("@move_cursor", &args[0], "first", context);
+            let column = position("@move_cursor", &args[1], "second", context);
+            write_escape(&format!("\x1B[{};{}H", row, column));
+            TronType::Null
+        },
+        environment,
+    );
+    declare_native(
+        "@styled".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::StringValue(style) = &args[1] else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@styled".to_string(),
+                        "string".to_string(),
+                        "second".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            let mut codes = vec![];
+            for name in style.split_whitespace() {
+                match STYLES.iter().find(|(style, _)| *style == name) {
+                    Some((_, code)) => codes.push(code.to_string()),
+                    None => {
+                        context.throw("E4030", vec!["@styled".to_string(), name.to_string()]);
+                        return TronType::Null;
+                    }
+                }
+            }
+            let text = args[0].to_plain_string();
+            if codes.is_empty() || !stdout().is_terminal() {
+                return TronType::StringValue(text);
+            }
+            TronType::StringValue(format!("\x1B[{}m{}\x1B[0m", codes.join(";"), text))
+        },
+        environment,
+    );
+}
+
+// writes an escape code to the terminal, and nothing when the output isn't one
+fn write_escape(code: &str) {
+    let mut out = stdout();
+    if out.is_terminal() {
+        let _ = out.write_all(code.as_bytes());
+        let _ = out.flush();
+    }
+}
+
+// a row or column for `@move_cursor`, a whole number from 1
+fn position(name: &str, value: &TronType, place: &str, context: &NativeContext) -> u32 {
+    match value {
+        TronType::Number(number) if *number >= 1.0 && number.fract() == 0.0 => *number as u32,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![name.to_string(), "number".to_string(), place.to_string()],
+            );
+            exit(1)
+        }
+    }
+}
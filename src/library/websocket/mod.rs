@@ -0,0 +1,604 @@
+use super::context::NativeContext;
+use super::declare_native;
+use super::signal::check_interrupt;
+use crate::environment::*;
+use crate::expressions::*;
+use crate::utils::deterministic;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+/// The GUID a server appends to the key of the handshake to prove it speaks WebSocket, from RFC 6455.
+///
+/// ### Last Updated: (v3.2.0)
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// How long a read waits before checking for Ctrl+C and the timeout of `@ws_recv` again.
+///
+/// ### Last Updated: (v3.2.0)
+const POLL: Duration = Duration::from_millis(100);
+
+/// The largest message `@ws_recv` accepts, in bytes. A frame, or the frames of a fragmented message, announcing more
+/// close the connection with `E4033` instead of being buffered until the memory runs out.
+///
+/// ### Last Updated: (v3.2.0)
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// An open WebSocket connection.
+///
+/// # Fields
+///
+/// - `stream`: The TCP connection to the server.
+/// - `buffer`: The bytes received that don't make a whole frame yet, kept when `@ws_recv` times out in the middle of
+///   one.
+/// - `message`: The payloads of the frames of a fragmented message received so far.
+///
+/// ### Last Updated: (v3.2.0)
+struct Connection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    message: Vec<u8>,
+}
+
+thread_local! {
+    // the open connections, by the handle `@ws_connect` returned
+    static CONNECTIONS: RefCell<HashMap<u32, Connection>> = RefCell::new(HashMap::new());
+    static NEXT_HANDLE: Cell<u32> = const { Cell::new(1) };
+}
+
+/// The `websocket_library` function declares the natives of the standard library that talk to WebSocket servers, for
+/// scripts using streaming APIs.
+///
+/// - `@ws_connect(url)`: connects to a `ws://` URL and returns the handle of the connection.
+/// - `@ws_send(ws, text)`: sends `text` as a message.
+/// - `@ws_recv(ws, timeout)`: waits for the next message and returns it as a string, or `null` once `timeout`
+///   milliseconds passed. Without `timeout`, it waits as long as it takes.
+/// - `@ws_close(ws)`: closes the connection.
+///
+/// Pings from the server are answered while waiting for a message, and a message longer than `MAX_MESSAGE_SIZE` closes
+/// the connection. Only `ws://` is supported, `wss://` needs TLS,
+/// which the dependency policy of `Cargo.toml` leaves out. A connection that fails or that the server closes throws `E4033`, and
+/// the native returns `null`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn websocket_library(environment: &mut Environment) {
+    declare_native(
+        "@ws_connect".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let TronType::StringValue(url) = &args[0] else {
+                context.throw(
+                    "E4021",
+                    vec![
+                        "@ws_connect".to_string(),
+                        "string".to_string(),
+                        "first".to_string(),
+                    ],
+                );
+                exit(1)
+            };
+            match connect(url) {
+                Ok(connection) => {
+                    let handle = NEXT_HANDLE.with(|next| next.replace(next.get() + 1));
+                    CONNECTIONS
+                        .with(|connections| connections.borrow_mut().insert(handle, connection));
+                    TronType::Number(handle as f32)
+                }
+                Err(reason) => {
+                    context.throw("E4033", vec!["@ws_connect".to_string(), reason]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@ws_send".to_string(),
+        2,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let handle = handle("@ws_send", &args[0], context);
+            let text = args[1].to_plain_string();
+            let result = with_connection(handle, |connection| {
+                send_frame(&mut connection.stream, 0x1, text.as_bytes())
+            });
+            if let Err(reason) = result {
+                context.throw("E4033", vec!["@ws_send".to_string(), reason]);
+            }
+            TronType::Null
+        },
+        environment,
+    );
+    declare_native(
+        "@ws_recv".to_string(),
+        Arity::between(1, 2),
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let handle = handle("@ws_recv", &args[0], context);
+            let deadline = match args.get(1) {
+                Some(TronType::Number(timeout)) if *timeout >= 0.0 => {
+                    Some(Instant::now() + Duration::from_millis(*timeout as u64))
+                }
+                None | Some(TronType::Null) => None,
+                Some(_) => {
+                    context.throw(
+                        "E4021",
+                        vec![
+                            "@ws_recv".to_string(),
+                            "number".to_string(),
+                            "second".to_string(),
+                        ],
+                    );
+                    exit(1)
+                }
+            };
+            match receive(handle, deadline, context) {
+                Ok(Some(message)) => TronType::StringValue(message),
+                Ok(None) => TronType::Null,
+                Err(reason) => {
+                    context.throw("E4033", vec!["@ws_recv".to_string(), reason]);
+                    TronType::Null
+                }
+            }
+        },
+        environment,
+    );
+    declare_native(
+        "@ws_close".to_string(),
+        1,
+        |context: &NativeContext, args: &Vec<TronType>| -> TronType {
+            let handle = handle("@ws_close", &args[0], context);
+            let connection =
+                CONNECTIONS.with(|connections| connections.borrow_mut().remove(&handle));
+            if let Some(mut connection) = connection {
+                // a normal closure, the server may already be gone
+                let _ = send_frame(&mut connection.stream, 0x8, &1000u16.to_be_bytes());
+            }
+            TronType::Null
+        },
+        environment,
+    );
+}
+
+fn handle(name: &str, value: &TronType, context: &NativeContext) -> u32 {
+    match value {
+        TronType::Number(handle) if *handle >= 1.0 && handle.fract() == 0.0 => *handle as u32,
+        _ => {
+            context.throw(
+                "E4021",
+                vec![
+                    name.to_string(),
+                    "WebSocket handle".to_string(),
+                    "first".to_string(),
+                ],
+            );
+            exit(1)
+        }
+    }
+}
+
+// runs `f` on an open connection, and closes it when `f` fails
+fn with_connection<T>(
+    handle: u32,
+    f: impl FnOnce(&mut Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    CONNECTIONS.with(|connections| {
+        let mut connections = connections.borrow_mut();
+        let connection = connections
+            .get_mut(&handle)
+            .ok_or_else(|| format!("{} isn't an open connection", handle))?;
+        let result = f(connection);
+        if result.is_err() {
+            connections.remove(&handle);
+        }
+        result
+    })
+}
+
+// opens the TCP connection and upgrades it to a WebSocket with the handshake of RFC 6455
+fn connect(url: &str) -> Result<Connection, String> {
+    if url.starts_with("wss://") {
+        return Err("wss:// needs TLS, which isn't supported, use ws://".to_string());
+    }
+    let rest = url
+        .strip_prefix("ws://")
+        .ok_or_else(|| format!("{} isn't a ws:// URL", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    };
+    let address = match authority.contains(':') {
+        true => authority.to_string(),
+        false => format!("{}:80", authority),
+    };
+    let mut stream = TcpStream::connect(&address).map_err(|error| error.to_string())?;
+    let key = base64(&random_bytes(16));
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        path, authority, key
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|error| error.to_string())?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .map_err(|error| error.to_string())?;
+    // the response ends with an empty line, the bytes after it are the first frames
+    let mut response = vec![];
+    let end = loop {
+        if let Some(end) = response.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        let mut chunk = [0; 1024];
+        match stream.read(&mut chunk) {
+            Ok(0) => {
+                return Err("the server closed the connection during the handshake".to_string())
+            }
+            Ok(count) => response.extend_from_slice(&chunk[..count]),
+            Err(error) => return Err(error.to_string()),
+        }
+    };
+    let head = String::from_utf8_lossy(&response[..end]).into_owned();
+    let mut lines = head.lines();
+    let status = lines.next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("101") {
+        return Err(format!(
+            "the server answered the handshake with `{}`",
+            status
+        ));
+    }
+    let accept = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("sec-websocket-accept")
+            .then(|| value.trim().to_string())
+    });
+    if accept.as_deref() != Some(accept_key(&key).as_str()) {
+        return Err("the server answered the handshake with the wrong key".to_string());
+    }
+    stream
+        .set_read_timeout(Some(POLL))
+        .map_err(|error| error.to_string())?;
+    Ok(Connection {
+        stream,
+        buffer: response[end + 4..].to_vec(),
+        message: vec![],
+    })
+}
+
+// sends a frame, masked like every frame from a client has to be
+fn send_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) -> Result<(), String> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length if length < 126 => frame.push(0x80 | length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    let mask = random_bytes(4);
+    frame.extend_from_slice(&mask);
+    frame.extend(
+        payload
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4]),
+    );
+    stream.write_all(&frame).map_err(|error| error.to_string())
+}
+
+// waits for the next text or binary message until the deadline, `None` when it passed
+fn receive(
+    handle: u32,
+    deadline: Option<Instant>,
+    context: &NativeContext,
+) -> Result<Option<String>, String> {
+    loop {
+        if let Some(message) = with_connection(handle, poll)? {
+            return Ok(Some(message));
+        }
+        // outside of `with_connection`, the handler may close the connection
+        check_interrupt(&context.environment);
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Ok(None);
+        }
+    }
+}
+
+// returns the next message when the frames received make one, or reads what arrives until `POLL` passes
+fn poll(connection: &mut Connection) -> Result<Option<String>, String> {
+    loop {
+        let limit = MAX_MESSAGE_SIZE - connection.message.len();
+        let (fin, opcode, payload) = match parse_frame(&mut connection.buffer, limit) {
+            Ok(Some(frame)) => frame,
+            Ok(None) => break,
+            Err(reason) => {
+                // 1009, the message is too big to process
+                let _ = send_frame(&mut connection.stream, 0x8, &1009u16.to_be_bytes());
+                return Err(reason);
+            }
+        };
+        match opcode {
+            // a ping is answered with the same payload
+            0x9 => send_frame(&mut connection.stream, 0xA, &payload)?,
+            0xA => {}
+            0x8 => {
+                let _ = send_frame(&mut connection.stream, 0x8, &payload);
+                return Err("the server closed the connection".to_string());
+            }
+            _ => {
+                connection.message.extend_from_slice(&payload);
+                if fin {
+                    let message = std::mem::take(&mut connection.message);
+                    return Ok(Some(String::from_utf8_lossy(&message).into_owned()));
+                }
+            }
+        }
+    }
+    let mut chunk = [0; 4096];
+    match connection.stream.read(&mut chunk) {
+        Ok(0) => Err("the server closed the connection".to_string()),
+        Ok(count) => {
+            connection.buffer.extend_from_slice(&chunk[..count]);
+            Ok(None)
+        }
+        Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+            Ok(None)
+        }
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+// takes the first frame out of the buffer, when it's all there, as whether it's the last of its message, its opcode
+// and its payload, or fails when its payload is longer than `limit` bytes
+fn parse_frame(buffer: &mut Vec<u8>, limit: usize) -> Result<Option<(bool, u8, Vec<u8>)>, String> {
+    let Some((length, start, mask)) = frame_header(buffer) else {
+        return Ok(None);
+    };
+    let end = match usize::try_from(length)
+        .ok()
+        .filter(|&length| length <= limit)
+        .and_then(|length| start.checked_add(length))
+    {
+        Some(end) => end,
+        None => {
+            return Err(format!(
+                "the server sent a message longer than {} bytes",
+                MAX_MESSAGE_SIZE
+            ))
+        }
+    };
+    let Some(payload) = buffer.get(start..end) else {
+        return Ok(None);
+    };
+    let mut payload = payload.to_vec();
+    if let Some(mask) = mask {
+        payload
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, byte)| *byte ^= mask[i % 4]);
+    }
+    let first = buffer[0];
+    buffer.drain(..end);
+    Ok(Some((first & 0x80 != 0, first & 0x0F, payload)))
+}
+
+// the length of the payload of the first frame, where it starts and its mask, once the header is all in the buffer
+fn frame_header(buffer: &[u8]) -> Option<(u64, usize, Option<[u8; 4]>)> {
+    let second = *buffer.get(1)?;
+    let (length, start) = match second & 0x7F {
+        126 => (
+            u16::from_be_bytes(buffer.get(2..4)?.try_into().ok()?) as u64,
+            4,
+        ),
+        127 => (u64::from_be_bytes(buffer.get(2..10)?.try_into().ok()?), 10),
+        length => (length as u64, 2),
+    };
+    match second & 0x80 != 0 {
+        true => Some((
+            length,
+            start + 4,
+            Some(buffer.get(start..start + 4)?.try_into().ok()?),
+        )),
+        false => Some((length, start, None)),
+    }
+}
+
+// bytes for the key of the handshake and the masks of frames, which only have to be unpredictable to proxies
+fn random_bytes(count: usize) -> Vec<u8> {
+    let mut bytes = vec![];
+    while bytes.len() < count {
+        bytes.extend_from_slice(&deterministic::random().to_le_bytes());
+    }
+    bytes.truncate(count);
+    bytes
+}
+
+// the `Sec-WebSocket-Accept` a server answers the `Sec-WebSocket-Key` of the handshake with
+fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, GUID).as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+// SHA-1, only used to check the handshake, as RFC 6455 asks
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes(block[i * 4..i * 4 + 4].try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    let mut digest = [0; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    // the example of the opening handshake, RFC 6455 section 1.3
+    #[test]
+    fn answers_the_key_of_the_rfc_6455_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    // the examples of FIPS 180-4
+    #[test]
+    fn hashes_the_sha1_examples() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+
+    // the examples of RFC 4648 section 10
+    #[test]
+    fn encodes_the_base64_examples() {
+        let encoded: Vec<String> = ["", "f", "fo", "foo", "foob", "fooba", "foobar"]
+            .iter()
+            .map(|text| base64(text.as_bytes()))
+            .collect();
+        assert_eq!(
+            encoded,
+            ["", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy"]
+        );
+    }
+
+    // an unmasked text frame with the given length bytes, followed by `payload`
+    fn frame(length: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x81];
+        frame.extend_from_slice(length);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn parses_the_16_bit_length_form() {
+        let payload = vec![b'a'; 300];
+        let mut buffer = frame(&[126, 0x01, 0x2C], &payload);
+        buffer.push(0x81);
+        assert_eq!(
+            parse_frame(&mut buffer, MAX_MESSAGE_SIZE),
+            Ok(Some((true, 0x1, payload)))
+        );
+        // the next frame stays in the buffer
+        assert_eq!(buffer, [0x81]);
+    }
+
+    #[test]
+    fn parses_the_64_bit_length_form() {
+        let payload = vec![b'a'; 70000];
+        let mut buffer = frame(&[127, 0, 0, 0, 0, 0, 0x01, 0x11, 0x70], &payload);
+        assert_eq!(
+            parse_frame(&mut buffer, MAX_MESSAGE_SIZE),
+            Ok(Some((true, 0x1, payload)))
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn waits_for_the_rest_of_a_frame() {
+        let mut buffer = frame(&[127, 0, 0, 0, 0, 0, 0x01, 0x11, 0x70], b"abc");
+        assert_eq!(parse_frame(&mut buffer, MAX_MESSAGE_SIZE), Ok(None));
+        assert_eq!(buffer.len(), 13);
+        let mut header = vec![0x81, 126, 0x01];
+        assert_eq!(parse_frame(&mut header, MAX_MESSAGE_SIZE), Ok(None));
+    }
+
+    #[test]
+    fn unmasks_the_payload() {
+        let mask = [1, 2, 3, 4];
+        let masked: Vec<u8> = b"hello"
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4])
+            .collect();
+        let mut buffer = frame(&[0x80 | 5], &[&mask[..], &masked].concat());
+        assert_eq!(
+            parse_frame(&mut buffer, MAX_MESSAGE_SIZE),
+            Ok(Some((true, 0x1, b"hello".to_vec())))
+        );
+    }
+
+    #[test]
+    fn rejects_a_frame_longer_than_the_limit() {
+        let mut huge = frame(&[127, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF], b"");
+        assert!(parse_frame(&mut huge, MAX_MESSAGE_SIZE).is_err());
+        // a fragment that would make the message too long
+        let mut fragment = frame(&[126, 0x01, 0x2C], &[b'a'; 300]);
+        assert!(parse_frame(&mut fragment, 299).is_err());
+        assert!(parse_frame(&mut fragment, 300).is_ok());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::environment::Environment;
+use crate::expressions::Expression;
+use crate::scanner::{Directives, Statement};
+use crate::utils::TronError;
+use crate::visitor::{walk_expression, walk_statement, Visitor};
+use crate::TokenType;
+use std::rc::Rc;
+
+/// The `ConstantLinter` struct evaluates the parts of a program that only depend on literals, for
+/// `tron check --run-lints`.
+///
+/// An expression like `"total: " - 1` or `"a" * "b"` fails the same way every time it runs, so it can be evaluated
+/// without running the program around it. Every such expression is evaluated once, wherever it is (in a function that
+/// is never called, or in a branch that is never taken), and the runtime errors it reports are counted as problems.
+/// An expression that fails doesn't stop the others from being evaluated, so a single run shows every one of them.
+///
+/// Expressions that read a variable or call a function aren't constant and are never evaluated, only the constant
+/// expressions inside them are.
+///
+/// # Fields
+///
+/// - `environment`: The environment the expressions are evaluated in, which only provides the settings.
+/// - `problems`: The number of errors reported so far.
+///
+/// # Example
+///
+/// ```
+/// let interpreter = Interpreter::new();
+/// let problems = ConstantLinter::new(interpreter.environment.clone()).check(&statements);
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+pub struct ConstantLinter {
+    environment: Environment,
+    problems: usize,
+}
+
+impl ConstantLinter {
+    /// Creates a linter evaluating constant expressions with the settings of `environment`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn new(environment: Environment) -> Self {
+        Self {
+            environment,
+            problems: 0,
+        }
+    }
+    /// Evaluates every constant expression of a program, reporting the errors they raise.
+    ///
+    /// # Return Value
+    ///
+    /// The number of errors reported.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn check(mut self, statements: &[Statement]) -> usize {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+        self.problems
+    }
+    // the errors are reported while evaluating, like when the program runs, an error returned only ends the expression
+    fn evaluate(&mut self, expression: &Expression) {
+        let reported = TronError::reported();
+        let _ = expression.evaluate(self.environment.clone());
+        self.problems += TronError::reported() - reported;
+    }
+}
+
+impl Visitor for ConstantLinter {
+    // evaluates the largest constant expressions, a literal alone can't fail so it isn't worth evaluating
+    fn visit_expression(&mut self, expression: &Expression) {
+        if !is_constant(expression) {
+            walk_expression(self, expression);
+        } else if !matches!(expression, Expression::Literal { .. }) {
+            self.evaluate(expression);
+        }
+    }
+}
+
+// whether an expression only depends on literals, so it evaluates to the same value (or fails the same way) every time
+fn is_constant(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal { .. } => true,
+        Expression::Object { properties, .. } => properties.iter().all(|(_, v)| is_constant(v)),
+        Expression::Array { elements, .. } => elements.iter().all(|e| is_constant(e)),
+        Expression::Index { object, index, .. } => is_constant(object) && is_constant(index),
+        Expression::Grouping { expression, .. } => is_constant(expression),
+        Expression::Binary { left, right, .. } | Expression::Logical { left, right, .. } => {
+            is_constant(left) && is_constant(right)
+        }
+        Expression::Unary { right, .. } => is_constant(right),
+        Expression::Template { parts, .. }
+        | Expression::Comparison {
+            operands: parts, ..
+        } => parts.iter().all(is_constant),
+        Expression::OptionalGet { object, .. } => is_constant(object),
+        Expression::ObjectCall { .. }
+        | Expression::Assign { .. }
+        | Expression::Call { .. }
+        | Expression::Variable { .. }
+        | Expression::Function { .. } => false,
+    }
+}
+
+/// The `ComplexityLinter` struct warns about the functions that grew too complex or too long to be maintained easily,
+/// for `tron check`.
+///
+/// The cyclomatic complexity of a function is the number of paths through it: 1, plus 1 for every `if`, `elif`,
+/// `while`, `do while`, `for`, `case` and every `&`, `||` and `??`, whose right side doesn't always run. The length of a
+/// function is the number of lines from its `fn` to its last statement. A function more complex than `max_complexity`
+/// is reported with `W5001` and one longer than `max_lines` with `W5002`.
+///
+/// The functions declared inside a function are measured on their own, the anonymous functions count as part of the
+/// function they're written in.
+///
+/// # Fields
+///
+/// - `max_complexity`: The highest complexity allowed, 10 by default or `max-complexity` in the `[lints]` section of
+///   `tron.toml`.
+/// - `max_lines`: The most lines allowed, 50 by default or `max-function-lines` in the `[lints]` section of `tron.toml`.
+/// - `directives`: The warnings turned off by `// tron:allow complexity` and `// tron:allow long-function` comments
+///   on the line of a function or the one before it.
+///
+/// # Example
+///
+/// ```
+/// let linter = ComplexityLinter::new(10, 50, directives);
+/// linter.check(&statements);
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+pub struct ComplexityLinter {
+    max_complexity: usize,
+    max_lines: usize,
+    directives: Directives,
+    // the complexity and the last line of the function being measured so far
+    complexity: usize,
+    last_line: usize,
+}
+
+impl ComplexityLinter {
+    /// The highest complexity allowed when the project doesn't set one.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub const MAX_COMPLEXITY: usize = 10;
+    /// The most lines allowed in a function when the project doesn't set it.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub const MAX_LINES: usize = 50;
+    /// Creates a linter with the given thresholds, and the directives of the file it checks.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn new(max_complexity: usize, max_lines: usize, directives: Directives) -> Self {
+        Self {
+            max_complexity,
+            max_lines,
+            directives,
+            complexity: 1,
+            last_line: 0,
+        }
+    }
+    /// Measures every function of a program, warning about the ones over the thresholds.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn check(mut self, statements: &[Statement]) {
+        // the code outside functions is measured too, but never reported
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+    fn function(&mut self, name: &str, line: usize, body: &[Rc<Statement>]) {
+        let (complexity, lines) = self.measure(line, body);
+        if complexity > self.max_complexity && !self.is_allowed("complexity", line) {
+            TronError::warn(
+                "W5001",
+                line,
+                vec![
+                    name.to_string(),
+                    complexity.to_string(),
+                    self.max_complexity.to_string(),
+                ],
+            );
+        }
+        if lines > self.max_lines && !self.is_allowed("long-function", line) {
+            TronError::warn(
+                "W5002",
+                line,
+                vec![
+                    name.to_string(),
+                    lines.to_string(),
+                    self.max_lines.to_string(),
+                ],
+            );
+        }
+    }
+    // the complexity and the number of lines of a function starting on `line`
+    fn measure(&mut self, line: usize, body: &[Rc<Statement>]) -> (usize, usize) {
+        let outer = (self.complexity, self.last_line);
+        (self.complexity, self.last_line) = (1, line);
+        for statement in body {
+            self.visit_statement(statement);
+        }
+        let measured = (self.complexity, self.last_line - line + 1);
+        (self.complexity, self.last_line) = outer;
+        measured
+    }
+    // whether a `// tron:allow` comment on the line or the one before it turns the warning off
+    fn is_allowed(&self, warning: &str, line: usize) -> bool {
+        [line, line.saturating_sub(1)].iter().any(|line| {
+            self.directives
+                .get(line)
+                .is_some_and(|allowed| allowed.iter().any(|name| name == warning))
+        })
+    }
+}
+
+impl Visitor for ComplexityLinter {
+    fn visit_statement(&mut self, statement: &Statement) {
+        self.last_line = self.last_line.max(statement.line());
+        match statement {
+            // `for` loops are parsed as `while` loops
+            Statement::While { .. } | Statement::DoWhile { .. } => self.complexity += 1,
+            Statement::If { elif_branches, .. } => self.complexity += 1 + elif_branches.len(),
+            Statement::Switch { case_branches, .. } => self.complexity += case_branches.len(),
+            Statement::Function {
+                name, body, line, ..
+            } => return self.function(&name.lexeme, *line, body),
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let Expression::Logical { operator, .. } = expression {
+            if matches!(
+                operator.token_type,
+                TokenType::And | TokenType::Or | TokenType::QuestionQuestion
+            ) {
+                self.complexity += 1;
+            }
+        }
+        walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    // the complexity and the number of lines of the function a source starts with
+    fn measured(source: &str) -> (usize, usize) {
+        let (tokens, directives) = Scanner::new(source).scan_tokens_with_directives().unwrap();
+        let Statement::Function { body, line, .. } = Parser::new(tokens).parse().unwrap().remove(0)
+        else {
+            panic!("expected a function");
+        };
+        ComplexityLinter::new(
+            ComplexityLinter::MAX_COMPLEXITY,
+            ComplexityLinter::MAX_LINES,
+            directives,
+        )
+        .measure(line, &body)
+    }
+
+    #[test]
+    fn a_straight_function_has_one_path() {
+        assert_eq!(measured("fn f() { let a = 1; @print(a); }"), (1, 1));
+    }
+
+    #[test]
+    fn every_branch_and_loop_adds_a_path() {
+        assert_eq!(
+            measured("fn f(a: number) { if a > 1 x(); elif a > 0 y(); else z(); }").0,
+            3
+        );
+        assert_eq!(measured("fn f(a: number) { while a > 0 a = a - 1; }").0, 2);
+        assert_eq!(
+            measured("fn f(a: number) { for let i = 0; i < a; i++ x(); }").0,
+            2
+        );
+    }
+
+    #[test]
+    fn short_circuiting_operators_add_a_path() {
+        assert_eq!(
+            measured("fn f(a: bool, b: bool) { return a & b || a; }").0,
+            3
+        );
+        assert_eq!(measured("fn f(a: number) { return a ?? 0; }").0, 2);
+        assert_eq!(measured("fn f(a: number) { return a + 1 * 2; }").0, 1);
+    }
+
+    #[test]
+    fn nested_functions_are_measured_on_their_own() {
+        assert_eq!(
+            measured("fn f() {\n  fn g(a: bool) {\n    if a x();\n  }\n  g(true);\n}").0,
+            1
+        );
+    }
+
+    #[test]
+    fn the_length_runs_from_fn_to_the_last_statement() {
+        assert_eq!(measured("fn f() {\n  x();\n\n  y();\n}"), (1, 4));
+    }
+
+    #[test]
+    fn the_thresholds_can_be_turned_off_by_a_comment() {
+        let linter = ComplexityLinter::new(
+            1,
+            1,
+            Directives::from([(1, vec!["complexity".to_string()])]),
+        );
+        assert!(linter.is_allowed("complexity", 2));
+        assert!(!linter.is_allowed("long-function", 2));
+        assert!(!linter.is_allowed("complexity", 3));
+    }
+}
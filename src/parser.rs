@@ -1,6 +1,11 @@
 use crate::expressions::{Expression, Expression::*, TronType};
-use crate::scanner::{Statement, Token, TokenType, TokenType::*};
-use crate::utils::TronError;
+use crate::scanner::{Statement, Token, TokenType, TokenType::*, INCOMPLETE_INPUT};
+use crate::utils::{hints, locale, TronError};
+use std::collections::HashMap;
+use std::rc::Rc;
+/// The most arguments a call can pass and parameters a function can declare, the ones after it are reported once with
+/// `E2004` and still parsed.
+pub const MAX_ARGUMENTS: usize = 255;
 /// The `Parser` struct in Rust is responsible for parsing.
 /// It maintains a list of tokens and provides methods to parse statements and expressions.
 ///
@@ -9,6 +14,7 @@ use crate::utils::TronError;
 /// - `tokens`: A vector of tokens that the parser will process.
 /// - `current`: The index of the current token being parsed.
 /// - `next_id`: A counter for generating unique IDs for expressions and statements.
+/// - `docs`: The doc comments (`### text` lines), by the index of the token that follows them.
 ///
 /// # Usage
 ///
@@ -22,16 +28,24 @@ use crate::utils::TronError;
 /// let statements = parser.parse()?;
 /// ```
 ///
-/// ### Last Updated: (v3.0.0)
+/// ### Last Updated: (v3.2.0)
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
     next_id: usize,
+    docs: HashMap<usize, Rc<str>>,
+    // in incremental mode, running out of tokens stops the parse with `INCOMPLETE_INPUT` instead of an error
+    incremental: bool,
+    // the lines of the errors reported so far, the parse goes on after them to find the others but fails in the end
+    errors: Vec<usize>,
+    // in strict mode every statement ends with a `;`
+    strict: bool,
 }
 impl Parser {
     /// The `new()` function is a constructor for the `Parser` struct.
     ///
     /// It initializes the `Parser` with a given vector of tokens, sets the current token index to 0, and initializes the `next_id` counter to 0.
+    /// Doc comment tokens are taken out of the tokens, consecutive lines are joined and kept for the token that follows them.
     ///
     /// # Parameters
     ///
@@ -52,14 +66,66 @@ impl Parser {
     /// let mut parser = Parser::new(tokens);
     /// ```
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     pub fn new(tokens: Vec<Token>) -> Self {
+        let mut docs = HashMap::new();
+        let mut doc: Vec<Rc<str>> = vec![];
+        let mut kept = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if token.token_type == DocComment {
+                doc.push(token.lexeme);
+                continue;
+            }
+            if !doc.is_empty() {
+                docs.insert(kept.len(), Rc::from(doc.join("\n")));
+                doc.clear();
+            }
+            kept.push(token);
+        }
         Self {
-            tokens,
+            tokens: kept,
             current: 0,
             next_id: 0,
+            docs,
+            incremental: false,
+            errors: vec![],
+            strict: false,
         }
     }
+    /// Turns on incremental mode, for input that is typed a line at a time like in the REPL.
+    ///
+    /// When the tokens run out in the middle of a statement (a block without its `end`, a call without its `)`),
+    /// `parse()` returns `INCOMPLETE_INPUT` without reporting anything, so the caller can read more input and parse
+    /// again. The `;` of a statement that ends with the input is optional, so `1 + 2` is complete. Every other syntax
+    /// error is reported as usual.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn incremental(mut self) -> Self {
+        self.incremental = true;
+        self
+    }
+    // whether the parse has to stop because an incremental input ends before the statement does
+    fn out_of_input(&self) -> bool {
+        self.incremental && self.is_at_end()
+    }
+    /// Turns on strict mode, from `--strict` or a `#strict` directive, where every statement has to end with a `;`, see
+    /// `terminate()`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+    /// Starts numbering the expressions at `first_id` instead of 0.
+    ///
+    /// The resolver records scope depths by expression id in an environment that outlives one parse, so code parsed
+    /// into the same environment later (like the lines of a REPL session) has to continue the numbering.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn with_first_id(mut self, first_id: usize) -> Self {
+        self.next_id = first_id;
+        self
+    }
     /// The `get_id()` method is used to generate a unique ID for expressions and statements.
     ///
     /// It increments the `next_id` counter and returns the current value, ensuring that each expression or statement has a unique ID.
@@ -92,6 +158,7 @@ impl Parser {
     /// # Return Value
     ///
     /// A `Result` containing a vector of `Statement` objects if the parsing is successful, or an error message if parsing fails.
+    /// In incremental mode, the error is `INCOMPLETE_INPUT` when the tokens end in the middle of a statement.
     ///
     /// # Usage
     ///
@@ -105,19 +172,63 @@ impl Parser {
     /// let statements = parser.parse()?;
     /// ```
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     pub fn parse(&mut self) -> Result<Vec<Statement>, String> {
         let mut stmts = vec![];
         while !self.is_at_end() {
             let stmt = self.declaration();
             match stmt {
                 Ok(s) => stmts.push(s),
+                Err(msg) if msg == INCOMPLETE_INPUT => return Err(msg),
                 Err(msg) => {
-                    TronError::throw("E2001", self.current, vec![msg]);
+                    self.error("E2001", self.peek().line_number, vec![msg]);
                 }
             }
         }
-        Ok(stmts)
+        // a program with a syntax error doesn't run, even when the parser made sense of the rest
+        match self.errors.len() {
+            0 => Ok(stmts),
+            1 => Err("1 syntax error".to_string()),
+            errors => Err(format!("{} syntax errors", errors)),
+        }
+    }
+    /// The `parse_expression()` method parses the tokens as a single expression, optionally followed by a `;`.
+    ///
+    /// It is used to evaluate an expression on its own, like `Interpreter::eval_expr()` does, where a statement or
+    /// anything left after the expression is an error.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing the `Expression` if the tokens are exactly one expression, or an error message, which is
+    /// also reported, if they aren't.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let tokens = Scanner::new("1 + 2").scan_tokens()?;
+    /// let expression = Parser::new(tokens).parse_expression()?;
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn parse_expression(&mut self) -> Result<Expression, String> {
+        let expression = self.expression().and_then(|expression| {
+            self.match_token(Semicolon);
+            // the parser recovers from some errors on its own, the expression is still broken
+            if !self.errors.is_empty() {
+                Err("invalid expression".to_string())
+            } else if self.is_at_end() {
+                Ok(expression)
+            } else {
+                Err(format!(
+                    "expected a single expression, found '{}' after it",
+                    self.peek().lexeme
+                ))
+            }
+        });
+        if let Err(msg) = &expression {
+            self.error("E2001", self.peek().line_number, vec![msg.clone()]);
+        }
+        expression
     }
     /// The `declaration()` method is responsible for parsing declarations.
     ///
@@ -151,7 +262,7 @@ impl Parser {
     }
     /// The `function()` method is responsible for parsing function declarations.
     ///
-    /// It handles the parsing of function names, parameters, body, and output type. It ensures that the function declaration is syntactically correct and constructs a `FunctionStatement` object.
+    /// It handles the parsing of function names, parameters, body, and output type. The types of the parameters and the output are optional annotations. It ensures that the function declaration is syntactically correct and constructs a `Statement::Function` object.
     ///
     /// # Return Value
     ///
@@ -169,82 +280,77 @@ impl Parser {
     /// let function_statement = parser.function()?;
     /// ```
     ///
-    /// ### Last Updated: (v3.1.0)
+    /// ### Last Updated: (v3.2.0)
     fn function(&mut self) -> Result<Statement, String> {
+        // the doc comment is attached to the `fn` keyword, which was just consumed
+        let doc = self.docs.get(&(self.current - 1)).cloned();
         let line_number = self.peek().line_number;
-        let name = self.consume(Identifier, &format!("expected function name"), line_number)?;
-        self.consume(
-            LeftParen,
-            &format!("expected '(' after function name"),
-            line_number,
-        )?;
+        let name = self.consume(Identifier, "function-name", line_number)?;
+        self.consume(LeftParen, "paren-after-function-name", line_number)?;
         let mut params: Vec<(Token, Token)> = vec![];
         if !self.check(RightParen) {
             loop {
-                if params.len() >= 32 {
-                    TronError::throw("E2004", self.current, vec![]);
+                if params.len() == MAX_ARGUMENTS {
+                    self.error("E2004", line_number, vec![]);
                 }
-                let param_name =
-                    self.consume(Identifier, "expected parameter name", line_number)?;
-                self.consume(Colon, "expected `:` after parameter name", line_number)?;
-                let param_type = self.consume(Identifier, "expected type after ':'", line_number);
+                let param_name = self.consume(Identifier, "parameter-name", line_number)?;
+                let param_type = self.type_annotation(line_number)?;
 
-                params.push((param_name, param_type?));
-                if !self.match_token(Comma) {
+                params.push((param_name, param_type));
+                // a trailing comma is allowed before the `)`
+                if !self.match_token(Comma) || self.check(RightParen) {
                     break;
                 }
             }
         }
-        self.consume(RightParen, "expected ')' after parameters.", line_number)?;
-        self.consume(Colon, "expected `:` before function body", line_number)?;
-        let output_type = self.consume(Identifier, "expected type after `:`", line_number)?;
+        self.consume(RightParen, "paren-after-parameters", line_number)?;
+        let output_type = self.type_annotation(line_number)?;
 
         if self.match_token(Equal) {
             let body_expr = self.expression()?;
-            self.consume(
-                Semicolon,
-                "expected ';' after function body expression.",
-                line_number,
-            )?;
-            return Ok(Statement::FunctionStatement {
+            self.terminate("semicolon-after-function-expression", line_number)?;
+            return Ok(Statement::Function {
                 name,
-                params,
-                body: vec![Box::new(Statement::ReturnStatement {
+                params: params.into(),
+                body: Rc::from([Rc::new(Statement::Return {
                     keyword: Token {
                         token_type: TokenType::Return,
-                        lexeme: "".to_string(),
-                        line_number: line_number,
+                        lexeme: "".into(),
+                        line_number,
+                        column: 0,
                         literal: None,
                     },
                     value: Some(body_expr),
                     line: line_number,
-                })],
+                })]),
                 output_type,
+                doc,
                 line: line_number,
             });
         }
-        self.consume(LeftBrace, "Expected '{' before function body.", line_number)?;
+        self.consume(LeftBrace, "brace-before-function-body", line_number)?;
         let body = match self.block_statement()? {
-            Statement::BlockStatement {
+            Statement::Block {
                 statements,
                 line: _,
             } => statements,
             _ => {
-                TronError::throw("E2002", self.current, vec![]);
+                self.error("E2002", self.previous(1).line_number, vec![]);
                 vec![]
             }
         };
-        Ok(Statement::FunctionStatement {
+        Ok(Statement::Function {
             name,
-            params,
-            body,
+            params: params.into(),
+            body: body.into(),
             output_type,
+            doc,
             line: line_number,
         })
     }
     /// The `var_declaration()` method is responsible for parsing variable declarations.
     ///
-    /// It handles the parsing of variable names and their initial values. It ensures that the variable declaration is syntactically correct and constructs a `VariableStatement` object.
+    /// It handles the parsing of variable names, their optional type annotation and their initial values. It ensures that the variable declaration is syntactically correct and constructs a `Statement::Variable` object.
     ///
     /// # Return Value
     ///
@@ -262,31 +368,48 @@ impl Parser {
     /// let variable_statement = parser.var_declaration()?;
     /// ```
     ///
-    /// ### Last Updated: (v3.1.0)
+    /// ### Last Updated: (v3.2.0)
     fn var_declaration(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
-        let name = self.consume(Identifier, "Expected variable name", line_number)?;
-        let _col = self.consume(Colon, "Expected `:` after variable name", line_number);
-        let value_type = if self.match_tokens(&[Identifier, StringLit, Number]) {
-            self.previous(1)
-        } else {
-            return Err(format!("Expected type after ':'"));
-        };
+        let name = self.consume(Identifier, "variable-name", line_number)?;
+        let value_type = self.type_annotation(line_number)?;
 
-        self.consume(Equal, "Expected '=' after variable name", line_number)?;
+        self.consume(Equal, "equal-after-variable-name", line_number)?;
         let value = self.expression()?;
-        self.consume(
-            Semicolon,
-            "Expected ';' after variable declaration",
-            line_number,
-        )?;
-        Ok(Statement::VariableStatement {
+        self.terminate("semicolon-after-declaration", line_number)?;
+        Ok(Statement::Variable {
             name,
             value_type,
             value,
             line: line_number,
         })
     }
+    /// Parses the optional `: type` annotation of a variable, a parameter or a function output.
+    ///
+    /// Types are names (`null` included) or literals. A missing annotation is the `any` type, which the type checker
+    /// never reports; the interpreter ignores annotations either way.
+    ///
+    /// # Return Value
+    ///
+    /// The token of the type, or a synthesized `any` token when there's no annotation.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn type_annotation(&mut self, line_number: usize) -> Result<Token, String> {
+        if !self.match_token(Colon) {
+            return Ok(Token {
+                token_type: Identifier,
+                lexeme: "any".into(),
+                line_number,
+                column: 0,
+                literal: None,
+            });
+        }
+        if self.match_tokens(&[Identifier, StringLit, Number, Null]) {
+            Ok(self.previous(1).clone())
+        } else {
+            Err("Expected type after ':'".to_string())
+        }
+    }
     /// The `statement()` method is responsible for parsing various types of statements.
     ///
     /// It handles different types of statements, including blocks, variable declarations, function declarations, expressions, and control flow statements like if, while, and switch. It recursively resolves nested statements and expressions, ensuring that all symbols are correctly resolved within the current scope.
@@ -319,10 +442,26 @@ impl Parser {
             self.while_statement()
         } else if self.match_token(For) {
             self.for_statement()
+        } else if self.match_token(Repeat) {
+            self.repeat_statement()
+        } else if self.match_token(Loop) {
+            self.loop_statement()
+        } else if self.match_token(Do) {
+            self.do_while_statement()
         } else if self.match_token(Return) {
             self.return_statement()
         } else if self.match_token(Break) {
             self.break_statement()
+        } else if self.match_token(Continue) {
+            self.continue_statement()
+        } else if self.check(Identifier)
+            && self.tokens.get(self.current + 1).map(|t| t.token_type) == Some(Colon)
+            && self
+                .tokens
+                .get(self.current + 2)
+                .is_some_and(|t| matches!(t.token_type, While | For | Loop | Repeat | Do))
+        {
+            self.labeled_statement()
         } else if self.match_token(Switch) {
             self.switch_statement()
         } else {
@@ -331,7 +470,7 @@ impl Parser {
     }
     /// The `switch_statement()` method is responsible for parsing switch statements.
     ///
-    /// It handles the parsing of the switch condition, case branches, and the optional default branch. It ensures that the switch statement is syntactically correct and constructs a `SwitchStatement` object.
+    /// It handles the parsing of the switch condition, case branches, and the optional default branch. It ensures that the switch statement is syntactically correct and constructs a `Statement::Switch` object.
     ///
     /// # Return Value
     ///
@@ -353,40 +492,32 @@ impl Parser {
     fn switch_statement(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
         let condition = self.expression()?;
-        self.consume(LeftBrace, "Expected '{' after match value.", line_number)?;
+        self.consume(LeftBrace, "brace-after-switch-value", line_number)?;
         let mut case_branches: Vec<(Expression, Vec<Statement>)> = Vec::new();
         while self.match_token(Case) {
             let case_value = self.expression()?;
-            self.consume(LeftBrace, "Expected Start after case value.", line_number)?;
+            self.consume(LeftBrace, "brace-after-case-value", line_number)?;
             let mut case_body = Vec::new();
             while !self.check(RightBrace) && !self.check(Case) && !self.check(Default) {
                 let stmt = self.declaration()?;
                 case_body.push(stmt);
             }
-            self.consume(RightBrace, "Expected End after case body.", line_number)?;
+            self.consume(RightBrace, "brace-after-case-body", line_number)?;
             case_branches.push((case_value, case_body));
         }
         let mut default_branch = None;
         if self.match_token(Default) {
-            self.consume(
-                LeftBrace,
-                "Expected Start after default keyword.",
-                line_number,
-            )?;
+            self.consume(LeftBrace, "brace-after-default", line_number)?;
             let mut default_body = Vec::new();
             while !self.check(RightBrace) {
                 let stmt = self.declaration()?;
                 default_body.push(stmt);
             }
-            self.consume(RightBrace, "Expected End after default body.", line_number)?;
+            self.consume(RightBrace, "brace-after-default-body", line_number)?;
             default_branch = Some(default_body);
         }
-        self.consume(
-            RightBrace,
-            "Expected End after switch statement.",
-            line_number,
-        )?;
-        Ok(Statement::SwitchStatement {
+        self.consume(RightBrace, "brace-after-switch", line_number)?;
+        Ok(Statement::Switch {
             condition,
             case_branches,
             default_branch,
@@ -395,7 +526,7 @@ impl Parser {
     }
     /// The `return_statement()` method is responsible for parsing return statements.
     ///
-    /// It handles the parsing of the return keyword and the optional expression that follows it. It ensures that the return statement is syntactically correct and constructs a `ReturnStatement` object.
+    /// It handles the parsing of the return keyword and the optional expression that follows it. It ensures that the return statement is syntactically correct and constructs a `Statement::Return` object.
     ///
     /// # Return Value
     ///
@@ -413,18 +544,18 @@ impl Parser {
     /// let return_statement = parser.return_statement()?;
     /// ```
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn return_statement(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
-        let keyword = self.previous(1);
-        let value;
-        if !self.check(Semicolon) {
-            value = Some(self.expression()?);
+        let keyword = self.previous(1).clone();
+        // a `return` closing a block without a `;` returns nothing
+        let value = if !self.check(Semicolon) && !self.check(RightBrace) {
+            Some(self.expression()?)
         } else {
-            value = None;
-        }
-        self.consume(Semicolon, "Expected ';' after return value;", line_number)?;
-        Ok(Statement::ReturnStatement {
+            None
+        };
+        self.terminate("semicolon-after-return", line_number)?;
+        Ok(Statement::Return {
             keyword,
             value,
             line: line_number,
@@ -432,7 +563,7 @@ impl Parser {
     }
     /// The `break_statement()` method is responsible for parsing break statements.
     ///
-    /// It handles the parsing of the break keyword and ensures that the break statement is syntactically correct and constructs a `BreakStatement` object.
+    /// It handles the parsing of the break keyword and ensures that the break statement is syntactically correct and constructs a `Statement::Break` object.
     ///
     /// # Return Value
     ///
@@ -453,20 +584,83 @@ impl Parser {
     /// ### Last Updated: (v3.0.0)
     fn break_statement(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
-        let keyword = self.previous(1);
-        self.consume(
-            Semicolon,
-            "Expected Semicolon after return value",
-            line_number,
-        )?;
-        Ok(Statement::BreakStatement {
+        let keyword = self.previous(1).clone();
+        let label = if self.match_token(Identifier) {
+            Some(self.previous(1).clone())
+        } else {
+            None
+        };
+        self.terminate("semicolon-after-break", line_number)?;
+        Ok(Statement::Break {
+            keyword,
+            label,
+            line: line_number,
+        })
+    }
+    /// The `continue_statement()` method is responsible for parsing continue statements.
+    ///
+    /// The `continue` keyword can be followed by the label of the loop to continue (`continue outer;`).
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing a `Statement::Continue` if parsing is successful, or an error message if parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn continue_statement(&mut self) -> Result<Statement, String> {
+        let line_number = self.peek().line_number;
+        let keyword = self.previous(1).clone();
+        let label = if self.match_token(Identifier) {
+            Some(self.previous(1).clone())
+        } else {
+            None
+        };
+        self.terminate("semicolon-after-continue", line_number)?;
+        Ok(Statement::Continue {
             keyword,
+            label,
             line: line_number,
         })
     }
+    /// The `labeled_statement()` method is responsible for parsing labeled loops (`outer: while ... { ... }`).
+    ///
+    /// The label is attached to the loop, so `break outer;` and `continue outer;` inside nested loops can refer to it.
+    /// `for` and `repeat` loops are desugared into a block ending with a `while` loop, which gets the label.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing the labeled loop if parsing is successful, or an error message if the label isn't followed by a loop.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn labeled_statement(&mut self) -> Result<Statement, String> {
+        let label = self.advance().clone();
+        self.advance();
+        let mut statement = self.statement()?;
+        let target = match &mut statement {
+            Statement::Block {
+                statements,
+                line: _,
+            } => statements.last_mut().and_then(Rc::get_mut),
+            other => Some(other),
+        };
+        match target {
+            Some(Statement::While {
+                label: loop_label, ..
+            })
+            | Some(Statement::DoWhile {
+                label: loop_label, ..
+            }) => {
+                *loop_label = Some(label);
+                Ok(statement)
+            }
+            _ => Err(format!(
+                "Label '{}' must be followed by a loop (line {})",
+                label.lexeme, label.line_number
+            )),
+        }
+    }
     /// The `for_statement()` method is responsible for parsing for loop statements.
     ///
-    /// It handles the parsing of the for loop initialization, condition, and increment, as well as the loop body. It ensures that the for loop statement is syntactically correct and constructs a `WhileStatement` object that represents the for loop.
+    /// It handles the parsing of the for loop initialization, condition, and increment, as well as the loop body. It ensures that the for loop statement is syntactically correct and constructs a `Statement::While` object that represents the for loop.
     ///
     /// # Return Value
     ///
@@ -487,6 +681,9 @@ impl Parser {
     /// ### Last Updated: (v3.0.0)
     fn for_statement(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
+        if let Some(name) = self.for_in_variable() {
+            return self.for_in_statement(name, line_number);
+        }
         let initializer;
         if self.match_token(Semicolon) {
             initializer = None;
@@ -497,60 +694,325 @@ impl Parser {
             let expr = self.expression_statement()?;
             initializer = Some(expr);
         }
-        let condition;
-        if !self.check(Semicolon) {
-            let expr = self.expression()?;
-            condition = Some(expr);
+        let condition = if !self.check(Semicolon) {
+            Some(self.expression()?)
         } else {
-            condition = None;
-        }
-        self.consume(Semicolon, "Expected ';' after loop condition.", line_number)?;
-        let increment;
-        if !self.check(RightParen) {
-            let expr = self.expression()?;
-            increment = Some(expr);
+            None
+        };
+        self.consume(Semicolon, "semicolon-after-loop-condition", line_number)?;
+        let increment = if !self.check(RightParen) {
+            let expression = self.expression()?;
+            Some(self.postfix_update(expression)?)
         } else {
-            increment = None;
-        }
+            None
+        };
         let mut body = self.statement()?;
-        if let Some(incr) = increment {
-            body = Statement::BlockStatement {
+        let cond = match condition {
+            None => Expression::Literal {
+                id: self.get_id(),
+                value: TronType::True,
+            },
+            Some(c) => c,
+        };
+        body = Statement::While {
+            conditions: vec![cond],
+            body: Rc::new(body),
+            increment,
+            label: None,
+            line: line_number,
+        };
+        if let Some(init) = initializer {
+            body = Statement::Block {
+                statements: vec![Rc::new(init), Rc::new(body)],
+                line: line_number,
+            };
+        }
+        Ok(body)
+    }
+    // the variable of `for x in` or `for let x in`, consuming the tokens up to `in`, or `None` for a C-style loop, `in` is
+    // only a keyword there so it stays usable as a name everywhere else
+    fn for_in_variable(&mut self) -> Option<Token> {
+        let start = self.current + usize::from(self.check(Variable));
+        let name = self.tokens.get(start)?;
+        let keyword = self.tokens.get(start + 1)?;
+        if name.token_type != Identifier
+            || keyword.token_type != Identifier
+            || keyword.lexeme.as_ref() != "in"
+        {
+            return None;
+        }
+        let name = name.clone();
+        self.current = start + 2;
+        Some(name)
+    }
+    /// The `for_in_statement()` method is responsible for parsing loops over the elements of an array.
+    ///
+    /// `for x in xs { ... }` (or `for let x in xs { ... }`) runs the body once per element of `xs`, in order, with `x`
+    /// declared anew for each one. The array is evaluated once, before the first iteration. Like `for` and `repeat`,
+    /// the loop is desugared into a block with hidden variables and a `while` loop:
+    ///
+    /// ```
+    /// {
+    ///     let for items = xs;
+    ///     let for index: number = 0;
+    ///     while for index < @length(for items) {
+    ///         let x = for items[for index];
+    ///         for index = for index + 1;
+    ///         ...
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// The index moves on before the body runs, so `continue` goes to the next element. Objects are iterated with
+    /// `for entry in @entries(object)`, where every entry is a `[key, value]` array.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing the block the loop is desugared into if parsing is successful, or an error message if
+    /// parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn for_in_statement(&mut self, name: Token, line_number: usize) -> Result<Statement, String> {
+        let items = self.expression()?;
+        let body = self.statement()?;
+        let token = |token_type: TokenType, lexeme: &str| Token {
+            token_type,
+            lexeme: lexeme.into(),
+            literal: None,
+            line_number,
+            column: 0,
+        };
+        let (items_name, index_name) = (
+            token(Identifier, "for items"),
+            token(Identifier, "for index"),
+        );
+        let variable = |parser: &mut Self, name: &Token| Expression::Variable {
+            id: parser.get_id(),
+            name: name.clone(),
+        };
+        let length = Call {
+            id: self.get_id(),
+            callee: Rc::new(Expression::Variable {
+                id: self.get_id(),
+                name: token(Identifier, "@length"),
+            }),
+            paren: token(RightParen, ")"),
+            arguments: vec![variable(self, &items_name)],
+        };
+        let condition = Binary {
+            id: self.get_id(),
+            left: Rc::new(variable(self, &index_name)),
+            operator: token(Less, "<"),
+            right: Rc::new(length),
+        };
+        let element = Expression::Index {
+            id: self.get_id(),
+            object: Rc::new(variable(self, &items_name)),
+            index: Rc::new(variable(self, &index_name)),
+        };
+        let increment = Assign {
+            id: self.get_id(),
+            name: index_name.clone(),
+            value: Rc::new(Binary {
+                id: self.get_id(),
+                left: Rc::new(variable(self, &index_name)),
+                operator: token(Plus, "+"),
+                right: Rc::new(Expression::Literal {
+                    id: self.get_id(),
+                    value: TronType::Number(1.0),
+                }),
+            }),
+        };
+        let body = Statement::While {
+            increment: None,
+            label: None,
+            conditions: vec![condition],
+            body: Rc::new(Statement::Block {
                 statements: vec![
-                    Box::new(body),
-                    Box::new(Statement::ExpressionStatement {
-                        expression: incr,
+                    Rc::new(Statement::Variable {
+                        name,
+                        value_type: token(Identifier, "any"),
+                        value: element,
+                        line: line_number,
+                    }),
+                    Rc::new(Statement::Expression {
+                        expression: increment,
                         line: line_number,
                     }),
+                    Rc::new(body),
                 ],
                 line: line_number,
-            };
-        }
-        let cond;
-        match condition {
-            None => {
-                cond = Expression::Literal {
-                    id: self.get_id(),
-                    value: TronType::True,
-                }
-            }
-            Some(c) => cond = c,
-        }
-        body = Statement::WhileStatement {
-            conditions: vec![cond],
-            body: Box::new(body),
+            }),
             line: line_number,
         };
-        if let Some(init) = initializer {
-            body = Statement::BlockStatement {
-                statements: vec![Box::new(init), Box::new(body)],
+        Ok(Statement::Block {
+            statements: vec![
+                Rc::new(Statement::Variable {
+                    name: items_name,
+                    value_type: token(Identifier, "any"),
+                    value: items,
+                    line: line_number,
+                }),
+                Rc::new(Statement::Variable {
+                    name: index_name,
+                    value_type: token(Identifier, "number"),
+                    value: Expression::Literal {
+                        id: self.get_id(),
+                        value: TronType::Number(0.0),
+                    },
+                    line: line_number,
+                }),
+                Rc::new(body),
+            ],
+            line: line_number,
+        })
+    }
+    /// The `repeat_statement()` method is responsible for parsing counted repetition statements.
+    ///
+    /// `repeat 3 { ... }` runs the body the given number of times. It is desugared into a block with a hidden counter
+    /// and a `while` loop, the same way `for` loops are:
+    ///
+    /// ```
+    /// {
+    ///     let repeat count: number = 3;
+    ///     while repeat count > 0 {
+    ///         repeat count = repeat count - 1;
+    ///         ...
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// The counter name contains a space, so it can never clash with a variable of the program.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing a `Statement` object representing the repeat loop if parsing is successful, or an error message if parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn repeat_statement(&mut self) -> Result<Statement, String> {
+        let line_number = self.previous(1).line_number;
+        let times = self.expression()?;
+        let body = self.statement()?;
+        let token = |token_type: TokenType, lexeme: &str| Token {
+            token_type,
+            lexeme: lexeme.into(),
+            literal: None,
+            line_number,
+            column: 0,
+        };
+        let counter = token(Identifier, "repeat count");
+        let condition = Binary {
+            id: self.get_id(),
+            left: Rc::new(Expression::Variable {
+                id: self.get_id(),
+                name: counter.clone(),
+            }),
+            operator: token(Greater, ">"),
+            right: Rc::new(Expression::Literal {
+                id: self.get_id(),
+                value: TronType::Number(0.0),
+            }),
+        };
+        let decrement = Assign {
+            id: self.get_id(),
+            name: counter.clone(),
+            value: Rc::new(Binary {
+                id: self.get_id(),
+                left: Rc::new(Expression::Variable {
+                    id: self.get_id(),
+                    name: counter.clone(),
+                }),
+                operator: token(Minus, "-"),
+                right: Rc::new(Expression::Literal {
+                    id: self.get_id(),
+                    value: TronType::Number(1.0),
+                }),
+            }),
+        };
+        let body = Statement::While {
+            increment: None,
+            label: None,
+            conditions: vec![condition],
+            body: Rc::new(Statement::Block {
+                statements: vec![
+                    Rc::new(Statement::Expression {
+                        expression: decrement,
+                        line: line_number,
+                    }),
+                    Rc::new(body),
+                ],
                 line: line_number,
-            };
+            }),
+            line: line_number,
+        };
+        Ok(Statement::Block {
+            statements: vec![
+                Rc::new(Statement::Variable {
+                    name: counter,
+                    value_type: token(Identifier, "number"),
+                    value: times,
+                    line: line_number,
+                }),
+                Rc::new(body),
+            ],
+            line: line_number,
+        })
+    }
+    /// The `loop_statement()` method is responsible for parsing infinite loop statements.
+    ///
+    /// `loop { ... }` is the same as `while true { ... }`; the loop only ends with `break` or `return`.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing a `Statement::While` with an always-true condition if parsing is successful, or an error message if parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn loop_statement(&mut self) -> Result<Statement, String> {
+        let line_number = self.previous(1).line_number;
+        let body = self.statement()?;
+        Ok(Statement::While {
+            conditions: vec![Expression::Literal {
+                id: self.get_id(),
+                value: TronType::True,
+            }],
+            body: Rc::new(body),
+            increment: None,
+            label: None,
+            line: line_number,
+        })
+    }
+    /// The `do_while_statement()` method is responsible for parsing do-while loop statements.
+    ///
+    /// It parses the body first and then the `while` keyword with its conditions, followed by a semicolon:
+    /// `do { ... } while x < 10;`.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing a `Statement::DoWhile` if parsing is successful, or an error message if parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn do_while_statement(&mut self) -> Result<Statement, String> {
+        let line_number = self.previous(1).line_number;
+        let body = self.statement()?;
+        self.consume(While, "while-after-do-body", line_number)?;
+        let mut conditions = Vec::new();
+        loop {
+            conditions.push(self.expression()?);
+            if !self.match_token(Comma) {
+                break;
+            }
         }
-        Ok(body)
+        self.terminate("semicolon-after-do-while", line_number)?;
+        Ok(Statement::DoWhile {
+            body: Rc::new(body),
+            conditions,
+            label: None,
+            line: line_number,
+        })
     }
     /// The `while_statement()` method is responsible for parsing while loop statements.
     ///
-    /// It handles the parsing of the while loop condition and the loop body. It ensures that the while loop statement is syntactically correct and constructs a `WhileStatement` object.
+    /// It handles the parsing of the while loop condition and the loop body. It ensures that the while loop statement is syntactically correct and constructs a `Statement::While` object.
     ///
     /// # Return Value
     ///
@@ -580,15 +1042,17 @@ impl Parser {
             }
         }
         let body = self.statement()?;
-        Ok(Statement::WhileStatement {
+        Ok(Statement::While {
             conditions,
-            body: Box::new(body),
+            body: Rc::new(body),
+            increment: None,
+            label: None,
             line: line_number,
         })
     }
     /// The `if_statement()` method is responsible for parsing if statements.
     ///
-    /// It handles the parsing of the if condition, the then branch, and any optional elif branches and an else branch. It ensures that the if statement is syntactically correct and constructs an `IfStatement` object.
+    /// It handles the parsing of the if condition, the then branch, and any optional elif branches and an else branch. It ensures that the if statement is syntactically correct and constructs a `Statement::If` object.
     ///
     /// # Return Value
     ///
@@ -617,7 +1081,7 @@ impl Parser {
                 break;
             }
         }
-        let then_branch = Box::new(self.statement()?);
+        let then_branch = Rc::new(self.statement()?);
         let mut elif_branches = Vec::new();
         while self.match_token(Elif) {
             let mut elif_predicates = Vec::new();
@@ -628,15 +1092,15 @@ impl Parser {
                     break;
                 }
             }
-            let elif_stmt = Box::new(self.statement()?);
+            let elif_stmt = Rc::new(self.statement()?);
             elif_branches.push((elif_predicates, elif_stmt));
         }
         let else_branch = if self.match_token(Else) {
-            Some(Box::new(self.statement()?))
+            Some(Rc::new(self.statement()?))
         } else {
             None
         };
-        Ok(Statement::IfStatement {
+        Ok(Statement::If {
             conditions,
             then_branch,
             elif_branches,
@@ -670,17 +1134,17 @@ impl Parser {
         let mut statements = vec![];
         while !self.check(RightBrace) && !self.is_at_end() {
             let decl = self.declaration()?;
-            statements.push(Box::new(decl));
+            statements.push(Rc::new(decl));
         }
-        self.consume(RightBrace, "Expected 'end' after a block", line_number)?;
-        Ok(Statement::BlockStatement {
+        self.consume(RightBrace, "brace-after-block", line_number)?;
+        Ok(Statement::Block {
             statements,
             line: line_number,
         })
     }
     /// The `use_statement()` method is responsible for parsing use statements.
     ///
-    /// It handles the parsing of the use keyword and the expression that follows it, which typically represents the module or resource to be used. It ensures that the use statement is syntactically correct and constructs an `UseStatement` object.
+    /// It handles the parsing of the use keyword and the expression that follows it, which typically represents the module or resource to be used. It ensures that the use statement is syntactically correct and constructs a `Statement::Use` object.
     ///
     /// # Return Value
     ///
@@ -702,15 +1166,15 @@ impl Parser {
     fn use_statement(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
         let expression = self.expression()?;
-        self.consume(Semicolon, "Expected ';' after value.", line_number)?;
-        Ok(Statement::UseStatement {
+        self.terminate("semicolon-after-use", line_number)?;
+        Ok(Statement::Use {
             expression,
             line: line_number,
         })
     }
     /// The `expression_statement()` method is responsible for parsing expression statements.
     ///
-    /// It handles the parsing of expressions that are not part of a larger statement, such as standalone expressions or assignments. It ensures that the expression statement is syntactically correct and constructs an `ExpressionStatement` object.
+    /// It handles the parsing of expressions that are not part of a larger statement, such as standalone expressions or assignments. It ensures that the expression statement is syntactically correct and constructs a `Statement::Expression` object.
     ///
     /// # Return Value
     ///
@@ -728,16 +1192,70 @@ impl Parser {
     /// let expression_statement = parser.expression_statement()?;
     /// ```
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn expression_statement(&mut self) -> Result<Statement, String> {
         let line_number = self.peek().line_number;
         let expression = self.expression()?;
-        self.consume(Semicolon, "Expected ';' after expression.", line_number)?;
-        Ok(Statement::ExpressionStatement {
+        let expression = self.postfix_update(expression)?;
+        self.terminate("semicolon-after-expression", line_number)?;
+        Ok(Statement::Expression {
             expression,
             line: line_number,
         })
     }
+    /// The `postfix_update()` method is responsible for parsing postfix increments and decrements (`i++`, `i--`).
+    ///
+    /// They are only allowed as statements (and as the increment of a `for` loop), where they are turned into an
+    /// assignment that updates the variable, so `i++;` is the same as `i = i + 1;`.
+    ///
+    /// # Parameters
+    ///
+    /// - `expr`: The expression that was parsed before the `++`/`--` token.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing the `Assign` expression if a postfix operator follows a variable, or the unchanged expression otherwise.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn postfix_update(&mut self, expr: Expression) -> Result<Expression, String> {
+        if !self.match_tokens(&[Increment, Decrement]) {
+            return Ok(expr);
+        }
+        let postfix = self.previous(1).clone();
+        match expr {
+            Expression::Variable { id: _, ref name } => {
+                let (token_type, lexeme) = if postfix.token_type == Increment {
+                    (Plus, "+")
+                } else {
+                    (Minus, "-")
+                };
+                let name = name.clone();
+                Ok(Assign {
+                    id: self.get_id(),
+                    name,
+                    value: Rc::new(Binary {
+                        id: self.get_id(),
+                        left: Rc::new(expr),
+                        operator: Token {
+                            token_type,
+                            lexeme: lexeme.into(),
+                            literal: None,
+                            line_number: postfix.line_number,
+                            column: postfix.column,
+                        },
+                        right: Rc::new(Expression::Literal {
+                            id: self.get_id(),
+                            value: TronType::Number(1.0),
+                        }),
+                    }),
+                })
+            }
+            _ => {
+                self.error("E2005", postfix.line_number, vec![]);
+                Ok(expr)
+            }
+        }
+    }
     /// The `expression()` method is responsible for parsing expressions.
     ///
     /// It handles the parsing of various types of expressions, including literals, variables, binary operations, unary operations, and function calls. It recursively resolves nested expressions, ensuring that all symbols are correctly resolved within the current scope.
@@ -760,17 +1278,18 @@ impl Parser {
     ///
     /// ### Last Updated: (v3.0.0)
     fn expression(&mut self) -> Result<Expression, String> {
-        let expr = self.or()?;
+        let expr = self.compose()?;
         if self.match_token(Equal) {
+            let line = self.previous(1).line_number;
             let value = self.expression()?;
             match expr {
                 Expression::Variable { id: _, name } => Ok(Assign {
                     id: self.get_id(),
                     name,
-                    value: Box::from(value),
+                    value: Rc::from(value),
                 }),
                 _ => {
-                    TronError::throw("E2007", self.current, vec![]);
+                    self.error("E2005", line, vec![]);
                     Ok(Expression::Literal {
                         id: self.get_id() * 19,
                         value: TronType::Null,
@@ -781,6 +1300,55 @@ impl Parser {
             Ok(expr)
         }
     }
+    /// The `compose()` method is responsible for parsing function composition expressions.
+    ///
+    /// It handles the parsing of expressions with the `>>` operator, which binds looser than every other binary operator,
+    /// and constructs a `Binary` expression object. `f >> g` is a function that calls `f` with its arguments and then `g`
+    /// with the result, and a chain like `trim >> upper >> len` is read from left to right.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing an `Expression` object representing the composition if parsing is successful, or an error message if parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn compose(&mut self) -> Result<Expression, String> {
+        let mut expr = self.coalesce()?;
+        while self.match_token(GreaterGreater) {
+            let operator = self.previous(1).clone();
+            let right = self.coalesce()?;
+            expr = Binary {
+                id: self.get_id(),
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            };
+        }
+        Ok(expr)
+    }
+    /// The `coalesce()` method is responsible for parsing null-coalescing expressions.
+    ///
+    /// It handles the parsing of expressions with the `??` operator, which has the lowest precedence of all binary operators,
+    /// and constructs a `Logical` expression object so the right side is only evaluated if the left side is `null`.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing an `Expression` object representing the null-coalescing expression if parsing is successful, or an error message if parsing fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn coalesce(&mut self) -> Result<Expression, String> {
+        let mut expr = self.or()?;
+        while self.match_token(QuestionQuestion) {
+            let operator = self.previous(1).clone();
+            let right = self.or()?;
+            expr = Logical {
+                id: self.get_id(),
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            };
+        }
+        Ok(expr)
+    }
     /// The `or()` method is responsible for parsing logical OR expressions.
     ///
     /// It handles the parsing of expressions with the OR operator and constructs a `Logical` expression object.
@@ -797,13 +1365,13 @@ impl Parser {
     fn or(&mut self) -> Result<Expression, String> {
         let mut expr = self.nor()?;
         while self.match_token(Or) {
-            let operator = self.previous(1);
-            let right = self.and()?;
+            let operator = self.previous(1).clone();
+            let right = self.nor()?;
             expr = Logical {
                 id: self.get_id(),
-                left: Box::new(expr),
+                left: Rc::new(expr),
                 operator,
-                right: Box::new(right),
+                right: Rc::new(right),
             };
         }
         Ok(expr)
@@ -824,13 +1392,13 @@ impl Parser {
     fn nor(&mut self) -> Result<Expression, String> {
         let mut expr = self.xor()?;
         while self.match_token(Nor) {
-            let operator = self.previous(1);
-            let right = self.and()?;
+            let operator = self.previous(1).clone();
+            let right = self.xor()?;
             expr = Logical {
                 id: self.get_id(),
-                left: Box::new(expr),
+                left: Rc::new(expr),
                 operator,
-                right: Box::new(right),
+                right: Rc::new(right),
             };
         }
         Ok(expr)
@@ -851,13 +1419,13 @@ impl Parser {
     fn xor(&mut self) -> Result<Expression, String> {
         let mut expr = self.and()?;
         while self.match_token(Xor) {
-            let operator = self.previous(1);
+            let operator = self.previous(1).clone();
             let right = self.and()?;
             expr = Logical {
                 id: self.get_id(),
-                left: Box::new(expr),
+                left: Rc::new(expr),
                 operator,
-                right: Box::new(right),
+                right: Rc::new(right),
             };
         }
         Ok(expr)
@@ -878,13 +1446,13 @@ impl Parser {
     fn and(&mut self) -> Result<Expression, String> {
         let mut expr = self.equality()?;
         while self.match_token(And) {
-            let operator = self.previous(1);
+            let operator = self.previous(1).clone();
             let right = self.equality()?;
             expr = Logical {
                 id: self.get_id(),
-                left: Box::new(expr),
+                left: Rc::new(expr),
                 operator,
-                right: Box::new(right),
+                right: Rc::new(right),
             };
         }
         Ok(expr)
@@ -905,13 +1473,13 @@ impl Parser {
     fn equality(&mut self) -> Result<Expression, String> {
         let mut expr = self.comparasion()?;
         while self.match_tokens(&[BangEqual, EqualEqual]) {
-            let operator = self.previous(1);
+            let operator = self.previous(1).clone();
             let rhs = self.comparasion()?;
             expr = Binary {
                 id: self.get_id(),
-                left: Box::from(expr),
+                left: Rc::from(expr),
                 operator,
-                right: Box::from(rhs),
+                right: Rc::from(rhs),
             };
         }
         Ok(expr)
@@ -928,19 +1496,36 @@ impl Parser {
     ///
     /// The `comparasion()` method is called internally by the `Parser` to process comparasion expressions within the code.
     ///
+    /// Chained comparisons like `0 < x < 10` are parsed into a single `Comparison` expression, which is true
+    /// only if every pair is true. Every operand is evaluated at most once and the chain stops at the first false pair.
+    ///
+    /// ### Last Updated: (v3.2.0)
     fn comparasion(&mut self) -> Result<Expression, String> {
-        let mut expr = self.term()?;
+        let expr = self.term()?;
+        let mut operands = vec![expr];
+        let mut operators = vec![];
         while self.match_tokens(&[Greater, GreaterEqual, Less, LessEqual]) {
-            let op = self.previous(1);
-            let rhs = self.term()?;
-            expr = Binary {
+            operators.push(self.previous(1).clone());
+            operands.push(self.term()?);
+        }
+        match operators.len() {
+            0 => Ok(operands.remove(0)),
+            1 => {
+                let rhs = operands.pop().unwrap();
+                let lhs = operands.pop().unwrap();
+                Ok(Binary {
+                    id: self.get_id(),
+                    left: Rc::from(lhs),
+                    operator: operators.remove(0),
+                    right: Rc::from(rhs),
+                })
+            }
+            _ => Ok(Expression::Comparison {
                 id: self.get_id(),
-                left: Box::from(expr),
-                operator: op,
-                right: Box::from(rhs),
-            };
+                operands,
+                operators,
+            }),
         }
-        Ok(expr)
     }
     /// The `term()` method is responsible for parsing term expressions.
     ///
@@ -958,20 +1543,20 @@ impl Parser {
     fn term(&mut self) -> Result<Expression, String> {
         let mut expr = self.factor()?;
         while self.match_tokens(&[Minus, Plus]) {
-            let op = self.previous(1);
+            let op = self.previous(1).clone();
             let rhs = self.factor()?;
             expr = Binary {
                 id: self.get_id(),
-                left: Box::from(expr),
+                left: Rc::from(expr),
                 operator: op,
-                right: Box::from(rhs),
+                right: Rc::from(rhs),
             };
         }
         Ok(expr)
     }
     /// The `factor()` method is responsible for parsing factor expressions.
     ///
-    /// It handles the parsing of expressions with multiplication, division, and exponentiation operators. It constructs a `Binary` expression object representing the factor expression.
+    /// It handles the parsing of expressions with multiplication, division, remainder and exponentiation operators. It constructs a `Binary` expression object representing the factor expression.
     ///
     /// # Return Value
     ///
@@ -981,17 +1566,17 @@ impl Parser {
     ///
     /// The `factor()` method is called internally by the `Parser` to process factor expressions within the code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn factor(&mut self) -> Result<Expression, String> {
         let mut expr = self.unary()?;
-        while self.match_tokens(&[Slash, Star, Power]) {
-            let op = self.previous(1);
+        while self.match_tokens(&[Slash, Star, Power, Percent]) {
+            let op = self.previous(1).clone();
             let rhs = self.unary()?;
             expr = Binary {
                 id: self.get_id(),
-                left: Box::from(expr),
+                left: Rc::from(expr),
                 operator: op,
-                right: Box::from(rhs),
+                right: Rc::from(rhs),
             };
         }
         Ok(expr)
@@ -1008,15 +1593,22 @@ impl Parser {
     ///
     /// The `unary()` method is called internally by the `Parser` to process unary expressions within the code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// `%` is only the remainder operator, a `%` before an operand is reported as `E2006` and left out.
+    ///
+    /// ### Last Updated: (v3.2.0)
     fn unary(&mut self) -> Result<Expression, String> {
-        if self.match_tokens(&[Bang, Minus, Increment, Decrement, Percent]) {
-            let op = self.previous(1);
+        if self.match_token(Percent) {
+            let percent = self.previous(1);
+            self.error_at("E2006", percent.line_number, percent.column, vec![]);
+            return self.unary();
+        }
+        if self.match_tokens(&[Bang, Minus, Increment, Decrement]) {
+            let op = self.previous(1).clone();
             let rhs = self.unary()?;
             Ok(Unary {
                 id: self.get_id(),
                 operator: op,
-                right: Box::from(rhs),
+                right: Rc::from(rhs),
             })
         } else {
             self.call()
@@ -1040,6 +1632,14 @@ impl Parser {
         loop {
             if self.match_token(LeftParen) {
                 expr = self.finish_call(expr)?;
+            } else if self.match_token(QuestionDot) {
+                let line_number = self.previous(1).line_number;
+                let key = self.consume(Identifier, "key-after-question-dot", line_number)?;
+                expr = Expression::OptionalGet {
+                    id: self.get_id(),
+                    object: Rc::new(expr),
+                    key,
+                };
             } else {
                 break;
             }
@@ -1058,37 +1658,51 @@ impl Parser {
     ///
     /// The `primary()` method is called internally by the `Parser` to process primary expressions within the code.
     ///
-    /// ### Last Updated: (v3.1.0)
+    /// ### Last Updated: (v3.2.0)
     fn primary(&mut self) -> Result<Expression, String> {
         let line_number = self.peek().line_number;
-        let token = self.peek();
         let result;
-        let var_name: Token = Token {
-            token_type: Identifier,
-            lexeme: self.previous(2).lexeme,
-            line_number,
-            literal: None,
-        };
-        match token.token_type {
+        match self.peek().token_type {
             Function => {
+                // `let f = fn ...` and `let f: function = fn ...` name the function after its variable, so it can
+                // call itself, while a function passed as an argument, like `@memo(fn ...)`, is anonymous and
+                // doesn't define anything
+                let variable = match self.previous(3).token_type {
+                    Colon => self.previous(4),
+                    _ => self.previous(2),
+                };
+                let named =
+                    self.previous(1).token_type == Equal && variable.token_type == Identifier;
+                let var_name: Token = Token {
+                    token_type: if named { Identifier } else { Function },
+                    lexeme: if named {
+                        variable.lexeme.clone()
+                    } else {
+                        "fn".into()
+                    },
+                    line_number,
+                    column: 0,
+                    literal: None,
+                };
                 result = self.parse_function_expr(var_name)?;
             }
             Identifier => {
-                self.advance();
+                let token = self.advance().clone();
                 let mut expr = Expression::Variable {
                     id: self.get_id(),
-                    name: self.previous(1),
+                    name: token.clone(),
                 };
 
                 if self.match_token(LeftBracket) {
                     let index = self.expression()?;
-                    self.consume(RightBracket, "Expected ']' after index", line_number)?;
-                    expr = Expression::Array {
+                    self.consume(RightBracket, "bracket-after-index", line_number)?;
+                    expr = Expression::Index {
                         id: self.get_id(),
-                        elements: vec![Box::new(expr), Box::new(index)],
+                        object: Rc::new(expr),
+                        index: Rc::new(index),
                     };
                 } else if self.match_token(Dot) {
-                    let key = self.consume(Identifier, "Expected key after '.'", line_number)?;
+                    let key = self.consume(Identifier, "key-after-dot", line_number)?;
                     expr = Expression::ObjectCall {
                         id: self.get_id(),
                         name: token,
@@ -1100,14 +1714,14 @@ impl Parser {
             LeftParen => {
                 self.advance();
                 let expr = self.expression()?;
-                self.consume(RightParen, "Expected ')' after expression", line_number)?;
+                self.consume(RightParen, "paren-after-expression", line_number)?;
                 result = Expression::Grouping {
                     id: self.get_id(),
-                    expression: Box::new(expr),
+                    expression: Rc::new(expr),
                 };
             }
             False | True | Null | Number | StringLit => {
-                self.advance();
+                let token = self.advance().clone();
                 result = Expression::Literal {
                     id: self.get_id(),
                     value: TronType::from_token(token),
@@ -1117,9 +1731,18 @@ impl Parser {
                 return self.parse_array();
             }
             TokenType::LeftBrace => return self.parse_object(),
-
+            Backtick => return self.parse_template(),
+            _ if self.out_of_input() => return Err(INCOMPLETE_INPUT.to_string()),
             _ => {
-                TronError::throw("E2003", self.current, vec![token.token_type.to_string()]);
+                self.error(
+                    "E2003",
+                    self.current,
+                    vec![self.peek().token_type.to_string()],
+                );
+                // skipped, or the parse would stop on the same token forever
+                if !self.is_at_end() {
+                    self.advance();
+                }
                 result = Expression::Literal {
                     id: self.get_id() * 23,
                     value: TronType::Null,
@@ -1131,6 +1754,7 @@ impl Parser {
     /// The `finish_call()` method is responsible for parsing the arguments of a function call.
     ///
     /// It handles the parsing of expressions with function calls and constructs a `Call` expression object representing the function call expression.
+    /// The last argument can be followed by a comma, like the last element of an array, so `f(a, b,)` is a call with two arguments.
     ///
     /// # Return Value
     ///
@@ -1140,25 +1764,26 @@ impl Parser {
     ///
     /// The `finish_call()` method is called internally by the `Parser` to process function call expressions within the code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn finish_call(&mut self, callee: Expression) -> Result<Expression, String> {
         let line_number = self.peek().line_number;
         let mut arguments = vec![];
         if !self.check(RightParen) {
             loop {
-                let arg = self.expression()?;
-                arguments.push(arg);
-                if arguments.len() >= 32 {
-                    TronError::throw("E2004", line_number, vec![]);
-                } else if !self.match_token(Comma) {
+                // checked before the argument is added, so exactly `MAX_ARGUMENTS` arguments are fine
+                if arguments.len() == MAX_ARGUMENTS {
+                    self.error("E2004", line_number, vec![]);
+                }
+                arguments.push(self.expression()?);
+                if !self.match_token(Comma) || self.check(RightParen) {
                     break;
                 }
             }
         }
-        let paren = self.consume(RightParen, "Expected ')' after arguments.", line_number)?;
+        let paren = self.consume(RightParen, "paren-after-arguments", line_number)?;
         Ok(Call {
             id: self.get_id(),
-            callee: Box::new(callee),
+            callee: Rc::new(callee),
             paren,
             arguments,
         })
@@ -1183,7 +1808,7 @@ impl Parser {
         self.advance();
         while !self.check(TokenType::RightBracket) && !self.is_at_end() {
             let element = self.expression()?;
-            elements.push(Box::new(element));
+            elements.push(Rc::new(element));
 
             if !self.match_token(TokenType::Comma) {
                 break;
@@ -1191,7 +1816,7 @@ impl Parser {
         }
         self.consume(
             TokenType::RightBracket,
-            "Expect ']' after array elements.",
+            "bracket-after-elements",
             line_number,
         )?;
 
@@ -1219,12 +1844,8 @@ impl Parser {
         let object_id = self.get_id();
         self.advance();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            let key = self.consume(
-                Identifier,
-                "Expected identifier for object key",
-                line_number,
-            )?;
-            self.consume(Colon, "Expected ':' after object key", line_number)?;
+            let key = self.consume(Identifier, "object-key", line_number)?;
+            self.consume(Colon, "colon-after-object-key", line_number)?;
             let value = self.expression()?;
             properties.push((key, value));
             if !self.match_token(Comma) {
@@ -1233,7 +1854,7 @@ impl Parser {
         }
         self.consume(
             TokenType::RightBrace,
-            "Expected '}' after object fields",
+            "brace-after-object-fields",
             line_number,
         )?;
         Ok(Expression::Object {
@@ -1241,48 +1862,84 @@ impl Parser {
             properties,
         })
     }
+    /// The `parse_template()` method is responsible for parsing template literals.
+    ///
+    /// It handles the token group emitted by the scanner for a backtick string: text segments become string literals and
+    /// every `${...}` group is parsed as a regular expression. It constructs a `Template` expression object whose parts are
+    /// concatenated at runtime.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing an `Expression` object representing the template literal if parsing is successful, or an error message if parsing fails.
+    ///
+    /// # Usage
+    ///
+    /// The `parse_template()` method is called internally by the `Parser` to process template literals within the code.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn parse_template(&mut self) -> Result<Expression, String> {
+        let line_number = self.peek().line_number;
+        let template_id = self.get_id();
+        self.advance();
+        let mut parts = Vec::new();
+        while !self.check(Backtick) && !self.is_at_end() {
+            if self.match_token(DollarBrace) {
+                let expr = self.expression()?;
+                self.consume(RightBrace, "brace-after-interpolation", line_number)?;
+                parts.push(expr);
+            } else {
+                let segment = self.advance().clone();
+                if segment.token_type != StringLit {
+                    self.error("E2003", line_number, vec![segment.token_type.to_string()]);
+                    continue;
+                }
+                parts.push(Expression::Literal {
+                    id: self.get_id(),
+                    value: TronType::from_token(segment),
+                });
+            }
+        }
+        self.consume(Backtick, "backtick-after-template", line_number)?;
+        Ok(Expression::Template {
+            id: template_id,
+            parts,
+        })
+    }
 
     fn parse_function_expr(&mut self, var_name: Token) -> Result<Expression, String> {
         let line_number = self.peek().line_number;
-        self.consume(Function, "", line_number)?;
-        self.consume(
-            LeftParen,
-            &format!("expected '(' after function"),
-            line_number,
-        )?;
+        self.consume(Function, "fn", line_number)?;
+        self.consume(LeftParen, "paren-after-fn", line_number)?;
 
         let mut params = Vec::new();
         if !self.check(RightParen) {
             loop {
-                if params.len() >= 32 {
-                    TronError::throw("E2004", self.current, vec![]);
+                if params.len() == MAX_ARGUMENTS {
+                    self.error("E2004", line_number, vec![]);
                 }
-                let param_name =
-                    self.consume(Identifier, "expected parameter name", line_number)?;
-                self.consume(Colon, "expected `:` after parameter name", line_number)?;
-                let param_type = self.consume(Identifier, "expected type after ':'", line_number);
+                let param_name = self.consume(Identifier, "parameter-name", line_number)?;
+                let param_type = self.type_annotation(line_number)?;
 
-                params.push((param_name, param_type?));
-                if !self.match_token(Comma) {
+                params.push((param_name, param_type));
+                // a trailing comma is allowed before the `)`
+                if !self.match_token(Comma) || self.check(RightParen) {
                     break;
                 }
             }
         }
-        self.consume(RightParen, "expected ')' after parameters.", line_number)?;
+        self.consume(RightParen, "paren-after-parameters", line_number)?;
 
-        self.consume(Colon, "expected `:` before function body", line_number)?;
+        let output_type = self.type_annotation(line_number)?;
 
-        let output_type = self.consume(Identifier, "expected type after `:`", line_number)?;
-
-        self.consume(LeftBrace, "Expected '{' before function body.", line_number)?;
+        self.consume(LeftBrace, "brace-before-function-body", line_number)?;
 
         let body = match self.block_statement()? {
-            Statement::BlockStatement {
+            Statement::Block {
                 statements,
                 line: _,
             } => statements,
             _ => {
-                TronError::throw("E2002", self.current, vec![]);
+                self.error("E2002", self.previous(1).line_number, vec![]);
                 vec![]
             }
         };
@@ -1290,8 +1947,8 @@ impl Parser {
         Ok(Expression::Function {
             id: self.get_id(),
             name: var_name,
-            params,
-            body,
+            params: params.into(),
+            body: body.into(),
             output_type,
         })
     }
@@ -1302,7 +1959,8 @@ impl Parser {
     /// # Parameters
     ///
     /// - `token_type`: The type of token expected.
-    /// - `msg`: The error message to return if the token does not match the expected type.
+    /// - `expected`: The name of the message reported with `E2003` if the token does not match the expected type, see
+    ///   `expected_message()`.
     ///
     /// # Return Value
     ///
@@ -1312,16 +1970,54 @@ impl Parser {
     ///
     /// The `consume()` method is called internally by the `Parser` to ensure that the token stream matches the expected syntax.
     ///
-    /// ### Last Updated: (v3.0.0)
-    fn consume(&mut self, token_type: TokenType, msg: &str, line: usize) -> Result<Token, String> {
-        let token = self.peek();
-        if token.token_type == token_type {
-            self.advance();
-            let token = self.previous(1);
-            return Ok(token);
+    /// ### Last Updated: (v3.2.0)
+    fn consume(
+        &mut self,
+        token_type: TokenType,
+        expected: &str,
+        line: usize,
+    ) -> Result<Token, String> {
+        if self.check(token_type) {
+            return Ok(self.advance().clone());
+        }
+        if self.out_of_input() && token_type == Semicolon {
+            return Ok(self.peek().clone());
+        }
+        if self.out_of_input() {
+            return Err(INCOMPLETE_INPUT.to_string());
+        }
+        self.error("E2003", line, vec![expected_message(expected)]);
+        if let Some((hint, args)) = hints::missing_token(token_type, &self.tokens, self.current) {
+            hints::show(hint, args);
+        }
+        Ok(self.peek().clone())
+    }
+    /// The `terminate()` method ends a statement, with a `;` or without one where the statement can't go on.
+    ///
+    /// The `;` can be left out at the end of a line, before the `}` closing a block and at the end of the source, so
+    /// `let x = 1` on a line of its own is a complete statement. An expression that continues on the next line, like
+    /// `let total = a` followed by `+ b;`, is still read as a single statement, since the `;` is only looked for once
+    /// the expression can't continue. Anywhere else a missing `;` is reported like `consume()` does. In strict mode the
+    /// `;` is never optional.
+    ///
+    /// # Parameters
+    ///
+    /// - `expected`: The name of the message reported if the statement isn't terminated, see `expected_message()`.
+    /// - `line`: The line number of the statement, for the error.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn terminate(&mut self, expected: &str, line: usize) -> Result<(), String> {
+        if self.match_token(Semicolon) {
+            return Ok(());
+        }
+        if self.strict {
+            return self.consume(Semicolon, expected, line).map(|_| ());
         }
-        TronError::throw("E2003", line, vec![msg.to_string()]);
-        Ok(token)
+        let line_break = self.peek().line_number > self.previous(1).line_number;
+        if line_break || self.check(RightBrace) || self.is_at_end() {
+            return Ok(());
+        }
+        self.consume(Semicolon, expected, line).map(|_| ())
     }
     /// The `check()` method is used to check if the current token is of a specific type without consuming it.
     ///
@@ -1337,8 +2033,8 @@ impl Parser {
     ///
     /// The `check()` method is called internally by the `Parser` to look ahead at the next token without advancing the token stream.
     ///
-    /// ### Last Updated: (v3.0.0)
-    fn check(&mut self, typ: TokenType) -> bool {
+    /// ### Last Updated: (v3.2.0)
+    fn check(&self, typ: TokenType) -> bool {
         self.peek().token_type == typ
     }
     /// The `match_token()` method is used to check if the current token matches a specific type and consumes it if it does.
@@ -1391,12 +2087,16 @@ impl Parser {
     }
     /// The `advance()` method is used to consume the current token and move to the next one in the token stream.
     ///
+    /// # Return Value
+    ///
+    /// A reference to the consumed token. Callers that keep the token in the AST clone it themselves.
+    ///
     /// # Usage
     ///
     /// The `advance()` method is called internally by the `Parser` to move forward in the token stream.
     ///
-    /// ### Last Updated: (v3.0.0)
-    fn advance(&mut self) -> Token {
+    /// ### Last Updated: (v3.2.0)
+    fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
         }
@@ -1412,32 +2112,25 @@ impl Parser {
     ///
     /// The `peek()` method is called internally by the `Parser` to inspect the next token without advancing the token stream.
     ///
-    /// ### Last Updated: (v3.0.0)
-    fn peek(&mut self) -> Token {
-        self.tokens[self.current].clone()
+    /// ### Last Updated: (v3.2.0)
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
     }
     /// The `previous()` method is used to look at the previously consumed token.
     ///
     /// # Return Value
     ///
-    /// A reference to the previously consumed token.
+    /// A reference to the previously consumed token, or to the trailing `Eof` token when stepping back past the start of the stream.
     ///
     /// # Usage
     ///
     /// The `previous()` method is called internally by the `Parser` to inspect the last token that was consumed.
     ///
-    /// ### Last Updated: (v3.0.0)
-
-    fn previous(&mut self, steps_back: usize) -> Token {
-        if self.current < steps_back {
-            Token {
-                token_type: Eof,
-                lexeme: String::new(),
-                line_number: 0,
-                literal: None,
-            }
-        } else {
-            self.tokens[self.current - steps_back].clone()
+    /// ### Last Updated: (v3.2.0)
+    fn previous(&self, steps_back: usize) -> &Token {
+        match self.current.checked_sub(steps_back) {
+            Some(index) => &self.tokens[index],
+            None => &self.tokens[self.tokens.len() - 1],
         }
     }
     /// The `is_at_end()` method is used to check if the parser has reached the end of the token stream.
@@ -1450,9 +2143,290 @@ impl Parser {
     ///
     /// The `is_at_end()` method is called internally by the `Parser` to determine if there are more tokens to process.
     ///
-    /// ### Last Updated: (v3.0.0)
-
-    fn is_at_end(&mut self) -> bool {
+    /// ### Last Updated: (v3.2.0)
+    fn is_at_end(&self) -> bool {
         self.peek().token_type == Eof
     }
+    // reports an error and counts it, so `parse()` fails once the rest of the tokens are parsed
+    fn error(&mut self, code: &str, line: usize, args: Vec<String>) {
+        self.errors.push(line);
+        TronError::throw(code, line, args);
+    }
+    fn error_at(&mut self, code: &str, line: usize, column: usize, args: Vec<String>) {
+        self.errors.push(line);
+        TronError::throw_at(code, line, column, args);
+    }
+}
+
+/// Returns the message of a token `consume()` or `terminate()` expected, by its name, in the language of the diagnostics.
+///
+/// The message is the argument of `E2003`, and a message catalog translates it as `expected.<name>`, like
+/// `expected.semicolon-after-expression`, see `locale::select()`. A name without a message is reported as it is.
+///
+/// ### Last Updated: (v3.2.0)
+fn expected_message(name: &str) -> String {
+    let message = match name {
+        "function-name" => "expected function name",
+        "paren-after-function-name" => "expected '(' after function name",
+        "parameter-name" => "expected parameter name",
+        "paren-after-parameters" => "expected ')' after parameters.",
+        "semicolon-after-function-expression" => "expected ';' after function body expression.",
+        "brace-before-function-body" => "Expected '{' before function body.",
+        "variable-name" => "Expected variable name",
+        "equal-after-variable-name" => "Expected '=' after variable name",
+        "semicolon-after-declaration" => "Expected ';' after variable declaration",
+        "brace-after-switch-value" => "Expected '{' after match value.",
+        "brace-after-case-value" => "Expected Start after case value.",
+        "brace-after-case-body" => "Expected End after case body.",
+        "brace-after-default" => "Expected Start after default keyword.",
+        "brace-after-default-body" => "Expected End after default body.",
+        "brace-after-switch" => "Expected End after switch statement.",
+        "semicolon-after-return" => "Expected ';' after return value;",
+        "semicolon-after-break" => "Expected Semicolon after return value",
+        "semicolon-after-continue" => "Expected ';' after continue",
+        "semicolon-after-loop-condition" => "Expected ';' after loop condition.",
+        "while-after-do-body" => "Expected 'while' after do body",
+        "semicolon-after-do-while" => "Expected ';' after do-while condition",
+        "brace-after-block" => "Expected '}' after a block",
+        "semicolon-after-use" => "Expected ';' after value.",
+        "semicolon-after-expression" => "Expected ';' after expression.",
+        "key-after-question-dot" => "Expected key after '?.'",
+        "bracket-after-index" => "Expected ']' after index",
+        "key-after-dot" => "Expected key after '.'",
+        "paren-after-expression" => "Expected ')' after expression",
+        "paren-after-arguments" => "Expected ')' after arguments.",
+        "bracket-after-elements" => "Expect ']' after array elements.",
+        "object-key" => "Expected identifier for object key",
+        "colon-after-object-key" => "Expected ':' after object key",
+        "brace-after-object-fields" => "Expected '}' after object fields",
+        "brace-after-interpolation" => "Expected '}' after template expression",
+        "backtick-after-template" => "Expected '`' after template literal",
+        "paren-after-fn" => "expected '(' after function",
+        "fn" => "expected fn",
+        _ => name,
+    };
+    locale::translate(&format!("expected.{}", name), &[]).unwrap_or(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    fn parse(source: &str) -> Result<Vec<Statement>, String> {
+        Parser::new(Scanner::new(source).scan_tokens().unwrap()).parse()
+    }
+
+    // `p0, p1, ...` with `count` names
+    fn names(count: usize) -> String {
+        (0..count)
+            .map(|index| format!("p{}", index))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // `0, 1, ...` with `count` numbers
+    fn numbers(count: usize) -> String {
+        (0..count)
+            .map(|index| index.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[test]
+    fn a_call_takes_up_to_255_arguments() {
+        assert!(parse(&format!("f({});", numbers(MAX_ARGUMENTS))).is_ok());
+        assert!(parse(&format!("f({});", numbers(MAX_ARGUMENTS + 1))).is_err());
+    }
+
+    #[test]
+    fn a_function_takes_up_to_255_parameters() {
+        assert!(parse(&format!("fn f({}) {{ return p0; }}", names(MAX_ARGUMENTS))).is_ok());
+        assert!(parse(&format!(
+            "fn f({}) {{ return p0; }}",
+            names(MAX_ARGUMENTS + 1)
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn an_anonymous_function_takes_up_to_255_parameters() {
+        assert!(parse(&format!(
+            "let f = fn({}) {{ return p0; }};",
+            names(MAX_ARGUMENTS)
+        ))
+        .is_ok());
+        assert!(parse(&format!(
+            "let f = fn({}) {{ return p0; }};",
+            names(MAX_ARGUMENTS + 1)
+        ))
+        .is_err());
+    }
+
+    // the branches of the `if` statement a source starts with: the `then` branch, the number of `elif`s and the `else`
+    fn branches(source: &str) -> (Rc<Statement>, usize, Option<Rc<Statement>>) {
+        match parse(source).unwrap().remove(0) {
+            Statement::If {
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => (then_branch, elif_branches.len(), else_branch),
+            statement => panic!("expected an if statement, found {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn a_dangling_else_belongs_to_the_closest_if() {
+        let (then_branch, elifs, else_branch) = branches("if a if b x(); else y();");
+        assert_eq!((elifs, else_branch.is_none()), (0, true));
+        match then_branch.as_ref() {
+            Statement::If { else_branch, .. } => assert!(else_branch.is_some()),
+            statement => panic!("expected the inner if, found {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn braces_give_the_else_to_the_outer_if() {
+        let (then_branch, _, else_branch) = branches("if a { if b x(); } else y();");
+        assert!(else_branch.is_some());
+        assert!(matches!(then_branch.as_ref(), Statement::Block { .. }));
+    }
+
+    #[test]
+    fn elif_chains_without_nesting() {
+        let (then_branch, elifs, else_branch) =
+            branches("if a x(); elif b y(); elif c z(); else w();");
+        assert_eq!(elifs, 2);
+        assert!(else_branch.is_some());
+        assert!(matches!(then_branch.as_ref(), Statement::Expression { .. }));
+    }
+
+    #[test]
+    fn an_elif_after_a_nested_if_belongs_to_it() {
+        let (then_branch, elifs, _) = branches("if a if b x(); elif c y();");
+        assert_eq!(elifs, 0);
+        match then_branch.as_ref() {
+            Statement::If { elif_branches, .. } => assert_eq!(elif_branches.len(), 1),
+            statement => panic!("expected the inner if, found {:?}", statement),
+        }
+    }
+
+    // the expression with its binary operations in parentheses, like `((a % b) * c)`
+    fn grouped(source: &str) -> String {
+        fn group(expression: &Expression) -> String {
+            match expression {
+                Expression::Binary {
+                    left,
+                    operator,
+                    right,
+                    ..
+                }
+                | Expression::Logical {
+                    left,
+                    operator,
+                    right,
+                    ..
+                } => format!("({} {} {})", group(left), operator.lexeme, group(right)),
+                Expression::Comparison {
+                    operands,
+                    operators,
+                    ..
+                } => {
+                    let mut chain = group(&operands[0]);
+                    for (operator, operand) in operators.iter().zip(&operands[1..]) {
+                        chain.push_str(&format!(" {} {}", operator.lexeme, group(operand)));
+                    }
+                    format!("({})", chain)
+                }
+                Expression::Variable { name, .. } => name.lexeme.to_string(),
+                expression => panic!("unexpected expression {:?}", expression),
+            }
+        }
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        group(&Parser::new(tokens).parse_expression().unwrap())
+    }
+
+    #[test]
+    fn remainder_has_the_precedence_of_multiplication() {
+        assert_eq!(grouped("a % b * c"), "((a % b) * c)");
+        assert_eq!(grouped("a * b % c"), "((a * b) % c)");
+        assert_eq!(grouped("a / b % c / d"), "(((a / b) % c) / d)");
+        assert_eq!(grouped("a + b % c - d"), "((a + (b % c)) - d)");
+    }
+
+    #[test]
+    fn a_prefix_remainder_is_an_error() {
+        assert!(parse("let x = %a;").is_err());
+        assert!(parse("let x = a * %b;").is_err());
+        assert!(parse("let x = a % b;").is_ok());
+    }
+
+    #[test]
+    fn an_alias_parses_as_its_keyword_and_keeps_its_word() {
+        crate::scanner::set_keyword_aliases(&[("si".to_string(), "if".to_string())].into());
+        let statements = parse("si true { 1; }").unwrap();
+        let Statement::If { conditions, .. } = &statements[0] else {
+            panic!("expected an if statement, got {:?}", statements[0]);
+        };
+        assert_eq!(conditions.len(), 1);
+        let tokens = Scanner::new("si").scan_tokens().unwrap();
+        assert_eq!(tokens[0].token_type, TokenType::If);
+        assert_eq!(&*tokens[0].lexeme, "si");
+    }
+
+    // parses a source the way a program is, strict when it has a `#strict` line
+    fn parse_program(source: &str) -> Result<Vec<Statement>, String> {
+        let (tokens, directives) = Scanner::new(source).scan_tokens_with_directives().unwrap();
+        Parser::new(tokens)
+            .with_strict(crate::scanner::is_strict(&directives))
+            .parse()
+    }
+
+    #[test]
+    fn a_line_break_ends_a_statement_without_strict_mode() {
+        assert!(parse_program("let x = 1\nlet y = x + 1\n@print(y)").is_ok());
+        assert!(parse_program("if true { @print(1) }").is_ok());
+        // an expression continuing on the next line is still one statement
+        assert_eq!(parse_program("let total = 1\n+ 2;").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn strict_mode_requires_every_semicolon() {
+        assert!(parse_program("#strict\nlet x = 1\nlet y = 2;").is_err());
+        assert!(parse_program("#strict\nif true { @print(1) }").is_err());
+        assert!(parse_program("#strict\n@print(1)").is_err());
+        assert!(parse_program("#strict\nlet x = 1;\nif true { @print(x); }").is_ok());
+    }
+
+    // the lines of the errors reported while parsing `source`
+    fn error_lines(source: &str) -> Vec<usize> {
+        let mut parser = Parser::new(Scanner::new(source).scan_tokens().unwrap());
+        let _ = parser.parse();
+        parser.errors
+    }
+
+    #[test]
+    fn errors_are_reported_at_the_line_of_their_token() {
+        assert_eq!(error_lines("let a = 1;\nlet b = 2;\n1 = a;"), vec![3]);
+        assert_eq!(error_lines("let a = 1;\n\nlet = 2;"), vec![3]);
+    }
+
+    #[test]
+    fn chained_comparisons_parse_into_one_chain() {
+        assert_eq!(grouped("a < b"), "(a < b)");
+        assert_eq!(grouped("a < b <= c"), "(a < b <= c)");
+        assert_eq!(grouped("a < b + c > d"), "(a < (b + c) > d)");
+        // equality isn't part of the chain
+        assert_eq!(grouped("a < b == c < d"), "((a < b) == (c < d))");
+    }
+
+    #[test]
+    fn logical_operators_bind_from_or_to_and() {
+        assert_eq!(grouped("a || b nor c"), "(a || (b nor c))");
+        assert_eq!(grouped("a nor b xor c"), "(a nor (b xor c))");
+        assert_eq!(grouped("a xor b & c"), "(a xor (b & c))");
+        assert_eq!(grouped("a & b || c nor d"), "((a & b) || (c nor d))");
+        assert_eq!(grouped("a || b || c"), "((a || b) || c)");
+    }
 }
@@ -0,0 +1,145 @@
+use super::display;
+use crate::library::docs::signature;
+use crate::parser::Parser;
+use crate::scanner::{Scanner, Statement};
+use std::fs;
+use std::path::Path;
+
+/// The documentation of a function declared at the top level of a module.
+///
+/// # Fields
+///
+/// - `signature`: The signature of the function as it is declared, e.g. `add(a: number, b: number): number`.
+/// - `doc`: The `###` doc comment written right before the function, if there is one.
+/// - `line`: The line the function is declared on.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+pub struct FunctionDoc {
+    pub signature: String,
+    pub doc: Option<String>,
+    pub line: usize,
+}
+
+/// The documented API of one file of a script or a project.
+///
+/// # Fields
+///
+/// - `path`: The path of the file, relative to the root it was found from.
+/// - `functions`: The functions declared at the top level of the file, in the order they are declared.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+pub struct ModuleDoc {
+    pub path: String,
+    pub functions: Vec<FunctionDoc>,
+}
+
+/// The `Format` enum lists the formats `tron doc` can generate documentation in.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+/// Reads the documentation of a file, which is its top-level function declarations with their doc comments.
+///
+/// # Return Value
+///
+/// The documentation of the module, or an error message when the file can't be read or parsed.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn module_doc(root: &Path, file: &Path) -> Result<ModuleDoc, String> {
+    let source = fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+    let statements = Scanner::new(&source)
+        .scan_tokens()
+        .and_then(|tokens| Parser::new(tokens).parse())?;
+    let functions = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::Function {
+                name,
+                params,
+                output_type,
+                doc,
+                line,
+                ..
+            } => Some(FunctionDoc {
+                signature: signature(&name.lexeme, params, output_type),
+                doc: doc.as_ref().map(|doc| doc.to_string()),
+                line: *line,
+            }),
+            _ => None,
+        })
+        .collect();
+    Ok(ModuleDoc {
+        path: display(root, file),
+        functions,
+    })
+}
+
+/// Renders the documentation of a module tree as a single Markdown or HTML page, with one section per module.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn render(title: &str, modules: &[ModuleDoc], format: Format) -> String {
+    match format {
+        Format::Markdown => markdown(title, modules),
+        Format::Html => html(title, modules),
+    }
+}
+
+fn markdown(title: &str, modules: &[ModuleDoc]) -> String {
+    let mut out = format!("# {}\n", title);
+    for module in modules {
+        out.push_str(&format!("\n## {}\n", module.path));
+        if module.functions.is_empty() {
+            out.push_str("\nNo functions.\n");
+        }
+        for function in &module.functions {
+            out.push_str(&format!(
+                "\n### `{}`\n\n{}\n\n_line {}_\n",
+                function.signature,
+                function.doc.as_deref().unwrap_or("No documentation."),
+                function.line
+            ));
+        }
+    }
+    out
+}
+
+fn html(title: &str, modules: &[ModuleDoc]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape(title)
+    );
+    for module in modules {
+        out.push_str(&format!("<section>\n<h2>{}</h2>\n", escape(&module.path)));
+        if module.functions.is_empty() {
+            out.push_str("<p>No functions.</p>\n");
+        }
+        for function in &module.functions {
+            let doc = match &function.doc {
+                Some(doc) => escape(doc).replace('\n', "<br>\n"),
+                None => "No documentation.".to_string(),
+            };
+            out.push_str(&format!(
+                "<h3><code>{}</code></h3>\n<p>{}</p>\n<p><small>line {}</small></p>\n",
+                escape(&function.signature),
+                doc,
+                function.line
+            ));
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
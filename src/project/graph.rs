@@ -0,0 +1,472 @@
+use super::{display, import_path};
+use crate::expressions::{Expression, TronType};
+use crate::parser::Parser;
+use crate::scanner::{Scanner, Statement};
+use crate::visitor::{walk_expression, walk_statement, Visitor};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The `NodeKind` enum lists what a node of a call graph stands for.
+///
+/// - `Module`: The code at the top level of a file, which runs when the file is run or imported.
+/// - `Function`: A function declared with `fn`, at the top level or inside another function.
+/// - `Native`: A native function, like `@print`.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+    Module,
+    Function,
+    Native,
+}
+
+/// A node of a call graph.
+///
+/// # Fields
+///
+/// - `id`: The name of the function, or the path of the file for a module, which is unique in the graph. A function
+///   declared in several files has one node, for its first declaration, like the program only keeps one of them.
+/// - `kind`: What the node stands for.
+/// - `file`: The file the function is declared in, relative to the root, empty for natives.
+/// - `line`: The line the function is declared on, 0 for modules and natives.
+/// - `used`: Whether another node calls the function, a function only calling itself isn't used.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub id: String,
+    pub kind: NodeKind,
+    pub file: String,
+    pub line: usize,
+    pub used: bool,
+}
+
+/// The static call graph of a script or a project, built by `call_graph()`.
+///
+/// # Fields
+///
+/// - `nodes`: The modules, the functions and the natives of the program, in the order they're found.
+/// - `edges`: A `(caller, callee)` pair for every module a file imports and every function a node calls or passes
+///   around as a value, with the ids of the nodes, each pair once.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<(String, String)>,
+}
+
+/// The `Format` enum lists the formats `tron graph` can export a call graph in.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Dot,
+    Json,
+}
+
+/// Builds the call graph of files, usually the ones `check_imports()` found, without running them.
+///
+/// The names are resolved the way the resolver does: a call to a name is a call to the function with that name, unless
+/// a parameter or a local variable of an enclosing scope has the same name. The code of anonymous functions belongs to
+/// the function they're written in, since that's where they come from. Calls whose callee isn't a name, like
+/// `handlers[0]()`, or whose name isn't a function declared in the files, like a variable holding a function, can only
+/// be resolved at runtime and are left out.
+///
+/// # Return Value
+///
+/// The call graph, or an error message when a file can't be read or parsed.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn call_graph(root: &Path, files: &[PathBuf]) -> Result<CallGraph, String> {
+    let mut builder = Builder {
+        root: root.to_path_buf(),
+        ..Default::default()
+    };
+    for file in files {
+        let source = fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+        let statements = Scanner::new(&source)
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse())?;
+        let module = display(root, file);
+        builder.nodes.push(Node {
+            id: module.clone(),
+            kind: NodeKind::Module,
+            file: module.clone(),
+            line: 0,
+            used: false,
+        });
+        builder.file = module.clone();
+        builder.caller = module;
+        for statement in &statements {
+            builder.visit_statement(statement);
+        }
+    }
+    Ok(builder.finish())
+}
+
+// the state of the walk through the statements of the program
+#[derive(Default)]
+struct Builder {
+    nodes: Vec<Node>,
+    // the calls by name, the ones to names that aren't functions are dropped at the end
+    calls: Vec<(String, Rc<str>)>,
+    // the modules imported by each file, which are always kept
+    imports: Vec<(String, String)>,
+    root: PathBuf,
+    // the names of the parameters and local variables in scope, the innermost scope last
+    scopes: Vec<HashSet<Rc<str>>>,
+    file: String,
+    caller: String,
+}
+
+impl Visitor for Builder {
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Use {
+                expression:
+                    Expression::Literal {
+                        value: TronType::StringValue(path),
+                        ..
+                    },
+                ..
+            } => {
+                let module = import_path(&self.root, path);
+                let module = display(&self.root, &fs::canonicalize(&module).unwrap_or(module));
+                self.imports.push((self.file.clone(), module));
+            }
+            Statement::Variable { name, value, .. } => {
+                self.visit_expression(value);
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(name.lexeme.clone());
+                }
+            }
+            Statement::Block { .. } => {
+                self.scopes.push(HashSet::new());
+                walk_statement(self, statement);
+                self.scopes.pop();
+            }
+            Statement::Function {
+                name, params, line, ..
+            } => {
+                // a local function hides the parameters and variables of the same name around it
+                for scope in self.scopes.iter_mut() {
+                    scope.remove(&name.lexeme);
+                }
+                if !self.nodes.iter().any(|node| *node.id == *name.lexeme) {
+                    self.nodes.push(Node {
+                        id: name.lexeme.to_string(),
+                        kind: NodeKind::Function,
+                        file: self.file.clone(),
+                        line: *line,
+                        used: false,
+                    });
+                }
+                let caller = std::mem::replace(&mut self.caller, name.lexeme.to_string());
+                self.scopes
+                    .push(params.iter().map(|(name, _)| name.lexeme.clone()).collect());
+                walk_statement(self, statement);
+                self.scopes.pop();
+                self.caller = caller;
+            }
+            _ => walk_statement(self, statement),
+        }
+    }
+    fn visit_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Variable { name, .. } => {
+                if !self.scopes.iter().any(|scope| scope.contains(&name.lexeme)) {
+                    self.calls.push((self.caller.clone(), name.lexeme.clone()));
+                }
+            }
+            Expression::Function { params, .. } => {
+                self.scopes
+                    .push(params.iter().map(|(name, _)| name.lexeme.clone()).collect());
+                walk_expression(self, expression);
+                self.scopes.pop();
+            }
+            _ => walk_expression(self, expression),
+        }
+    }
+}
+
+impl Builder {
+    // keeps the calls to functions and natives, and marks the functions called by another node as used
+    fn finish(mut self) -> CallGraph {
+        let functions: HashMap<String, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.kind == NodeKind::Function)
+            .map(|(index, node)| (node.id.clone(), index))
+            .collect();
+        let mut edges = std::mem::take(&mut self.imports);
+        let mut seen: HashSet<_> = edges.iter().cloned().collect();
+        for (caller, callee) in std::mem::take(&mut self.calls) {
+            let callee = callee.to_string();
+            match functions.get(&callee) {
+                Some(&index) if caller != callee => self.nodes[index].used = true,
+                Some(_) => {}
+                None if callee.starts_with('@') => {
+                    if !self.nodes.iter().any(|node| node.id == callee) {
+                        self.nodes.push(Node {
+                            id: callee.clone(),
+                            kind: NodeKind::Native,
+                            file: String::new(),
+                            line: 0,
+                            used: true,
+                        });
+                    }
+                }
+                None => continue,
+            }
+            if seen.insert((caller.clone(), callee.clone())) {
+                edges.push((caller, callee));
+            }
+        }
+        CallGraph {
+            nodes: self.nodes,
+            edges,
+        }
+    }
+}
+
+/// Renders a call graph as a Graphviz DOT digraph or as JSON.
+///
+/// In DOT, modules are drawn as folders, with an arrow to the modules they import, natives as gray ellipses and the functions nothing calls, which are either
+/// entry points or dead code, in red. `tron graph --dot | dot -Tsvg > calls.svg` draws the graph.
+///
+/// The JSON has a `nodes` array, with the `id`, `kind` (`module`, `function` or `native`), `file`, `line` and `used`
+/// of each node, and an `edges` array of `{"from": ..., "to": ...}` objects.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn render(graph: &CallGraph, format: Format) -> String {
+    match format {
+        Format::Dot => dot(graph),
+        Format::Json => json(graph),
+    }
+}
+
+fn dot(graph: &CallGraph) -> String {
+    let mut out =
+        String::from("digraph calls {\n    rankdir=LR;\n    node [shape=box, style=rounded];\n");
+    for node in &graph.nodes {
+        let attributes = match node.kind {
+            NodeKind::Module => "shape=folder, style=solid".to_string(),
+            NodeKind::Native => "shape=ellipse, color=gray, fontcolor=gray".to_string(),
+            NodeKind::Function => {
+                let label = format!("{}\n{}:{}", node.id, node.file, node.line);
+                match node.used {
+                    true => format!("label={}", quote(&label)),
+                    false => format!("label={}, color=red, fontcolor=red", quote(&label)),
+                }
+            }
+        };
+        out.push_str(&format!("    {} [{}];\n", quote(&node.id), attributes));
+    }
+    for (caller, callee) in &graph.edges {
+        out.push_str(&format!("    {} -> {};\n", quote(caller), quote(callee)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn json(graph: &CallGraph) -> String {
+    let nodes: Vec<String> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let kind = match node.kind {
+                NodeKind::Module => "module",
+                NodeKind::Function => "function",
+                NodeKind::Native => "native",
+            };
+            format!(
+                "    {{\"id\": {}, \"kind\": \"{}\", \"file\": {}, \"line\": {}, \"used\": {}}}",
+                quote(&node.id),
+                kind,
+                quote(&node.file),
+                node.line,
+                node.used
+            )
+        })
+        .collect();
+    let edges: Vec<String> = graph
+        .edges
+        .iter()
+        .map(|(caller, callee)| {
+            format!(
+                "    {{\"from\": {}, \"to\": {}}}",
+                quote(caller),
+                quote(callee)
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"nodes\": [\n{}\n  ],\n  \"edges\": [\n{}\n  ]\n}}\n",
+        nodes.join(",\n"),
+        edges.join(",\n")
+    )
+}
+
+// a string literal of DOT and JSON, which escape quotes, backslashes and line breaks the same way
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the call graph of a project whose files are written to a fresh temp directory, the first one being the entry
+    fn graph_of(name: &str, files: &[(&str, &str)]) -> CallGraph {
+        let root = std::env::temp_dir().join(format!("tron-graph-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let root = fs::canonicalize(root).unwrap();
+        let paths: Vec<PathBuf> = files
+            .iter()
+            .map(|(file, source)| {
+                fs::write(root.join(file), source).unwrap();
+                root.join(file)
+            })
+            .collect();
+        let graph = call_graph(&root, &paths).unwrap();
+        fs::remove_dir_all(root).unwrap();
+        graph
+    }
+
+    // the node with an id, which must be in the graph
+    fn node<'a>(graph: &'a CallGraph, id: &str) -> &'a Node {
+        graph.nodes.iter().find(|node| node.id == id).unwrap()
+    }
+
+    // whether the caller has an edge to the callee
+    fn edge(graph: &CallGraph, caller: &str, callee: &str) -> bool {
+        graph
+            .edges
+            .contains(&(caller.to_string(), callee.to_string()))
+    }
+
+    #[test]
+    fn calls_link_modules_functions_and_natives() {
+        let graph = graph_of(
+            "calls",
+            &[(
+                "main.tron",
+                "fn greet() { @print(\"hi\"); }\n\nfn unused() { unused(); }\ngreet();\ngreet();",
+            )],
+        );
+        assert_eq!(node(&graph, "main.tron").kind, NodeKind::Module);
+        assert_eq!(node(&graph, "greet").line, 1);
+        assert!(node(&graph, "greet").used);
+        // a function only calling itself isn't used
+        assert!(!node(&graph, "unused").used);
+        assert_eq!(node(&graph, "@print").kind, NodeKind::Native);
+        assert!(edge(&graph, "main.tron", "greet"));
+        assert!(edge(&graph, "greet", "@print"));
+        assert!(edge(&graph, "unused", "unused"));
+        // every pair is kept once
+        assert_eq!(graph.edges.len(), 3);
+    }
+
+    #[test]
+    fn parameters_and_locals_hide_the_functions_they_name() {
+        let graph = graph_of(
+            "scopes",
+            &[(
+                "main.tron",
+                "fn f() {}\nfn g(f: number) { return f; }\nfn h() { let f = 1; return f; }\nfn k() { f(); }",
+            )],
+        );
+        assert!(!edge(&graph, "g", "f"));
+        assert!(!edge(&graph, "h", "f"));
+        assert!(edge(&graph, "k", "f"));
+    }
+
+    #[test]
+    fn imports_link_modules() {
+        let graph = graph_of(
+            "imports",
+            &[
+                ("main.tron", "use \"lib.tron\";\nhelp();"),
+                ("lib.tron", "fn help() {}"),
+            ],
+        );
+        assert!(edge(&graph, "main.tron", "lib.tron"));
+        assert!(edge(&graph, "main.tron", "help"));
+        assert_eq!(node(&graph, "help").file, "lib.tron");
+    }
+
+    // a module calling a used function, which calls a native, and a function nothing calls
+    fn sample() -> CallGraph {
+        let function = |id: &str, line, used| Node {
+            id: id.to_string(),
+            kind: NodeKind::Function,
+            file: "main.tron".to_string(),
+            line,
+            used,
+        };
+        CallGraph {
+            nodes: vec![
+                Node {
+                    id: "main.tron".to_string(),
+                    kind: NodeKind::Module,
+                    file: "main.tron".to_string(),
+                    line: 0,
+                    used: false,
+                },
+                function("greet", 1, true),
+                function("old", 3, false),
+                Node {
+                    id: "@print".to_string(),
+                    kind: NodeKind::Native,
+                    file: String::new(),
+                    line: 0,
+                    used: true,
+                },
+            ],
+            edges: vec![
+                ("main.tron".to_string(), "greet".to_string()),
+                ("greet".to_string(), "@print".to_string()),
+            ],
+        }
+    }
+
+    #[test]
+    fn renders_dot() {
+        assert_eq!(
+            render(&sample(), Format::Dot),
+            "digraph calls {\n    rankdir=LR;\n    node [shape=box, style=rounded];\n    \"main.tron\" [shape=folder, style=solid];\n    \"greet\" [label=\"greet\\nmain.tron:1\"];\n    \"old\" [label=\"old\\nmain.tron:3\", color=red, fontcolor=red];\n    \"@print\" [shape=ellipse, color=gray, fontcolor=gray];\n    \"main.tron\" -> \"greet\";\n    \"greet\" -> \"@print\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn renders_json() {
+        assert_eq!(
+            render(&sample(), Format::Json),
+            "{\n  \"nodes\": [\n    {\"id\": \"main.tron\", \"kind\": \"module\", \"file\": \"main.tron\", \"line\": 0, \"used\": false},\n    {\"id\": \"greet\", \"kind\": \"function\", \"file\": \"main.tron\", \"line\": 1, \"used\": true},\n    {\"id\": \"old\", \"kind\": \"function\", \"file\": \"main.tron\", \"line\": 3, \"used\": false},\n    {\"id\": \"@print\", \"kind\": \"native\", \"file\": \"\", \"line\": 0, \"used\": true}\n  ],\n  \"edges\": [\n    {\"from\": \"main.tron\", \"to\": \"greet\"},\n    {\"from\": \"greet\", \"to\": \"@print\"}\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn quotes_escape_what_dot_and_json_cant_hold() {
+        assert_eq!(
+            quote("a \"b\"\\c\nd\u{1}"),
+            "\"a \\\"b\\\"\\\\c\\nd\\u0001\""
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use crate::expressions::{Expression, TronType};
+use crate::parser::Parser;
+use crate::scanner::{Scanner, Statement};
+use crate::utils::TronError;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+pub mod docs;
+pub mod graph;
+pub mod packages;
+
+/// The sections of a TOML file, with the `key = "value"` pairs of each section.
+pub type Sections = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Parses the small subset of TOML used by `tron.toml` and `tron.lock`: `[section]` headers, `key = "value"` pairs and
+/// `#` comments. Pairs before the first header belong to the `""` section.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn parse_sections(contents: &str, file: &str) -> Result<Sections, String> {
+    let mut sections = Sections::new();
+    let mut section = String::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            sections.entry(section.clone()).or_default();
+            continue;
+        }
+        let invalid = || format!("invalid line {} in {}: {}", index + 1, file, line);
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(invalid)?;
+        sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key.trim().trim_matches('"').to_string(), value.to_string());
+    }
+    Ok(sections)
+}
+
+/// The `Manifest` struct holds the project settings read from the `tron.toml` file in the root of a project.
+///
+/// Only the small subset of TOML used by manifests is supported, see `parse_sections()`.
+///
+/// # Fields
+///
+/// - `name`: The name of the project.
+/// - `version`: The version of the project.
+/// - `entry`: The file run by `tron run`, relative to the project root (`main.tron` by default).
+/// - `dependencies`: The libraries installed into `tron_modules/` by `tron install`, as `name = "git url"` pairs.
+/// - `registry`: The git URL that packages added by name only are cloned from, as `<registry>/<name>`.
+/// - `aliases`: Other words for the keywords, as `alias = "keyword"` pairs, for teaching in another language. The
+///   scanner reads them as the keyword, see `keyword_aliases()`.
+/// - `max_complexity`: The highest cyclomatic complexity `tron check` allows in a function, `max-complexity` in the
+///   `[lints]` section.
+/// - `max_function_lines`: The most lines `tron check` allows in a function, `max-function-lines` in the `[lints]`
+///   section.
+///
+/// # Example
+///
+/// ```toml
+/// [project]
+/// name = "hello"
+/// version = "0.1.0"
+/// entry = "src/main.tron"
+///
+/// [dependencies]
+/// colors = "https://github.com/user/colors.git"
+///
+/// [registry]
+/// url = "https://git.example.com/tron"
+///
+/// [aliases]
+/// si = "if"
+/// sinon = "else"
+///
+/// [lints]
+/// max-complexity = "15"
+/// max-function-lines = "80"
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    pub entry: String,
+    pub dependencies: BTreeMap<String, String>,
+    pub registry: Option<String>,
+    pub aliases: BTreeMap<String, String>,
+    pub max_complexity: Option<usize>,
+    pub max_function_lines: Option<usize>,
+}
+
+impl Manifest {
+    /// Reads the `tron.toml` manifest of the project rooted at `root`.
+    ///
+    /// # Return Value
+    ///
+    /// The manifest, or an error message when the file is missing or isn't valid.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(root.join("tron.toml"))
+            .map_err(|_| "tron.toml not found in the current directory".to_string())?;
+        Self::parse(&contents)
+    }
+    /// Parses the contents of a manifest.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut sections = parse_sections(contents, "tron.toml")?;
+        let project = sections
+            .get("project")
+            .ok_or("tron.toml is missing the [project] section")?;
+        let name = project
+            .get("name")
+            .ok_or("tron.toml is missing the project name")?
+            .clone();
+        let version = project
+            .get("version")
+            .cloned()
+            .unwrap_or_else(|| "0.1.0".to_string());
+        let entry = project
+            .get("entry")
+            .cloned()
+            .unwrap_or_else(|| "main.tron".to_string());
+        let lints = sections.remove("lints").unwrap_or_default();
+        let limit = |key: &str| match lints.get(key) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{} in [lints] must be a number, not {}", key, value)),
+            None => Ok(None),
+        };
+        Ok(Self {
+            name,
+            version,
+            entry,
+            max_complexity: limit("max-complexity")?,
+            max_function_lines: limit("max-function-lines")?,
+            dependencies: sections.remove("dependencies").unwrap_or_default(),
+            registry: sections
+                .get("registry")
+                .and_then(|registry| registry.get("url"))
+                .cloned(),
+            aliases: sections.remove("aliases").unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns the file a `use` path refers to.
+///
+/// Paths are relative to the project root. When no such file exists and the path is the name of an installed package
+/// (`use "colors";`), the entry file of the package in `tron_modules/` is used instead.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn import_path(root: &Path, import: &str) -> PathBuf {
+    let import = import.trim_matches('"').trim_start_matches('/');
+    let path = root.join(import);
+    if path.exists() {
+        return path;
+    }
+    let package = root.join(packages::MODULES_DIR).join(import);
+    if package.is_dir() {
+        let entry = Manifest::load(&package)
+            .map(|manifest| manifest.entry)
+            .unwrap_or_else(|_| "main.tron".to_string());
+        return package.join(entry);
+    }
+    path
+}
+
+/// Checks the import graph of a project before running it.
+///
+/// Starting at `entry`, every file is scanned and parsed, and the libraries imported with `use "path";` are followed. Imports
+/// of files that don't exist are reported with `E0006` and import cycles with `E0005`. Imports that aren't string literals
+/// can only be resolved at runtime and are skipped.
+///
+/// # Return Value
+///
+/// The files of the project in the order they were reached, or the number of problems found.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn check_imports(root: &Path, entry: &Path) -> Result<Vec<PathBuf>, usize> {
+    let root = &fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut graph = ImportGraph {
+        root,
+        files: vec![],
+        finished: vec![],
+        stack: vec![],
+        problems: 0,
+    };
+    if !entry.exists() {
+        TronError::throw(
+            "E0006",
+            0,
+            vec![display(root, entry), "tron.toml".to_string()],
+        );
+        return Err(1);
+    }
+    graph.visit(entry);
+    match graph.problems {
+        0 => Ok(graph.files),
+        problems => Err(problems),
+    }
+}
+
+/// Returns the path of a file relative to the project root, for messages.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn display(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+struct ImportGraph<'a> {
+    root: &'a Path,
+    files: Vec<PathBuf>,
+    finished: Vec<PathBuf>,
+    // the chain of imports leading to the file being visited
+    stack: Vec<PathBuf>,
+    problems: usize,
+}
+
+impl ImportGraph<'_> {
+    fn visit(&mut self, file: &Path) {
+        let file = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        if let Some(start) = self.stack.iter().position(|f| *f == file) {
+            let cycle: Vec<String> = self.stack[start..]
+                .iter()
+                .chain([&file])
+                .map(|f| display(self.root, f))
+                .collect();
+            TronError::throw("E0005", 0, vec![cycle.join(" -> ")]);
+            self.problems += 1;
+            return;
+        }
+        if self.finished.contains(&file) {
+            return;
+        }
+        self.files.push(file.clone());
+        self.stack.push(file.clone());
+        for (import, line) in self.imports(&file) {
+            let path = import_path(self.root, &import);
+            if path.exists() {
+                self.visit(&path);
+            } else {
+                TronError::throw("E0006", line, vec![import, display(self.root, &file)]);
+                self.problems += 1;
+            }
+        }
+        self.stack.pop();
+        self.finished.push(file);
+    }
+    fn imports(&mut self, file: &Path) -> Vec<(String, usize)> {
+        let contents = match fs::read_to_string(file) {
+            Ok(contents) => contents,
+            Err(_) => {
+                TronError::throw("E0001", 0, vec![]);
+                self.problems += 1;
+                return vec![];
+            }
+        };
+        let statements = Scanner::new(&contents)
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse());
+        let mut imports = vec![];
+        match statements {
+            Ok(statements) => {
+                for statement in &statements {
+                    collect_imports(statement, &mut imports);
+                }
+            }
+            Err(msg) => {
+                TronError::throw("E2001", 0, vec![msg]);
+                self.problems += 1;
+            }
+        }
+        imports
+    }
+}
+
+fn collect_imports(statement: &Statement, imports: &mut Vec<(String, usize)>) {
+    match statement {
+        Statement::Use {
+            expression:
+                Expression::Literal {
+                    value: TronType::StringValue(path),
+                    ..
+                },
+            line,
+        } => imports.push((path.clone(), *line)),
+        Statement::Block { statements, .. } => {
+            for statement in statements {
+                collect_imports(statement, imports);
+            }
+        }
+        Statement::If {
+            then_branch,
+            elif_branches,
+            else_branch,
+            ..
+        } => {
+            collect_imports(then_branch, imports);
+            for (_, branch) in elif_branches {
+                collect_imports(branch, imports);
+            }
+            if let Some(branch) = else_branch {
+                collect_imports(branch, imports);
+            }
+        }
+        Statement::While { body, .. } | Statement::DoWhile { body, .. } => {
+            collect_imports(body, imports)
+        }
+        Statement::Function { body, .. } => {
+            for statement in body.iter() {
+                collect_imports(statement, imports);
+            }
+        }
+        Statement::Switch {
+            case_branches,
+            default_branch,
+            ..
+        } => {
+            for statement in case_branches
+                .iter()
+                .flat_map(|(_, body)| body)
+                .chain(default_branch.iter().flatten())
+            {
+                collect_imports(statement, imports);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_thresholds_are_read_from_the_lints_section() {
+        let manifest = Manifest::parse(
+            "[project]\nname = \"app\"\n\n[lints]\nmax-complexity = \"15\"\nmax-function-lines = \"80\"\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.max_complexity, Some(15));
+        assert_eq!(manifest.max_function_lines, Some(80));
+        let manifest = Manifest::parse("[project]\nname = \"app\"\n").unwrap();
+        assert_eq!(manifest.max_complexity, None);
+    }
+
+    #[test]
+    fn lint_thresholds_must_be_numbers() {
+        let error =
+            Manifest::parse("[project]\nname = \"app\"\n\n[lints]\nmax-complexity = \"high\"\n")
+                .unwrap_err();
+        assert_eq!(
+            error,
+            "max-complexity in [lints] must be a number, not high"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::{parse_sections, Manifest, Sections};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The directory, in the project root, that packages are installed into.
+pub const MODULES_DIR: &str = "tron_modules";
+/// The lockfile, in the project root, that records the exact commit of every installed package.
+pub const LOCKFILE: &str = "tron.lock";
+
+/// A package recorded in `tron.lock`.
+///
+/// # Fields
+///
+/// - `source`: The git URL the package was cloned from.
+/// - `commit`: The commit that was installed, which `tron install` checks out again as long as the source is unchanged.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    pub source: String,
+    pub commit: String,
+}
+
+/// Reads `tron.lock`, a missing lockfile has no packages.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn read_lockfile(root: &Path) -> Result<BTreeMap<String, LockedPackage>, String> {
+    let contents = match fs::read_to_string(root.join(LOCKFILE)) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(BTreeMap::new()),
+    };
+    let sections: Sections = parse_sections(&contents, LOCKFILE)?;
+    let mut packages = BTreeMap::new();
+    for (name, fields) in sections {
+        if let (Some(source), Some(commit)) = (fields.get("source"), fields.get("commit")) {
+            packages.insert(
+                name,
+                LockedPackage {
+                    source: source.clone(),
+                    commit: commit.clone(),
+                },
+            );
+        }
+    }
+    Ok(packages)
+}
+
+/// Writes `tron.lock`, with the packages sorted by name so the file only changes when a package does.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn write_lockfile(
+    root: &Path,
+    packages: &BTreeMap<String, LockedPackage>,
+) -> Result<(), String> {
+    let mut contents = String::from("# generated by `tron install`, don't edit by hand\n");
+    for (name, package) in packages {
+        contents.push_str(&format!(
+            "\n[{}]\nsource = \"{}\"\ncommit = \"{}\"\n",
+            name, package.source, package.commit
+        ));
+    }
+    fs::write(root.join(LOCKFILE), contents).map_err(|e| e.to_string())
+}
+
+/// Returns the git URL of a package added by name only, which is the package name under the registry of the project.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn registry_source(manifest: &Manifest, name: &str) -> Result<String, String> {
+    match &manifest.registry {
+        Some(registry) => Ok(format!("{}/{}", registry.trim_end_matches('/'), name)),
+        None => Err("no git URL given and no [registry] url in tron.toml".to_string()),
+    }
+}
+
+/// Adds a `name = "source"` dependency to the `tron.toml` text, replacing the package if it is already listed.
+///
+/// The rest of the file is kept as it is, including comments and formatting.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn add_dependency(manifest: &str, name: &str, source: &str) -> String {
+    let entry = format!("{} = \"{}\"", name, source);
+    let mut lines: Vec<String> = manifest.lines().map(|line| line.to_string()).collect();
+    let is_header = |line: &str| line.trim().starts_with('[');
+    match lines.iter().position(|l| l.trim() == "[dependencies]") {
+        Some(header) => {
+            let end = lines[header + 1..]
+                .iter()
+                .position(|line| is_header(line))
+                .map_or(lines.len(), |offset| header + 1 + offset);
+            let existing = lines[header + 1..end].iter().position(|line| {
+                line.split_once('=')
+                    .is_some_and(|(key, _)| key.trim().trim_matches('"') == name)
+            });
+            match existing {
+                Some(offset) => lines[header + 1 + offset] = entry,
+                None => {
+                    // after the last pair of the section, before the blank lines separating it from the next one
+                    let last = lines[header..end]
+                        .iter()
+                        .rposition(|line| !line.trim().is_empty())
+                        .map_or(end, |offset| header + offset + 1);
+                    lines.insert(last, entry);
+                }
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push("[dependencies]".to_string());
+            lines.push(entry);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Installs a package into `tron_modules/<name>` and returns the commit that was installed.
+///
+/// The package is cloned when it isn't installed yet. With a `locked` commit that commit is checked out, fetching it
+/// first if needed, so every checkout of the project gets the same code. Without one, the installed checkout is kept.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn install_package(
+    root: &Path,
+    name: &str,
+    source: &str,
+    locked: Option<&str>,
+) -> Result<String, String> {
+    let modules = root.join(MODULES_DIR);
+    let directory = modules.join(name);
+    let directory_str = directory.to_string_lossy().to_string();
+    if !directory.is_dir() {
+        fs::create_dir_all(&modules).map_err(|e| e.to_string())?;
+        git(&["clone", "--quiet", source, &directory_str])?;
+    }
+    if let Some(commit) = locked {
+        if git(&["-C", &directory_str, "checkout", "--quiet", commit]).is_err() {
+            git(&["-C", &directory_str, "fetch", "--quiet", "origin"])?;
+            git(&["-C", &directory_str, "checkout", "--quiet", commit])?;
+        }
+    }
+    git(&["-C", &directory_str, "rev-parse", "HEAD"])
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -1,8 +1,12 @@
 use crate::environment::Environment;
-use crate::expressions::Expression;
-use crate::scanner::{Statement, Token};
-use crate::utils::TronError;
-use std::collections::HashMap;
+use crate::expressions::{Expression, TronType};
+use crate::parser::Parser;
+use crate::project::import_path;
+use crate::scanner::{is_strict, Directives, Scanner, Statement, Token, TokenType};
+use crate::utils::{hints, TronError};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 enum FunctionType {
@@ -12,6 +16,7 @@ enum FunctionType {
 #[derive(Copy, Clone, PartialEq, Debug)]
 enum LoopType {
     None,
+    Loop,
 }
 /// The `Resolver` struct in Rust is responsible for resolving symbols.
 /// It maintains a stack of scopes, tracks the current function and loop context, and manages local variables.
@@ -21,7 +26,22 @@ enum LoopType {
 /// - `scopes`: A stack of scopes, where each scope is a `HashMap` mapping variable names to a boolean indicating if the variable is initialized.
 /// - `current_function`: The type of the current function being resolved.
 /// - `current_loop`: The type of the current loop being resolved.
+/// - `loop_labels`: The labels of the loops enclosing the statement being resolved, from the outermost to the innermost one.
+/// - `globals`: The names of the variables declared at the top level of the program.
+/// - `directives`: The warnings turned off by `// tron:allow` comments, by line number.
 /// - `locals`: A map of local variable IDs to their scope depth.
+/// - `strict`: Whether strict mode is on, from the settings or a `#strict` directive.
+/// - `declared`: In strict mode, the top level names that can be used at this point: natives, globals defined before the
+///   program, imported names and the top level variables and functions declared so far.
+/// - `top_level`: In strict mode, every top level name of the program, which functions can use before it's declared.
+/// - `errors`: The number of errors found that don't stop the resolution, like a native redefined or the problems of
+///   strict mode, the program doesn't run when there is any.
+/// - `declarations`: Every variable in scope with the depth of its scope, from the first declaration to the last one, and
+///   the line of the declaration while the variable still holds the `null` it was declared with.
+/// - `uses`: With `tron check`, the functions declared and the names used so far, to find dead code, see
+///   `find_dead_code()`.
+/// - `function_names`: The names of the functions being resolved, from the outermost to the innermost one, so a
+///   function calling itself doesn't count as a use.
 ///
 /// # Usage
 ///
@@ -36,13 +56,38 @@ enum LoopType {
 /// let result = resolver.resolve(&statements, &mut environment);
 /// ```
 ///
-/// ### Last Updated: (v3.0.0)
+/// ### Last Updated: (v3.2.0)
 #[derive(Debug, Clone)]
 pub struct Resolver {
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<Rc<str>, bool>>,
     current_function: FunctionType,
     current_loop: LoopType,
+    loop_labels: Vec<Rc<str>>,
+    globals: HashSet<Rc<str>>,
+    directives: Directives,
     locals: HashMap<usize, usize>,
+    declarations: Vec<(Rc<str>, usize, Option<usize>)>,
+    strict: bool,
+    declared: HashSet<Rc<str>>,
+    top_level: HashSet<Rc<str>>,
+    errors: usize,
+    uses: Option<FunctionUses>,
+    function_names: Vec<Rc<str>>,
+}
+/// The functions of a file and the names it uses, found by `Resolver::find_dead_code()` so `tron check` can report the
+/// functions no file of the program uses.
+///
+/// # Fields
+///
+/// - `declared`: The name and line of every function declared with `fn`, but the documented ones, which are the API of
+///   a library, and the ones a `// tron:allow unused-function` comment allows.
+/// - `used`: The names read or called, by other functions than the one they name.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Default)]
+pub struct FunctionUses {
+    pub declared: Vec<(Rc<str>, usize)>,
+    pub used: HashSet<Rc<str>>,
 }
 impl Resolver {
     /// The `new()` function is a constructor for the `Resolver` struct.
@@ -69,7 +114,196 @@ impl Resolver {
             scopes: vec![],
             current_function: FunctionType::None,
             current_loop: LoopType::None,
+            loop_labels: vec![],
+            globals: HashSet::new(),
+            directives: HashMap::new(),
             locals: HashMap::new(),
+            declarations: vec![],
+            strict: false,
+            declared: HashSet::new(),
+            top_level: HashSet::new(),
+            errors: 0,
+            uses: None,
+            function_names: vec![],
+        }
+    }
+    /// Sets the directives found by the scanner, so `// tron:allow` comments can turn off warnings.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn with_directives(mut self, directives: Directives) -> Self {
+        self.directives = directives;
+        self
+    }
+    /// Checks whether a warning is turned off by a `// tron:allow` comment on the given line or on the line before it.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn is_allowed(&self, warning: &str, line: usize) -> bool {
+        [line, line.saturating_sub(1)].iter().any(|line| {
+            self.directives
+                .get(line)
+                .is_some_and(|allowed| allowed.iter().any(|name| name == warning))
+        })
+    }
+    /// Records that a name is used, for `find_dead_code()`, unless it's the name of the function using it.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn use_name(&mut self, name: &Token) {
+        if let Some(uses) = &mut self.uses {
+            if !self.function_names.contains(&name.lexeme) {
+                uses.used.insert(name.lexeme.clone());
+            }
+        }
+    }
+    /// Warns when a variable declared in a block has the same name as a variable of an enclosing scope.
+    ///
+    /// Top level variables are remembered in `globals`, since they don't live in `scopes`.
+    /// The warning can be turned off with a `// tron:allow shadowing` comment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_shadowing(&mut self, name: &Token) {
+        // hidden variables made by the parser (like the counter of `repeat`) can't be named by the program
+        if name.lexeme.contains(' ') {
+            return;
+        }
+        if self.scopes.is_empty() {
+            self.globals.insert(name.lexeme.clone());
+            return;
+        }
+        let outer_scopes = &self.scopes[..self.scopes.len() - 1];
+        let shadows = self.globals.contains(&name.lexeme)
+            || outer_scopes
+                .iter()
+                .any(|scope| scope.contains_key(&name.lexeme));
+        if shadows && !self.is_allowed("shadowing", name.line_number) {
+            TronError::warn("W3002", name.line_number, vec![name.lexeme.to_string()]);
+        }
+    }
+    /// Throws `E3010` when `name` is a native function. Natives live in the globals, so declaring a top level variable or
+    /// function with the same name, or assigning to it, would replace the native, and the program is rejected.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_native(&mut self, name: &Token, environment: &Environment) {
+        if environment.natives.borrow().contains(&name.lexeme) {
+            TronError::throw("E3010", name.line_number, vec![name.lexeme.to_string()]);
+            self.errors += 1;
+        }
+    }
+    /// Warns when a variable or function declared in a local scope has the name of a native function.
+    ///
+    /// Overriding a native locally is allowed, e.g. to replace `@input` in a test, and only affects that scope. The warning
+    /// can be turned off with a `// tron:allow native-override` comment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_native_override(&self, name: &Token, environment: &Environment) {
+        if environment.natives.borrow().contains(&name.lexeme)
+            && !self.is_allowed("native-override", name.line_number)
+        {
+            TronError::warn("W3003", name.line_number, vec![name.lexeme.to_string()]);
+        }
+    }
+    /// In strict mode, throws `E3011` when a top level name is used before it's declared and `E3012` when a name isn't
+    /// declared at all.
+    ///
+    /// Functions can use any top level name, since they usually run after the whole program is declared.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_declared(&mut self, name: &Token) {
+        // `object.key` names are checked by their object, hidden variables made by the parser always exist
+        let object = name.lexeme.split('.').next().unwrap_or_default();
+        if !self.strict
+            || object.contains(' ')
+            || self.declared.contains(object)
+            || self.scopes.iter().any(|scope| scope.contains_key(object))
+        {
+            return;
+        }
+        if self.top_level.contains(object) {
+            if self.current_function != FunctionType::None {
+                return;
+            }
+            TronError::throw("E3011", name.line_number, vec![object.to_string()]);
+        } else {
+            TronError::throw("E3012", name.line_number, vec![object.to_string()]);
+        }
+        self.errors += 1;
+    }
+    /// Adds the top level names of an imported file, and of the files it imports, to the names strict mode knows about.
+    ///
+    /// Imports that can't be read or parsed are skipped, the interpreter reports them when the program runs.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn declare_imports(&mut self, expression: &Expression, visited: &mut Vec<PathBuf>) {
+        let Expression::Literal {
+            value: TronType::StringValue(import),
+            ..
+        } = expression
+        else {
+            return;
+        };
+        let root = std::env::current_dir().unwrap_or_default();
+        let path = import_path(&root, import);
+        if visited.contains(&path) {
+            return;
+        }
+        visited.push(path.clone());
+        let statements = std::fs::read_to_string(&path).ok().and_then(|source| {
+            Scanner::new(&source)
+                .scan_tokens()
+                .and_then(|tokens| Parser::new(tokens).parse())
+                .ok()
+        });
+        for statement in statements.iter().flatten() {
+            match statement {
+                Statement::Use { expression, .. } => self.declare_imports(expression, visited),
+                statement => {
+                    self.declared.extend(top_level_name(statement));
+                }
+            }
+        }
+    }
+    /// Warns when a variable is used in arithmetic while it still holds the `null` it was declared with, pointing at the
+    /// declaration so the `E4015` error that follows at runtime can be traced back to it.
+    ///
+    /// Assignments anywhere before the use count, even in a branch that may not run, so the warning only shows up when
+    /// the variable can't have a value yet. It can be turned off with a `// tron:allow null-arithmetic` comment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_null_arithmetic(&self, operand: &Expression, operator: &Token) {
+        let arithmetic = matches!(
+            operator.token_type,
+            TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Star
+                | TokenType::Slash
+                | TokenType::Percent
+                | TokenType::Power
+                | TokenType::Increment
+                | TokenType::Decrement
+        );
+        if !arithmetic {
+            return;
+        }
+        let name = match operand {
+            Expression::Variable { id: _, name } => name,
+            Expression::Grouping { id: _, expression } => {
+                return self.check_null_arithmetic(expression, operator)
+            }
+            _ => return,
+        };
+        let declared = self
+            .declarations
+            .iter()
+            .rev()
+            .find(|(declared, _, _)| *declared == name.lexeme)
+            .and_then(|(_, _, null_since)| *null_since);
+        if let Some(declared) = declared {
+            if !self.is_allowed("null-arithmetic", name.line_number) {
+                TronError::warn(
+                    "W3004",
+                    name.line_number,
+                    vec![name.lexeme.to_string(), declared.to_string()],
+                );
+            }
         }
     }
     /// Resolves a single statement within the given environment.
@@ -90,7 +324,7 @@ impl Resolver {
     /// ```
     /// let mut resolver = Resolver::new();
     /// let mut environment = Environment::new();
-    /// let statement = Statement::VariableStatement
+    /// let statement = Statement::Variable
     /// ```
     ///
     /// # Panics
@@ -106,37 +340,63 @@ impl Resolver {
         environment: &mut Environment,
     ) -> Result<(), String> {
         match stmt {
-            Statement::BlockStatement {
+            Statement::Block {
                 statements: _,
                 line,
             } => self.resolve_block(stmt, environment, *line)?,
-            Statement::VariableStatement {
+            Statement::Variable {
                 name: _,
                 value_type: _,
                 value: _,
                 line,
             } => self.resolve_var(stmt, environment, *line)?,
-            Statement::FunctionStatement {
-                name: _,
+            Statement::Function {
+                name,
                 params: _,
                 body: _,
                 output_type: _,
+                doc: _,
                 line: _,
-            } => self.resolve_function(stmt, FunctionType::Function, environment)?,
-            Statement::ExpressionStatement { expression, line } => {
+            } => {
+                if self.scopes.is_empty() {
+                    self.check_native(name, environment);
+                } else {
+                    self.check_native_override(name, environment);
+                }
+                // declared before the body is resolved, so local functions can call themselves
+                self.declare(name)?;
+                self.define(name);
+                if self.scopes.is_empty() && self.strict {
+                    self.declared.insert(name.lexeme.clone());
+                }
+                let allowed = self.is_allowed("unused-function", name.line_number);
+                if let (Some(uses), Statement::Function { doc: None, .. }, false) =
+                    (&mut self.uses, stmt, allowed)
+                {
+                    uses.declared.push((name.lexeme.clone(), name.line_number));
+                }
+                self.function_names.push(name.lexeme.clone());
+                let resolved = self.resolve_function(stmt, FunctionType::Function, environment);
+                self.function_names.pop();
+                resolved?
+            }
+            Statement::Expression { expression, line } => {
                 self.resolve_expr(expression, *line, environment)?
             }
-            Statement::IfStatement {
+            Statement::If {
                 conditions: _,
                 then_branch: _,
                 elif_branches: _,
                 else_branch: _,
                 line,
             } => self.resolve_if_stmt(stmt, environment, *line)?,
-            Statement::UseStatement { expression, line } => {
+            Statement::Use { expression, line } => {
+                if self.strict {
+                    self.declare_imports(expression, &mut vec![]);
+                }
                 self.resolve_expr(expression, *line, environment)?
             }
-            Statement::ReturnStatement {
+            Statement::Return {
                 keyword: _,
                 value,
                 line,
@@ -147,22 +407,69 @@ impl Resolver {
                     self.resolve_expr(value, *line, environment)?;
                 }
             }
-            Statement::WhileStatement {
+            Statement::While {
                 conditions,
                 body,
+                increment,
+                label,
                 line,
             } => {
                 for condition in conditions {
+                    self.check_assignment_condition(condition, *line);
                     self.resolve_expr(condition, *line, environment)?;
                 }
-                self.resolve_internal(body.as_ref(), environment)?;
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment, *line, environment)?;
+                }
+                let endless = conditions.iter().all(|condition| {
+                    matches!(
+                        condition,
+                        Expression::Literal {
+                            id: _,
+                            value: TronType::True
+                        }
+                    )
+                });
+                if endless && !Self::can_leave_loop(body, false) {
+                    TronError::warn("W3001", *line, vec![]);
+                }
+                self.resolve_loop_body(body, label, environment)?;
             }
-            Statement::BreakStatement { keyword: _, line } => {
+            Statement::DoWhile {
+                body,
+                conditions,
+                label,
+                line,
+            } => {
+                self.resolve_loop_body(body, label, environment)?;
+                for condition in conditions {
+                    self.check_assignment_condition(condition, *line);
+                    self.resolve_expr(condition, *line, environment)?;
+                }
+            }
+            Statement::Break {
+                keyword: _,
+                label,
+                line,
+            } => {
                 if self.current_loop == LoopType::None {
                     TronError::throw("E3007", *line, vec![]);
+                } else {
+                    self.check_label(label, *line);
                 }
             }
-            Statement::SwitchStatement {
+            Statement::Continue {
+                keyword: _,
+                label,
+                line,
+            } => {
+                if self.current_loop == LoopType::None {
+                    TronError::throw("E3008", *line, vec![]);
+                } else {
+                    self.check_label(label, *line);
+                }
+            }
+            Statement::Switch {
                 condition,
                 case_branches,
                 default_branch,
@@ -170,19 +477,115 @@ impl Resolver {
             } => {
                 self.resolve_expr(condition, *line, environment)?;
                 for case_branch in case_branches {
-                    for branch in case_branch.1.clone() {
-                        self.resolve_internal(&branch, environment)?;
+                    for branch in &case_branch.1 {
+                        self.resolve_internal(branch, environment)?;
                     }
                 }
                 if let Some(default_branch) = default_branch {
                     for branch in default_branch {
-                        self.resolve_internal(&branch, environment)?;
+                        self.resolve_internal(branch, environment)?;
                     }
                 }
             }
         }
         Ok(())
     }
+    /// Resolves the body of a loop, allowing `break` and `continue` inside it and registering the loop's label.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn resolve_loop_body(
+        &mut self,
+        body: &Statement,
+        label: &Option<Token>,
+        environment: &mut Environment,
+    ) -> Result<(), String> {
+        let enclosing_loop = self.current_loop;
+        self.current_loop = LoopType::Loop;
+        if let Some(label) = label {
+            self.loop_labels.push(label.lexeme.clone());
+        }
+        self.resolve_internal(body, environment)?;
+        if label.is_some() {
+            self.loop_labels.pop();
+        }
+        self.current_loop = enclosing_loop;
+        Ok(())
+    }
+    /// Checks that the label of a `break` or `continue` statement belongs to one of the enclosing loops.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_label(&self, label: &Option<Token>, line: usize) {
+        if let Some(label) = label {
+            if !self.loop_labels.contains(&label.lexeme) {
+                TronError::throw("E3009", line, vec![label.lexeme.to_string()]);
+            }
+        }
+    }
+    /// Checks whether a loop body contains a way out of the loop.
+    ///
+    /// A body can leave its loop with a `break` (that isn't inside a nested loop), a labeled `break` or `continue`
+    /// (which usually targets an outer loop), a `return` or a call to `@panic`.
+    /// It's used to warn about `loop { ... }` and `while true { ... }` loops that would never end.
+    ///
+    /// # Parameters
+    ///
+    /// - `stmt`: The statement to search.
+    /// - `nested`: Whether `stmt` is inside a loop nested in the checked one, where `break` only leaves the inner loop.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn can_leave_loop(stmt: &Statement, nested: bool) -> bool {
+        match stmt {
+            Statement::Break { label, .. } => !nested || label.is_some(),
+            Statement::Continue { label, .. } => label.is_some(),
+            Statement::Return { .. } => true,
+            Statement::Expression {
+                expression:
+                    Expression::Call {
+                        id: _,
+                        callee,
+                        paren: _,
+                        arguments: _,
+                    },
+                line: _,
+            } => {
+                matches!(callee.as_ref(), Expression::Variable { id: _, name } if &*name.lexeme == "@panic")
+            }
+            Statement::Block {
+                statements,
+                line: _,
+            } => statements
+                .iter()
+                .any(|statement| Self::can_leave_loop(statement, nested)),
+            Statement::If {
+                conditions: _,
+                then_branch,
+                elif_branches,
+                else_branch,
+                line: _,
+            } => {
+                Self::can_leave_loop(then_branch, nested)
+                    || elif_branches
+                        .iter()
+                        .any(|(_, branch)| Self::can_leave_loop(branch, nested))
+                    || else_branch
+                        .as_ref()
+                        .is_some_and(|branch| Self::can_leave_loop(branch, nested))
+            }
+            Statement::While { body, .. } => Self::can_leave_loop(body, true),
+            Statement::DoWhile { body, .. } => Self::can_leave_loop(body, true),
+            Statement::Switch {
+                condition: _,
+                case_branches,
+                default_branch,
+                line: _,
+            } => case_branches
+                .iter()
+                .flat_map(|(_, branch)| branch.iter())
+                .chain(default_branch.iter().flatten())
+                .any(|statement| Self::can_leave_loop(statement, nested)),
+            _ => false,
+        }
+    }
     /// Resolves a collection of statements within the given environment.
     ///
     /// This method iterates over a collection of statements and resolves each one using the `resolve_internal` method. It's designed to handle multiple statements in sequence, ensuring that all symbols within the statements are correctly resolved within the current scope.
@@ -211,11 +614,61 @@ impl Resolver {
         stmts: &Vec<&Statement>,
         environment: &mut Environment,
     ) -> Result<(), String> {
+        self.check_unreachable(stmts);
         for stmt in stmts {
             self.resolve_internal(stmt, environment)?;
         }
         Ok(())
     }
+    /// With `tron check`, warns about the first statement of a block that comes after a `return`, `break`, `continue`
+    /// or `@panic()` of the same block, since it can never run. The warning can be turned off with a
+    /// `// tron:allow unreachable` comment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_unreachable(&self, stmts: &[&Statement]) {
+        if self.uses.is_none() {
+            return;
+        }
+        let exit = stmts.iter().position(|stmt| match stmt {
+            Statement::Return { .. }
+            | Statement::Break { .. }
+            | Statement::Continue { .. } => true,
+            Statement::Expression {
+                expression: Expression::Call { callee, .. },
+                ..
+            } => matches!(callee.as_ref(), Expression::Variable { name, .. } if &*name.lexeme == "@panic"),
+            _ => false,
+        });
+        let Some(exit) = exit else {
+            return;
+        };
+        if let Some(unreachable) = stmts.get(exit + 1) {
+            let keyword = match stmts[exit] {
+                Statement::Return { .. } => "return",
+                Statement::Break { .. } => "break",
+                Statement::Continue { .. } => "continue",
+                _ => "@panic()",
+            };
+            let line = unreachable.line();
+            if !self.is_allowed("unreachable", line) {
+                TronError::warn("W3007", line, vec![keyword.to_string()]);
+            }
+        }
+    }
+    /// With `tron check`, warns about a branch of an `if` or `elif` whose condition is always false, like `if false`,
+    /// since it can never run. The warning can be turned off with a `// tron:allow constant-condition` comment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_constant_condition(&self, conditions: &[Expression], line: usize) {
+        if self.uses.is_some()
+            && conditions
+                .iter()
+                .any(|condition| is_constant_bool(condition) == Some(false))
+            && !self.is_allowed("constant-condition", line)
+        {
+            TronError::warn("W3008", line, vec![]);
+        }
+    }
     /// Resolves a collection of statements within the given environment and returns a map of local variable IDs to their scope depth.
     ///
     /// This method is a wrapper around `resolve_many` that also returns the `locals` map of the `Resolver` instance. It's designed to resolve multiple statements in sequence and then provide information about the local variables that were resolved.
@@ -244,9 +697,56 @@ impl Resolver {
         stmts: &Vec<&Statement>,
         environment: &mut Environment,
     ) -> Result<HashMap<usize, usize>, String> {
-        self.resolve_many(stmts, environment)?;
+        self.resolve_program(stmts, environment)?;
         Ok(self.locals)
     }
+    /// Resolves a program like `resolve()` for `tron check`, and also looks for dead code in it.
+    ///
+    /// Code after a `return`, `break`, `continue` or `@panic()` of the same block and the branches of `if` statements
+    /// whose condition is always false are reported as warnings right away. Unused functions can only be found once
+    /// every file of the program is resolved, since a function of a module is used by the files importing it, so the
+    /// functions declared and the names used are returned instead, for `tron check` to compare.
+    ///
+    /// # Parameters
+    ///
+    /// - `stmts`: The statements of the file.
+    /// - `environment`: The environment the file is resolved in.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<FunctionUses, String>`: The functions of the file and the names it uses, or an error message if the
+    ///   resolution fails.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn find_dead_code(
+        mut self,
+        stmts: &Vec<&Statement>,
+        environment: &mut Environment,
+    ) -> Result<FunctionUses, String> {
+        self.uses = Some(FunctionUses::default());
+        self.resolve_program(stmts, environment)?;
+        Ok(self.uses.unwrap_or_default())
+    }
+    // resolves the statements of a file, with the checks of strict mode when it's on
+    fn resolve_program(
+        &mut self,
+        stmts: &Vec<&Statement>,
+        environment: &mut Environment,
+    ) -> Result<(), String> {
+        self.strict = environment.settings.borrow().strict || is_strict(&self.directives);
+        if self.strict {
+            self.declared = environment.visible_names().into_iter().collect();
+            self.top_level = stmts
+                .iter()
+                .filter_map(|stmt| top_level_name(stmt))
+                .collect();
+        }
+        self.resolve_many(stmts, environment)?;
+        if self.errors > 0 {
+            return Err(format!("resolving found {} problems", self.errors));
+        }
+        Ok(())
+    }
     /// Resolves a block statement within the given environment.
     ///
     /// This method is responsible for handling block statements, which are essentially a collection of statements enclosed within curly braces `{}`. It begins a new scope for the block, resolves all statements within the block, and then ends the scope. This ensures that any variables declared within the block are properly scoped and do not leak into the surrounding environment.
@@ -265,7 +765,7 @@ impl Resolver {
     /// ```
     /// let mut resolver = Resolver::new();
     /// let mut environment = Environment::new();
-    /// let block_statement = Statement::BlockStatement {
+    /// let block_statement = Statement::Block {
     ///  statements: vec![/* ... */]
     /// };
     /// resolver.resolve_block(&block_statement, &mut environment)?;
@@ -284,7 +784,7 @@ impl Resolver {
         line: usize,
     ) -> Result<(), String> {
         match stmt {
-            Statement::BlockStatement {
+            Statement::Block {
                 statements,
                 line: _,
             } => {
@@ -301,7 +801,9 @@ impl Resolver {
     }
     /// Resolves a variable declaration statement within the given environment.
     ///
-    /// This method is responsible for handling variable declarations. It declares the variable in the current scope, checks for type mismatches if a type is specified, and then defines the variable. This ensures that variables are correctly declared and initialized within the current scope.
+    /// This method is responsible for handling variable declarations. It declares the variable in the current scope, records its type annotation, and then defines the variable. This ensures that variables are correctly declared and initialized within the current scope.
+    ///
+    /// The initializer isn't evaluated here; the value is checked against the annotation by the interpreter, where local variables are available.
     ///
     /// # Parameters
     ///
@@ -317,51 +819,46 @@ impl Resolver {
     /// ```
     /// let mut resolver = Resolver::new();
     /// let mut environment = Environment::new();
-    /// let variable_statement = Statement::VariableStatement {/* ... */};
+    /// let variable_statement = Statement::Variable {/* ... */};
     /// resolver.resolve_var(&variable_statement, &mut environment)?;
     /// ```
     ///
     /// # Panics
     ///
     /// - Panics if the provided statement is not a variable declaration statement.
-    /// - Panics if a variable is declared with a mismatched type.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn resolve_var(
         &mut self,
         stmt: &Statement,
         environment: &mut Environment,
         line: usize,
     ) -> Result<(), String> {
-        if let Statement::VariableStatement {
-            name,
-            value_type,
-            value,
-            line,
+        if let Statement::Variable {
+            name, value, line, ..
         } = stmt
         {
-            self.declare(name)?;
-            let new_value = (*value).evaluate(environment.clone())?;
-            let value_clone = new_value.clone();
-
-            if value_type.lexeme == value_clone.to_type()
-                || value_type.lexeme == value_clone.to_string()
-            {
-                environment.set_value_type(name.lexeme.clone(), value_type.lexeme.clone());
+            if self.scopes.is_empty() {
+                self.check_native(name, environment);
             } else {
-                TronError::throw(
-                    "E4003",
-                    *line,
-                    vec![
-                        "variable".to_string(),
-                        name.lexeme.to_string(),
-                        value_type.lexeme.to_string(),
-                        value_clone.to_type().to_string(),
-                    ],
-                );
+                self.check_native_override(name, environment);
             }
+            self.check_shadowing(name);
+            self.declare(name)?;
             self.resolve_expr(value, *line, environment)?;
             self.define(name);
+            if self.scopes.is_empty() && self.strict {
+                self.declared.insert(name.lexeme.clone());
+            }
+            if let Expression::Literal {
+                value: TronType::Null,
+                ..
+            } = value
+            {
+                if let Some(declaration) = self.declarations.last_mut() {
+                    declaration.2 = Some(*line);
+                }
+            }
         } else {
             TronError::throw("E3001", line, vec!["variable".to_string()]);
         }
@@ -386,7 +883,7 @@ impl Resolver {
     /// ```
     /// let mut resolver = Resolver::new();
     /// let mut environment = Environment::new();
-    /// let function_statement = Statement::FunctionStatement {/* ... */};
+    /// let function_statement = Statement::Function {/* ... */};
     /// resolver.resolve_function(&function_statement, FunctionType::Function, &mut environment)?;
     /// ```
     ///
@@ -401,24 +898,30 @@ impl Resolver {
         resolving_function: FunctionType,
         environment: &mut Environment,
     ) -> Result<(), String> {
-        if let Statement::FunctionStatement {
+        if let Statement::Function {
             name: _,
             params,
             body,
             output_type: _,
+            doc: _,
             line: _,
         } = stmt
         {
             let enclosing_function = self.current_function;
+            let enclosing_loop = self.current_loop;
             self.current_function = resolving_function;
+            self.current_loop = LoopType::None;
+            let enclosing_labels = std::mem::take(&mut self.loop_labels);
             self.begin_scope();
-            for (param_name, _param_type) in params {
+            for (param_name, _param_type) in params.iter() {
                 self.declare(param_name)?;
                 self.define(param_name);
             }
             self.resolve_many(&body.iter().map(|b| b.as_ref()).collect(), environment)?;
             self.end_scope();
             self.current_function = enclosing_function;
+            self.current_loop = enclosing_loop;
+            self.loop_labels = enclosing_labels;
             Ok(())
         } else {
             panic!("resolve_function called with non-function statement");
@@ -441,7 +944,7 @@ impl Resolver {
     ///
     /// ```
     /// let mut resolver = Resolve::new();
-    /// let mut environment = Statement:IfStatement {/* ... */};
+    /// let mut environment = Statement::If {/* ... */};
     /// resolver.resolve_if_stmt(&if_statement, &mut environment)?;
     /// ```
     ///
@@ -456,7 +959,7 @@ impl Resolver {
         environment: &mut Environment,
         line: usize,
     ) -> Result<(), String> {
-        if let Statement::IfStatement {
+        if let Statement::If {
             conditions,
             then_branch: then,
             elif_branches,
@@ -465,13 +968,19 @@ impl Resolver {
         } = stmt
         {
             for condition in conditions {
+                self.check_assignment_condition(condition, *line);
                 self.resolve_expr(condition, *line, environment)?;
             }
+            self.check_constant_condition(conditions, *line);
+            self.check_dangling_else(then);
             self.resolve_internal(then.as_ref(), environment)?;
             for (elif_predicates, elif_stmt) in elif_branches {
                 for elif_predicate in elif_predicates {
+                    self.check_assignment_condition(elif_predicate, *line);
                     self.resolve_expr(elif_predicate, *line, environment)?;
                 }
+                self.check_constant_condition(elif_predicates, elif_stmt.line());
+                self.check_dangling_else(elif_stmt);
                 self.resolve_internal(elif_stmt.as_ref(), environment)?;
             }
             if let Some(els) = els {
@@ -483,6 +992,50 @@ impl Resolver {
             Ok(())
         }
     }
+    /// Warns when the branch of an `if` is another `if` without braces that has an `else` or `elif`.
+    ///
+    /// In `if a if b x(); else y();` the `else` belongs to the closest `if`, the inner one, which is how most languages
+    /// parse it but not always what was meant. Wrapping the inner `if` in `{ }` makes it explicit either way, and the
+    /// warning can be turned off with a `// tron:allow dangling-else` comment.
+    ///
+    /// Returns whether the warning was reported.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_dangling_else(&self, branch: &Statement) -> bool {
+        let Statement::If {
+            elif_branches,
+            else_branch,
+            line,
+            ..
+        } = branch
+        else {
+            return false;
+        };
+        let dangling = (else_branch.is_some() || !elif_branches.is_empty())
+            && !self.is_allowed("dangling-else", *line);
+        if dangling {
+            TronError::warn("W3005", *line, vec![]);
+        }
+        dangling
+    }
+    /// Warns when the condition of an `if`, `elif` or loop is an assignment, like `if x = 1 { }`.
+    ///
+    /// The assignment runs and the condition is the value assigned, which is rarely what was meant: `==` compares. The
+    /// warning comes with a hint, and it can be turned off with a `// tron:allow assignment-condition` comment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn check_assignment_condition(&self, condition: &Expression, line: usize) {
+        let mut condition = condition;
+        while let Expression::Grouping { expression, .. } = condition {
+            condition = expression;
+        }
+        if let Expression::Assign { name, .. } = condition {
+            if !self.is_allowed("assignment-condition", line) {
+                TronError::warn("W3006", line, vec![name.lexeme.to_string()]);
+                hints::show("assignment-condition", vec![name.lexeme.to_string()]);
+            }
+        }
+    }
     /// Begins a new scope for variable resolution.
     ///
     /// This method is called to start a new scope, which is typically used when entering a block of code. It pushes a new `HashMap` onto the `scopes` stack to track variables declared within this scope.
@@ -514,6 +1067,14 @@ impl Resolver {
     /// ### Last Updated: (v3.0.0)
     fn end_scope(&mut self) {
         self.scopes.pop().expect("Stack underflow");
+        // the declarations of a scope are always the last ones
+        while self
+            .declarations
+            .last()
+            .is_some_and(|(_, depth, _)| *depth > self.scopes.len())
+        {
+            self.declarations.pop();
+        }
     }
     /// Declares a variable in the current scope.
     ///
@@ -542,6 +1103,7 @@ impl Resolver {
     /// ### Last Updated: (v3.0.0)
     fn declare(&mut self, name: &Token) -> Result<(), String> {
         let size = self.scopes.len();
+        self.declarations.push((name.lexeme.clone(), size, None));
         if self.scopes.is_empty() {
             return Ok(());
         } else if self.scopes[size - 1].contains_key(&name.lexeme.clone()) {
@@ -596,7 +1158,7 @@ impl Resolver {
     /// resolver.resolve_expr(&expression)?;
     /// ```
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn resolve_expr(
         &mut self,
         expr: &Expression,
@@ -612,15 +1174,20 @@ impl Resolver {
                 output_type: _,
             } => {
                 let enclosing_function = self.current_function;
+                let enclosing_loop = self.current_loop;
                 self.current_function = FunctionType::Function;
+                self.current_loop = LoopType::None;
+                let enclosing_labels = std::mem::take(&mut self.loop_labels);
                 self.begin_scope();
-                for (param_name, _param_type) in params {
+                for (param_name, _param_type) in params.iter() {
                     self.declare(param_name)?;
                     self.define(param_name);
                 }
                 self.resolve_many(&body.iter().map(|b| b.as_ref()).collect(), environment)?;
                 self.end_scope();
                 self.current_function = enclosing_function;
+                self.current_loop = enclosing_loop;
+                self.loop_labels = enclosing_labels;
                 Ok(())
             }
             Expression::Object { id: _, properties } => {
@@ -629,11 +1196,7 @@ impl Resolver {
                 }
                 Ok(())
             }
-            Expression::ObjectCall {
-                id: _,
-                key: _,
-                name: _,
-            } => Ok(()), //
+            Expression::ObjectCall { id, key: _, name } => self.resolve_local(name, *id),
             Expression::Variable { id: _, name: _ } => {
                 self.resolve_expr_var(expr, expr.get_id(), line)
             }
@@ -648,12 +1211,22 @@ impl Resolver {
                 }
                 Ok(())
             }
+            Expression::Index {
+                id: _,
+                object,
+                index,
+            } => {
+                self.resolve_expr(object, line, environment)?;
+                self.resolve_expr(index, line, environment)
+            }
             Expression::Binary {
                 id: _,
                 left,
-                operator: _,
+                operator,
                 right,
             } => {
+                self.check_null_arithmetic(left, operator);
+                self.check_null_arithmetic(right, operator);
                 self.resolve_expr(left, line, environment)?;
                 self.resolve_expr(right, line, environment)
             }
@@ -684,9 +1257,33 @@ impl Resolver {
             }
             Expression::Unary {
                 id: _,
-                operator: _,
+                operator,
                 right,
-            } => self.resolve_expr(right, line, environment),
+            } => {
+                self.check_null_arithmetic(right, operator);
+                self.resolve_expr(right, line, environment)
+            }
+            Expression::OptionalGet {
+                id: _,
+                object,
+                key: _,
+            } => self.resolve_expr(object, line, environment),
+            Expression::Comparison {
+                id: _,
+                operands,
+                operators: _,
+            } => {
+                for operand in operands {
+                    self.resolve_expr(operand, line, environment)?;
+                }
+                Ok(())
+            }
+            Expression::Template { id: _, parts } => {
+                for part in parts {
+                    self.resolve_expr(part, line, environment)?;
+                }
+                Ok(())
+            }
         }
     }
     /// Resolves a variable expression within the given environment.
@@ -729,6 +1326,8 @@ impl Resolver {
                         TronError::throw("E3003", name.line_number, vec![]);
                     }
                 }
+                self.check_declared(name);
+                self.use_name(name);
                 self.resolve_local(name, resolve_id)
             }
             Expression::Call {
@@ -737,7 +1336,11 @@ impl Resolver {
                 paren: _,
                 arguments: _,
             } => match callee.as_ref() {
-                Expression::Variable { id: _, name } => self.resolve_local(&name, resolve_id),
+                Expression::Variable { id: _, name } => {
+                    self.check_declared(name);
+                    self.use_name(name);
+                    self.resolve_local(name, resolve_id)
+                }
                 _ => {
                     TronError::throw("E3001", line, vec!["variable".to_string()]);
                     Ok(())
@@ -821,11 +1424,173 @@ impl Resolver {
         environment: &mut Environment,
     ) -> Result<(), String> {
         if let Expression::Assign { id: _, name, value } = expr {
+            let is_local = self
+                .scopes
+                .iter()
+                .any(|scope| scope.contains_key(&name.lexeme));
+            if !is_local && !self.globals.contains(&name.lexeme) {
+                self.check_native(name, environment);
+            }
+            self.check_declared(name);
             self.resolve_expr(value.as_ref(), line, environment)?;
             self.resolve_local(name, resolve_id)?;
+            if let Some(declaration) = self
+                .declarations
+                .iter_mut()
+                .rev()
+                .find(|(declared, _, _)| *declared == name.lexeme)
+            {
+                declaration.2 = None;
+            }
         } else {
             TronError::throw("E3001", line, vec!["assign".to_string()]);
         }
         Ok(())
     }
 }
+
+// the name a top level statement declares, if it declares one
+// the value of a condition that doesn't depend on anything, `None` when it isn't constant
+fn is_constant_bool(condition: &Expression) -> Option<bool> {
+    match condition {
+        Expression::Literal {
+            value: TronType::True,
+            ..
+        } => Some(true),
+        Expression::Literal {
+            value: TronType::False | TronType::Null,
+            ..
+        } => Some(false),
+        Expression::Grouping { expression, .. } => is_constant_bool(expression),
+        Expression::Unary {
+            operator, right, ..
+        } if operator.token_type == TokenType::Bang => is_constant_bool(right).map(|value| !value),
+        _ => None,
+    }
+}
+
+fn top_level_name(statement: &Statement) -> Option<Rc<str>> {
+    match statement {
+        Statement::Variable { name, .. } | Statement::Function { name, .. } => {
+            Some(name.lexeme.clone())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    // whether W3005 is reported for the `then` branch of the `if` a source starts with
+    fn warns_dangling_else(source: &str) -> bool {
+        let (tokens, directives) = Scanner::new(source).scan_tokens_with_directives().unwrap();
+        let Statement::If { then_branch, .. } = Parser::new(tokens).parse().unwrap().remove(0)
+        else {
+            panic!("expected an if statement");
+        };
+        Resolver::new()
+            .with_directives(directives)
+            .check_dangling_else(&then_branch)
+    }
+
+    #[test]
+    fn a_dangling_else_is_reported() {
+        assert!(warns_dangling_else("if a if b x(); else y();"));
+        assert!(warns_dangling_else("if a if b x(); elif c y();"));
+    }
+
+    #[test]
+    fn an_unambiguous_if_is_not_reported() {
+        assert!(!warns_dangling_else("if a if b x();"));
+        assert!(!warns_dangling_else("if a { if b x(); else y(); }"));
+        assert!(!warns_dangling_else("if a x(); elif b y(); else z();"));
+    }
+
+    #[test]
+    fn a_dangling_else_can_be_allowed() {
+        assert!(!warns_dangling_else(
+            "// tron:allow dangling-else\nif a if b x(); else y();"
+        ));
+    }
+
+    // resolves a source in a fresh interpreter, with the natives of the standard library
+    fn resolve(source: &str) -> Result<HashMap<usize, usize>, String> {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        let mut interpreter = crate::interpreter::Interpreter::new();
+        Resolver::new().resolve(&statements.iter().collect(), &mut interpreter.environment)
+    }
+
+    #[test]
+    fn redefining_a_native_rejects_the_program() {
+        assert!(resolve("fn @print(x: string) {}").is_err());
+        assert!(resolve("let @print = 1;").is_err());
+        assert!(resolve("@print = 1;").is_err());
+    }
+
+    #[test]
+    fn a_native_can_be_overridden_locally() {
+        assert!(resolve("fn f() { let @print = 1; }").is_ok());
+        assert!(resolve("let x = 1; x = 2;").is_ok());
+    }
+
+    // the functions a source declares and the names it uses, as `tron check` finds them
+    fn dead_code(source: &str) -> FunctionUses {
+        let (tokens, directives) = Scanner::new(source).scan_tokens_with_directives().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        let mut interpreter = crate::interpreter::Interpreter::new();
+        Resolver::new()
+            .with_directives(directives)
+            .find_dead_code(&statements.iter().collect(), &mut interpreter.environment)
+            .unwrap()
+    }
+
+    #[test]
+    fn declared_functions_are_found_with_their_line() {
+        let uses = dead_code("fn used() {}\n\nfn unused() {}\nused();");
+        assert_eq!(
+            uses.declared,
+            vec![(Rc::from("used"), 1), (Rc::from("unused"), 3)]
+        );
+        assert!(uses.used.contains("used"));
+        assert!(!uses.used.contains("unused"));
+    }
+
+    #[test]
+    fn a_function_calling_itself_is_not_used() {
+        let uses = dead_code("fn f(n: number) { if n > 0 f(n - 1); }");
+        assert!(!uses.used.contains("f"));
+        let uses = dead_code("fn f() { g(); }\nfn g() { f(); }");
+        assert!(uses.used.contains("f") && uses.used.contains("g"));
+    }
+
+    #[test]
+    fn documented_and_allowed_functions_are_not_reported() {
+        let uses = dead_code(
+            "### the api of the library\nfn api() {}\n// tron:allow unused-function\nfn kept() {}\nfn helper() {}",
+        );
+        assert_eq!(uses.declared, vec![(Rc::from("helper"), 5)]);
+    }
+
+    // whether the condition of the `if` a source starts with is a constant
+    fn constant_condition(source: &str) -> Option<bool> {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let Statement::If { conditions, .. } = Parser::new(tokens).parse().unwrap().remove(0)
+        else {
+            panic!("expected an if statement");
+        };
+        is_constant_bool(&conditions[0])
+    }
+
+    #[test]
+    fn conditions_that_are_always_false_are_found() {
+        assert_eq!(constant_condition("if false x();"), Some(false));
+        assert_eq!(constant_condition("if null x();"), Some(false));
+        assert_eq!(constant_condition("if (!true) x();"), Some(false));
+        assert_eq!(constant_condition("if !!true x();"), Some(true));
+        assert_eq!(constant_condition("if x x();"), None);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::expressions::Expression;
 use crate::utils::TronError;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
 use std::string::String;
 
 /// Returns a `HashMap` containing keywords as keys and corresponding `TokenType` values.
@@ -52,13 +54,79 @@ pub fn get_keywords_hashmap() -> HashMap<&'static str, TokenType> {
         ("true", True),
         ("let", Variable),
         ("while", While),
-        ("else if", Elif),
+        ("elif", Elif),
         ("break", Break),
         ("switch", Switch),
         ("case", Case),
         ("default", Default),
+        ("repeat", Repeat),
+        ("loop", Loop),
+        ("do", Do),
+        ("continue", Continue),
     ])
 }
+thread_local! {
+    // the keyword aliases of the project, installed by the command that loaded its manifest
+    static ALIASES: RefCell<Rc<HashMap<String, TokenType>>> = RefCell::new(Rc::new(HashMap::new()));
+}
+
+/// Installs the keyword aliases of a project, the `[aliases]` of the `tron.toml` a command loaded.
+///
+/// An alias is another word for a keyword, like `si = "if"`, so a class can write Tron with the keywords of its
+/// language. The scanner gives the alias the token type of the keyword and keeps the word as the lexeme, so the source
+/// is never rewritten and prints back the way it was written. Every scanner created afterwards uses the aliases,
+/// which is how the files a project imports use them too, and the compile cache keys its entries on them.
+///
+/// An alias that isn't a name, is a keyword already, or stands for a word that isn't a keyword is reported with `E0007`
+/// and ignored.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn set_keyword_aliases(manifest_aliases: &BTreeMap<String, String>) {
+    let keywords = get_keywords_hashmap();
+    let mut aliases = HashMap::new();
+    for (alias, keyword) in manifest_aliases {
+        let is_name = alias.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && alias.chars().all(|c| c.is_alphanumeric() || c == '_');
+        let problem = match keywords.get(keyword.as_str()) {
+            _ if !is_name => "isn't a name",
+            _ if keywords.contains_key(alias.as_str()) => "is a keyword already",
+            None => "doesn't stand for a keyword",
+            Some(token_type) => {
+                aliases.insert(alias.clone(), *token_type);
+                continue;
+            }
+        };
+        TronError::throw(
+            "E0007",
+            0,
+            vec![format!("the alias {} = \"{}\" {}", alias, keyword, problem)],
+        );
+    }
+    ALIASES.with(|installed| *installed.borrow_mut() = Rc::new(aliases));
+}
+
+/// Returns the keyword aliases installed by `set_keyword_aliases()`, there are none until a command installs them.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn keyword_aliases() -> Rc<HashMap<String, TokenType>> {
+    ALIASES.with(|installed| installed.borrow().clone())
+}
+
+/// The warnings turned off by `// tron:allow <warning>` comments, by the line number of the comment.
+///
+/// Line 0 holds the directives of the whole file, which is where `#strict` is recorded.
+pub type Directives = HashMap<usize, Vec<String>>;
+
+/// Returns whether the file of `directives` turned strict mode on with `#strict`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn is_strict(directives: &Directives) -> bool {
+    directives
+        .get(&0)
+        .is_some_and(|file| file.iter().any(|directive| directive == "strict"))
+}
+/// The error of an incremental scan or parse that ran out of input in the middle of a string or a statement.
+pub const INCOMPLETE_INPUT: &str = "incomplete input";
 /// Enum list of tokens (`TokenType`) used in the interpreter.
 ///
 /// Each variant of the `TokenType` enum represents a specific type of token, such as keywords, punctuation symbols, operators, literals,
@@ -124,6 +192,8 @@ pub enum TokenType {
     Greater,
     /// - `GreaterEqual`: Represents the greater-than-or-equal-to symbol (`>=`).
     GreaterEqual,
+    /// - `GreaterGreater`: Represents the function composition operator (`>>`).
+    GreaterGreater,
     /// - `Less`: Represents the less-than symbol (`<`).
     Less,
     /// - `LessEqual`: Represents the less-than-or-equal-to symbol (`<=`).
@@ -150,7 +220,7 @@ pub enum TokenType {
     For,
     /// - `If`: Represents the `if` keyword.
     If,
-    /// - `Elif`: Represents the `else if` keyword.
+    /// - `Elif`: Represents the `elif` keyword, the same as `else if` without nesting another `if` in the `else`.
     Elif,
     /// - `Null`: Represents the `null` keyword.
     Null,
@@ -184,6 +254,24 @@ pub enum TokenType {
     Question,
     /// - `Line`: Represents the line Symbold (`|`)
     Line,
+    /// - `Backtick`: Represents the start or the end of a template literal (`` ` ``).
+    Backtick,
+    /// - `DollarBrace`: Represents the start of an interpolation inside a template literal (`${`).
+    DollarBrace,
+    /// - `Repeat`: Represents the `repeat` keyword.
+    Repeat,
+    /// - `Loop`: Represents the `loop` keyword.
+    Loop,
+    /// - `Do`: Represents the `do` keyword.
+    Do,
+    /// - `Continue`: Represents the `continue` keyword.
+    Continue,
+    /// - `QuestionQuestion`: Represents the null-coalescing operator (`??`).
+    QuestionQuestion,
+    /// - `QuestionDot`: Represents the optional chaining operator (`?.`).
+    QuestionDot,
+    /// - `DocComment`: Represents a line of a doc comment (`### text`), the lexeme is the text after `###`.
+    DocComment,
 }
 use TokenType::*;
 impl std::fmt::Display for TokenType {
@@ -236,7 +324,7 @@ use LiteralValue::*;
 
 #[derive(Debug, Clone)]
 pub enum Statement {
-    /// The `ExpressionStatement` variant in the `Statement` enum represents a statement in the code that
+    /// The `Expression` variant in the `Statement` enum represents a statement in the code that
     /// consists of a single expression. It is used to handle statements where the primary purpose is to
     /// evaluate an expression without any additional side effects or operations.
     ///
@@ -255,19 +343,19 @@ pub enum Statement {
     /// print(x + 3);
     /// ```
     ///
-    /// In this example, the `ExpressionStatement` evaluates the expression `x + 3`, which adds the value of
+    /// In this example, the `Statement::Expression` evaluates the expression `x + 3`, which adds the value of
     /// the variable `x` to `3`. The result of this expression is printed.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify expressions that do not have any
     /// side effects or operations associated with them (such as assignments or function calls) and
-    /// represent them as `ExpressionStatement` variants. These statements are then evaluated by the
+    /// represent them as `Statement::Expression` variants. These statements are then evaluated by the
     /// interpreter or compiler as part of the execution or compilation process.
     ///
     /// ### Last Updated: (v3.0.0)
-    ExpressionStatement { expression: Expression, line: usize },
-    /// The `UseStatement` variant in the `Statement` enum represents an use statement.
+    Expression { expression: Expression, line: usize },
+    /// The `Use` variant in the `Statement` enum represents an use statement.
     ///
     /// Use statements are used to include external modules or libraries into the current scope of the program.
     /// They allow the programmer to use functions, classes, or other code elements defined in other files or modules.
@@ -283,17 +371,17 @@ pub enum Statement {
     /// ```
     /// use "math.tron";
     /// ```
-    /// In this example, the `UseStatement` is used to use the "math.tron" file.
+    /// In this example, the `Statement::Use` is used to use the "math.tron" file.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify use statements and represent them as
-    /// `UseStatement` variants. These statements are then processed by the interpreter or compiler to include the
+    /// `Statement::Use` variants. These statements are then processed by the interpreter or compiler to include the
     /// specified modules or libraries into the current scope.
     ///
     /// ### Last Updated: (v3.0.0)
-    UseStatement { expression: Expression, line: usize },
-    /// The `VariableStatement` variant in the `Statement` enum represents a variable declaration statement.
+    Use { expression: Expression, line: usize },
+    /// The `Variable` variant in the `Statement` enum represents a variable declaration statement.
     ///
     /// Variable declaration statements are used to introduce new variables into the current scope of the program.
     /// They allow the programmer to define variables with a specified name, value, and optionally, a type.
@@ -311,22 +399,22 @@ pub enum Statement {
     /// let x: number = 0;
     /// ```
     ///
-    /// In this example, the `VariableStatement` declares a variable named `x` with an initial value of `5`.
+    /// In this example, the `Statement::Variable` declares a variable named `x` with an initial value of `5`.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify variable declaration statements and represent them as
-    /// `VariableStatement` variants. These statements are then processed by the interpreter or compiler to declare the
+    /// `Statement::Variable` variants. These statements are then processed by the interpreter or compiler to declare the
     /// specified variables in the current scope.
     ///
     /// ### Last Updated: (v3.1.0)
-    VariableStatement {
+    Variable {
         name: Token,
         value_type: Token,
         value: Expression,
         line: usize,
     },
-    /// The `BlockStatement` variant in the `Statement` enum represents a block of statements.
+    /// The `Block` variant in the `Statement` enum represents a block of statements.
     ///
     /// A block statement is a sequence of statements enclosed within a pair of braces (`{}`). It is used to group multiple statements
     /// together, typically for control flow structures such as loops, conditionals, and function bodies.
@@ -348,20 +436,20 @@ pub enum Statement {
     /// }
     /// ```
     ///
-    /// In this example, the `BlockStatement` contains three statements: two variable declarations and a print statement.
+    /// In this example, the `Statement::Block` contains three statements: two variable declarations and a print statement.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify blocks of statements and represent them as
-    /// `BlockStatement` variants. These blocks are then processed by the interpreter or compiler to execute the contained
+    /// `Statement::Block` variants. These blocks are then processed by the interpreter or compiler to execute the contained
     /// statements in sequence.
     ///
     /// ### Last Updated: (v3.0.0)
-    BlockStatement {
-        statements: Vec<Box<Statement>>,
+    Block {
+        statements: Vec<Rc<Statement>>,
         line: usize,
     },
-    /// The `WhileStatement` variant in the `Statement` enum represents a while loop statement.
+    /// The `While` variant in the `Statement` enum represents a while loop statement.
     ///
     /// A while loop statement is used to repeatedly execute a block of code as long as a given condition is true.
     /// It consists of a condition expression and a body block that contains the statements to be executed.
@@ -370,6 +458,8 @@ pub enum Statement {
     ///
     /// - `conditions`: This field holds a vector of `Expression` enums, which represent the conditions that must be true for the loop to continue.
     /// - `body`: This field holds a boxed `Statement` enum, which represents the body of the loop that is executed repeatedly.
+    /// - `increment`: This field holds the increment of a `for` loop, which runs after every iteration, even the ones ended by `continue`.
+    /// - `label`: This field holds the label of the loop (`outer: while ...`), which `break` and `continue` can refer to.
     /// - `line`: This field represents the line number in the source code where the statement was found.
     ///
     /// # Example
@@ -381,22 +471,50 @@ pub enum Statement {
     /// }
     /// ```
     ///
-    /// In this example, the `WhileStatement` contains a condition `x < 10` and a body block with two statements:
+    /// In this example, the `Statement::While` contains a condition `x < 10` and a body block with two statements:
     /// a print statement and an assignment statement.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify while loop statements and represent them as
-    /// `WhileStatement` variants. These statements are then processed by the interpreter or compiler to execute the contained
+    /// `Statement::While` variants. These statements are then processed by the interpreter or compiler to execute the contained
     /// statements in a loop until the condition is no longer true.
     ///
-    /// ### Last Updated: (v3.0.0)
-    WhileStatement {
+    /// ### Last Updated: (v3.2.0)
+    While {
+        conditions: Vec<Expression>,
+        body: Rc<Statement>,
+        increment: Option<Expression>,
+        label: Option<Token>,
+        line: usize,
+    },
+    /// The `DoWhile` variant in the `Statement` enum represents a do-while loop statement.
+    ///
+    /// It works like a `Statement::While`, but the conditions are checked after the body, so the body always runs at least once.
+    ///
+    /// # Fields
+    ///
+    /// - `body`: This field holds a boxed `Statement` enum, which represents the body of the loop.
+    /// - `conditions`: This field holds the conditions that must all be true for the loop to run again.
+    /// - `label`: This field holds the label of the loop, which `break` and `continue` can refer to.
+    /// - `line`: This field represents the line number in the source code where the statement was found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// do {
+    ///  let answer: string = @ask("continue? ");
+    /// } while answer != "no";
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    DoWhile {
+        body: Rc<Statement>,
         conditions: Vec<Expression>,
-        body: Box<Statement>,
+        label: Option<Token>,
         line: usize,
     },
-    /// The `IfStatement` variant in the `Statement` enum represents an if statement.
+    /// The `If` variant in the `Statement` enum represents an if statement.
     ///
     /// An if statement is used to conditionally execute a block of code based on the evaluation of a condition.
     /// It can also include optional else branches and else-if branches to handle multiple conditions.
@@ -419,22 +537,22 @@ pub enum Statement {
     ///  print("x is greater than 10");
     /// }
     /// ```
-    /// In this example, the `IfStatement` contains a condition `x < 10` and a body block with a print statement. It also includes an else-if branch for the condition `x == 10` and an else branch for when `x` is greater than `10`.
+    /// In this example, the `Statement::If` contains a condition `x < 10` and a body block with a print statement. It also includes an else-if branch for the condition `x == 10` and an else branch for when `x` is greater than `10`.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify if statements and represent them as
-    /// `IfStatement` variants. These statements are then processed by the interpreter or compiler to execute the appropriate branch based on the evaluation of the conditions.
+    /// `Statement::If` variants. These statements are then processed by the interpreter or compiler to execute the appropriate branch based on the evaluation of the conditions.
     ///
     /// ### Last Updated: (v3.0.0)
-    IfStatement {
+    If {
         conditions: Vec<Expression>,
-        then_branch: Box<Statement>,
-        elif_branches: Vec<(Vec<Expression>, Box<Statement>)>,
-        else_branch: Option<Box<Statement>>,
+        then_branch: Rc<Statement>,
+        elif_branches: Vec<(Vec<Expression>, Rc<Statement>)>,
+        else_branch: Option<Rc<Statement>>,
         line: usize,
     },
-    /// The `FunctionStatement` variant in the `Statement` enum represents a function declaration statement.
+    /// The `Function` variant in the `Statement` enum represents a function declaration statement.
     ///
     /// A function declaration statement is used to define a new function with a specified name, parameters, body, and optionally, an output type.
     /// It allows the programmer to encapsulate a sequence of statements into a reusable piece of code that can be called by name.
@@ -445,6 +563,7 @@ pub enum Statement {
     /// - `params`: This field is a vector of tuples, where each tuple contains a `Token` representing the name of a parameter and `Token` representing the type of the parameter.
     /// - `body`: This field holds a vector of boxed `Statement` enums, which represent the sequence of statements that make up the body of the function.
     /// - `output_type`: `Token` that represents the return type of the function.
+    /// - `doc`: The doc comment (`### text` lines) written right before the function, shown by `@help()` and `tron doc`.
     /// - `line`: This field represents the line number in the source code where the statement was found.
     ///
     /// # Example
@@ -455,22 +574,23 @@ pub enum Statement {
     /// }
     /// ```
     ///
-    /// In this example, the `FunctionStatement` declares a function named `add` with two parameters `a` and `b`, and a body that returns the sum of `a` and `b`.
+    /// In this example, the `Statement::Function` declares a function named `add` with two parameters `a` and `b`, and a body that returns the sum of `a` and `b`.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify function declaration statements and represent them as
-    /// `FunctionStatement` variants. These statements are then processed by the interpreter or compiler to declare the specified functions in the current scope.
+    /// `Statement::Function` variants. These statements are then processed by the interpreter or compiler to declare the specified functions in the current scope.
     ///
-    /// ### Last Updated: (v3.1.0)
-    FunctionStatement {
+    /// ### Last Updated: (v3.2.0)
+    Function {
         name: Token,
-        params: Vec<(Token, Token)>,
-        body: Vec<Box<Statement>>,
+        params: Rc<[(Token, Token)]>,
+        body: Rc<[Rc<Statement>]>,
         output_type: Token,
+        doc: Option<Rc<str>>,
         line: usize,
     },
-    /// The `ReturnStatement` variant in the `Statement` enum represents a return statement.
+    /// The `Return` variant in the `Statement` enum represents a return statement.
     ///
     /// A return statement is used to exit a function and optionally return a value to the caller. It is typically the last statement in a function body.
     ///
@@ -488,21 +608,21 @@ pub enum Statement {
     /// }
     /// ```
     ///
-    /// In this example, the `ReturnStatement` is used to return the sum of `a` and `b` from the `add` function.
+    /// In this example, the `Statement::Return` is used to return the sum of `a` and `b` from the `add` function.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify return statements and represent them as
-    /// `ReturnStatement` variants. These statements are then processed by the interpreter or compiler to return the specified value from the function.
+    /// `Statement::Return` variants. These statements are then processed by the interpreter or compiler to return the specified value from the function.
     /// - `line`: This field represents the line number in the source code where the statement was found.
     ///
     /// ### Last Updated: (v3.0.0)
-    ReturnStatement {
+    Return {
         keyword: Token,
         value: Option<Expression>,
         line: usize,
     },
-    /// The `BreakStatement` variant in the `Statement` enum represents a break statement.
+    /// The `Break` variant in the `Statement` enum represents a break statement.
     ///
     /// A break statement is used to exit the current loop or switch statement prematurely. It is typically used within the body of a loop or switch
     /// to terminate the loop or switch when a certain condition is met.
@@ -510,6 +630,7 @@ pub enum Statement {
     /// # Fields
     ///
     /// - `keyword`: This field holds the `Token` that represents the `break` keyword.
+    /// - `label`: This field holds the label of the loop to exit (`break outer;`). Without a label the innermost loop is exited.
     /// - `line`: This field represents the line number in the source code where the statement was found.
     ///
     /// # Example
@@ -523,17 +644,48 @@ pub enum Statement {
     /// }
     /// ```
     ///
-    /// In this example, the `BreakStatement` is used to exit the loop when the variable `x` is equal to `5`.
+    /// In this example, the `Statement::Break` is used to exit the loop when the variable `x` is equal to `5`.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify break statements and represent them as
-    /// `BreakStatement` variants. These statements are then processed by the interpreter or compiler to exit the current loop or switch statement.
+    /// `Statement::Break` variants. These statements are then processed by the interpreter or compiler to exit the current loop or switch statement.
     /// - `line`: This field represents the line number in the source code where the statement was found.
     ///
-    /// ### Last Updated: (v3.0.0)
-    BreakStatement { keyword: Token, line: usize },
-    /// The `SwitchStatement` variant in the `Statement` enum represents a switch statement.
+    /// ### Last Updated: (v3.2.0)
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+        line: usize,
+    },
+    /// The `Continue` variant in the `Statement` enum represents a continue statement.
+    ///
+    /// A continue statement skips the rest of the current iteration of a loop and starts the next one.
+    ///
+    /// # Fields
+    ///
+    /// - `keyword`: This field holds the `Token` that represents the `continue` keyword.
+    /// - `label`: This field holds the label of the loop to continue (`continue outer;`). Without a label the innermost loop is continued.
+    /// - `line`: This field represents the line number in the source code where the statement was found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// outer: while x < 10 {
+    ///  x = x + 1;
+    ///  while true {
+    ///     continue outer;
+    ///  }
+    /// }
+    /// ```
+    ///
+    /// ### Last Updated: (v3.2.0)
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
+        line: usize,
+    },
+    /// The `Switch` variant in the `Statement` enum represents a switch statement.
     ///
     /// A switch statement is used to perform different actions based on different conditions. It evaluates an expression and executes the corresponding case block.
     ///
@@ -560,15 +712,15 @@ pub enum Statement {
     /// }
     /// ```
     ///
-    /// In this example, the `SwitchStatement` evaluates the value of `x` and executes the corresponding case block. If `x` is not `1` or `2`, it executes the default case block.
+    /// In this example, the `Statement::Switch` evaluates the value of `x` and executes the corresponding case block. If `x` is not `1` or `2`, it executes the default case block.
     ///
     /// # Usage
     ///
     /// When parsing Tron code, the scanner and parser will identify switch statements and represent them as
-    /// `SwitchStatement` variants. These statements are then processed by the interpreter or compiler to execute the appropriate case block based on the evaluation of the condition.
+    /// `Statement::Switch` variants. These statements are then processed by the interpreter or compiler to execute the appropriate case block based on the evaluation of the condition.
     ///
     /// ### Last Updated: (v3.0.0)
-    SwitchStatement {
+    Switch {
         condition: Expression,
         case_branches: Vec<(Expression, Vec<Statement>)>,
         default_branch: Option<Vec<Statement>>,
@@ -576,6 +728,28 @@ pub enum Statement {
     },
 }
 
+impl Statement {
+    /// Returns the line the statement starts on.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn line(&self) -> usize {
+        match self {
+            Statement::Expression { line, .. }
+            | Statement::Use { line, .. }
+            | Statement::Variable { line, .. }
+            | Statement::Block { line, .. }
+            | Statement::While { line, .. }
+            | Statement::DoWhile { line, .. }
+            | Statement::If { line, .. }
+            | Statement::Function { line, .. }
+            | Statement::Return { line, .. }
+            | Statement::Break { line, .. }
+            | Statement::Continue { line, .. }
+            | Statement::Switch { line, .. } => *line,
+        }
+    }
+}
+
 /// The `Token` struct in Rust represents a token in the Tron.
 ///
 /// Each `Token` contains information about the type of token, the lexeme (the actual text of the token), an optional literal value, and the line number where the token was found.
@@ -583,22 +757,26 @@ pub enum Statement {
 /// # Fields
 ///
 /// - `token_type`: This field holds the `TokenType` enum variant that represents the type of the token.
-/// - `lexeme`: This field holds the `String` that represents the actual text of the token in the source code.
+/// - `lexeme`: This field holds the actual text of the token in the source code. It's an interned `Rc<str>`, so every token
+///   with the same text shares one allocation and cloning a token doesn't copy its text.
 /// - `literal`: This field is an optional `LiteralValue` enum variant that represents the literal value associated with the token, if any.
 /// - `line_number`: This field holds the `usize` that represents the line number in the source code where the token was found.
+/// - `column`: The column of the first character of the token on its line, starting at 1. Tokens that aren't read from
+///   the source code, like the ones synthesized by the parser, have column 0.
 ///
 /// # Example
 ///
 /// ```
 /// let token = Token {
 ///  token_type: TokenType::Identifier,
-///  lexeme: "x".to_string(),
+///  lexeme: "x".into(),
 ///  literal: None,
-///  line_number: 1
+///  line_number: 1,
+///  column: 5,
 /// }
 /// ```
 ///
-/// In this example, a `Token` is created to represent an identifier with the name "x" on line 1 of the source code.
+/// In this example, a `Token` is created to represent an identifier with the name "x" on line 1, column 5 of the source code.
 ///
 /// # Usage
 ///
@@ -606,17 +784,18 @@ pub enum Statement {
 /// Each token is categorized by its type, which can be a keyword, operator, identifier, literal, or other types of tokens.
 /// The `literal` field is used to store the actual value of literals, such as numbers or strings, while the `lexeme` field stores the text of the token.
 ///
-/// ### Last Updated: (v3.0.0)
+/// ### Last Updated: (v3.2.0)
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
-    pub lexeme: String,
+    pub lexeme: Rc<str>,
     pub literal: Option<LiteralValue>,
     pub line_number: usize,
+    pub column: usize,
 }
-impl Token {
-    pub fn to_string(&self) -> String {
-        format!("{} {} {:?}", self.token_type, self.lexeme, self.literal)
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {} {:?}", self.token_type, self.lexeme, self.literal)
     }
 }
 /// The `Scanner` struct in Rust is responsible for tokenizing the source code of the Tron.
@@ -627,12 +806,14 @@ impl Token {
 ///
 /// # Fields
 ///
-/// - `source`: This field holds the source code as a `String`.
+/// - `source`: This field holds the characters of the source code.
 /// - `tokens`: This field is a vector of `Token` enums that will hold the tokens generated by the scanner.
 /// - `start`: This field holds the starting position of the current token being scanned.
 /// - `current`: This field holds the current position in the source code.
 /// - `line`: This field holds the current line number in the source code.
 /// - `keywords`: This field is a `HashMap` that maps keywords to their corresponding `TokenType` values.
+/// - `aliases`: This field maps the keyword aliases of the project to the `TokenType` of their keyword, see `keyword_aliases()`.
+/// - `interned`: This field holds the text of every token scanned so far, so repeated identifiers and operators share one allocation.
 ///
 /// # Usage
 ///
@@ -649,15 +830,27 @@ impl Token {
 ///
 /// In this example, a `Scanner` is created with the source code `"let x = 5;"`, and then the `scan_tokens` method is called to tokenize the code.
 ///
-/// ### Last Updated: (v3.0.0)
+/// ### Last Updated: (v3.2.0)
 #[derive(Debug, Clone)]
 pub struct Scanner {
-    source: String,
+    // positions in `source` are byte offsets, lexemes are sliced from it without collecting characters
+    source: Rc<str>,
+    directives: Directives,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    // where the current line starts in `source`, and the column (in characters) of the token being scanned
+    line_start: usize,
+    column: usize,
     keywords: HashMap<&'static str, TokenType>,
+    // the other words for keywords the project has, see `keyword_aliases()`
+    aliases: Rc<HashMap<String, TokenType>>,
+    interned: HashSet<Rc<str>>,
+    // in incremental mode, a string that isn't closed yet stops the scan with `INCOMPLETE_INPUT`
+    incremental: bool,
+    // the lexical errors found so far, as their code, line, column and arguments, reported together once the scan ends
+    errors: Vec<(&'static str, usize, usize, Vec<String>)>,
 }
 impl Scanner {
     /// The `new()` function is a constructor for the `Scanner` struct.
@@ -690,17 +883,42 @@ impl Scanner {
     ///
     /// In this example, the `new()` function is called with the source code `"let x = 5;"`, creating a new `Scanner` that is ready to tokenize this code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     pub fn new(source: &str) -> Self {
         Self {
-            source: source.to_string(),
+            source: Rc::from(source),
+            directives: HashMap::new(),
             tokens: vec![],
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            column: 1,
             keywords: get_keywords_hashmap(),
+            aliases: keyword_aliases(),
+            interned: HashSet::new(),
+            incremental: false,
+            errors: vec![],
         }
     }
+    /// Turns on incremental mode, for input that is typed a line at a time like in the REPL.
+    ///
+    /// A string, template or interpolation that is still open at the end of the source returns `INCOMPLETE_INPUT`
+    /// instead of reporting `E1001`, so the caller can read more input and scan again.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn incremental(mut self) -> Self {
+        self.incremental = true;
+        self
+    }
+    // records an unterminated string starting at `line`, unless more input can still close it
+    fn unterminated(&mut self, line: usize) -> Result<(), String> {
+        if self.incremental {
+            return Err(INCOMPLETE_INPUT.to_string());
+        }
+        self.errors.push(("E1001", line, self.column, vec![]));
+        Ok(())
+    }
     /// The `scan_tokens()` method of the `Scanner` struct is responsible for tokenizing the source code of the Tron language.
     ///
     /// It iterates over the source code, character by character, and categorizes them into tokens based on the language's syntax rules.
@@ -727,23 +945,50 @@ impl Scanner {
     /// ```
     ///
     /// In this example, the `scan_tokens()` method is called on a `Scanner` instance to tokenize the source code `"let x = 5;"`.
-    pub fn scan_tokens(mut self) -> Result<Vec<Token>, String> {
+    pub fn scan_tokens(self) -> Result<Vec<Token>, String> {
+        Ok(self.scan_tokens_with_directives()?.0)
+    }
+    /// The `scan_tokens_with_directives()` method of the `Scanner` struct tokenizes the source code like `scan_tokens()`,
+    /// and also returns the directives found in comments.
+    ///
+    /// A directive is a comment like `// tron:allow shadowing, unused`, which turns off the listed warnings
+    /// for the line of the comment and the line after it.
+    ///
+    /// # Return Value
+    ///
+    /// A `Result` containing the tokens and the directives, or an error message if an error occurs.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn scan_tokens_with_directives(mut self) -> Result<(Vec<Token>, Directives), String> {
+        self.scan()?;
+        // every error at once, in the order of the source, an unterminated string ends the source so it comes last
+        for (error_code, line, column, args) in std::mem::take(&mut self.errors) {
+            TronError::throw_at(error_code, line, column, args);
+        }
+        self.tokens.push(Token {
+            token_type: TokenType::Eof,
+            lexeme: "".into(),
+            literal: None,
+            line_number: self.line,
+            column: self.column_at(self.current),
+        });
+        Ok((self.tokens, self.directives))
+    }
+    // scans every token, recording the lexical errors instead of reporting them
+    fn scan(&mut self) -> Result<(), String> {
         while !self.is_at_end() {
             self.start = self.current;
+            self.column = self.column_at(self.start);
             match self.scan_token() {
                 Ok(_) => (),
+                Err(msg) if msg == INCOMPLETE_INPUT => return Err(msg),
                 Err(msg) => {
-                    TronError::throw("E1004", self.line, vec![msg]);
+                    let column = self.column;
+                    self.errors.push(("E1004", self.line, column, vec![msg]));
                 }
             }
         }
-        self.tokens.push(Token {
-            token_type: TokenType::Eof,
-            lexeme: "".to_string(),
-            literal: None,
-            line_number: self.line,
-        });
-        Ok(self.tokens)
+        Ok(())
     }
     /// The `scan_token()` method of the `Scanner` struct is responsible for scanning a single token from the source code.
     ///
@@ -759,7 +1004,7 @@ impl Scanner {
     /// The `scan_token()` method is called repeatedly by the `scan_tokens()` method to tokenize the entire source code.
     /// It processes one character at a time and categorizes it into a token based on the language's syntax rules.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn scan_token(&mut self) -> Result<(), String> {
         let c = self.advance();
         match c {
@@ -778,7 +1023,16 @@ impl Scanner {
                 let token = if self.char_match('|') { Or } else { Line };
                 self.add_token(token);
             }
-            '?' => self.add_token(Question),
+            '?' => {
+                let token = if self.char_match('?') {
+                    QuestionQuestion
+                } else if self.char_match('.') {
+                    QuestionDot
+                } else {
+                    Question
+                };
+                self.add_token(token);
+            }
             '-' => {
                 let token = if self.char_match('-') {
                     Decrement
@@ -826,6 +1080,8 @@ impl Scanner {
             '>' => {
                 let token = if self.char_match('=') {
                     GreaterEqual
+                } else if self.char_match('>') {
+                    GreaterGreater
                 } else {
                     Greater
                 };
@@ -839,6 +1095,16 @@ impl Scanner {
                         }
                         self.advance();
                     }
+                    let source = Rc::clone(&self.source);
+                    let comment = &source[self.start + 2..self.current];
+                    if let Some(allowed) = comment.trim().strip_prefix("tron:allow") {
+                        self.directives.entry(self.line).or_default().extend(
+                            allowed
+                                .split(|c: char| c == ',' || c.is_whitespace())
+                                .filter(|name| !name.is_empty())
+                                .map(|name| name.to_string()),
+                        );
+                    }
                 } else {
                     self.add_token(Slash);
                 }
@@ -846,16 +1112,43 @@ impl Scanner {
             ' ' | '\r' | '\t' => {}
             '\n' => {
                 self.line += 1;
+                self.line_start = self.current;
             }
             '"' => self.string()?,
             '\'' => self.string()?,
+            '`' => self.template()?,
+            '#' if self.peek() == '#' && self.peek_next() == '#' => {
+                while self.peek() != '\n' && !self.is_at_end() {
+                    self.advance();
+                }
+                let source = Rc::clone(&self.source);
+                self.push_token(
+                    DocComment,
+                    source[self.start + 3..self.current].trim(),
+                    None,
+                );
+            }
+            '#' if self.source[self.current..].starts_with("strict")
+                && !self.source[self.current + 6..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphanumeric()) =>
+            {
+                self.current += 6;
+                self.directives
+                    .entry(0)
+                    .or_default()
+                    .push("strict".to_string());
+            }
             c => {
-                if c.is_digit(10) {
+                if c.is_ascii_digit() {
                     self.number()?;
                 } else if c.is_alphabetic() || c == '@' || c == '_' || c == '$' {
                     self.identifier();
                 } else {
-                    TronError::throw("E1002", self.current, vec![c.to_string()]);
+                    let column = self.column;
+                    self.errors
+                        .push(("E1002", self.line, column, vec![c.to_string()]));
                 }
             }
         }
@@ -871,7 +1164,7 @@ impl Scanner {
     /// The `identifier()` method is called internally by the `Scanner` when it encounters a character that could be the start of an identifier.
     /// It processes the source code and generates a token of type `Identifier` if the sequence of characters forms a valid identifier.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn identifier(&mut self) {
         while self.peek().is_alphanumeric()
             || self.peek() == '@'
@@ -880,12 +1173,14 @@ impl Scanner {
         {
             self.advance();
         }
-        let substring = &self.source[self.start..self.current];
-        if let Some(&t_type) = self.keywords.get(substring) {
-            self.add_token(t_type);
-        } else {
-            self.add_token(Identifier);
-        }
+        let word = self.substring(self.start, self.current);
+        let t_type = self
+            .keywords
+            .get(word)
+            .or_else(|| self.aliases.get(word))
+            .copied()
+            .unwrap_or(Identifier);
+        self.add_token(t_type);
     }
     /// The `number()` method of the `Scanner` struct is responsible for scanning a number literal from the source code.
     ///
@@ -897,20 +1192,37 @@ impl Scanner {
     /// The `number()` method is called internally by the `Scanner` when it encounters a character that could be the start of a number literal.
     /// It processes the source code and generates a token of type `Number` if the sequence of characters forms a valid number literal.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// A number can have a fraction and an exponent in scientific notation, like `12`, `0.5`, `1.5e-3` or `6E23`.
+    ///
+    /// ### Last Updated: (v3.2.0)
     fn number(&mut self) -> Result<(), String> {
-        while self.peek().is_digit(10) {
+        while self.peek().is_ascii_digit() {
             self.advance();
         }
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
-            while self.peek().is_digit(10) {
+            while self.peek().is_ascii_digit() {
                 self.advance();
             }
         }
-        let substring = &self.source[self.start..self.current];
-        let value = substring.parse::<f32>();
-        match value {
+        // an exponent like `1.5e-3`, only when digits follow, so `2e` is still a number and a name
+        if matches!(self.peek(), 'e' | 'E') {
+            let signed = matches!(self.peek_next(), '+' | '-');
+            let exponent = self.current + 1 + usize::from(signed);
+            if self
+                .source
+                .as_bytes()
+                .get(exponent)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                self.current = exponent;
+                while self.peek().is_ascii_digit() {
+                    self.advance();
+                }
+            }
+        }
+        let substring = self.substring(self.start, self.current);
+        match substring.parse::<f32>() {
             Ok(value) => self.add_token_lit(Number, Some(NumericValue(value))),
             Err(_) => {
                 panic!("\n Could not parse number: {}", substring)
@@ -938,12 +1250,14 @@ impl Scanner {
     /// - The method is designed to be safe and will not panic if called at the end of the source code. Instead, it will return a null character (`'\0'`).
     /// - This method is a crucial part of the scanner's functionality, as it allows the scanner to make decisions based on the upcoming characters in the source code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
+        let next = self.current + self.peek().len_utf8();
+        match self.source.as_bytes().get(next) {
+            Some(&byte) if byte.is_ascii() => byte as char,
+            Some(_) => self.source[next..].chars().next().unwrap_or('\0'),
+            None => '\0',
         }
-        self.source.chars().nth(self.current + 1).unwrap()
     }
     /// The `string` method of the `Scanner` struct is responsible for scanning a string literal from the source code.
     ///
@@ -966,22 +1280,160 @@ impl Scanner {
     /// - The method increments the line number if a newline character (`\n`) is encountered within the string literal.
     /// - The scanned string literal is added to the list of tokens with its associated literal value.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn string(&mut self) -> Result<(), String> {
+        let line = self.line;
         while self.peek() != '"' && !self.is_at_end() {
             if self.peek() == '\n' {
                 self.line += 1;
+                self.line_start = self.current + 1;
             }
             self.advance();
         }
         if self.is_at_end() {
-            TronError::throw("E1001", self.current, vec![]);
+            return self.unterminated(line);
         }
         self.advance();
-        let value = &self.source[self.start + 1..self.current - 1];
-        self.add_token_lit(StringLit, Some(StringValue(value.to_string())));
+        let value = self.substring(self.start + 1, self.current - 1).to_string();
+        self.add_token_lit(StringLit, Some(StringValue(value)));
+        Ok(())
+    }
+    /// The `template` method of the `Scanner` struct is responsible for scanning a template literal from the source code.
+    ///
+    /// Template literals are enclosed in backticks (`` ` ``), preserve newlines and may contain `${expression}` interpolations.
+    /// The method emits a `Backtick` token, followed by `StringLit` tokens for the text segments and `DollarBrace ... RightBrace`
+    /// groups for the interpolated expressions, and finishes with a closing `Backtick` token.
+    ///
+    /// # Usage
+    ///
+    /// The `template` method is called internally by the `Scanner` when it encounters a backtick.
+    /// The parser then turns the emitted token group into an `Expression::Template`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let name: string = "Tron";
+    /// @print(`hello ${name},
+    /// you have ${2 + 3} messages`);
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// - `` \` ``, `\$` and `\\` can be used to escape a backtick, a dollar sign and a backslash.
+    /// - If the end of the source code is reached before the closing backtick, an "unterminated string" error is thrown.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn template(&mut self) -> Result<(), String> {
+        let line = self.line;
+        self.push_token(Backtick, "`", None);
+        let mut segment = String::new();
+        loop {
+            if self.is_at_end() {
+                self.unterminated(line)?;
+                break;
+            }
+            match self.advance() {
+                '`' => break,
+                '\\' if matches!(self.peek(), '`' | '$' | '\\') => {
+                    let escaped = self.advance();
+                    segment.push(escaped);
+                }
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.push_segment(&mut segment);
+                    self.interpolation()?;
+                }
+                '\n' => {
+                    self.line += 1;
+                    self.line_start = self.current;
+                    segment.push('\n');
+                }
+                c => segment.push(c),
+            }
+        }
+        self.push_segment(&mut segment);
+        self.push_token(Backtick, "`", None);
+        Ok(())
+    }
+    /// The `interpolation` method of the `Scanner` struct scans the expression of a `${...}` group inside a template literal.
+    ///
+    /// It collects the source up to the matching closing brace (skipping over nested braces and string literals),
+    /// tokenizes it with a nested `Scanner` and splices the resulting tokens between a `DollarBrace` and a `RightBrace` token.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn interpolation(&mut self) -> Result<(), String> {
+        let line = self.line;
+        let column = self.column_at(self.current) - 1;
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut inner = String::new();
+        loop {
+            if self.is_at_end() {
+                self.unterminated(line)?;
+                break;
+            }
+            let c = self.advance();
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') | (None, '`') => quote = Some(c),
+                (None, '{') => depth += 1,
+                (None, '}') if depth == 0 => break,
+                (None, '}') => depth -= 1,
+                _ => {}
+            }
+            if c == '\n' {
+                self.line += 1;
+                self.line_start = self.current;
+            }
+            inner.push(c);
+        }
+        let mut scanner = Scanner::new(&inner);
+        scanner.line = line;
+        scanner.scan()?;
+        // the inner scanner counts columns from the start of the interpolation
+        for token in scanner
+            .tokens
+            .iter_mut()
+            .filter(|token| token.line_number == line)
+        {
+            token.column += column;
+        }
+        for (_, error_line, error_column, _) in &mut scanner.errors {
+            if *error_line == line {
+                *error_column += column;
+            }
+        }
+        self.errors.append(&mut scanner.errors);
+        self.push_token(DollarBrace, "${", None);
+        self.tokens.append(&mut scanner.tokens);
+        self.push_token(RightBrace, "}", None);
         Ok(())
     }
+    /// The `push_segment` method adds the text collected so far in a template literal as a `StringLit` token and clears it.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn push_segment(&mut self, segment: &mut String) {
+        if !segment.is_empty() {
+            let value = std::mem::take(segment);
+            self.push_token(StringLit, &value.clone(), Some(StringValue(value)));
+        }
+    }
+    /// The `push_token` method adds a token with an explicit lexeme to the list of tokens.
+    ///
+    /// Unlike `add_token_lit`, the lexeme is not taken from the source code, which is needed for tokens synthesized while scanning template literals.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn push_token(&mut self, token_type: TokenType, lexeme: &str, literal: Option<LiteralValue>) {
+        let lexeme = self.intern(lexeme);
+        self.tokens.push(Token {
+            token_type,
+            lexeme,
+            literal,
+            line_number: self.line,
+            column: self.column,
+        });
+    }
     /// The `is_at_end` method of the `Scanner` struct checks if the scanner has reached the end of the source code.
     ///
     /// This method is used to determine if there are no more characters left to scan in the source code. It returns `true` if the current position is equal to or greater than the length of the source code, indicating that the end of the source code has been reached.
@@ -1024,12 +1476,13 @@ impl Scanner {
     /// - The method is designed to be safe and will not panic if called at the end of the source code. Instead, it will return a null character (`'\0'`).
     /// - This method is a crucial part of the scanner's functionality, as it allows the scanner to make decisions based on the upcoming characters in the source code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
+        match self.source.as_bytes().get(self.current) {
+            Some(&byte) if byte.is_ascii() => byte as char,
+            Some(_) => self.source[self.current..].chars().next().unwrap_or('\0'),
+            None => '\0',
         }
-        self.source.chars().nth(self.current).unwrap()
     }
     /// The `char_match` method of the `Scanner` struct is used to check if the next character in the source code matches a specified character.
     ///
@@ -1051,12 +1504,12 @@ impl Scanner {
     /// - The method is designed to be safe and will not panic if called at the end of the source code. Instead, it will return `false`.
     /// - This method is a crucial part of the scanner's functionality, as it allows the scanner to make decisions based on the upcoming characters in the source code.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn char_match(&mut self, ch: char) -> bool {
-        if self.is_at_end() || self.source.chars().nth(self.current).unwrap() != ch {
+        if self.is_at_end() || self.peek() != ch {
             return false;
         }
-        self.current += 1;
+        self.current += ch.len_utf8();
         true
     }
     /// The `advance` method of the `Scanner` struct is used to move the current position in the source code to the next character.
@@ -1077,12 +1530,45 @@ impl Scanner {
     /// - The `advance` method is a crucial part of the scanner's functionality, as it allows the scanner to progress through the source code character by character.
     /// - It is used to update the current position in the source code, which is essential for the scanning process.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn advance(&mut self) -> char {
-        let c = self.source.chars().nth(self.current).unwrap();
-        self.current += 1;
+        let c = self.peek();
+        self.current += c.len_utf8();
         c
     }
+    /// The `substring` method of the `Scanner` struct returns the characters of the source code between two positions.
+    ///
+    /// Positions are counted in characters rather than bytes, so multi-byte characters never get split.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn substring(&self, start: usize, end: usize) -> &str {
+        &self.source[start..end]
+    }
+    // the column of a byte offset on the current line, counted in characters from 1
+    fn column_at(&self, offset: usize) -> usize {
+        let line = &self.source[self.line_start..offset];
+        if line.is_ascii() {
+            line.len() + 1
+        } else {
+            line.chars().count() + 1
+        }
+    }
+    /// The `intern` method of the `Scanner` struct returns the shared copy of a token text.
+    ///
+    /// The first time a text is seen it is stored in `interned`; every later token with the same text gets a clone
+    /// of the same `Rc<str>`, which is only a reference count increment.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn intern(&mut self, text: &str) -> Rc<str> {
+        match self.interned.get(text) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned: Rc<str> = Rc::from(text);
+                self.interned.insert(interned.clone());
+                interned
+            }
+        }
+    }
     /// The `add_token` method of the `Scanner` struct is used to add a token to the list of tokens that have been scanned from the source code.
     ///
     /// This method takes a `TokenType` as an argument and creates a new `Token` with the current lexeme and line number. It then adds this token to the `tokens` vector.
@@ -1124,14 +1610,72 @@ impl Scanner {
     /// - The `add_token_lit` method is a crucial part of the scanner's functionality, as it allows the scanner to build a list of tokens with associated literal values that can be used by the parser to construct the abstract syntax tree (AST).
     /// - It is used to ensure that the scanner's output is a sequence of tokens that accurately represents the source code, including any literal values that are part of the tokens.
     ///
-    /// ### Last Updated: (v3.0.0)
+    /// ### Last Updated: (v3.2.0)
     fn add_token_lit(&mut self, token_type: TokenType, literal: Option<LiteralValue>) {
-        let text = self.source[self.start..self.current].to_string();
+        let source = Rc::clone(&self.source);
+        let lexeme = self.intern(&source[self.start..self.current]);
         self.tokens.push(Token {
             token_type,
-            lexeme: text,
+            lexeme,
             literal,
             line_number: self.line,
+            column: self.column,
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the types and lexemes of the tokens of `source`, without the end of the file
+    fn tokens(source: &str) -> Vec<(TokenType, String)> {
+        let mut tokens: Vec<(TokenType, String)> = Scanner::new(source)
+            .scan_tokens()
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.token_type, token.lexeme.to_string()))
+            .collect();
+        tokens.pop();
+        tokens
+    }
+
+    fn number(source: &str) -> f32 {
+        match &Scanner::new(source).scan_tokens().unwrap()[0].literal {
+            Some(NumericValue(number)) => *number,
+            literal => panic!("expected a number, got {:?}", literal),
+        }
+    }
+
+    #[test]
+    fn scans_numbers_in_scientific_notation() {
+        assert_eq!(number("1.5e-3"), 0.0015);
+        assert_eq!(number("6E23"), 6e23);
+        assert_eq!(number("2e+2"), 200.0);
+        assert_eq!(number("12"), 12.0);
+        assert_eq!(
+            tokens("1.5e-3"),
+            [(TokenType::Number, "1.5e-3".to_string())]
+        );
+    }
+
+    #[test]
+    fn an_exponent_needs_digits() {
+        assert_eq!(
+            tokens("2e"),
+            [
+                (TokenType::Number, "2".to_string()),
+                (TokenType::Identifier, "e".to_string())
+            ]
+        );
+        assert_eq!(
+            tokens("2e-x"),
+            [
+                (TokenType::Number, "2".to_string()),
+                (TokenType::Identifier, "e".to_string()),
+                (TokenType::Minus, "-".to_string()),
+                (TokenType::Identifier, "x".to_string())
+            ]
+        );
+    }
+}
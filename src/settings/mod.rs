@@ -0,0 +1,162 @@
+/// The `Settings` struct holds the options that change how a Tron program is interpreted.
+///
+/// Settings are created from the command line flags passed to `tron run` and are shared by every
+/// environment of a program, so they can be read anywhere during the evaluation.
+///
+/// # Fields
+///
+/// - `strict_types`: Disables implicit conversions, e.g. `"count: " + 3` throws an error instead of producing `"count: 3"`.
+/// - `strict_bool`: Requires actual booleans in conditions and logical operators, e.g. `if (0) {}` throws an error instead of being skipped.
+/// - `cache`: Stores the resolved program in a `.tronc` file next to the source and reuses it while the source is unchanged.
+/// - `plugins`: Shared libraries loaded with `--plugin <path>` before the program runs, see `library/plugin/tron_plugin.h`.
+/// - `strict`: Strict mode (`--strict`, or `#strict` in the file): names have to be declared before they are used and
+///   implicit conversions are turned off like with `--strict-types`.
+/// - `warn_coercions`: Reports every implicit conversion that strict mode would reject (a value that isn't a bool used as
+///   a condition, a value added to a string) with a warning, and lets the program go on.
+/// - `allow_infinity`: Lets a division or a remainder by zero produce infinity or NaN like floats do, instead of
+///   reporting `E4024`.
+/// - `lang`: The language of the diagnostics (`--lang fr`), read from the message catalog of the language, see
+///   `utils::locale::select()`. The language of the environment is used when it's left out, and English when there's no
+///   catalog for it.
+/// - `deterministic`: Deterministic mode (`--deterministic`), for golden tests comparing the output of a script byte for
+///   byte: random numbers are seeded with `--seed <n>` (0 by default), `clock()` is frozen at `--epoch <seconds>` (0
+///   by default), the fields of objects are shown sorted, and the input is read from `--input <file>`, see
+///   `utils::deterministic::enable()`. `--seed`, `--epoch` and `--input` turn it on as well.
+/// - `seed`, `epoch` and `input`: The seed, epoch and input file of deterministic mode.
+/// - `record` and `replay`: The cassette the effects of the natives are recorded into with `--record <file>`, or
+///   replayed from with `--replay <file>`, to test scripts that run commands and download files offline, see
+///   `library::effects::perform()`.
+/// - `audit_cmds`: The log every shell command of the script is appended to with `--audit-cmds <file>`, with its
+///   arguments, duration and exit status, see `library::audit::audit_to()`.
+/// - `dry_run`: Shows the commands of `@cmd` and `@spawn_detached` instead of running them (`--dry-run`), see
+///   `library::effects::dry_run()`.
+/// - `ask`: Asks the user before a native runs a command, writes files or accesses the network (`--ask`), see
+///   `library::effects::ask()`.
+///
+/// # Example
+///
+/// ```
+/// tron run main.tron --strict-types
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub strict_types: bool,
+    pub strict_bool: bool,
+    pub cache: bool,
+    pub plugins: Vec<String>,
+    pub strict: bool,
+    pub warn_coercions: bool,
+    pub allow_infinity: bool,
+    pub lang: Option<String>,
+    pub deterministic: bool,
+    pub seed: u64,
+    pub epoch: u64,
+    pub input: Option<String>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub ask: bool,
+    pub audit_cmds: Option<String>,
+    pub dry_run: bool,
+}
+
+impl Settings {
+    /// Creates `Settings` from command line flags.
+    ///
+    /// Unknown flags are reported with `E0004` and ignored.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn from_args(args: &[String]) -> Self {
+        let mut settings = Settings::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--strict-types" => settings.strict_types = true,
+                "--strict-bool" => settings.strict_bool = true,
+                "--cache" => settings.cache = true,
+                "--warn-coercions" => settings.warn_coercions = true,
+                "--allow-infinity" => settings.allow_infinity = true,
+                "--strict" => {
+                    settings.strict = true;
+                    settings.strict_types = true;
+                }
+                "--lang" => match args.next() {
+                    Some(lang) => {
+                        crate::utils::locale::select(lang);
+                        settings.lang = Some(lang.clone());
+                    }
+                    None => crate::utils::TronError::throw("E0004", 0, vec![arg.to_string()]),
+                },
+                "--deterministic" => settings.deterministic = true,
+                "--seed" | "--epoch" => match args.next().and_then(|value| value.parse().ok()) {
+                    Some(value) => {
+                        settings.deterministic = true;
+                        match arg.as_str() {
+                            "--seed" => settings.seed = value,
+                            _ => settings.epoch = value,
+                        }
+                    }
+                    None => crate::utils::TronError::throw("E0004", 0, vec![arg.to_string()]),
+                },
+                "--input" => match args.next() {
+                    Some(path) => {
+                        settings.deterministic = true;
+                        settings.input = Some(path.clone());
+                    }
+                    None => crate::utils::TronError::throw("E0004", 0, vec![arg.to_string()]),
+                },
+                "--ask" => {
+                    settings.ask = true;
+                    crate::library::effects::ask();
+                }
+                "--dry-run" => {
+                    settings.dry_run = true;
+                    crate::library::effects::dry_run();
+                }
+                "--audit-cmds" => match args.next() {
+                    Some(path) => {
+                        if let Err(message) = crate::library::audit::audit_to(path) {
+                            crate::utils::TronError::throw("E0010", 0, vec![path.clone(), message]);
+                        }
+                        settings.audit_cmds = Some(path.clone());
+                    }
+                    None => crate::utils::TronError::throw("E0004", 0, vec![arg.to_string()]),
+                },
+                "--record" | "--replay" => match args.next() {
+                    Some(path) if arg == "--record" => settings.record = Some(path.clone()),
+                    Some(path) => settings.replay = Some(path.clone()),
+                    None => crate::utils::TronError::throw("E0004", 0, vec![arg.to_string()]),
+                },
+                "--plugin" => match args.next() {
+                    Some(path) => settings.plugins.push(path.clone()),
+                    None => crate::utils::TronError::throw("E0004", 0, vec![arg.to_string()]),
+                },
+                flag => crate::utils::TronError::throw("E0004", 0, vec![flag.to_string()]),
+            }
+        }
+        if settings.deterministic {
+            let input = settings.input.as_deref();
+            if let Err(message) =
+                crate::utils::deterministic::enable(settings.seed, settings.epoch, input)
+            {
+                crate::utils::TronError::throw(
+                    "E0011",
+                    0,
+                    vec![input.unwrap_or_default().to_string(), message],
+                );
+            }
+        }
+        if let Some(path) = &settings.record {
+            if let Err(message) = crate::library::effects::record(path) {
+                crate::utils::TronError::throw("E0010", 0, vec![path.clone(), message]);
+            }
+        }
+        if let Some(path) = &settings.replay {
+            if let Err(message) = crate::library::effects::replay(path) {
+                crate::utils::TronError::throw("E0011", 0, vec![path.clone(), message]);
+            }
+        }
+        settings
+    }
+}
@@ -0,0 +1,564 @@
+use crate::expressions::{Expression, TronType};
+use crate::library::registry::NativeRegistry;
+use crate::scanner::{Statement, Token, TokenType};
+use crate::utils::TronError;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// The static type of an expression, as far as the type checker can tell.
+///
+/// `Any` is the type of everything the checker can't know, like unannotated parameters, values read from arrays and
+/// objects or names imported from other files. It is compatible with every other type, which is what makes the
+/// annotations gradual: code without them is never reported.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Number,
+    String,
+    Bool,
+    Array,
+    Object,
+    Null,
+    Function(Option<Rc<Signature>>),
+    Any,
+}
+
+/// The parameter and output types of a function, from its annotations.
+///
+/// # Fields
+///
+/// - `name`: The name of the function, used in the messages of the type checker.
+/// - `params`: The name and type of every parameter, `Any` when a parameter isn't annotated.
+/// - `output`: The type the function is declared to return, `Any` when it isn't annotated.
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug, PartialEq)]
+pub struct Signature {
+    name: Rc<str>,
+    params: Vec<(Rc<str>, Type)>,
+    output: Type,
+}
+
+impl Type {
+    /// Returns the type of an annotation, `None` when it doesn't name a type.
+    ///
+    /// Annotations are type names (`bool` and `boolean` are the same type) or literals, like `let answer: 42 = 42;`,
+    /// which have the type of the literal.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn from_annotation(annotation: &Token) -> Option<Type> {
+        match annotation.token_type {
+            TokenType::Number => return Some(Type::Number),
+            TokenType::StringLit => return Some(Type::String),
+            _ => {}
+        }
+        match &*annotation.lexeme {
+            "number" => Some(Type::Number),
+            "string" => Some(Type::String),
+            "bool" | "boolean" => Some(Type::Bool),
+            "array" => Some(Type::Array),
+            "object" => Some(Type::Object),
+            "null" => Some(Type::Null),
+            "function" => Some(Type::Function(None)),
+            "any" => Some(Type::Any),
+            _ => None,
+        }
+    }
+    fn of_value(value: &TronType) -> Type {
+        match value {
+            TronType::Number(_) => Type::Number,
+            TronType::StringValue(_) => Type::String,
+            TronType::True | TronType::False => Type::Bool,
+            TronType::Null => Type::Null,
+            TronType::ArrayValue(_) => Type::Array,
+            TronType::Object(_) => Type::Object,
+            TronType::Callable(_) => Type::Function(None),
+            // there is no annotation for big integers, decimals, sets, deques and buffers
+            #[cfg(feature = "bigint")]
+            TronType::BigInt(_) | TronType::Decimal(_) => Type::Any,
+            TronType::Set(_) | TronType::Deque(_) | TronType::Buffer(_) => Type::Any,
+        }
+    }
+    // whether a value of type `other` can be stored where `self` is expected
+    fn accepts(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Function(_), Type::Function(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Bool => "bool",
+            Type::Array => "array",
+            Type::Object => "object",
+            Type::Null => "null",
+            Type::Function(_) => "function",
+            Type::Any => "any",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// a variable in scope: its type, whether the type comes from an annotation or from the value it holds, and the depth of
+// the function it's declared in
+#[derive(Debug, Clone)]
+struct Binding {
+    value_type: Type,
+    annotated: bool,
+    function: usize,
+}
+
+/// The `TypeChecker` struct verifies the type annotations of a program without running it, for `tron check`.
+///
+/// It checks that variables are declared and assigned values of their annotated type, that functions are called with
+/// arguments of the annotated parameter types and that they return their annotated output type. Variables without an
+/// annotation take the type of the value they are declared with, so `let count = 0;` is a number until it's assigned
+/// a value of another type, after which the checker stops assuming anything about it.
+///
+/// The interpreter ignores annotations, so a program with type errors still runs.
+///
+/// # Fields
+///
+/// - `scopes`: A stack of scopes, the first one being the top level, mapping variable names to their binding.
+/// - `natives`: The output type of every documented native, from its signature.
+/// - `returns`: The signatures of the functions enclosing the statement being checked, from the outermost to the
+///   innermost one.
+/// - `problems`: The number of type errors found so far.
+///
+/// # Example
+///
+/// ```
+/// let interpreter = Interpreter::new();
+/// let natives = interpreter.environment.natives.borrow();
+/// let problems = TypeChecker::new(&natives).check(&statements);
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+#[derive(Debug)]
+pub struct TypeChecker {
+    scopes: Vec<HashMap<Rc<str>, Binding>>,
+    natives: HashMap<Rc<str>, Type>,
+    returns: Vec<Rc<Signature>>,
+    problems: usize,
+}
+
+impl TypeChecker {
+    /// Creates a type checker that knows the output types of the natives documented in `natives`.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn new(natives: &NativeRegistry) -> Self {
+        let natives = natives
+            .natives()
+            .into_iter()
+            .filter_map(|(name, _)| {
+                let doc = natives.doc(&name)?;
+                let (_, output) = doc.signature.rsplit_once("): ")?;
+                let output = match output {
+                    "number" => Type::Number,
+                    "string" => Type::String,
+                    "bool" => Type::Bool,
+                    "array" => Type::Array,
+                    "null" => Type::Null,
+                    _ => Type::Any,
+                };
+                Some((name, output))
+            })
+            .collect();
+        Self {
+            scopes: vec![HashMap::new()],
+            natives,
+            returns: vec![],
+            problems: 0,
+        }
+    }
+    /// Checks the annotations of a program, reporting every type error found.
+    ///
+    /// # Return Value
+    ///
+    /// The number of type errors found.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn check(mut self, statements: &[Statement]) -> usize {
+        for statement in statements {
+            self.statement(statement);
+        }
+        self.problems
+    }
+    fn report(&mut self, code: &str, token: &Token, args: Vec<String>) {
+        self.problems += 1;
+        TronError::throw_at(code, token.line_number, token.column, args);
+    }
+    fn annotation(&mut self, annotation: &Token) -> Type {
+        match Type::from_annotation(annotation) {
+            Some(annotated) => annotated,
+            None => {
+                self.report("E5003", annotation, vec![annotation.lexeme.to_string()]);
+                Type::Any
+            }
+        }
+    }
+    fn signature(
+        &mut self,
+        name: &Token,
+        params: &[(Token, Token)],
+        output: &Token,
+    ) -> Rc<Signature> {
+        let params = params
+            .iter()
+            .map(|(param, annotation)| (param.lexeme.clone(), self.annotation(annotation)))
+            .collect();
+        Rc::new(Signature {
+            name: name.lexeme.clone(),
+            params,
+            output: self.annotation(output),
+        })
+    }
+    fn declare(&mut self, name: &Token, value_type: Type, annotated: bool) {
+        let binding = Binding {
+            value_type,
+            annotated,
+            function: self.returns.len(),
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), binding);
+        }
+    }
+    fn lookup(&self, name: &str) -> Type {
+        match self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            // a function can run after the variable was assigned a value of another type, only annotations hold there
+            Some(binding) if !binding.annotated && binding.function != self.returns.len() => {
+                Type::Any
+            }
+            Some(binding) => binding.value_type.clone(),
+            None => Type::Any,
+        }
+    }
+    fn block<'a>(&mut self, statements: impl IntoIterator<Item = &'a Statement>) {
+        self.scopes.push(HashMap::new());
+        for statement in statements {
+            self.statement(statement);
+        }
+        self.scopes.pop();
+    }
+    fn function(&mut self, signature: Rc<Signature>, body: &[Rc<Statement>]) {
+        let mut scope = HashMap::new();
+        for (name, value_type) in &signature.params {
+            let binding = Binding {
+                value_type: value_type.clone(),
+                annotated: true,
+                function: self.returns.len() + 1,
+            };
+            scope.insert(name.clone(), binding);
+        }
+        self.scopes.push(scope);
+        self.returns.push(signature);
+        for statement in body {
+            self.statement(statement);
+        }
+        self.returns.pop();
+        self.scopes.pop();
+    }
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expression { expression, .. } | Statement::Use { expression, .. } => {
+                self.expression(expression);
+            }
+            Statement::Variable {
+                name,
+                value_type,
+                value,
+                ..
+            } => {
+                let actual = self.expression(value);
+                match self.annotation(value_type) {
+                    Type::Any => self.declare(name, actual, false),
+                    declared => {
+                        if !declared.accepts(&actual) {
+                            let args = vec![
+                                "variable".to_string(),
+                                name.lexeme.to_string(),
+                                declared.to_string(),
+                                actual.to_string(),
+                            ];
+                            self.report("E5001", name, args);
+                        }
+                        self.declare(name, declared, true);
+                    }
+                }
+            }
+            Statement::Block { statements, .. } => {
+                self.block(statements.iter().map(|statement| statement.as_ref()))
+            }
+            Statement::While {
+                conditions,
+                body,
+                increment,
+                ..
+            } => {
+                for condition in conditions {
+                    self.expression(condition);
+                }
+                self.statement(body);
+                if let Some(increment) = increment {
+                    self.expression(increment);
+                }
+            }
+            Statement::DoWhile {
+                body, conditions, ..
+            } => {
+                self.statement(body);
+                for condition in conditions {
+                    self.expression(condition);
+                }
+            }
+            Statement::If {
+                conditions,
+                then_branch,
+                elif_branches,
+                else_branch,
+                ..
+            } => {
+                for condition in conditions {
+                    self.expression(condition);
+                }
+                self.statement(then_branch);
+                for (conditions, branch) in elif_branches {
+                    for condition in conditions {
+                        self.expression(condition);
+                    }
+                    self.statement(branch);
+                }
+                if let Some(branch) = else_branch {
+                    self.statement(branch);
+                }
+            }
+            Statement::Function {
+                name,
+                params,
+                body,
+                output_type,
+                ..
+            } => {
+                let signature = self.signature(name, params, output_type);
+                // declared before the body is checked, so recursive calls are checked too
+                self.declare(name, Type::Function(Some(signature.clone())), true);
+                self.function(signature, body);
+            }
+            Statement::Return { keyword, value, .. } => {
+                let actual = match value {
+                    Some(value) => self.expression(value),
+                    None => Type::Null,
+                };
+                let Some(signature) = self.returns.last().cloned() else {
+                    return;
+                };
+                if !signature.output.accepts(&actual) {
+                    let args = vec![
+                        "return value of".to_string(),
+                        format!("{}()", signature.name),
+                        signature.output.to_string(),
+                        actual.to_string(),
+                    ];
+                    self.report("E5001", keyword, args);
+                }
+            }
+            Statement::Break { .. } | Statement::Continue { .. } => {}
+            Statement::Switch {
+                condition,
+                case_branches,
+                default_branch,
+                ..
+            } => {
+                self.expression(condition);
+                for (case, branch) in case_branches {
+                    self.expression(case);
+                    self.block(branch.iter());
+                }
+                if let Some(branch) = default_branch {
+                    self.block(branch.iter());
+                }
+            }
+        }
+    }
+    fn expression(&mut self, expression: &Expression) -> Type {
+        match expression {
+            Expression::Literal { value, .. } => Type::of_value(value),
+            Expression::Object { properties, .. } => {
+                for (_, value) in properties {
+                    self.expression(value);
+                }
+                Type::Object
+            }
+            Expression::Array { elements, .. } => {
+                for element in elements {
+                    self.expression(element);
+                }
+                Type::Array
+            }
+            Expression::ObjectCall { .. } => Type::Any,
+            Expression::OptionalGet { object, .. } => {
+                self.expression(object);
+                Type::Any
+            }
+            Expression::Index { object, index, .. } => {
+                self.expression(object);
+                self.expression(index);
+                Type::Any
+            }
+            Expression::Grouping { expression, .. } => self.expression(expression),
+            Expression::Template { parts, .. } => {
+                for part in parts {
+                    self.expression(part);
+                }
+                Type::String
+            }
+            Expression::Comparison { operands, .. } => {
+                for operand in operands {
+                    self.expression(operand);
+                }
+                Type::Bool
+            }
+            Expression::Logical {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.expression(left);
+                let right = self.expression(right);
+                match operator.token_type {
+                    TokenType::Nor => Type::Bool,
+                    // the other operators return one of their operands
+                    _ if left == right => left,
+                    _ => Type::Any,
+                }
+            }
+            Expression::Unary {
+                operator, right, ..
+            } => {
+                let right = self.expression(right);
+                match (operator.token_type, right) {
+                    (TokenType::Bang, _) => Type::Bool,
+                    (_, Type::Number) => Type::Number,
+                    _ => Type::Any,
+                }
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.expression(left);
+                let right = self.expression(right);
+                binary_type(&left, operator.token_type, &right)
+            }
+            Expression::Variable { name, .. } => self.lookup(&name.lexeme),
+            Expression::Assign { name, value, .. } => {
+                let actual = self.expression(value);
+                let function = self.returns.len();
+                let binding = self
+                    .scopes
+                    .iter_mut()
+                    .rev()
+                    .find_map(|scope| scope.get_mut(&name.lexeme));
+                let declared = match binding {
+                    Some(binding) if binding.annotated => binding.value_type.clone(),
+                    // the variable can hold values of different types from here on
+                    Some(binding)
+                        if binding.value_type != actual || binding.function != function =>
+                    {
+                        binding.value_type = Type::Any;
+                        Type::Any
+                    }
+                    _ => Type::Any,
+                };
+                if !declared.accepts(&actual) {
+                    let args = vec![
+                        "variable".to_string(),
+                        name.lexeme.to_string(),
+                        declared.to_string(),
+                        actual.to_string(),
+                    ];
+                    self.report("E5001", name, args);
+                }
+                actual
+            }
+            Expression::Call {
+                callee,
+                paren,
+                arguments,
+                ..
+            } => {
+                let arguments: Vec<Type> = arguments.iter().map(|a| self.expression(a)).collect();
+                let callee = match &**callee {
+                    Expression::Variable { name, .. }
+                        if self.natives.contains_key(&name.lexeme)
+                            && !self.scopes.iter().any(|s| s.contains_key(&name.lexeme)) =>
+                    {
+                        return self.natives[&name.lexeme].clone();
+                    }
+                    callee => self.expression(callee),
+                };
+                let Type::Function(Some(signature)) = callee else {
+                    return Type::Any;
+                };
+                if signature.params.len() != arguments.len() {
+                    let args = vec![
+                        signature.name.to_string(),
+                        signature.params.len().to_string(),
+                        arguments.len().to_string(),
+                    ];
+                    self.report("E5002", paren, args);
+                    return signature.output.clone();
+                }
+                for ((param, expected), actual) in signature.params.iter().zip(&arguments) {
+                    if !expected.accepts(actual) {
+                        let args = vec![
+                            "argument".to_string(),
+                            format!("{} of {}()", param, signature.name),
+                            expected.to_string(),
+                            actual.to_string(),
+                        ];
+                        self.report("E5001", paren, args);
+                    }
+                }
+                signature.output.clone()
+            }
+            Expression::Function {
+                name,
+                params,
+                body,
+                output_type,
+                ..
+            } => {
+                let signature = self.signature(name, params, output_type);
+                self.function(signature.clone(), body);
+                Type::Function(Some(signature))
+            }
+        }
+    }
+}
+
+// the type of a binary operation, following `binary_operation()` of the interpreter
+fn binary_type(left: &Type, operator: TokenType, right: &Type) -> Type {
+    use TokenType::*;
+    match (left, operator, right) {
+        (_, Greater | GreaterEqual | Less | LessEqual | EqualEqual | BangEqual, _) => Type::Bool,
+        (_, GreaterGreater, _) => Type::Function(None),
+        (Type::Number, Plus | Minus | Star | Slash | Percent, Type::Number) => Type::Number,
+        (Type::String, Plus, _) | (_, Plus, Type::String) => Type::String,
+        (Type::Array, Plus, Type::Array) => Type::Array,
+        (Type::String, Star, Type::Number) | (Type::Number, Star, Type::String) => Type::String,
+        (Type::Array, Star, Type::Number) | (Type::Number, Star, Type::Array) => Type::Array,
+        _ => Type::Any,
+    }
+}
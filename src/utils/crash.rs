@@ -0,0 +1,183 @@
+use super::sources;
+use crate::environment::Environment;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    // the id of the expression evaluated last, `None` before the first one
+    static NODE: Cell<Option<usize>> = const { Cell::new(None) };
+    // the names of the Tron functions running, the innermost last
+    static CALLS: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+    // the global environment of the program running
+    static ENVIRONMENT: RefCell<Option<Environment>> = const { RefCell::new(None) };
+}
+
+/// Installs the crash reporter, which replaces the message Rust prints when the interpreter panics.
+///
+/// A panic is a bug of Tron, not of the script, so instead of a Rust backtrace the user gets a short message and a
+/// report in `tron-crash-<time>-<pid>.log`, in the current directory or the temporary one when it can't be written.
+/// The report has what makes a bug report actionable without sending anything anywhere:
+///
+/// - the version of Tron, the platform and the command line,
+/// - the panic message and where it happened in the interpreter,
+/// - the script and the files it imported, with the one running at the time,
+/// - the id of the expression being evaluated, numbered in the order the parser read them, and the Tron functions
+///   running,
+/// - a summary of the state of the interpreter, its settings and the number of globals,
+/// - the Rust backtrace.
+///
+/// `main()` catches the panic afterwards to clean up and exit with `101`, like Rust does.
+///
+/// Printing to a pipe that was closed, like `tron run script.tron | head`, isn't a crash: Rust panics on it since it
+/// ignores `SIGPIPE`, and that panic is let through quietly, see `is_broken_pipe()`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn install() {
+    panic::set_hook(Box::new(|info| {
+        if is_broken_pipe(info.payload()) {
+            return;
+        }
+        let report = report(info);
+        let name = format!(
+            "tron-crash-{}-{}.log",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+            process::id()
+        );
+        let written = [PathBuf::from(&name), std::env::temp_dir().join(&name)]
+            .into_iter()
+            .find(|path| fs::write(path, &report).is_ok());
+        eprintln!(
+            "\x1B[91;1mTron crashed\x1B[0m, this is a bug of the interpreter and not of your script: {}",
+            message(info)
+        );
+        match written {
+            Some(path) => eprintln!(
+                "A crash report was written to {}, please attach it to a bug report.",
+                path.display()
+            ),
+            None => eprintln!("The crash report couldn't be written:\n\n{}", report),
+        }
+    }));
+}
+
+/// Records the expression the interpreter evaluates, called by `Expression::evaluate()`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn evaluating(id: usize) {
+    NODE.with(|node| node.set(Some(id)));
+}
+
+/// Records a call to a Tron function, until the `leave_call()` after it returns.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn enter_call(name: &str) {
+    CALLS.with(|calls| calls.borrow_mut().push(name.to_string()));
+}
+
+/// Records that the Tron function called last returned.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn leave_call() {
+    CALLS.with(|calls| calls.borrow_mut().pop());
+}
+
+/// Records the global environment of the program, which the report summarizes.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn watch(environment: &Environment) {
+    ENVIRONMENT.with(|watched| *watched.borrow_mut() = Some(environment.clone()));
+}
+
+/// Returns whether a panic comes from `print!()` or `println!()` writing to a closed pipe, which `main()` ends quietly
+/// with `141`, the status of a program killed by `SIGPIPE`, instead of reporting a crash.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn is_broken_pipe(payload: &(dyn Any + Send)) -> bool {
+    // the panic message of std has the message of `EPIPE`, like `failed printing to stdout: Broken pipe (os error 32)`
+    let message = payload_message(payload);
+    message.starts_with("failed printing to std") && message.contains("Broken pipe")
+}
+
+// the message the code panicked with
+fn message(info: &PanicHookInfo) -> String {
+    let message = payload_message(info.payload());
+    match info.location() {
+        Some(location) => format!("{} at {}", message, location),
+        None => message,
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn report(info: &PanicHookInfo) -> String {
+    let mut report = format!(
+        "Tron v{} crash report\n\nplatform: {}-{}\ncommand: {}\npanic: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::args().collect::<Vec<_>>().join(" "),
+        message(info)
+    );
+    let files = sources::names();
+    let current = sources::current();
+    report.push_str(&format!(
+        "\nscript: {}\nrunning: {}\nfiles: {}\n",
+        files.first().map_or("none", String::as_str),
+        current
+            .checked_sub(1)
+            .and_then(|index| files.get(index))
+            .map_or("none", String::as_str),
+        files.join(", ")
+    ));
+    let node = NODE.with(|node| node.get());
+    report.push_str(&format!(
+        "node: {}\n",
+        node.map_or("none".to_string(), |id| id.to_string())
+    ));
+    let calls = CALLS.with(|calls| calls.try_borrow().map(|calls| calls.clone()));
+    report.push_str(&format!(
+        "calls: {}\n",
+        match calls {
+            Ok(calls) if calls.is_empty() => "none".to_string(),
+            Ok(calls) => calls.join(" > "),
+            Err(_) => "unknown".to_string(),
+        }
+    ));
+    report.push_str(&ENVIRONMENT.with(|environment| {
+        let environment = environment.try_borrow().ok();
+        match environment
+            .as_ref()
+            .and_then(|environment| environment.as_ref())
+        {
+            Some(environment) => format!(
+                "globals: {}\nsettings: {}\n",
+                environment
+                    .values
+                    .try_borrow()
+                    .map_or("unknown".to_string(), |values| values.len().to_string()),
+                environment
+                    .settings
+                    .try_borrow()
+                    .map_or("unknown".to_string(), |settings| format!("{:?}", settings))
+            ),
+            None => "globals: none\nsettings: none\n".to_string(),
+        }
+    }));
+    report.push_str(&format!("\nbacktrace:\n{}\n", Backtrace::force_capture()));
+    report
+}
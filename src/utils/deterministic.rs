@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, stdin};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The state of deterministic mode, turned on with `--deterministic`.
+///
+/// # Fields
+///
+/// - `random`: The state of the random number generator, starting from the seed.
+/// - `epoch`: The time `clock()` is frozen at, in seconds since 1970.
+/// - `input`: The lines left of the file standing for the standard input, with their line breaks.
+///
+/// ### Last Updated: (v3.2.0)
+struct State {
+    random: u64,
+    epoch: u64,
+    input: VecDeque<String>,
+}
+
+thread_local! {
+    // `None` while the program runs normally
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// Turns deterministic mode on, so a script prints the same bytes on every run and its output can be compared with a
+/// golden file:
+///
+/// - the random numbers (`@random()`, the names of temporary files, the keys of websockets) come from a generator
+///   seeded with `seed`,
+/// - `clock()` always returns `epoch`,
+/// - the fields of objects are shown sorted by name instead of in the order of the hash map holding them,
+/// - the input (`@ask`, `@prompt`, ...) is read from the lines of the file `input`, and has ended once they're read
+///   or when there's no file, so a test never waits for a user.
+///
+/// # Return Value
+///
+/// An error message when the input file can't be read, deterministic mode being on without an input then.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn enable(seed: u64, epoch: u64, input: Option<&str>) -> Result<(), String> {
+    let lines = match input {
+        Some(path) => std::fs::read_to_string(path).map_err(|error| error.to_string()),
+        None => Ok(String::new()),
+    };
+    STATE.with(|state| {
+        *state.borrow_mut() = Some(State {
+            random: seed,
+            epoch,
+            input: lines
+                .as_deref()
+                .unwrap_or_default()
+                .split_inclusive('\n')
+                .map(String::from)
+                .collect(),
+        })
+    });
+    lines.map(|_| ())
+}
+
+/// Returns whether deterministic mode is on.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn is_enabled() -> bool {
+    STATE.with(|state| state.borrow().is_some())
+}
+
+/// Returns the time since 1970, frozen at the epoch of deterministic mode when it's on.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn now() -> Duration {
+    match STATE.with(|state| state.borrow().as_ref().map(|state| state.epoch)) {
+        Some(epoch) => Duration::from_secs(epoch),
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns 64 random bits, from the seeded generator in deterministic mode and from the random keys std seeds its hash
+/// maps with otherwise. Neither is fit for cryptography.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn random() -> u64 {
+    STATE.with(|state| match state.borrow_mut().as_mut() {
+        // splitmix64
+        Some(state) => {
+            state.random = state.random.wrapping_add(0x9e3779b97f4a7c15);
+            let mut bits = state.random;
+            bits = (bits ^ (bits >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            bits = (bits ^ (bits >> 27)).wrapping_mul(0x94d049bb133111eb);
+            bits ^ (bits >> 31)
+        }
+        None => RandomState::new().build_hasher().finish(),
+    })
+}
+
+/// Reads a line of the input into `line` like `Stdin::read_line()`, from the input file in deterministic mode.
+///
+/// # Return Value
+///
+/// The number of bytes read, 0 once the input has ended.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn read_line(line: &mut String) -> io::Result<usize> {
+    let stubbed = STATE.with(|state| {
+        state
+            .borrow_mut()
+            .as_mut()
+            .map(|state| state.input.pop_front().unwrap_or_default())
+    });
+    match stubbed {
+        Some(stubbed) => {
+            line.push_str(&stubbed);
+            Ok(stubbed.len())
+        }
+        None => stdin().read_line(line),
+    }
+}
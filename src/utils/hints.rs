@@ -0,0 +1,236 @@
+use super::locale;
+use crate::scanner::{Token, TokenType};
+
+/// Prints a `hint:` line under the diagnostic printed last, telling a beginner how to fix it.
+///
+/// Hints are named like the codes of the diagnostics, and a message catalog translates them as `hint.<name>`, see
+/// `locale::select()`:
+///
+/// - `semicolon`: a `;` is missing between two statements on the same line, after `{0}` and before `{1}`.
+/// - `unclosed`: the bracket `{0}` opened on line `{1}` is never closed, with `{2}` the one that closes it.
+/// - `assignment-condition`: `{0}` is assigned in a condition where it was probably compared.
+/// - `not-callable`: `{0}` is called while it holds a `{1}`.
+/// - `not-declared`: `{0}` is called while nothing is declared with that name.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn show(name: &str, args: Vec<String>) {
+    let message = match (name, args.as_slice()) {
+        ("semicolon", [after, before]) => format!(
+            "add a ';' after '{}' to end the statement before '{}', or put them on separate lines",
+            after, before
+        ),
+        ("unclosed", [open, line, close]) => format!(
+            "the '{}' on line {} is never closed, add a '{}' where it ends",
+            open, line, close
+        ),
+        ("assignment-condition", [variable]) => {
+            format!("'=' gives {} a new value, use '==' to compare it", variable)
+        }
+        ("not-callable", [name, type_name]) => format!(
+            "{} is a {}, only functions can be called with ( )",
+            name, type_name
+        ),
+        ("not-declared", [name]) => format!(
+            "{} isn't declared, declare it with 'fn {}() {{ ... }}' before calling it",
+            name, name
+        ),
+        _ => return,
+    };
+    let message = locale::translate(&format!("hint.{}", name), &args).unwrap_or(message);
+    eprintln!("  \x1B[96;1mhint:\x1B[0m {}", message);
+}
+
+/// The hint for a call of `name` that isn't a function, as the name and arguments `show()` takes.
+///
+/// `type_name` is the type of the value `name` holds, or `None` when nothing is declared with that name, which is
+/// a different mistake than calling a variable that holds something else, like a typo in the name of a function.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn not_callable(name: &str, type_name: Option<&str>) -> (&'static str, Vec<String>) {
+    match type_name {
+        Some(type_name) => (
+            "not-callable",
+            vec![name.to_string(), type_name.to_string()],
+        ),
+        None => ("not-declared", vec![name.to_string()]),
+    }
+}
+
+/// The hint engine of the parser, finding the hint for a token that was expected but not found when the tokens around
+/// it tell what went wrong, as the name and arguments `show()` takes.
+///
+/// - A missing `;` with another statement starting on the same line asks for the `;` between them, unless the token
+///   before is a `;` or an operator, where no statement ended, or the tokens are a number typed halfway, like `1e5`
+///   written `1e 5`.
+/// - A missing closing bracket points at the bracket it should close, the innermost one left open before `current`,
+///   which can be many lines above where the parser noticed, like a `{` whose `}` was forgotten at the end of a file.
+///
+/// # Parameters
+///
+/// - `expected`: The type of token the parser expected.
+/// - `tokens`: The tokens of the source.
+/// - `current`: The index of the token found instead.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn missing_token(
+    expected: TokenType,
+    tokens: &[Token],
+    current: usize,
+) -> Option<(&'static str, Vec<String>)> {
+    let found = tokens.get(current)?;
+    match expected {
+        TokenType::Semicolon => {
+            let previous = tokens.get(current.checked_sub(1)?)?;
+            if previous.line_number != found.line_number
+                || found.token_type == TokenType::Eof
+                || expects_operand(previous.token_type)
+                || partial_literal(&tokens[..=current])
+            {
+                return None;
+            }
+            Some((
+                "semicolon",
+                vec![previous.lexeme.to_string(), found.lexeme.to_string()],
+            ))
+        }
+        TokenType::RightBrace | TokenType::RightParen | TokenType::RightBracket => {
+            let open = unclosed(&tokens[..current])?;
+            let close = match open.token_type {
+                TokenType::LeftParen => ")",
+                TokenType::LeftBracket => "]",
+                _ => "}",
+            };
+            Some((
+                "unclosed",
+                vec![
+                    open.lexeme.to_string(),
+                    open.line_number.to_string(),
+                    close.to_string(),
+                ],
+            ))
+        }
+        _ => None,
+    }
+}
+
+// a `;` or an operator still waits for what follows it, it can't end a statement
+fn expects_operand(token_type: TokenType) -> bool {
+    use TokenType::*;
+    matches!(
+        token_type,
+        Semicolon
+            | Comma
+            | Dot
+            | Minus
+            | Plus
+            | Slash
+            | Star
+            | Power
+            | Percent
+            | Bang
+            | BangEqual
+            | Equal
+            | EqualEqual
+            | Greater
+            | GreaterEqual
+            | GreaterGreater
+            | Less
+            | LessEqual
+            | And
+            | Or
+            | Nor
+            | Xor
+            | Question
+            | QuestionQuestion
+            | QuestionDot
+            | Line
+    )
+}
+
+// whether the last tokens are a number with letters or a dot run into it, like `1e` or `0x`, which the scanner split
+fn partial_literal(tokens: &[Token]) -> bool {
+    let touching = |left: &Token, right: &Token| {
+        left.line_number == right.line_number
+            && right.column == left.column + left.lexeme.chars().count()
+    };
+    tokens.windows(2).rev().take(2).any(|pair| {
+        pair[0].token_type == TokenType::Number
+            && matches!(pair[1].token_type, TokenType::Identifier | TokenType::Dot)
+            && touching(&pair[0], &pair[1])
+    })
+}
+
+// the innermost bracket left open by the tokens
+fn unclosed(tokens: &[Token]) -> Option<&Token> {
+    let mut open = vec![];
+    for token in tokens {
+        match token.token_type {
+            TokenType::LeftBrace
+            | TokenType::LeftParen
+            | TokenType::LeftBracket
+            | TokenType::DollarBrace => open.push(token),
+            TokenType::RightBrace | TokenType::RightParen | TokenType::RightBracket => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open.pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    // the hint for a `;` missing before the token at `index` of the source
+    fn semicolon_hint(source: &str, index: usize) -> Option<Vec<String>> {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        missing_token(TokenType::Semicolon, &tokens, index).map(|(_, args)| args)
+    }
+
+    #[test]
+    fn two_statements_on_a_line_get_the_hint() {
+        // `let x = 1 let`, the second `let` is token 4
+        assert_eq!(
+            semicolon_hint("let x = 1 let y = 2;", 4),
+            Some(vec!["1".to_string(), "let".to_string()])
+        );
+    }
+
+    #[test]
+    fn no_hint_after_a_semicolon_or_an_operator() {
+        assert_eq!(semicolon_hint("let x = 1;; 2", 5), None);
+        assert_eq!(semicolon_hint("let x = 1 + let", 5), None);
+        assert_eq!(semicolon_hint("let x = a ?? 2", 5), None);
+    }
+
+    #[test]
+    fn no_hint_inside_a_number_typed_halfway() {
+        // `1e` scans as `1` and `e`, then `5`
+        assert_eq!(semicolon_hint("let x = 1e 5;", 4), None);
+        assert_eq!(semicolon_hint("let x = 1e 5;", 5), None);
+        assert_eq!(semicolon_hint("let x = 0x;", 4), None);
+        // with a space, `e` is a name of its own
+        assert!(semicolon_hint("let x = 1 e;", 4).is_some());
+    }
+
+    #[test]
+    fn calling_a_value_names_its_type() {
+        assert_eq!(
+            not_callable("count", Some("number")),
+            (
+                "not-callable",
+                vec!["count".to_string(), "number".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn calling_an_undeclared_name_says_it_isnt_declared() {
+        assert_eq!(
+            not_callable("prnt", None),
+            ("not-declared", vec!["prnt".to_string()])
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::project::parse_sections;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// the directory of the message catalogs, e.g. `locales/fr.toml`
+const LOCALES_DIR: &str = "locales";
+// where an installation keeps the catalogs, relative to the directory of the executable, e.g. `/usr/local/bin`
+const SHARED_LOCALES_DIR: &str = "../share/tron/locales";
+
+thread_local! {
+    // the messages of the selected language by code, `None` until the language is selected
+    static CATALOG: RefCell<Option<BTreeMap<String, String>>> = const { RefCell::new(None) };
+}
+
+/// Selects the language of the diagnostics, with the `--lang` flag of `tron run`.
+///
+/// The messages of a language other than English are read from a catalog, `<lang>.toml`, found in the first of:
+///
+/// - the `locales` directory of the current directory, for a project shipping its own catalogs;
+/// - the directory named by the `TRON_LOCALES` environment variable;
+/// - the `locales` directory next to the `tron` executable, or `share/tron/locales` in the prefix it's installed in,
+///   like `/usr/local/share/tron/locales` for `/usr/local/bin/tron`.
+///
+/// A catalog is a `tron.toml`-like file mapping codes to messages, with `{0}`, `{1}`, ... standing for the arguments
+/// of the message in the order of `errors.md`, so they can be moved around. Besides the codes of errors and warnings,
+/// `E4011.suggestion` is the form of `E4011` with a suggestion, `expected.<name>` the token the parser expected in an
+/// `E2003` (see `parser::expected_message()`), and `hint.<name>` a hint (see `hints::show()`):
+///
+/// ```toml
+/// [errors]
+/// E4011 = "la variable {0} n'a pas été déclarée"
+/// "E4011.suggestion" = "la variable {0} n'a pas été déclarée, vouliez-vous dire {1} ?"
+/// "expected.semicolon-after-expression" = "';' attendu après l'expression"
+///
+/// [warnings]
+/// W3002 = "{0} masque une variable d'une portée englobante"
+/// ```
+///
+/// The codes a catalog doesn't have keep their English message. A language asked for explicitly without a catalog is
+/// reported with `E0011`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn select(language: &str) {
+    let catalog = match load(language) {
+        Ok(catalog) => catalog,
+        Err(message) => {
+            CATALOG.with(|catalog| *catalog.borrow_mut() = Some(BTreeMap::new()));
+            super::TronError::throw(
+                "E0011",
+                0,
+                vec![catalog_path(language).display().to_string(), message],
+            );
+            return;
+        }
+    };
+    CATALOG.with(|selected| *selected.borrow_mut() = Some(catalog));
+}
+
+/// Returns the message of a code in the selected language, `None` when it's English or the catalog doesn't have it.
+///
+/// Without `--lang`, the language is the one of the environment (`LC_ALL`, `LC_MESSAGES` or `LANG`, like
+/// `fr_FR.UTF-8`), and its catalog is used when there's one: `fr_FR.toml` first, then `fr.toml`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn translate(code: &str, args: &[String]) -> Option<String> {
+    CATALOG.with(|catalog| {
+        let mut catalog = catalog.borrow_mut();
+        let messages = catalog.get_or_insert_with(environment_catalog);
+        let mut message = messages.get(code)?.clone();
+        for (index, arg) in args.iter().enumerate() {
+            message = message.replace(&format!("{{{}}}", index), arg);
+        }
+        Some(message)
+    })
+}
+
+// the catalog of the language of the environment, silently empty when it has none
+fn environment_catalog() -> BTreeMap<String, String> {
+    let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+    else {
+        return BTreeMap::new();
+    };
+    // `fr_FR.UTF-8@euro` names the `fr_FR` language
+    let language = locale.split(['.', '@']).next().unwrap_or_default();
+    let base = language.split(['_', '-']).next().unwrap_or_default();
+    [language, base]
+        .iter()
+        .filter(|language| catalog_path(language).exists())
+        .find_map(|language| load(language).ok())
+        .unwrap_or_default()
+}
+
+// the messages of a catalog, English having none since it's built in
+fn load(language: &str) -> Result<BTreeMap<String, String>, String> {
+    if matches!(language, "" | "en" | "C" | "POSIX") || language.starts_with("en_") {
+        return Ok(BTreeMap::new());
+    }
+    let path = catalog_path(language);
+    let contents = fs::read_to_string(&path).map_err(|error| error.to_string())?;
+    let sections = parse_sections(&contents, &path.display().to_string())?;
+    Ok(sections.into_values().flatten().collect())
+}
+
+// the first catalog of the language in the directories `select()` lists, the one of the current directory when
+// there's none, for the error
+fn catalog_path(language: &str) -> PathBuf {
+    let name = format!("{}.toml", language);
+    let local = PathBuf::from(LOCALES_DIR).join(&name);
+    let installed = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .map(|dir| vec![dir.join(LOCALES_DIR), dir.join(SHARED_LOCALES_DIR)])
+        .unwrap_or_default();
+    env::var_os("TRON_LOCALES")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(installed)
+        .map(|dir| dir.join(&name))
+        .find(|path| !local.exists() && path.exists())
+        .unwrap_or(local)
+}
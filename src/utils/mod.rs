@@ -1,13 +1,31 @@
 use std::process::exit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+pub mod crash;
+pub mod deterministic;
+pub mod hints;
+pub mod locale;
+pub mod sources;
+pub mod suggest;
 
-pub struct TronError {
-    pub code: String,
-    pub line: usize,
-    pub message: String,
-}
+// number of errors and warnings printed so far
+static REPORTED: AtomicUsize = AtomicUsize::new(0);
+static WARNED: AtomicUsize = AtomicUsize::new(0);
+
+// only a namespace for the reporting functions, what they reported is counted above
+pub struct TronError;
 
 impl TronError {
     pub fn throw(error_code: &str, line: usize, args: Vec<String>) {
+        Self::throw_at(error_code, line, 0, args);
+    }
+    /// Prints an error like `throw()`, pointing at a column of the line as well.
+    ///
+    /// Runtime errors use it with the line and column of the operator token that failed, a column of 0 only prints
+    /// the line.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn throw_at(error_code: &str, line: usize, column: usize, args: Vec<String>) {
+        REPORTED.fetch_add(1, Ordering::Relaxed);
         let message: String = match args.len() {
             0 => match error_code {
                 "E0001" => "failed to run file".to_string(),
@@ -15,13 +33,17 @@ impl TronError {
                 "E0003" => "unsupported platform".to_string(),
                 "E1001" => "unterminated string".to_string(),
                 "E2002" => "failed to parse block statement".to_string(),
-                "E2004" => "function can't have more than 32 arguments".to_string(),
+                "E2004" => "function can't have more than 255 arguments".to_string(),
                 "E2005" => "invalid assigment target".to_string(),
+                "E2006" => {
+                    "% is the remainder operator, it needs a left operand like a % b".to_string()
+                }
                 "E3003" => "failed to read local variable".to_string(),
                 "E3004" => "failed to resolve a variable in a too deep level".to_string(),
                 "E3005" => "failed to define a variable in a too deep level".to_string(),
                 "E3006" => "return isn't allowed outside of a function".to_string(),
                 "E3007" => "break isn't allowed outside of a loop".to_string(),
+                "E3008" => "continue isn't allowed outside of a loop".to_string(),
                 "E4006" => "failed to make function".to_string(),
                 "E4009" => "array index is out of bounds".to_string(),
                 "E4010" => "failed to perform operation on array".to_string(),
@@ -32,20 +54,38 @@ impl TronError {
                 _ => "uknwon error".to_string(),
             },
             1 => {
+                let e0004 = format!("unknown flag: {}", args[0]);
                 let e1002 = format!("unrecognized character: {}", args[0]);
                 let e1003 = format!("unsupported character: {}", args[0]);
+                let e0005 = format!("import cycle: {}", args[0]);
+                let e0007 = format!("failed to load project: {}", args[0]);
+                let e0013 = format!("failed to check for a new version: {}", args[0]);
                 let e1004 = format!("failted to scan tokens: \n {}", args[0]);
                 let e2001 = format!("failed to parse statements: \n {}", args[0]);
                 let e2003 = format!("unexpected token:  {}", args[0]);
                 let e3001 = format!("failed to resolve {} statement: incorrect type", args[0]);
                 let e3002 = format!("variable {} already exists", args[0]);
+                let e3010 = format!("{} is a native function and can't be redefined", args[0]);
+                let e3011 = format!("{} is used before it's declared", args[0]);
+                let e3012 = format!("{} isn't declared", args[0]);
+                let e3009 = format!("label {} doesn't belong to an enclosing loop", args[0]);
                 let e4004 = format!("failed to execute command: \n {}", args[0]);
                 let e4005 = format!("failed to find library: {}", args[0]);
                 let e4008 = format!("failed to create type from {}", args[0]);
                 let e4011 = format!("variable {} has not been declared", args[0]);
                 let e4016 = format!("invalid operator {}", args[0]);
+                let e4024 = format!(
+                    "{} by zero, run with --allow-infinity to get infinity or NaN instead",
+                    args[0]
+                );
+                let e4026 = format!("{} needs at least one number", args[0]);
+                let e5003 = format!("unknown type {}", args[0]);
 
                 match error_code {
+                    "E0004" => e0004,
+                    "E0005" => e0005,
+                    "E0007" => e0007,
+                    "E0013" => e0013,
                     "E1002" => e1002,
                     "E1003" => e1003,
                     "E1004" => e1004,
@@ -53,26 +93,75 @@ impl TronError {
                     "E2003" => e2003,
                     "E3001" => e3001,
                     "E3002" => e3002,
+                    "E3009" => e3009,
+                    "E3010" => e3010,
+                    "E3011" => e3011,
+                    "E3012" => e3012,
                     "E4004" => e4004,
                     "E4005" => e4005,
                     "E4008" => e4008,
                     "E4011" => e4011,
                     "E4016" => e4016,
+                    "E4024" => e4024,
+                    "E4026" => e4026,
+                    "E5003" => e5003,
                     _ => "uknwon error".to_string(),
                 }
             }
             2 => {
+                let e0006 = format!("unresolved import {} in {}", args[0], args[1]);
+                let e0008 = format!("failed to install {}: {}", args[0], args[1]);
+                let e0009 = format!("failed to load plugin {}: {}", args[0], args[1]);
+                let e0010 = format!("failed to write {}: {}", args[0], args[1]);
+                let e0011 = format!("failed to read {}: {}", args[0], args[1]);
+                let e0012 = format!("failed to upgrade to {}: {}", args[0], args[1]);
+                let e4011 = format!(
+                    "variable {} has not been declared, did you mean {}?",
+                    args[0], args[1]
+                );
                 let e4007 = format!("failed to unwrap {} as {}", args[0], args[1]);
                 let e4015 = format!("{} is not implemented for {}", args[0], args[1]);
                 let e4018 = format!("{} requires at least {} arguments", args[0], args[1]);
-                let e4019 = format!("{} requires more than {} arguments", args[0], args[1]);
-                let e4020 = format!("{} exactly exactly {} arguments", args[0], args[1]);
+                let e4019 = format!("{} accepts at most {} arguments", args[0], args[1]);
+                let e4020 = format!("{} requires exactly {} arguments", args[0], args[1]);
+                let e4022 = format!("{} expects a bool, but got {}", args[0], args[1]);
+                let e4023 = format!("failed to call {}: {}", args[0], args[1]);
+                let e4028 = format!("{} can't add a {} to a set", args[0], args[1]);
+                let e4029 = format!("{} failed to parse the template: {}", args[0], args[1]);
+                let e4030 = format!("{} doesn't know the style {}", args[0], args[1]);
+                let e4031 = format!("{} can't reach the clipboard: {}", args[0], args[1]);
+                let e4033 = format!("{} failed on the WebSocket: {}", args[0], args[1]);
+                let e4034 = format!("{} failed to process the archive: {}", args[0], args[1]);
+                let e4037 = format!("{} wasn't allowed to {}", args[0], args[1]);
+                let e4038 = format!("{} of a {} by zero", args[0], args[1]);
+                let e4039 = format!(
+                    "can't repeat {} {} times, the count must be a whole number and the result at most 2^30 long",
+                    args[0], args[1]
+                );
                 match error_code {
+                    "E0006" => e0006,
+                    "E0008" => e0008,
+                    "E0009" => e0009,
+                    "E0010" => e0010,
+                    "E0011" => e0011,
+                    "E0012" => e0012,
+                    "E4011" => e4011,
                     "E4007" => e4007,
                     "E4015" => e4015,
                     "E4018" => e4018,
                     "E4019" => e4019,
                     "E4020" => e4020,
+                    "E4022" => e4022,
+                    "E4023" => e4023,
+                    "E4028" => e4028,
+                    "E4029" => e4029,
+                    "E4030" => e4030,
+                    "E4031" => e4031,
+                    "E4033" => e4033,
+                    "E4034" => e4034,
+                    "E4037" => e4037,
+                    "E4038" => e4038,
+                    "E4039" => e4039,
                     _ => "uknwon error".to_string(),
                 }
             }
@@ -82,15 +171,39 @@ impl TronError {
                     args[0], args[1], args[2]
                 );
                 let e4002 = format!("{}({}: {})", args[0], args[1], args[2]);
-                let e4018 = format!(
+                let e4021 = format!(
                     "{} expects {} type as {} argument",
                     args[0], args[1], args[2]
                 );
+                let e4025 = format!(
+                    "{} can't combine arrays of shapes {} and {}",
+                    args[0], args[1], args[2]
+                );
+                let e4027 = format!("{} can't order a {} and a {}", args[0], args[1], args[2]);
+                let e4032 = format!(
+                    "{} failed to listen on port {}: {}",
+                    args[0], args[1], args[2]
+                );
+                let e4035 = format!("{} failed to download {}: {}", args[0], args[1], args[2]);
+                let e4036 = format!(
+                    "{} failed to read the image {}: {}",
+                    args[0], args[1], args[2]
+                );
+                let e5002 = format!(
+                    "{}() expects {} arguments, but gets {}",
+                    args[0], args[1], args[2]
+                );
 
                 match error_code {
                     "E4001" => e4001,
                     "E4002" => e4002,
-                    "E4018" => e4018,
+                    "E4021" => e4021,
+                    "E4025" => e4025,
+                    "E4027" => e4027,
+                    "E4032" => e4032,
+                    "E4035" => e4035,
+                    "E4036" => e4036,
+                    "E5002" => e5002,
                     _ => "uknwon error".to_string(),
                 }
             }
@@ -99,26 +212,31 @@ impl TronError {
                     "{} {} is expecting {} type, but got {}",
                     args[0], args[1], args[2], args[3]
                 );
+                let e5001 = format!(
+                    "{} {} is declared as {}, but gets {}",
+                    args[0], args[1], args[2], args[3]
+                );
 
-                if error_code == "E4003" {
-                    e4003
-                } else {
-                    "uknwon error".to_string()
+                match error_code {
+                    "E4003" => e4003,
+                    "E5001" => e5001,
+                    _ => "uknwon error".to_string(),
                 }
             }
         };
 
-        if line == 0 {
-            eprintln!(
-                "[\x1B[91;1m{}\x1B[0m] \x1B[91;1m{} \x1B[0m",
-                error_code, message
-            );
-        } else {
-            eprintln!(
-                "[\x1B[91;1m{}\x1B[0m] \x1B[91;1m{} \x1B[0m(\x1B[96mline {}\x1B[0m)",
-                error_code, message, line
-            );
-        }
+        // the form of a message with a suggestion has a code of its own in the catalogs
+        let catalog_code = match (error_code, args.len()) {
+            ("E4011", 2) => "E4011.suggestion".to_string(),
+            _ => error_code.to_string(),
+        };
+        let message = locale::translate(&catalog_code, &args).unwrap_or(message);
+        eprintln!(
+            "[\x1B[91;1m{}\x1B[0m] \x1B[91;1m{} \x1B[0m{}",
+            error_code,
+            message,
+            position(line, column)
+        );
 
         match error_code {
             "E1001" => exit(1),
@@ -126,7 +244,102 @@ impl TronError {
             "E4019" => exit(1),
             "E4020" => exit(1),
             "E4021" => exit(1),
+            "E4022" => exit(1),
             _ => {}
         }
     }
+    /// Returns how many errors and warnings were printed so far.
+    ///
+    /// Used to tell whether a step of the pipeline reported anything, e.g. programs with diagnostics aren't cached
+    /// so the diagnostics show up again on the next run.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn reported() -> usize {
+        REPORTED.load(Ordering::Relaxed)
+    }
+    /// Returns how many of the diagnostics counted by `reported()` were warnings, so a step can tell its errors apart,
+    /// e.g. `tron check` only fails on errors.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn warned() -> usize {
+        WARNED.load(Ordering::Relaxed)
+    }
+    /// Prints a warning without stopping the program.
+    ///
+    /// Warnings use `W` codes (listed in `errors.md` next to the errors) and are printed in yellow,
+    /// in the same format as errors.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn warn(warning_code: &str, line: usize, args: Vec<String>) {
+        Self::warn_at(warning_code, line, 0, args);
+    }
+    /// Prints a warning like `warn()`, pointing at a column of the line as well, a column of 0 only prints the line.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    pub fn warn_at(warning_code: &str, line: usize, column: usize, args: Vec<String>) {
+        REPORTED.fetch_add(1, Ordering::Relaxed);
+        WARNED.fetch_add(1, Ordering::Relaxed);
+        let message: String = match args.len() {
+            0 => match warning_code {
+                "W3001" => "loop has no break or return and will never end".to_string(),
+                "W3005" => {
+                    "else belongs to the closest if, wrap the inner if in { } to make it explicit"
+                        .to_string()
+                }
+                "W3008" => "the condition is always false, this branch never runs".to_string(),
+                _ => "unknown warning".to_string(),
+            },
+            1 => match warning_code {
+                "W3002" => format!("{} shadows a variable of an outer scope", args[0]),
+                "W3003" => format!("{} overrides a native function in this scope", args[0]),
+                "W3006" => format!("{} is assigned in a condition", args[0]),
+                "W3007" => format!("code after {} never runs", args[0]),
+                "W3009" => format!("{} is never used", args[0]),
+                "W4002" => format!("{} is implicitly converted to a string by +", args[0]),
+                _ => "unknown warning".to_string(),
+            },
+            2 => match warning_code {
+                "W3004" => format!(
+                    "{} is used in arithmetic while it's still null, it was declared on line {}",
+                    args[0], args[1]
+                ),
+                "W4001" => format!(
+                    "{} is implicitly converted to a bool in {}",
+                    args[0], args[1]
+                ),
+                _ => "unknown warning".to_string(),
+            },
+            3 => match warning_code {
+                "W5001" => format!(
+                    "{} has a cyclomatic complexity of {}, more than {}",
+                    args[0], args[1], args[2]
+                ),
+                "W5002" => format!(
+                    "{} is {} lines long, more than {}",
+                    args[0], args[1], args[2]
+                ),
+                _ => "unknown warning".to_string(),
+            },
+            _ => "unknown warning".to_string(),
+        };
+        let message = locale::translate(warning_code, &args).unwrap_or(message);
+        eprintln!(
+            "[\x1B[93;1m{}\x1B[0m] \x1B[93;1m{} \x1B[0m{}",
+            warning_code,
+            message,
+            position(line, column)
+        );
+    }
+}
+
+// where a diagnostic points at, `file:line:column` with the imports leading to the file when the code comes from one,
+// nothing for line 0
+fn position(line: usize, column: usize) -> String {
+    let location = match (sources::location(line, column), line, column) {
+        (_, 0, _) => return String::new(),
+        (Some(location), _, _) => location,
+        (None, line, 0) => format!("line {}", line),
+        (None, line, column) => format!("line {}, column {}", line, column),
+    };
+    format!("(\x1B[96m{}\x1B[0m)", location)
 }
@@ -0,0 +1,104 @@
+use std::cell::{Cell, RefCell};
+
+/// A file the program runs code from, the entry file or one it imports.
+///
+/// # Fields
+///
+/// - `name`: The path of the file, relative to the directory Tron runs in when it's inside it.
+/// - `imported_from`: The id of the file and the line of the `use` statement that imported it, `None` for the entry file.
+///
+/// ### Last Updated: (v3.2.0)
+struct SourceFile {
+    name: String,
+    imported_from: Option<(usize, usize)>,
+}
+
+thread_local! {
+    // the files registered so far, the id of a file is its index plus 1
+    static FILES: RefCell<Vec<SourceFile>> = const { RefCell::new(vec![]) };
+    // the file of the code running now, 0 when it doesn't come from a file, like the source of `tron -e` or the REPL
+    static CURRENT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Registers a file and makes it the current one, returning the id of the file that was current before so it can be
+/// restored with `leave()` once the file ran.
+///
+/// An imported file is registered with the line of its `use` statement in the current file, which is how diagnostics
+/// show the chain of imports that led to it.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn enter(name: String, import_line: Option<usize>) -> usize {
+    let previous = current();
+    let imported_from = import_line.map(|line| (previous, line));
+    let id = FILES.with(|files| {
+        let mut files = files.borrow_mut();
+        files.push(SourceFile {
+            name,
+            imported_from,
+        });
+        files.len()
+    });
+    CURRENT.with(|current| current.replace(id))
+}
+
+/// Makes `id` the current file again, after `enter()` or `switch()`.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn leave(id: usize) {
+    CURRENT.with(|current| current.set(id));
+}
+
+/// Makes a file registered earlier the current one, returning the id of the file that was current, for functions
+/// running the code of the file they're declared in when they're called from another one.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn switch(id: usize) -> usize {
+    CURRENT.with(|current| current.replace(id))
+}
+
+/// Returns the id of the current file, 0 when the code running doesn't come from a file.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn current() -> usize {
+    CURRENT.with(|current| current.get())
+}
+
+/// Describes a position of the current file as `file:line:column`, followed by the imports that led to the file like
+/// `lib.tron:4:9, imported from main.tron:3`. The column is left out when it's 0.
+///
+/// # Return Value
+///
+/// The description, or `None` when the code running doesn't come from a file.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn location(line: usize, column: usize) -> Option<String> {
+    FILES.with(|files| {
+        let files = files.borrow();
+        let file = files.get(current().checked_sub(1)?)?;
+        let mut location = match column {
+            0 => format!("{}:{}", file.name, line),
+            column => format!("{}:{}:{}", file.name, line, column),
+        };
+        let mut imported_from = file.imported_from;
+        while let Some((id, line)) = imported_from {
+            let Some(importer) = id.checked_sub(1).and_then(|index| files.get(index)) else {
+                break;
+            };
+            location.push_str(&format!(", imported from {}:{}", importer.name, line));
+            imported_from = importer.imported_from;
+        }
+        Some(location)
+    })
+}
+
+/// Returns the paths of the files registered so far, in the order they were entered, the entry file first.
+///
+/// The list is empty while the files are being registered, so it can be read from a panic hook.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn names() -> Vec<String> {
+    FILES.with(|files| match files.try_borrow() {
+        Ok(files) => files.iter().map(|file| file.name.clone()).collect(),
+        Err(_) => vec![],
+    })
+}
@@ -0,0 +1,88 @@
+/// Returns the candidate closest to `name`, to suggest it when `name` isn't defined.
+///
+/// Candidates are compared with the Levenshtein distance, and only the ones within a third of the length of `name`
+/// are considered, so unrelated names aren't suggested. A name shorter than three characters gets no suggestion, every
+/// other short name is about as close to it. Ties are broken alphabetically, which keeps the suggestion stable across
+/// runs.
+///
+/// # Example
+///
+/// ```
+/// let names = vec!["counter".to_string(), "@push".to_string()];
+/// assert_eq!(did_you_mean("countr", names.iter().map(|n| n.as_str())), Some("counter".to_string()));
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+pub fn did_you_mean<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let limit = name.chars().count() / 3;
+    candidates
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(name: &str, candidates: &[&str]) -> Option<String> {
+        did_you_mean(name, candidates.iter().copied())
+    }
+
+    #[test]
+    fn suggests_the_closest_name() {
+        assert_eq!(
+            suggest("countr", &["counter", "@push", "count_all"]),
+            Some("counter".to_string())
+        );
+        // a tie goes to the first name alphabetically
+        assert_eq!(suggest("cat", &["cut", "bat"]), Some("bat".to_string()));
+    }
+
+    #[test]
+    fn suggests_a_name_within_a_third_of_the_length() {
+        // 9 characters allow 3 edits
+        assert_eq!(
+            suggest("abcdefghi", &["abcdefxyz"]),
+            Some("abcdefxyz".to_string())
+        );
+        // 8 characters allow 2 edits
+        assert_eq!(suggest("abcdefgh", &["abcdexyz"]), None);
+        assert_eq!(
+            suggest("abcdefgh", &["abcdefyz"]),
+            Some("abcdefyz".to_string())
+        );
+    }
+
+    #[test]
+    fn suggests_nothing_for_very_short_names() {
+        assert_eq!(suggest("x", &["y", "xy"]), None);
+        assert_eq!(suggest("ab", &["ac", "b"]), None);
+        assert_eq!(suggest("abc", &["abd"]), Some("abd".to_string()));
+    }
+
+    #[test]
+    fn doesnt_suggest_the_name_itself() {
+        assert_eq!(suggest("counter", &["counter"]), None);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::expressions::Expression;
+use crate::scanner::Statement;
+
+/// The `Visitor` trait walks the syntax tree of a program, for the tools that only care about a few kinds of nodes,
+/// like linters, metrics and codemods.
+///
+/// Both methods visit the children of the node by default, through `walk_statement()` and `walk_expression()`, so an
+/// implementation only overrides the method of the nodes it looks at, and calls the `walk_*` function from it to keep
+/// going down, or doesn't to skip the children. A new kind of statement or expression only has to be added to the
+/// `walk_*` functions, not to every tool.
+///
+/// The children are visited in the order of the source, the body of a function or an anonymous function included.
+///
+/// # Example
+///
+/// ```
+/// // counts the calls of a program
+/// struct Calls(usize);
+///
+/// impl Visitor for Calls {
+///     fn visit_expression(&mut self, expression: &Expression) {
+///         if let Expression::Call { .. } = expression {
+///             self.0 += 1;
+///         }
+///         walk_expression(self, expression);
+///     }
+/// }
+///
+/// let mut calls = Calls(0);
+/// for statement in &statements {
+///     calls.visit_statement(statement);
+/// }
+/// ```
+///
+/// ### Last Updated: (v3.2.0)
+pub trait Visitor {
+    /// Visits a statement, and its children by default.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+    /// Visits an expression, and its children by default.
+    ///
+    /// ### Last Updated: (v3.2.0)
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+}
+
+/// Visits the statements and the expressions directly inside a statement.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Expression { expression, .. }
+        | Statement::Use { expression, .. }
+        | Statement::Variable {
+            value: expression, ..
+        } => visitor.visit_expression(expression),
+        Statement::Block { statements, .. } => {
+            for statement in statements.iter() {
+                visitor.visit_statement(statement);
+            }
+        }
+        Statement::While {
+            conditions,
+            body,
+            increment,
+            ..
+        } => {
+            for condition in conditions {
+                visitor.visit_expression(condition);
+            }
+            visitor.visit_statement(body);
+            if let Some(increment) = increment {
+                visitor.visit_expression(increment);
+            }
+        }
+        Statement::DoWhile {
+            body, conditions, ..
+        } => {
+            visitor.visit_statement(body);
+            for condition in conditions {
+                visitor.visit_expression(condition);
+            }
+        }
+        Statement::If {
+            conditions,
+            then_branch,
+            elif_branches,
+            else_branch,
+            ..
+        } => {
+            for condition in conditions {
+                visitor.visit_expression(condition);
+            }
+            visitor.visit_statement(then_branch);
+            for (conditions, branch) in elif_branches {
+                for condition in conditions {
+                    visitor.visit_expression(condition);
+                }
+                visitor.visit_statement(branch);
+            }
+            if let Some(branch) = else_branch {
+                visitor.visit_statement(branch);
+            }
+        }
+        Statement::Function { body, .. } => {
+            for statement in body.iter() {
+                visitor.visit_statement(statement);
+            }
+        }
+        Statement::Return { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        Statement::Break { .. } | Statement::Continue { .. } => {}
+        Statement::Switch {
+            condition,
+            case_branches,
+            default_branch,
+            ..
+        } => {
+            visitor.visit_expression(condition);
+            for (case, branch) in case_branches {
+                visitor.visit_expression(case);
+                for statement in branch {
+                    visitor.visit_statement(statement);
+                }
+            }
+            for statement in default_branch.iter().flatten() {
+                visitor.visit_statement(statement);
+            }
+        }
+    }
+}
+
+/// Visits the expressions directly inside an expression, and the statements of the body of an anonymous function.
+///
+/// ### Last Updated: (v3.2.0)
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Object { properties, .. } => {
+            for (_, value) in properties {
+                visitor.visit_expression(value);
+            }
+        }
+        Expression::Array { elements, .. } => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Index { object, index, .. } => {
+            visitor.visit_expression(object);
+            visitor.visit_expression(index);
+        }
+        Expression::Assign { value, .. }
+        | Expression::Grouping {
+            expression: value, ..
+        } => visitor.visit_expression(value),
+        Expression::Binary { left, right, .. } | Expression::Logical { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        Expression::Unary { right, .. } => visitor.visit_expression(right),
+        Expression::OptionalGet { object, .. } => visitor.visit_expression(object),
+        Expression::Call {
+            callee, arguments, ..
+        } => {
+            visitor.visit_expression(callee);
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+        Expression::Template { parts, .. }
+        | Expression::Comparison {
+            operands: parts, ..
+        } => {
+            for part in parts {
+                visitor.visit_expression(part);
+            }
+        }
+        Expression::Function { body, .. } => {
+            for statement in body.iter() {
+                visitor.visit_statement(statement);
+            }
+        }
+        Expression::Literal { .. }
+        | Expression::Variable { .. }
+        | Expression::ObjectCall { .. } => {}
+    }
+}